@@ -0,0 +1,233 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This crate contains an AETG-style generator, which builds the [MCA] one complete row at a time.
+//!
+//! Unlike IPOG, which adds the parameters one by one, every row covers all parameters as soon as it is appended.
+//! A row is started with the first uncovered interaction, after which the other parameters get the value covering the most new interactions with the parameters set before.
+//! Ties are broken in the same way as in the horizontal extension of IPOG, by the least used value closest to the value used in the previous row.
+//! Rows are appended until all interactions are covered.
+//!
+//! The interactions of all parameters are tracked at once in the same way as the `check-mca` binary does:
+//! a dummy parameter with a single value is appended, such that a [CoverageMap] of strength *t*+1 initialised at the dummy parameter contains all the *t*-way interactions.
+//! This map can not be processed in chunks.
+//!
+//! With constraints every value is checked by the [Solver] before it is used, the interactions disallowed by the constraints are never covered.
+
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+#![feature(generic_const_exprs)]
+#![deny(missing_docs, rustdoc::missing_crate_level_docs, future_incompatible)]
+
+use std::marker::PhantomData;
+
+use cm::{CoverageMap, get_highscore_blacklisted};
+use common::{CancellationToken, next_combination, Number, Phase, Progress, ProgressObserver, time_it, u_vec, UVec};
+use mca::{MCA, Skipped};
+use pc_list::PCList;
+use sut::{ConstrainedSUT, Solver, SUT};
+
+#[cfg(test)]
+mod test;
+
+/// Decides whether the (partial) row is allowed.
+type IsValid<'v, ValueId> = dyn FnMut(&[ValueId]) -> bool + 'v;
+
+/// The AETG-style generator, see the [crate] documentation.
+pub struct AETG<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:,
+          [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    value_id: PhantomData<ValueId>,
+    parameter_id: PhantomData<ParameterId>,
+    locations_type: PhantomData<LocationsType>,
+}
+
+impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
+AETG<ValueId, ParameterId, LocationsType, STRENGTH>
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:,
+          [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    /// Generate an MCA for a SUT without constraints.
+    ///
+    /// The `observer` is called whenever the first uncovered interaction moves on to a later parameter.
+    /// If the `cancel` token is cancelled, the generation stops before the next row and [MCA::skipped] records the first parameter with uncovered interactions.
+    /// The interactions of the first *t* parameters are always covered, so the MCA can be completed like one of IPOG.
+    pub fn run(
+        sut: &SUT<ValueId, ParameterId>,
+        observer: &mut dyn ProgressObserver,
+        cancel: &CancellationToken,
+    ) -> MCA<ValueId, LocationsType> {
+        if sut.parameters.len() <= STRENGTH {
+            return MCA::new_unconstrained::<ParameterId, STRENGTH>(&sut.parameters);
+        }
+        Self::generate(&sut.parameters, observer, cancel, &mut |_| true)
+    }
+
+    /// Generate an MCA for a SUT with constraints, the `observer` and `cancel` token are used in the same way as by [AETG::run].
+    pub fn run_constrained<'a, S: Solver<'a>>(
+        sut: &mut ConstrainedSUT<ValueId, ParameterId>,
+        solver_init: &'a S::Init,
+        observer: &mut dyn ProgressObserver,
+        cancel: &CancellationToken,
+    ) -> MCA<ValueId, LocationsType> {
+        let mut solver = time_it!(sut.get_solver::<S>(solver_init), "Solver setup", DEBUG);
+        if sut.sub_sut.parameters.len() <= STRENGTH {
+            return MCA::new_constrained::<ParameterId, S, STRENGTH>(&sut.sub_sut.parameters, &mut solver);
+        }
+        Self::generate(&sut.sub_sut.parameters, observer, cancel, &mut |row| solver.check_row(row))
+    }
+
+    /// Append rows until all interactions allowed by `valid` are covered.
+    fn generate(
+        parameters: &UVec<ValueId>,
+        observer: &mut dyn ProgressObserver,
+        cancel: &CancellationToken,
+        valid: &mut IsValid<'_, ValueId>,
+    ) -> MCA<ValueId, LocationsType> {
+        let parameter_count = parameters.len();
+        let mut dummy_parameters = parameters.clone();
+        dummy_parameters.push(ValueId::from_usize(1));
+
+        let pc_list = time_it!(PCList::<ParameterId, LocationsType, { STRENGTH + 1 }>::new(parameter_count + 1), "PCList generation", DEBUG);
+        let mut coverage_map = time_it!(CoverageMap::<ValueId, { STRENGTH + 1 }>::new(dummy_parameters, &pc_list), "Coverage map generation", DEBUG);
+        coverage_map.initialise(parameter_count);
+
+        let mut mca = MCA {
+            array: UVec::with_capacity(parameter_count),
+            dont_care_locations: UVec::with_capacity(parameter_count),
+            vertical_extension_rows: UVec::with_capacity(0),
+            new_row: u_vec![ValueId::dont_care(); parameter_count],
+            skipped: None,
+        };
+
+        let mut scores: UVec<UVec<UVec<u64>>> = parameters.iter().map(|&level| u_vec![UVec::with_capacity(0); level.as_usize()]).collect();
+        let mut blacklist: UVec<UVec<bool>> = parameters.iter().map(|&level| u_vec![false; level.as_usize()]).collect();
+        let mut uses: UVec<UVec<usize>> = parameters.iter().map(|&level| u_vec![0; level.as_usize()]).collect();
+        let mut previous_values = u_vec![ValueId::default(); parameter_count];
+
+        let mut fixed: Vec<ParameterId> = Vec::with_capacity(parameter_count);
+        let mut combination = [0; STRENGTH - 1];
+        let mut at_parameter = 0;
+        let mut from = 0;
+
+        while let Some(seed) = coverage_map.first_uncovered(from) {
+            from = seed;
+            let (pc_id, values, _) = coverage_map.interaction(seed);
+            let pc = pc_list.pcs[pc_id];
+
+            // The first uncovered interaction is in the earliest PC, so the interactions of the earlier parameters are covered.
+            let seed_parameter = pc[STRENGTH - 1].as_usize().max(STRENGTH);
+            if seed_parameter != at_parameter {
+                at_parameter = seed_parameter;
+                observer.progress(&Progress { strength: STRENGTH, at_parameter, parameters: parameter_count, rows: mca.array.len(), uncovered: coverage_map.uncovered, phase: Phase::Horizontal });
+            }
+            if pc[STRENGTH - 1].as_usize() >= STRENGTH && cancel.is_cancelled() {
+                mca.skipped = Some(Skipped { at_parameter, parameters: parameter_count, phase: Phase::Horizontal, uncovered: coverage_map.uncovered, completion_rows: None });
+                break;
+            }
+
+            let mut row = mca.new_row.clone();
+            for (&parameter_id, &value) in pc.iter().zip(values.iter()) {
+                row[parameter_id.as_usize()] = value;
+            }
+            unsafe { coverage_map.set_index(seed) };
+            if !valid(row.as_slice()) {
+                continue;
+            }
+
+            fixed.clear();
+            fixed.extend_from_slice(&pc);
+            for parameter in 0..parameter_count {
+                if row[parameter] != ValueId::dont_care() {
+                    continue;
+                }
+                let parameter_id = ParameterId::from_usize(parameter);
+                let scores = &mut scores[parameter];
+                for score in scores.iter_mut() {
+                    score.clear();
+                }
+
+                // Score the values on the interactions with every combination of t-1 parameters set before.
+                for (index, element) in combination.iter_mut().enumerate() {
+                    *element = index;
+                }
+                loop {
+                    let mut new_pc = [ParameterId::default(); { STRENGTH + 1 } - 1];
+                    let insert_at = combination.partition_point(|&index| fixed[index] < parameter_id);
+                    for (index, &element) in combination.iter().enumerate() {
+                        new_pc[if index < insert_at { index } else { index + 1 }] = fixed[element];
+                    }
+                    new_pc[insert_at] = parameter_id;
+                    let new_pc_id = PCList::<ParameterId, LocationsType, { STRENGTH + 1 }>::pc_id(&new_pc);
+
+                    for (value, score) in scores.iter_mut().enumerate() {
+                        row[parameter] = ValueId::from_usize(value);
+                        if let Some(index) = unsafe { coverage_map.get_base_index(new_pc_id, &pc_list, row.as_slice()) } {
+                            if !unsafe { coverage_map.get(index) } {
+                                score.push(index);
+                            }
+                        }
+                    }
+
+                    if !next_combination(&mut combination, fixed.len()) {
+                        break;
+                    }
+                }
+
+                let value = unsafe { get_best_value(&mut row, parameter, previous_values[parameter], scores, &mut blacklist[parameter], &uses[parameter], valid) };
+                row[parameter] = value;
+                unsafe { coverage_map.set_indices(&scores[value.as_usize()]) };
+                uses[parameter][value.as_usize()] += 1;
+                previous_values[parameter] = value;
+                fixed.insert(fixed.partition_point(|&other| other < parameter_id), parameter_id);
+            }
+
+            mca.array.push(row);
+            mca.dont_care_locations.push(LocationsType::default());
+        }
+
+        tracing::debug!(rows = mca.array.len(), uncovered = coverage_map.uncovered, "Generated the MCA with AETG");
+        if mca.skipped.is_none() {
+            observer.progress(&Progress { strength: STRENGTH, at_parameter: parameter_count, parameters: parameter_count, rows: mca.array.len(), uncovered: 0, phase: Phase::Done });
+        }
+        mca
+    }
+}
+
+/// Returns the highest scoring value for the parameter which `valid` allows in the row.
+///
+/// Assumes that the row without a value for the parameter is valid, so one of the values is valid.
+unsafe fn get_best_value<ValueId: Number>(
+    row: &mut UVec<ValueId>,
+    parameter: usize,
+    mut previous_value: ValueId,
+    scores: &UVec<UVec<u64>>,
+    blacklist: &mut UVec<bool>,
+    uses: &UVec<usize>,
+    valid: &mut IsValid<'_, ValueId>,
+) -> ValueId {
+    for blacklisted in blacklist.iter_mut() {
+        *blacklisted = false;
+    }
+
+    for _ in 1..scores.len() {
+        let value = get_highscore_blacklisted(scores, uses, previous_value, blacklist);
+        row[parameter] = value;
+        if valid(row.as_slice()) {
+            return value;
+        }
+
+        blacklist[value.as_usize()] = true;
+        while blacklist[previous_value.as_usize()] {
+            previous_value = (previous_value + ValueId::from_usize(1)) % ValueId::from_usize(scores.len());
+        }
+    }
+
+    let mut value = ValueId::default();
+    while blacklist[value.as_usize()] {
+        value += ValueId::from_usize(1);
+    }
+    value
+}
@@ -0,0 +1,87 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::collections::HashMap;
+
+use common::{CancellationToken, UVec};
+use mca::min_coverage;
+use sut::{ConstrainedSUT, parse_constrained, parse_unconstrained, Predicate, PredicateSolver};
+
+use super::*;
+
+#[test]
+fn test_aetg() {
+    let model = "p0: 0, 1, 2, 3;p1: 0, 1, 2;p2: 0, 1, 2;p3: 0, 1, 2;p4: 0, 1;p5: 0, 1;p6: 0, 1;p7: 0, 1;p8: 0, 1;";
+    let sut = parse_unconstrained(model).unwrap();
+
+    let mca = AETG::<usize, usize, u64, 2>::run(&sut, &mut (), &CancellationToken::new());
+    assert_eq!(mca.skipped, None);
+    assert!(mca.check_locations());
+    assert!(min_coverage(sut.parameters.as_slice(), &mca.array, 2, |_, _| true) > 0);
+
+    let mca = AETG::<usize, usize, u64, 3>::run(&sut, &mut (), &CancellationToken::new());
+    assert_eq!(mca.skipped, None);
+    assert!(mca.check_locations());
+    assert!(min_coverage(sut.parameters.as_slice(), &mca.array, 3, |_, _| true) > 0);
+}
+
+#[test]
+fn test_aetg_constraints() {
+    type Generator = AETG<usize, usize, u64, 2>;
+    let parameters = UVec::from(vec![3, 3, 2, 2, 2, 2]);
+
+    // The first two parameters can not both have the value 1.
+    let allowed = |row: &[usize]| row[0] != 1 || row[1] != 1;
+    let mca = Generator::generate(&parameters, &mut (), &CancellationToken::new(), &mut |row| allowed(row));
+    assert!(mca.array.iter().all(|row| allowed(row.as_slice())));
+    assert!(min_coverage(parameters.as_slice(), &mca.array, 2, |pc, values| pc != [0, 1] || values != [1, 1]) > 0);
+}
+
+fn predicate(row: &HashMap<&str, &str>) -> bool {
+    (row["p0"] != "a" || row["p4"] != "0") && (row["p1"] != row["p0"] || row["p2"] == "y") && (row["p3"] != "x" || row["p4"] != "2")
+}
+
+/// Returns true iff the full row is allowed by the [predicate], independent of the solver.
+fn allowed(sut: &ConstrainedSUT<usize, usize>, row: &[usize]) -> bool {
+    row.iter().zip(sut.sub_sut.parameters.iter()).all(|(value_id, level)| value_id < level) && predicate(&row.iter().enumerate().map(|(parameter_id, &value_id)| {
+        (sut.sub_sut.parameter_names[parameter_id].as_str(), sut.sub_sut.values[parameter_id][value_id].as_str())
+    }).collect())
+}
+
+#[test]
+fn test_aetg_solver() {
+    let mut sut = parse_constrained("p0: a, b, c; p1: a, b, c; p2: x, y; p3: x, y; p4: 0, 1, 2;").unwrap();
+    let mca = AETG::<usize, usize, u64, 2>::run_constrained::<PredicateSolver>(&mut sut, &(predicate as Predicate), &mut (), &CancellationToken::new());
+    assert_eq!(mca.skipped, None);
+
+    // Setting up the solver reorders the values to allow a row of zeros, so the allowed rows are listed afterwards
+    let levels = sut.sub_sut.parameters.clone();
+    let allowed_rows: Vec<UVec<usize>> = (0..levels.iter().product()).map(|mut index: usize| levels.iter().map(|&level| {
+        let value = index % level;
+        index /= level;
+        value
+    }).collect::<UVec<usize>>()).filter(|row| allowed(&sut, row.as_slice())).collect();
+    assert!(mca.array.iter().all(|row| allowed(&sut, row.as_slice())));
+    assert!(min_coverage(levels.as_slice(), &mca.array, 2, |pc, values| {
+        allowed_rows.iter().any(|row| pc.iter().zip(values.iter()).all(|(&parameter_id, &value_id)| row[parameter_id] == value_id))
+    }) > 0);
+}
+
+#[test]
+fn test_aetg_cancel() {
+    let model = "p0: 0, 1;p1: 0, 1;p2: 0, 1;p3: 0, 1;p4: 0, 1;p5: 0, 1;p6: 0, 1;p7: 0, 1;p8: 0, 1;p9: 0, 1;";
+    let sut = parse_unconstrained(model).unwrap();
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+
+    // The interactions of the first two parameters need four rows, which do not cover all pairs.
+    let mca = AETG::<usize, usize, u64, 2>::run(&sut, &mut (), &cancel);
+    let skipped = mca.skipped.as_ref().unwrap();
+    assert_eq!(skipped.parameters, 10);
+    assert!(skipped.at_parameter >= 2);
+    assert!(skipped.uncovered > 0);
+    assert!(min_coverage(sut.parameters.as_slice(), &mca.array, 2, |pc, _| pc[1] < skipped.at_parameter) > 0);
+}
@@ -0,0 +1,402 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This crate contains a simulated annealing phase which removes rows from a finished [MCA].
+//!
+//! IPOG results are rarely minimal, so the annealing repeatedly removes the row covering the fewest interactions on its own
+//! and searches for cell changes which cover the interactions lost with it.
+//! A change covers a random missing interaction in a row which already has most of its values.
+//! Changes which lose more interactions than they cover are accepted with a probability which decreases with the temperature.
+//! Once nothing is missing, the next row is removed, until the time limit has passed.
+//!
+//! The number of rows covering each *t*-way interaction is tracked incrementally.
+//! The interactions are indexed in the same way as the `check-mca` binary does:
+//! a dummy parameter with a single value is appended, such that a [CoverageMap] of strength *t*+1 initialised at the dummy parameter contains all the *t*-way interactions.
+//!
+//! The interactions which have to stay covered are the ones covered by the [MCA] before the annealing.
+//! With constraints every changed row is checked by the [Solver], so the interactions disallowed by the constraints are never covered.
+//! Before the result replaces the [MCA], a recount of its rows is checked to cover every interaction of the original rows.
+
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+#![feature(generic_const_exprs)]
+#![deny(missing_docs, rustdoc::missing_crate_level_docs, future_incompatible)]
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use cm::CoverageMap;
+use common::{next_combination, Number, split_mix, u_vec, UVec};
+use mca::MCA;
+use pc_list::PCList;
+use sut::Solver;
+
+#[cfg(test)]
+mod test;
+
+/// The temperature at the start of the search for each removed row, and after reheating.
+pub const INITIAL_TEMPERATURE: f64 = 0.5;
+
+/// The factor by which the temperature decreases after every change.
+pub const COOLING: f64 = 0.9995;
+
+/// The temperature below which the search is reheated to the [INITIAL_TEMPERATURE].
+pub const MIN_TEMPERATURE: f64 = 0.02;
+
+/// The number of random rows of which the one closest to the missing interaction is changed.
+pub const ROW_SAMPLES: usize = 4;
+
+/// The number of changes between the checks of the time limit.
+const TIME_CHECK_INTERVAL: usize = 256;
+
+/// The seed of the random numbers, so the annealing is repeatable for the same time limit.
+const SEED: u64 = 0x1ca;
+
+/// Decides whether the row is allowed.
+type IsValid<'v, ValueId> = dyn FnMut(&[ValueId]) -> bool + 'v;
+
+/// The limit on the search, the tests use a number of changes so they do not depend on the speed of the machine.
+enum Budget {
+    Deadline(Instant),
+    #[cfg(test)]
+    Changes(usize),
+}
+
+impl Budget {
+    /// Returns true if the budget is exhausted before the next change, which is then counted.
+    fn exhausted(&mut self, iteration: usize) -> bool {
+        match self {
+            Budget::Deadline(deadline) => iteration % TIME_CHECK_INTERVAL == 0 && Instant::now() >= *deadline,
+            #[cfg(test)]
+            Budget::Changes(changes) => match changes.checked_sub(1) {
+                Some(remaining) => {
+                    *changes = remaining;
+                    false
+                }
+                None => true,
+            },
+        }
+    }
+}
+
+/// Remove rows from the [MCA] of a SUT without constraints with simulated annealing until the `time_limit` has passed.
+///
+/// The don't-care values are replaced, so the result has none.
+/// Returns the number of rows removed.
+pub fn anneal<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>(
+    parameters: &UVec<ValueId>,
+    mca: &mut MCA<ValueId, LocationsType>,
+    time_limit: Duration,
+) -> usize
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:,
+          [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    // No row can be removed once the rows equal the product of the largest levels.
+    let mut levels: Vec<usize> = parameters.iter().map(|level| level.as_usize()).collect();
+    levels.sort_unstable_by(|a, b| b.cmp(a));
+    let lower_bound = levels.iter().take(STRENGTH).product();
+    anneal_valid::<ValueId, ParameterId, LocationsType, STRENGTH>(parameters, mca, Budget::Deadline(Instant::now() + time_limit), lower_bound, &mut |_| true)
+}
+
+/// Same as [anneal], but every changed row is checked by the `solver` first.
+///
+/// Assumes that the rows of the [MCA] are allowed by the constraints.
+pub fn anneal_constrained<'a, ValueId: Number, ParameterId: Number, LocationsType: Number, S: Solver<'a>, const STRENGTH: usize>(
+    solver: &mut S,
+    parameters: &UVec<ValueId>,
+    mca: &mut MCA<ValueId, LocationsType>,
+    time_limit: Duration,
+) -> usize
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:,
+          [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    // The interactions disallowed by the constraints need no rows, so there is no lower bound besides a single row.
+    anneal_valid::<ValueId, ParameterId, LocationsType, STRENGTH>(parameters, mca, Budget::Deadline(Instant::now() + time_limit), 1, &mut |row| solver.check_row(row))
+}
+
+fn anneal_valid<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>(
+    parameters: &UVec<ValueId>,
+    mca: &mut MCA<ValueId, LocationsType>,
+    mut budget: Budget,
+    lower_bound: usize,
+    valid: &mut IsValid<'_, ValueId>,
+) -> usize
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:,
+          [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    let initial_rows = mca.array.len();
+    if parameters.len() <= STRENGTH || initial_rows <= 1 {
+        return 0;
+    }
+
+    let mut state = SEED;
+    let rows = fill_dont_cares(parameters, &mca.array, &mut state, valid);
+    let mut annealer = Annealer::<ValueId, ParameterId, LocationsType, STRENGTH>::new(parameters, rows, state);
+
+    let mut best = annealer.rows.clone();
+    while annealer.rows.len() > lower_bound && annealer.reduce(&mut budget, valid) {
+        best = annealer.rows.clone();
+        tracing::debug!(rows = best.len(), "Removed a row with simulated annealing");
+    }
+
+    // The coverage map of the annealer is only as correct as its counts, so the best rows are counted again
+    drop(annealer);
+    let recount = Annealer::<ValueId, ParameterId, LocationsType, STRENGTH>::new(parameters, best.clone(), state);
+    if !recount.covers(&mca.array) {
+        tracing::error!("The annealed MCA lost interactions, keeping the original MCA");
+        return 0;
+    }
+    drop(recount);
+
+    mca.dont_care_locations = u_vec![LocationsType::default(); best.len()];
+    mca.array = best;
+    mca.vertical_extension_rows.clear();
+    initial_rows - mca.array.len()
+}
+
+/// Replace the don't-care values by random values which `valid` allows, assuming the row without them is allowed.
+fn fill_dont_cares<ValueId: Number>(
+    parameters: &UVec<ValueId>,
+    array: &UVec<UVec<ValueId>>,
+    state: &mut u64,
+    valid: &mut IsValid<'_, ValueId>,
+) -> UVec<UVec<ValueId>> {
+    let mut rows = array.clone();
+    for row in rows.iter_mut() {
+        for parameter in 0..parameters.len() {
+            if row[parameter] != ValueId::dont_care() {
+                continue;
+            }
+            let level = parameters[parameter].as_usize();
+            let start = (split_mix(state) % level as u64) as usize;
+            for offset in 0..level {
+                row[parameter] = ValueId::from_usize((start + offset) % level);
+                if offset + 1 == level || valid(row.as_slice()) {
+                    break;
+                }
+            }
+        }
+    }
+    rows
+}
+
+/// The state of the annealing: the rows and the number of rows covering each interaction.
+struct Annealer<'p, ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
+    where [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    parameters: &'p UVec<ValueId>,
+    pc_list: PCList<ParameterId, LocationsType, { STRENGTH + 1 }>,
+
+    /// Indexes the interactions, the interactions covered by the original rows are set.
+    coverage_map: CoverageMap<ValueId, { STRENGTH + 1 }>,
+
+    /// The number of rows covering each interaction.
+    counts: UVec<u32>,
+
+    /// The set interactions of the [Annealer::coverage_map] which are not covered by any row.
+    missing: Vec<u64>,
+
+    /// The position of each interaction in [Annealer::missing].
+    missing_positions: HashMap<u64, usize>,
+
+    rows: UVec<UVec<ValueId>>,
+    state: u64,
+    indices: Vec<u64>,
+}
+
+impl<'p, ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
+Annealer<'p, ValueId, ParameterId, LocationsType, STRENGTH>
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:,
+          [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    fn new(parameters: &'p UVec<ValueId>, rows: UVec<UVec<ValueId>>, state: u64) -> Self {
+        let parameter_count = parameters.len();
+        let mut dummy_parameters = parameters.clone();
+        dummy_parameters.push(ValueId::from_usize(1));
+
+        let pc_list = PCList::<ParameterId, LocationsType, { STRENGTH + 1 }>::new(parameter_count + 1);
+        let mut coverage_map = CoverageMap::<ValueId, { STRENGTH + 1 }>::new(dummy_parameters, &pc_list);
+        coverage_map.initialise(parameter_count);
+
+        let mut counts = u_vec![0; coverage_map.uncovered];
+        for row in rows.iter() {
+            for pc_id in 0..pc_list.pcs.len() {
+                let index = unsafe { coverage_map.get_base_index_unchecked(pc_id, &pc_list, row.as_slice()) };
+                counts[index as usize] += 1;
+                unsafe { coverage_map.set_index(index) };
+            }
+        }
+
+        Self { parameters, pc_list, coverage_map, counts, missing: Vec::new(), missing_positions: HashMap::new(), rows, state, indices: Vec::new() }
+    }
+
+    fn random(&mut self, below: usize) -> usize {
+        (split_mix(&mut self.state) % below as u64) as usize
+    }
+
+    fn random_fraction(&mut self) -> f64 {
+        (split_mix(&mut self.state) >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Collect the indices of the interactions of the row which contain the parameter in [Annealer::indices].
+    fn collect_indices(&mut self, row_id: usize, parameter: usize) {
+        self.indices.clear();
+        let row = self.rows[row_id].as_slice();
+        let parameter_id = ParameterId::from_usize(parameter);
+
+        // Combinations of t-1 of the other parameters, the parameter is skipped by shifting the later ones.
+        let others = self.parameters.len() - 1;
+        let mut combination = [0; STRENGTH - 1];
+        for (index, element) in combination.iter_mut().enumerate() {
+            *element = index;
+        }
+        loop {
+            let mut pc = [ParameterId::default(); { STRENGTH + 1 } - 1];
+            let insert_at = combination.partition_point(|&other| other < parameter);
+            for (index, &other) in combination.iter().enumerate() {
+                if index < insert_at {
+                    pc[index] = ParameterId::from_usize(other);
+                } else {
+                    pc[index + 1] = ParameterId::from_usize(other + 1);
+                }
+            }
+            pc[insert_at] = parameter_id;
+            let pc_id = PCList::<ParameterId, LocationsType, { STRENGTH + 1 }>::pc_id(&pc);
+            self.indices.push(unsafe { self.coverage_map.get_base_index_unchecked(pc_id, &self.pc_list, row) });
+
+            if !next_combination(&mut combination, others) {
+                break;
+            }
+        }
+    }
+
+    fn cover(&mut self, index: u64) {
+        let count = &mut self.counts[index as usize];
+        if *count == 0 && unsafe { self.coverage_map.get(index) } {
+            let position = self.missing_positions.remove(&index).unwrap();
+            self.missing.swap_remove(position);
+            if let Some(&moved) = self.missing.get(position) {
+                self.missing_positions.insert(moved, position);
+            }
+        }
+        *count += 1;
+    }
+
+    fn uncover(&mut self, index: u64) {
+        let count = &mut self.counts[index as usize];
+        *count -= 1;
+        if *count == 0 && unsafe { self.coverage_map.get(index) } {
+            self.missing_positions.insert(index, self.missing.len());
+            self.missing.push(index);
+        }
+    }
+
+    /// Returns true if the rows cover every interaction of the `array`, except those with a don't-care value.
+    fn covers(&self, array: &UVec<UVec<ValueId>>) -> bool {
+        array.iter().all(|row| (0..self.pc_list.pcs.len()).all(|pc_id| {
+            self.pc_list.pcs[pc_id].iter().any(|&parameter| row[parameter.as_usize()] == ValueId::dont_care()) || {
+                let index = unsafe { self.coverage_map.get_base_index_unchecked(pc_id, &self.pc_list, row.as_slice()) };
+                self.counts[index as usize] != 0
+            }
+        }))
+    }
+
+    /// Change the value of the cell and update the counts.
+    fn change(&mut self, row_id: usize, parameter: usize, value: ValueId) {
+        self.collect_indices(row_id, parameter);
+        for position in 0..self.indices.len() {
+            self.uncover(self.indices[position]);
+        }
+        self.rows[row_id][parameter] = value;
+        self.collect_indices(row_id, parameter);
+        for position in 0..self.indices.len() {
+            self.cover(self.indices[position]);
+        }
+    }
+
+    /// Returns the number of interactions which only the row covers.
+    fn unique(&self, row_id: usize) -> usize {
+        (0..self.pc_list.pcs.len()).filter(|&pc_id| {
+            let index = unsafe { self.coverage_map.get_base_index_unchecked(pc_id, &self.pc_list, self.rows[row_id].as_slice()) };
+            self.counts[index as usize] == 1 && unsafe { self.coverage_map.get(index) }
+        }).count()
+    }
+
+    fn remove_row(&mut self, row_id: usize) {
+        for pc_id in 0..self.pc_list.pcs.len() {
+            let index = unsafe { self.coverage_map.get_base_index_unchecked(pc_id, &self.pc_list, self.rows[row_id].as_slice()) };
+            self.uncover(index);
+        }
+        self.rows.unwrap_ref_mut().swap_remove(row_id);
+    }
+
+    /// Remove the row covering the fewest interactions on its own and anneal until nothing is missing.
+    ///
+    /// Returns false if the budget was exhausted first, the rows are then not a covering array.
+    fn reduce(&mut self, budget: &mut Budget, valid: &mut IsValid<'_, ValueId>) -> bool {
+        let row_id = (0..self.rows.len()).min_by_key(|&row_id| self.unique(row_id)).unwrap();
+        self.remove_row(row_id);
+
+        let mut temperature = INITIAL_TEMPERATURE;
+        let mut changes: Vec<(usize, ValueId)> = Vec::with_capacity(STRENGTH);
+        let mut iteration = 0;
+        while !self.missing.is_empty() {
+            iteration += 1;
+            if budget.exhausted(iteration) {
+                return false;
+            }
+
+            let position = self.random(self.missing.len());
+            let index = self.missing[position];
+            let (pc_id, values, _) = self.coverage_map.interaction(index);
+            let pc = self.pc_list.pcs[pc_id];
+
+            // Change the sampled row which needs the fewest changes to cover the interaction.
+            let mut row_id = 0;
+            let mut fewest = usize::MAX;
+            for _ in 0..ROW_SAMPLES {
+                let sample = self.random(self.rows.len());
+                let row = &self.rows[sample];
+                let differences = pc.iter().zip(values.iter()).filter(|(&parameter, &value)| row[parameter.as_usize()] != value).count();
+                if differences < fewest {
+                    row_id = sample;
+                    fewest = differences;
+                }
+            }
+
+            changes.clear();
+            let row = &mut self.rows[row_id];
+            for (&parameter, &value) in pc.iter().zip(values.iter()) {
+                let parameter = parameter.as_usize();
+                if row[parameter] != value {
+                    changes.push((parameter, row[parameter]));
+                    row[parameter] = value;
+                }
+            }
+            let allowed = valid(row.as_slice());
+            for &(parameter, old_value) in changes.iter() {
+                row[parameter] = old_value;
+            }
+
+            if allowed {
+                let before = self.missing.len();
+                for (&parameter, &value) in pc.iter().zip(values.iter()) {
+                    if self.rows[row_id][parameter.as_usize()] != value {
+                        self.change(row_id, parameter.as_usize(), value);
+                    }
+                }
+
+                let delta = self.missing.len() as f64 - before as f64;
+                if delta > 0.0 && self.random_fraction() >= (-delta / temperature).exp() {
+                    for &(parameter, old_value) in changes.iter().rev() {
+                        self.change(row_id, parameter, old_value);
+                    }
+                }
+            }
+
+            temperature *= COOLING;
+            if temperature < MIN_TEMPERATURE {
+                temperature = INITIAL_TEMPERATURE;
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use common::{u_vec, UVec};
+use mca::min_coverage;
+
+use super::*;
+
+/// The changes of the searches, bounded so the results do not depend on the speed of the machine.
+const CHANGES: usize = 100_000;
+
+fn exhaustive(parameters: &UVec<usize>) -> MCA<usize, u64> {
+    let mut array = u_vec![u_vec![0; parameters.len()]];
+    let mut row = u_vec![0; parameters.len()];
+    'outer: loop {
+        for (value, &level) in row.iter_mut().zip(parameters.iter()) {
+            *value += 1;
+            if *value < level {
+                array.push(row.clone());
+                continue 'outer;
+            }
+            *value = 0;
+        }
+        break;
+    }
+
+    MCA {
+        dont_care_locations: u_vec![0; array.len()],
+        array,
+        vertical_extension_rows: UVec::default(),
+        new_row: u_vec![!0; parameters.len()],
+        skipped: None,
+    }
+}
+
+#[test]
+fn test_anneal() {
+    let parameters = u_vec![3, 2, 2, 2, 2];
+    let mut mca = exhaustive(&parameters);
+    mca.array[1][4] = !0;
+    mca.dont_care_locations[1] = 1 << 4;
+
+    let removed = anneal_valid::<usize, usize, u64, 2>(&parameters, &mut mca, Budget::Changes(CHANGES), 6, &mut |_| true);
+    assert_eq!(removed + mca.array.len(), 48);
+    assert_eq!(mca.array.len(), 6);
+    assert_eq!(mca.dont_care_locations.len(), mca.array.len());
+    assert!(mca.array.iter().all(|row| row.iter().all(|&value| value != !0)));
+    assert!(min_coverage(parameters.as_slice(), &mca.array, 2, |_, _| true) > 0);
+}
+
+#[test]
+fn test_anneal_constraints() {
+    let parameters = u_vec![3, 3, 2, 2, 2];
+    let mut mca = exhaustive(&parameters);
+
+    // The first two parameters can not both have the value 1.
+    let allowed = |row: &[usize]| row[0] != 1 || row[1] != 1;
+    mca.array.retain(|row| allowed(row.as_slice()));
+    mca.dont_care_locations.truncate(mca.array.len());
+
+    anneal_valid::<usize, usize, u64, 2>(&parameters, &mut mca, Budget::Changes(CHANGES), 1, &mut |row| allowed(row));
+    // Below the product of the largest levels, which only bounds the rows without constraints.
+    assert_eq!(mca.array.len(), 8);
+    assert!(mca.array.iter().all(|row| allowed(row.as_slice())));
+    assert!(min_coverage(parameters.as_slice(), &mca.array, 2, |pc, values| pc != [0, 1] || values != [1, 1]) > 0);
+}
+
+#[test]
+fn test_counts() {
+    let parameters = u_vec![3, 3, 2, 2, 2, 2];
+    let mca = exhaustive(&parameters);
+    let mut annealer = Annealer::<usize, usize, u64, 3>::new(&parameters, mca.array.clone(), SEED);
+
+    assert!(annealer.reduce(&mut Budget::Changes(CHANGES), &mut |_| true));
+    annealer.change(0, 2, 1 - annealer.rows[0][2]);
+
+    let recounted = Annealer::<usize, usize, u64, 3>::new(&parameters, annealer.rows.clone(), SEED);
+    assert_eq!(annealer.counts, recounted.counts.unwrap());
+    let missing = (0..annealer.counts.len()).filter(|&index| annealer.counts[index] == 0).count();
+    assert_eq!(annealer.missing.len(), missing);
+    assert_eq!(annealer.missing_positions.len(), missing);
+    assert!(annealer.missing.iter().enumerate().all(|(position, index)| annealer.missing_positions[index] == position));
+}
+
+#[test]
+fn test_covers() {
+    let parameters = u_vec![2, 2, 2];
+    let mca = exhaustive(&parameters);
+    let annealer = Annealer::<usize, usize, u64, 2>::new(&parameters, mca.array.clone(), SEED);
+    assert!(annealer.covers(&mca.array));
+
+    // Both pairs of rows cover six interactions, but not the same ones.
+    let rows = u_vec![u_vec![0, 0, 0], u_vec![0, 1, 1]];
+    let annealer = Annealer::<usize, usize, u64, 2>::new(&parameters, rows.clone(), SEED);
+    assert!(annealer.covers(&rows));
+    assert!(!annealer.covers(&u_vec![u_vec![0, 0, 0], u_vec![1, 0, 1]]));
+    assert!(annealer.covers(&u_vec![u_vec![0, 0, 0], u_vec![0, 1, !0]]));
+    assert!(!annealer.covers(&u_vec![u_vec![1, 1, !0]]));
+}
@@ -8,19 +8,49 @@
 
 #![deny(missing_docs, rustdoc::missing_crate_level_docs, future_incompatible)]
 
-use std::fs::read_to_string;
 use std::path::PathBuf;
+use std::time::Duration;
+
+pub use logging::init_logging;
+pub use progress::ProgressBar;
 
 pub use clap::crate_version;
-use clap::{App, Arg, ArgMatches};
-use common::{MAX_STRENGTH, MIN_STRENGTH};
-use sut::{parse_constrained, parse_unconstrained, ConstrainedSUT, SUT};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use cm::{Estimate, format_bytes};
+use common::{CancellationToken, MAX_STRENGTH, MIN_STRENGTH, Number, ProgressObserver, Report, ReportObserver, RunOptions, Scoring, SharedReport, UVec};
+use mca::checkpoint::{CheckpointFile, CheckpointHeader, Checkpointer};
+use sut::{parse_constrained_path, parse_unconstrained_path, CachingInit, ConstrainedSUT, CountingInit, DEFAULT_CACHE_CAPACITY, ParameterOrder, SUT};
 
 const INPUT_FILE_ARG: &str = "input_file";
 const OUTPUT_FILE_ARG: &str = "output_file";
 const STRENGTH_ARG: &str = "strength";
 const CONSTRAINTS_ARG: &str = "constraints";
 const NO_CONSTRAINTS_ARG: &str = "no-constraints";
+const PRIORITISE_ARG: &str = "prioritise";
+const LOWER_STRENGTH_ARG: &str = "lower-strength";
+const COVERAGE_REPORT_ARG: &str = "coverage-report";
+const PROGRESS_ARG: &str = "progress";
+const TIME_LIMIT_ARG: &str = "time-limit";
+const COMPLETE_ARG: &str = "complete";
+const VERBOSE_ARG: &str = "verbose";
+const QUIET_ARG: &str = "quiet";
+const REPORT_ARG: &str = "report";
+const CHECKPOINT_ARG: &str = "checkpoint";
+const RESUME_ARG: &str = "resume";
+const MEMORY_BUDGET_ARG: &str = "memory-budget";
+const INTERNAL_ORDER_ARG: &str = "internal-order";
+const ORDER_ARG: &str = "order";
+const IPOG_F_ARG: &str = "ipog-f";
+const IPOG_D_ARG: &str = "ipog-d";
+const ANNEAL_ARG: &str = "anneal";
+const CONSTRUCT_ARG: &str = "construct";
+const FILTER_MAP_ARG: &str = "filter-map";
+const NO_FILTER_MAP_ARG: &str = "no-filter-map";
+const SCORING_ARG: &str = "scoring";
+const CYCLE_SPLIT_ARG: &str = "cycle-split";
+const NO_CYCLE_SPLIT_ARG: &str = "no-cycle-split";
+const SOLVER_CACHE_ARG: &str = "solver-cache";
+const ESTIMATE_COMMAND: &str = "estimate";
 const EXAMPLE_PREFIX: &str = "examples/";
 const BIN_PREFIX: &str = "src/bin/";
 const RUST_EXT: &str = ".rs";
@@ -33,6 +63,160 @@ pub enum SUTWrapper {
     Unconstrained(SUT<usize, usize>),
 }
 
+/// The options provided on the commandline which specify what to do with the generated MCA.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// The file to which the resulting MCA is written.
+    pub output_path: PathBuf,
+
+    /// Reorder the rows of the MCA such that the interactions are covered as early as possible.
+    pub prioritise: bool,
+
+    /// Also consider the interactions of one strength lower when prioritising and reporting.
+    pub lower_strength: bool,
+
+    /// Write the cumulative coverage of the rows to this file.
+    pub coverage_report: Option<PathBuf>,
+
+    /// Show a [ProgressBar] during the generation.
+    pub progress: bool,
+
+    /// Stop the generation after this time and return the MCA built so far.
+    pub time_limit: Option<Duration>,
+
+    /// Complete an MCA of which the generation was stopped by appending a row for each uncovered interaction.
+    pub complete: bool,
+
+    /// The number of `-v` minus the number of `-q` flags, see [init_logging].
+    pub verbosity: i8,
+
+    /// Write the statistics of the run to this file as JSON.
+    pub report_path: Option<PathBuf>,
+
+    /// The statistics of the run, only collected if the [Options::report_path] is set.
+    pub report: SharedReport,
+
+    /// Save the state of the generation to this file after every parameter.
+    pub checkpoint: Option<PathBuf>,
+
+    /// Continue the generation from the [Options::checkpoint].
+    pub resume: bool,
+
+    /// Bound the estimated memory of the generation to this number of bytes, see [Estimate].
+    ///
+    /// Without constraints the coverage map is processed in chunks if it does not fit, see [Options::coverage_map_budget].
+    /// Otherwise the generation refuses to start.
+    pub memory_budget: Option<u128>,
+
+    /// The number of bytes for the map of a chunked coverage map, only set if the whole coverage map exceeds the [Options::memory_budget].
+    pub coverage_map_budget: Option<usize>,
+
+    /// Write the columns in the internal order of the parameters instead of the order in which they were declared.
+    pub internal_order: bool,
+
+    /// Order the parameters with this strategy before the generation, otherwise the order depends on the `no-sort` feature.
+    pub order: Option<ParameterOrder>,
+
+    /// Extend the rows horizontally in the order of IPOG-F, which extends the row with the highest gain first.
+    pub ipog_f: bool,
+
+    /// Generate the MCA with IPOG-D, which doubles the MCA for half of the parameters. Only available without constraints.
+    pub ipog_d: bool,
+
+    /// Remove rows from the generated MCA with simulated annealing for this time, see the anneal crate.
+    pub anneal: Option<Duration>,
+
+    /// Construct the MCA algebraically if the levels allow it, or start IPOG from a construction, see the constructions crate.
+    /// Only available without constraints.
+    pub construct: bool,
+
+    /// The algorithmic choices of the generation, which default to the features the binary was built with.
+    pub run_options: RunOptions,
+
+    /// The `--filter-map` flag was given, as opposed to [RunOptions::filter_map] being set by the `filter-map` feature.
+    pub filter_map_flag: bool,
+
+    /// Answer repeated queries of the solver from a cache, see [sut::CachingSolver].
+    pub solver_cache: bool,
+}
+
+impl Options {
+    /// Create the default options for the provided output path.
+    pub fn new(output_path: PathBuf) -> Self {
+        Self { output_path, ..Self::default() }
+    }
+
+    /// Returns the observer to pass to the generation, which is a [ProgressBar] if requested and ignores the progress otherwise.
+    ///
+    /// If a report is requested, the observer also adds the progress of every parameter to the [Options::report].
+    pub fn progress_observer(&self) -> Box<dyn ProgressObserver> {
+        let observer: Box<dyn ProgressObserver> = if self.progress {
+            Box::new(ProgressBar::new())
+        } else {
+            Box::new(())
+        };
+        if self.report_path.is_some() {
+            Box::new((observer, ReportObserver::new(self.report.clone())))
+        } else {
+            observer
+        }
+    }
+
+    /// Returns the checkpointer to pass to the generation, which uses the [Options::checkpoint] file if provided.
+    ///
+    /// The checkpoint only matches a generation by the same `generator` with the same SUT, strength and [Options::checkpoint_options].
+    /// Returns an error if the generation should resume, but the checkpoint can not be read, does not match or does not fit the levels of the `parameters`.
+    pub fn checkpointer<ValueId: Number, LocationsType: Number>(
+        &self, sut_fingerprint: u64, parameters: &[ValueId], strength: usize, generator: &str,
+    ) -> Result<Box<dyn Checkpointer<ValueId, LocationsType>>, String> {
+        match &self.checkpoint {
+            Some(path) => {
+                let header = CheckpointHeader { sut: sut_fingerprint, strength, generator: generator.to_string(), options: self.checkpoint_options() };
+                Ok(Box::new(CheckpointFile::new(path.clone(), header, parameters, self.resume)?))
+            }
+            None => Ok(Box::new(())),
+        }
+    }
+
+    /// Returns the options which change the generated MCA, as stored in the header of the checkpoint.
+    pub fn checkpoint_options(&self) -> String {
+        format!(
+            "scoring={:?} filter_map={} cycle_split={} coverage_map_budget={:?} construct={} order={:?}",
+            self.run_options.scoring, self.run_options.filter_map, self.run_options.cycle_split, self.coverage_map_budget, self.construct, self.order,
+        )
+    }
+
+    /// Apply the update to the [Options::report], if a report is requested.
+    pub fn update_report(&self, update: impl FnOnce(&mut Report)) {
+        if self.report_path.is_some() {
+            update(&mut self.report.lock().unwrap());
+        }
+    }
+
+    /// Returns the init of a [sut::CachingSolver] around solvers created with the `init`, which only caches if the [Options::solver_cache] is set.
+    pub fn caching_init<I>(&self, init: I) -> CachingInit<I> {
+        CachingInit::new(init, if self.solver_cache { DEFAULT_CACHE_CAPACITY } else { 0 })
+    }
+
+    /// Add the calls of the solvers created with the `init` and the hits of their caches to the [Options::report], if a report is requested.
+    pub fn report_solver<I>(&self, init: &CachingInit<CountingInit<I>>) {
+        self.update_report(|report| {
+            report.solver_calls = init.init.statistics.calls();
+            report.solver_cache = self.solver_cache.then(|| init.statistics.hits());
+        });
+    }
+
+    /// Returns the token to pass to the generation, which is cancelled once the [Options::time_limit] has passed.
+    ///
+    /// The time limit starts when this method is called.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        match self.time_limit {
+            Some(time_limit) => CancellationToken::with_time_limit(time_limit),
+            None => CancellationToken::new(),
+        }
+    }
+}
+
 fn get_app<'a, 'b>(app_name: &'a str, short_version: &'a str, long_version: &'a str) -> App<'a, 'b>
 where
     'a: 'b,
@@ -40,11 +224,15 @@ where
     App::new(app_name)
         .version(short_version)
         .long_version(long_version)
-        .arg(
-            Arg::with_name(INPUT_FILE_ARG)
-                .required(true)
-                .help("Set the input file with the definition of the system."),
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .subcommand(
+            SubCommand::with_name(ESTIMATE_COMMAND)
+                .about("Print the memory and the number of tests the generation is expected to need, without generating.")
+                .arg(input_file_arg())
+                .arg(strength_arg())
+                .arg(memory_budget_arg()),
         )
+        .arg(input_file_arg())
         .arg(
             Arg::with_name(OUTPUT_FILE_ARG)
                 .short("o")
@@ -53,14 +241,7 @@ where
                 .default_value("result.txt")
                 .help("Set the output file."),
         )
-        .arg(
-            Arg::with_name(STRENGTH_ARG)
-                .short("s")
-                .long("strength")
-                .takes_value(true)
-                .required(true)
-                .help("Set the strength of the resulting test suite."),
-        )
+        .arg(strength_arg())
         .arg(
             Arg::with_name(CONSTRAINTS_ARG)
                 .short("c")
@@ -77,9 +258,230 @@ where
                 .required_unless(CONSTRAINTS_ARG)
                 .help("Do not use the constraints in the provided file."),
         )
+        .arg(
+            Arg::with_name(PRIORITISE_ARG)
+                .short("p")
+                .long("prioritise")
+                .help("Reorder the tests such that each next test covers the most new interactions."),
+        )
+        .arg(
+            Arg::with_name(LOWER_STRENGTH_ARG)
+                .long("lower-strength")
+                .help("Also use the interactions of one strength lower when prioritising and reporting."),
+        )
+        .arg(
+            Arg::with_name(COVERAGE_REPORT_ARG)
+                .long("coverage-report")
+                .takes_value(true)
+                .help("Write the cumulative coverage of the tests to the given file."),
+        )
+        .arg(
+            Arg::with_name(PROGRESS_ARG)
+                .long("progress")
+                .help("Show the progress of the generation with an estimate of the remaining time."),
+        )
+        .arg(
+            Arg::with_name(TIME_LIMIT_ARG)
+                .long("time-limit")
+                .takes_value(true)
+                .value_name("seconds")
+                .help("Stop the generation after the given number of seconds and write the incomplete test suite."),
+        )
+        .arg(
+            Arg::with_name(COMPLETE_ARG)
+                .long("complete")
+                .requires(TIME_LIMIT_ARG)
+                .help("Complete a stopped generation with one test per uncovered interaction, so the test suite is valid."),
+        )
+        .arg(
+            Arg::with_name(VERBOSE_ARG)
+                .short("v")
+                .long("verbose")
+                .multiple(true)
+                .help("Log more details to the standard error, repeat for even more details."),
+        )
+        .arg(
+            Arg::with_name(QUIET_ARG)
+                .short("q")
+                .long("quiet")
+                .multiple(true)
+                .help("Log less to the standard error, repeat to only log errors or nothing at all."),
+        )
+        .arg(
+            Arg::with_name(REPORT_ARG)
+                .long("report")
+                .takes_value(true)
+                .help("Write the timings and statistics of the run to the given file as JSON."),
+        )
+        .arg(
+            Arg::with_name(CHECKPOINT_ARG)
+                .long("checkpoint")
+                .takes_value(true)
+                .help("Save the state of the generation to the given file after every parameter."),
+        )
+        .arg(
+            Arg::with_name(RESUME_ARG)
+                .long("resume")
+                .requires(CHECKPOINT_ARG)
+                .help("Continue the generation from the checkpoint, the result equals that of an uninterrupted generation."),
+        )
+        .arg(memory_budget_arg())
+        .arg(
+            Arg::with_name(INTERNAL_ORDER_ARG)
+                .long("internal-order")
+                .help("Write the parameters in the order used during the generation instead of the order of the input file."),
+        )
+        .arg(
+            Arg::with_name(ORDER_ARG)
+                .long("order")
+                .takes_value(true)
+                .value_name("strategy")
+                .help("Order the parameters for the generation: descending, ascending, declaration, constraints (most constrained first), random:<seed> or list:<name>,<name>,... (listed first, quoted as in the model). Does not change the order of the columns in the output."),
+        )
+        .arg(
+            Arg::with_name(IPOG_F_ARG)
+                .long("ipog-f")
+                .help("Extend the row with the highest gain first, as in IPOG-F. This usually results in fewer rows, but takes longer."),
+        )
+        .arg(
+            Arg::with_name(IPOG_D_ARG)
+                .long("ipog-d")
+                .help("Double the MCA for half of the parameters, as in IPOG-D. This is much faster for many parameters, but results in more rows. Only available without constraints."),
+        )
+        .arg(
+            Arg::with_name(ANNEAL_ARG)
+                .long("anneal")
+                .takes_value(true)
+                .value_name("seconds")
+                .help("Try to remove rows from the generated test suite with simulated annealing for the given number of seconds."),
+        )
+        .arg(
+            Arg::with_name(CONSTRUCT_ARG)
+                .long("construct")
+                .help("Construct the test suite algebraically if all parameters have the same level and a construction is known, otherwise start IPOG from an orthogonal array for the first parameters. Only available without constraints."),
+        )
+        .arg(
+            Arg::with_name(FILTER_MAP_ARG)
+                .long("filter-map")
+                .conflicts_with(NO_FILTER_MAP_ARG)
+                .help("Mark the interactions disallowed by the constraints as covered before extending each parameter. Only available in the single-threaded generation."),
+        )
+        .arg(
+            Arg::with_name(NO_FILTER_MAP_ARG)
+                .long("no-filter-map")
+                .conflicts_with(FILTER_MAP_ARG)
+                .help("Do not mark the disallowed interactions as covered before extending each parameter."),
+        )
+        .arg(
+            Arg::with_name(SCORING_ARG)
+                .long("scoring")
+                .takes_value(true)
+                .value_name("algorithm")
+                .help("Calculate the scores of the horizontal extension with the given algorithm: single, double or triple. All algorithms result in the same test suite."),
+        )
+        .arg(
+            Arg::with_name(CYCLE_SPLIT_ARG)
+                .long("cycle-split")
+                .conflicts_with(NO_CYCLE_SPLIT_ARG)
+                .help("Cycle the division of the work between the worker threads of the multithreaded generation."),
+        )
+        .arg(
+            Arg::with_name(NO_CYCLE_SPLIT_ARG)
+                .long("no-cycle-split")
+                .conflicts_with(CYCLE_SPLIT_ARG)
+                .help("Give each worker thread of the multithreaded generation the same part of the work for every row."),
+        )
+        .arg(
+            Arg::with_name(SOLVER_CACHE_ARG)
+                .long("solver-cache")
+                .help("Answer repeated checks of the constraints from a cache instead of the solver. Only available in the single-threaded generation."),
+        )
 }
 
-fn validate_args(matches: ArgMatches) -> Result<(PathBuf, PathBuf, usize, bool), String> {
+fn input_file_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(INPUT_FILE_ARG)
+        .required(true)
+        .help("Set the input file with the definition of the system.")
+}
+
+fn strength_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(STRENGTH_ARG)
+        .short("s")
+        .long("strength")
+        .takes_value(true)
+        .required(true)
+        .help("Set the strength of the resulting test suite.")
+}
+
+fn memory_budget_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(MEMORY_BUDGET_ARG)
+        .long("memory-budget")
+        .takes_value(true)
+        .value_name("bytes")
+        .help("Bound the memory of the generation, the number can be followed by K, M, G or T. Without constraints the coverage map is processed in chunks if needed, otherwise the generation refuses to start.")
+}
+
+/// Parse a number of bytes, optionally followed by a binary unit such as `512M` or `4GiB`.
+fn parse_bytes(text: &str) -> Result<u128, String> {
+    let text = text.trim();
+    let digits = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let shift = match text[digits..].trim_start().to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 10,
+        "M" | "MB" | "MIB" => 20,
+        "G" | "GB" | "GIB" => 30,
+        "T" | "TB" | "TIB" => 40,
+        _ => return Err(format!("Invalid unit in '{}', use K, M, G or T.", text)),
+    };
+    let number = text[..digits]
+        .parse::<u128>()
+        .map_err(|_| format!("The memory budget should be a number of bytes, not '{}'.", text))?;
+    number.checked_mul(1 << shift).ok_or_else(|| format!("The memory budget '{}' is too large.", text))
+}
+
+fn parse_memory_budget(matches: &ArgMatches) -> Result<Option<u128>, String> {
+    matches.value_of(MEMORY_BUDGET_ARG).map(parse_bytes).transpose()
+}
+
+/// Parse the [RunOptions], a flag which is not given keeps the default of the binary.
+fn parse_run_options(matches: &ArgMatches) -> Result<RunOptions, String> {
+    let defaults = RunOptions::default();
+    Ok(RunOptions {
+        filter_map: (defaults.filter_map || matches.is_present(FILTER_MAP_ARG)) && !matches.is_present(NO_FILTER_MAP_ARG),
+        scoring: matches.value_of(SCORING_ARG).map(Scoring::parse).transpose()?.unwrap_or(defaults.scoring),
+        cycle_split: (defaults.cycle_split || matches.is_present(CYCLE_SPLIT_ARG)) && !matches.is_present(NO_CYCLE_SPLIT_ARG),
+    })
+}
+
+/// Parse the value of the argument as a non-negative number of seconds, the `name` is used in the error.
+fn parse_seconds(matches: &ArgMatches, arg: &str, name: &str) -> Result<Option<Duration>, String> {
+    match matches.value_of(arg) {
+        Some(seconds) => match seconds.parse::<f64>() {
+            Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => Ok(Some(Duration::from_secs_f64(seconds))),
+            _ => Err(format!("The {} should be a non-negative number of seconds.", name)),
+        },
+        None => Ok(None),
+    }
+}
+
+fn parse_strength(matches: &ArgMatches) -> Result<usize, String> {
+    let strength = matches
+        .value_of(STRENGTH_ARG)
+        .ok_or("The strength argument is required.")?
+        .parse::<usize>()
+        .map_err(|_| "The strength argument should be a number.".to_string())?;
+
+    if strength < MIN_STRENGTH || MAX_STRENGTH < strength {
+        Err(format!(
+            "Please provide a strength between {} and {}.",
+            MIN_STRENGTH, MAX_STRENGTH
+        ))
+    } else {
+        Ok(strength)
+    }
+}
+
+fn validate_args(matches: ArgMatches) -> Result<(PathBuf, Options, usize, bool), String> {
     let input_path = PathBuf::from(
         matches
             .value_of(INPUT_FILE_ARG)
@@ -96,20 +498,50 @@ fn validate_args(matches: ArgMatches) -> Result<(PathBuf, PathBuf, usize, bool),
         return Err("Input and output should not be the same!".to_string())
     }
 
-    let strength = matches
-        .value_of(STRENGTH_ARG)
-        .ok_or("The strength argument is required.")?
-        .parse::<usize>()
-        .map_err(|_| "The strength argument should be a number.".to_string())?;
+    let coverage_report = matches.value_of(COVERAGE_REPORT_ARG).map(PathBuf::from);
+    if coverage_report.iter().any(|path| path == &input_path || path == &output_path) {
+        return Err("The coverage report should not overwrite the input or output!".to_string())
+    }
 
-    if strength < MIN_STRENGTH || MAX_STRENGTH < strength {
-        Err(format!(
-            "Please provide a strength between {} and {}.",
-            MIN_STRENGTH, MAX_STRENGTH
-        ))
-    } else {
-        Ok((input_path, output_path, strength, matches.is_present(CONSTRAINTS_ARG)))
+    let report_path = matches.value_of(REPORT_ARG).map(PathBuf::from);
+    if report_path.iter().any(|path| path == &input_path || path == &output_path || coverage_report.as_ref() == Some(path)) {
+        return Err("The report should not overwrite the input, output or coverage report!".to_string())
+    }
+
+    let checkpoint = matches.value_of(CHECKPOINT_ARG).map(PathBuf::from);
+    if checkpoint.iter().any(|path| path == &input_path || path == &output_path || coverage_report.as_ref() == Some(path) || report_path.as_ref() == Some(path)) {
+        return Err("The checkpoint should not overwrite the input, output, coverage report or report!".to_string())
     }
+
+    let options = Options {
+        output_path,
+        prioritise: matches.is_present(PRIORITISE_ARG),
+        lower_strength: matches.is_present(LOWER_STRENGTH_ARG),
+        coverage_report,
+        progress: matches.is_present(PROGRESS_ARG),
+        time_limit: parse_seconds(&matches, TIME_LIMIT_ARG, "time limit")?,
+        complete: matches.is_present(COMPLETE_ARG),
+        verbosity: (matches.occurrences_of(VERBOSE_ARG) as i64 - matches.occurrences_of(QUIET_ARG) as i64)
+            .clamp(i8::MIN as i64, i8::MAX as i64) as i8,
+        report_path,
+        report: SharedReport::default(),
+        checkpoint,
+        resume: matches.is_present(RESUME_ARG),
+        memory_budget: parse_memory_budget(&matches)?,
+        coverage_map_budget: None,
+        internal_order: matches.is_present(INTERNAL_ORDER_ARG),
+        order: matches.value_of(ORDER_ARG).map(ParameterOrder::parse).transpose()?,
+        ipog_f: matches.is_present(IPOG_F_ARG),
+        ipog_d: matches.is_present(IPOG_D_ARG),
+        anneal: parse_seconds(&matches, ANNEAL_ARG, "annealing time")?,
+        construct: matches.is_present(CONSTRUCT_ARG),
+        run_options: parse_run_options(&matches)?,
+        filter_map_flag: matches.is_present(FILTER_MAP_ARG),
+        solver_cache: matches.is_present(SOLVER_CACHE_ARG),
+    };
+
+    let strength = parse_strength(&matches)?;
+    Ok((input_path, options, strength, matches.is_present(CONSTRAINTS_ARG)))
 }
 
 fn check_sizes(strength: usize, parameters: usize) -> Result<(), String> {
@@ -120,25 +552,80 @@ fn check_sizes(strength: usize, parameters: usize) -> Result<(), String> {
     }
 }
 
-fn load_sut(args: (PathBuf, PathBuf, usize, bool)) -> Result<(SUTWrapper, PathBuf, usize), String> {
-    let contents = read_to_string(args.0).or_else(|e| Err(e.to_string()))?;
-    if args.3 {
-        let sut = parse_constrained(contents.as_str())?;
-        check_sizes(args.2, sut.sub_sut.parameters.len())?;
+/// Returns the [Estimate] for the parameters, using the PC list types of the binaries of LibreCA.
+fn estimate_sut(strength: usize, parameters: &UVec<usize>) -> Estimate {
+    Estimate::new::<u8, u128>(parameters.as_slice(), strength)
+}
+
+fn check_memory_budget(strength: usize, parameters: &UVec<usize>, options: &Options) -> Result<(), String> {
+    match options.memory_budget {
+        Some(budget) => estimate_sut(strength, parameters).check_budget(budget)
+            .map_err(|e| format!("{} Run the `{}` command for details.", e, ESTIMATE_COMMAND)),
+        None => Ok(()),
+    }
+}
+
+/// Returns the [Options::coverage_map_budget] for the parameters, or an error if even a chunked coverage map exceeds the memory budget.
+fn coverage_map_budget(strength: usize, parameters: &UVec<usize>, options: &Options) -> Result<Option<usize>, String> {
+    let budget = match options.memory_budget {
+        Some(budget) => estimate_sut(strength, parameters).coverage_map_budget(budget)
+            .map_err(|e| format!("{} Run the `{}` command for details.", e, ESTIMATE_COMMAND))?,
+        None => None,
+    };
+    if budget.is_some() && (options.prioritise || options.coverage_report.is_some()) {
+        return Err("Prioritising and the coverage report need the whole coverage map, which exceeds the memory budget.".into());
+    }
+    Ok(budget.map(|bytes| bytes.min(usize::MAX as u128) as usize))
+}
+
+/// Print the [Estimate] for the arguments of the `estimate` command, returns an error if it exceeds the memory budget.
+fn run_estimate(matches: &ArgMatches) -> Result<(), String> {
+    let input_path = PathBuf::from(matches.value_of(INPUT_FILE_ARG).ok_or("The input file should be provided")?);
+    let strength = parse_strength(matches)?;
+    let memory_budget = parse_memory_budget(matches)?;
+
+    let sut = parse_unconstrained_path(&input_path)?;
+    check_sizes(strength, sut.parameters.len())?;
+    let estimate = estimate_sut(strength, &sut.parameters);
+    println!("{}", estimate);
+    if let Some(bytes) = memory_budget.map(|budget| estimate.coverage_map_budget(budget)).transpose()?.flatten() {
+        println!("The coverage map exceeds the memory budget, without constraints it is processed in chunks of at most {}.", format_bytes(bytes));
+    }
+    Ok(())
+}
+
+fn load_sut(args: (PathBuf, Options, usize, bool)) -> Result<(SUTWrapper, Options, usize), String> {
+    let (input_path, mut options, strength, constraints) = args;
+    let sut = if constraints {
+        let mut sut = parse_constrained_path(&input_path)?;
+        check_sizes(strength, sut.sub_sut.parameters.len())?;
+        if let Some(order) = &options.order {
+            sut.order_parameters(order)?;
+        }
         if sut.has_constraints() {
-            Ok((SUTWrapper::Constrained(sut), args.1, args.2))
-        } else {
-            Ok((SUTWrapper::Unconstrained(sut.sub_sut), args.1, args.2))
+            check_memory_budget(strength, &sut.sub_sut.parameters, &options)?;
+            return Ok((SUTWrapper::Constrained(sut), options, strength));
         }
+        sut.sub_sut
     } else {
-        let sut = parse_unconstrained(contents.as_str())?;
-        check_sizes(args.2, sut.parameters.len())?;
-        Ok((SUTWrapper::Unconstrained(sut), args.1, args.2))
-    }
+        let mut sut = parse_unconstrained_path(&input_path)?;
+        check_sizes(strength, sut.parameters.len())?;
+        if let Some(order) = &options.order {
+            sut.order_parameters(order)?;
+        }
+        sut
+    };
+    options.coverage_map_budget = coverage_map_budget(strength, &sut.parameters, &options)?;
+    Ok((SUTWrapper::Unconstrained(sut), options, strength))
 }
 
-/// Parse the commandline arguments and return the [ConstrainedSUT] or [SUT], and strength for which an MCA should be created at the given output path.
-pub fn parse_arguments(mut app_name: &str, version: &str) -> Result<(SUTWrapper, PathBuf, usize), String> {
+/// Parse the commandline arguments and return the [ConstrainedSUT] or [SUT], the [Options] and strength for which an MCA should be created.
+///
+/// Installs the logging of the cli with the requested verbosity, see [init_logging].
+///
+/// The `estimate` command prints the [Estimate] for the SUT and exits, like `--help` does, instead of returning.
+/// The generation is refused if the [Options::memory_budget] is too small for the [Estimate], unless a chunked coverage map fits, see [Options::coverage_map_budget].
+pub fn parse_arguments(mut app_name: &str, version: &str) -> Result<(SUTWrapper, Options, usize), String> {
     if app_name.ends_with(RUST_EXT) {
         app_name = &app_name[..app_name.len() - RUST_EXT.len()];
     }
@@ -153,9 +640,19 @@ pub fn parse_arguments(mut app_name: &str, version: &str) -> Result<(SUTWrapper,
     let long_version = format!("v{} ({})", version, env!("GIT_HASH"));
 
     let matches = get_app(app_name, short_version.as_str(), long_version.as_str()).get_matches();
+    if let Some(matches) = matches.subcommand_matches(ESTIMATE_COMMAND) {
+        run_estimate(matches)?;
+        std::process::exit(0);
+    }
 
-    load_sut(validate_args(matches)?)
+    let args = validate_args(matches)?;
+    init_logging(args.1.verbosity, args.1.report_path.as_ref().map(|_| args.1.report.clone()));
+    common::time_it!(load_sut(args), "Parsing")
 }
 
+mod logging;
+mod progress;
+mod report;
+
 #[cfg(test)]
 mod test_lib;
@@ -0,0 +1,44 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use common::SharedReport;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::prelude::*;
+
+use crate::report::{is_reported, ReportLayer};
+
+/// Returns the maximum level to log for the verbosity, which is the number of `-v` minus the number of `-q` flags.
+pub(crate) fn level_filter(verbosity: i8) -> LevelFilter {
+    match verbosity {
+        i8::MIN..=-3 => LevelFilter::OFF,
+        -2 => LevelFilter::ERROR,
+        -1 => LevelFilter::WARN,
+        0 => LevelFilter::INFO,
+        1 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// Install a subscriber which writes the events and the durations of the spans to the standard error.
+///
+/// If a report is provided, the durations of all the phases and the size of the largest coverage map are added to it,
+/// independent of the verbosity.
+///
+/// Does nothing if a global subscriber has already been installed, for example by the user of the library.
+pub fn init_logging(verbosity: i8, report: Option<SharedReport>) {
+    let _ = tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_span_events(FmtSpan::CLOSE)
+                .with_target(false)
+                .with_filter(level_filter(verbosity)),
+        )
+        .with(report.map(|report| ReportLayer::new(report).with_filter(filter_fn(is_reported))))
+        .try_init();
+}
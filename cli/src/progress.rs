@@ -0,0 +1,94 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::io::{stderr, Write};
+use std::time::{Duration, Instant};
+
+use common::{Phase, Progress, ProgressObserver};
+
+/// The number of characters of the bar itself.
+const BAR_WIDTH: usize = 30;
+
+/// The minimal time between two redraws of the bar, except for the final one.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Renders the progress of the generation as a single line on the standard error, with an estimate of the remaining time.
+pub struct ProgressBar {
+    start: Instant,
+    last_draw: Option<Instant>,
+    last_length: usize,
+}
+
+impl ProgressBar {
+    /// Create a new [ProgressBar], the remaining time is estimated from the time since its creation.
+    pub fn new() -> Self {
+        Self { start: Instant::now(), last_draw: None, last_length: 0 }
+    }
+}
+
+impl Default for ProgressBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Format the duration as hours, minutes and seconds, leaving out the leading zero parts.
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!("{}h{:02}m{:02}s", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    } else if seconds >= 60 {
+        format!("{}m{:02}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// Render the line for the progress after the elapsed time.
+pub(crate) fn render(progress: &Progress, elapsed: Duration) -> String {
+    let fraction = progress.fraction();
+    let filled = (fraction * BAR_WIDTH as f64) as usize;
+    let mut bar = "=".repeat(filled);
+    if filled < BAR_WIDTH {
+        bar.push('>');
+        bar.push_str(&" ".repeat(BAR_WIDTH - filled - 1));
+    }
+
+    let time = match progress.phase {
+        Phase::Done => format!("took {}", format_duration(elapsed)),
+        _ if fraction > 0.0 => format!("ETA {}", format_duration(elapsed.mul_f64((1.0 - fraction) / fraction))),
+        _ => "ETA ?".to_string(),
+    };
+    let phase = match progress.phase {
+        Phase::Horizontal => "horizontal",
+        Phase::Vertical => "vertical",
+        Phase::Done => "done",
+    };
+
+    format!(
+        "[{}] {:5.1}% parameter {}/{} {}, {} rows, {} uncovered, {}",
+        bar, fraction * 100.0, progress.at_parameter, progress.parameters, phase, progress.rows, progress.uncovered, time,
+    )
+}
+
+impl ProgressObserver for ProgressBar {
+    fn progress(&mut self, progress: &Progress) {
+        let now = Instant::now();
+        let done = progress.phase == Phase::Done;
+        if !done && self.last_draw.map_or(false, |last| now.duration_since(last) < REDRAW_INTERVAL) {
+            return;
+        }
+        self.last_draw = Some(now);
+
+        let line = render(progress, now.duration_since(self.start));
+        let padding = self.last_length.saturating_sub(line.len());
+        self.last_length = line.len();
+
+        let mut stderr = stderr().lock();
+        let _ = write!(stderr, "\r{}{}", line, " ".repeat(padding));
+        let _ = if done { writeln!(stderr) } else { stderr.flush() };
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::fmt::Debug;
+use std::time::Instant;
+
+use common::SharedReport;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Metadata, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// The name of the spans created by [common::time_it].
+const TIME_SPAN: &str = "time";
+
+/// The field of the span with the name of the phase.
+const PHASE_FIELD: &str = "phase";
+
+/// The field of the event emitted when a coverage map is allocated.
+const COVERAGE_MAP_FIELD: &str = "coverage_map_bytes";
+
+/// The start of a `time` span, stored in the extensions of the span.
+struct PhaseStart {
+    phase: String,
+    start: Instant,
+}
+
+/// Collects the phase name or the coverage map size from the fields of a span or event.
+#[derive(Default)]
+struct FieldVisitor {
+    phase: Option<String>,
+    coverage_map_bytes: Option<usize>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == COVERAGE_MAP_FIELD {
+            self.coverage_map_bytes = Some(value as usize);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == PHASE_FIELD {
+            self.phase = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == PHASE_FIELD {
+            self.phase = Some(format!("{:?}", value));
+        }
+    }
+}
+
+/// Returns true for the spans and events used by the [ReportLayer], whatever their level.
+pub(crate) fn is_reported(metadata: &Metadata<'_>) -> bool {
+    if metadata.is_span() {
+        metadata.name() == TIME_SPAN
+    } else {
+        metadata.fields().field(COVERAGE_MAP_FIELD).is_some()
+    }
+}
+
+/// Adds the durations of the `time` spans and the size of the largest coverage map to the report.
+pub(crate) struct ReportLayer {
+    report: SharedReport,
+}
+
+impl ReportLayer {
+    pub(crate) fn new(report: SharedReport) -> Self {
+        Self { report }
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for ReportLayer {
+    fn on_new_span(&self, attributes: &Attributes<'_>, id: &Id, context: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        attributes.record(&mut visitor);
+        if let (Some(phase), Some(span)) = (visitor.phase, context.span(id)) {
+            span.extensions_mut().insert(PhaseStart { phase, start: Instant::now() });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        if let Some(bytes) = visitor.coverage_map_bytes {
+            let mut report = self.report.lock().unwrap();
+            report.peak_coverage_map_bytes = report.peak_coverage_map_bytes.max(bytes);
+        }
+    }
+
+    fn on_close(&self, id: Id, context: Context<'_, S>) {
+        if let Some(span) = context.span(&id) {
+            if let Some(phase_start) = span.extensions().get::<PhaseStart>() {
+                self.report.lock().unwrap().add_phase_timing(&phase_start.phase, phase_start.start.elapsed());
+            }
+        }
+    }
+}
@@ -45,3 +45,340 @@ fn test_validate_strength() {
     )
     .is_err());
 }
+
+#[test]
+fn test_validate_options() {
+    let (_, options, _, _) = validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c"])
+    ).unwrap();
+    assert!(!options.prioritise);
+    assert!(!options.lower_strength);
+    assert_eq!(options.coverage_report, None);
+    assert!(!options.progress);
+    assert_eq!(options.time_limit, None);
+    assert!(!options.complete);
+
+    let (_, options, _, _) = validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "-p", "--lower-strength", "--coverage-report", "report.csv", "--progress"])
+    ).unwrap();
+    assert!(options.progress);
+    assert!(options.prioritise);
+    assert!(options.lower_strength);
+    assert_eq!(options.coverage_report, Some(PathBuf::from("report.csv")));
+
+    let (_, options, _, _) = validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--time-limit", "1.5", "--complete"])
+    ).unwrap();
+    assert_eq!(options.time_limit, Some(std::time::Duration::from_millis(1500)));
+    assert!(options.complete);
+    assert_eq!(options.anneal, None);
+
+    let (_, options, _, _) = validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--anneal", "2"])
+    ).unwrap();
+    assert_eq!(options.anneal, Some(std::time::Duration::from_secs(2)));
+    assert!(validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--anneal", "long"])
+    )
+    .is_err());
+
+    assert!(validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--time-limit=-1"])
+    )
+    .is_err());
+    assert!(validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--time-limit", "soon"])
+    )
+    .is_err());
+    assert!(get_app("", "", "").get_matches_from_safe(&["exe", "-s", "2", "ignored", "-c", "--complete"]).is_err());
+
+    let (_, options, _, _) = validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--order", "random:3", "--internal-order"])
+    ).unwrap();
+    assert_eq!(options.order, Some(ParameterOrder::Random(3)));
+    assert!(options.internal_order);
+    assert!(!options.ipog_f);
+    let (_, options, _, _) = validate_args(get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-n", "--ipog-f", "--ipog-d"])).unwrap();
+    assert!(options.ipog_f);
+    assert!(options.ipog_d);
+    assert!(!options.construct);
+    let (_, options, _, _) = validate_args(get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-n", "--construct"])).unwrap();
+    assert!(options.construct);
+    assert!(validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--order", "best"])
+    )
+    .is_err());
+}
+
+#[test]
+fn test_run_options() {
+    let run_options = |args: &[&str]| validate_args(
+        get_app("", "", "").get_matches_from([&["exe", "-s", "2", "ignored", "-c"], args].concat())
+    ).map(|(_, options, _, _)| options.run_options);
+    assert_eq!(run_options(&[]), Ok(RunOptions::default()));
+
+    let options = run_options(&["--filter-map", "--scoring", "single", "--no-cycle-split"]).unwrap();
+    assert_eq!(options, RunOptions { filter_map: true, scoring: Scoring::Single, cycle_split: false });
+    let options = run_options(&["--no-filter-map", "--scoring=triple", "--cycle-split"]).unwrap();
+    assert_eq!(options, RunOptions { filter_map: false, scoring: Scoring::Triple, cycle_split: true });
+    assert_eq!(run_options(&["--scoring", "double"]).unwrap().scoring, Scoring::Double);
+
+    let filter_map_flag = |args: &[&str]| validate_args(
+        get_app("", "", "").get_matches_from([&["exe", "-s", "2", "ignored", "-c"], args].concat())
+    ).unwrap().1.filter_map_flag;
+    assert!(!filter_map_flag(&[]));
+    assert!(!filter_map_flag(&["--no-filter-map"]));
+    assert!(filter_map_flag(&["--filter-map"]));
+
+    assert!(run_options(&["--scoring", "quadruple"]).is_err());
+    assert!(get_app("", "", "").get_matches_from_safe(&["exe", "-s", "2", "ignored", "-c", "--filter-map", "--no-filter-map"]).is_err());
+    assert!(get_app("", "", "").get_matches_from_safe(&["exe", "-s", "2", "ignored", "-c", "--cycle-split", "--no-cycle-split"]).is_err());
+}
+
+#[test]
+fn test_verbosity() {
+    use tracing::level_filters::LevelFilter;
+    use logging::level_filter;
+
+    let verbosity = |args: &[&str]| validate_args(
+        get_app("", "", "").get_matches_from([&["exe", "-s", "2", "ignored", "-c"], args].concat())
+    ).unwrap().1.verbosity;
+    assert_eq!(verbosity(&[]), 0);
+    assert_eq!(verbosity(&["-vv"]), 2);
+    assert_eq!(verbosity(&["-q", "--verbose", "-qq"]), -2);
+
+    assert_eq!(level_filter(-5), LevelFilter::OFF);
+    assert_eq!(level_filter(-2), LevelFilter::ERROR);
+    assert_eq!(level_filter(-1), LevelFilter::WARN);
+    assert_eq!(level_filter(0), LevelFilter::INFO);
+    assert_eq!(level_filter(1), LevelFilter::DEBUG);
+    assert_eq!(level_filter(3), LevelFilter::TRACE);
+}
+
+#[test]
+fn test_report() {
+    let (_, options, _, _) = validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c"])
+    ).unwrap();
+    assert_eq!(options.report_path, None);
+    options.update_report(|report| report.rows = 3);
+    assert_eq!(options.report.lock().unwrap().rows, 0);
+
+    let (_, options, _, _) = validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--report", "report.json"])
+    ).unwrap();
+    assert_eq!(options.report_path, Some(PathBuf::from("report.json")));
+    options.update_report(|report| report.rows = 3);
+    assert_eq!(options.report.lock().unwrap().rows, 3);
+
+    assert!(validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--report", "result.txt"])
+    )
+    .is_err());
+    assert!(validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--coverage-report", "r.json", "--report", "r.json"])
+    )
+    .is_err());
+}
+
+#[test]
+fn test_checkpoint() {
+    let (_, options, _, _) = validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c"])
+    ).unwrap();
+    assert_eq!(options.checkpoint, None);
+    assert!(!options.resume);
+
+    let (_, options, _, _) = validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--checkpoint", "c.txt", "--resume"])
+    ).unwrap();
+    assert_eq!(options.checkpoint, Some(PathBuf::from("c.txt")));
+    assert!(options.resume);
+
+    assert!(get_app("", "", "").get_matches_from_safe(&["exe", "-s", "2", "ignored", "-c", "--resume"]).is_err());
+    assert!(validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--checkpoint", "result.txt"])
+    )
+    .is_err());
+    assert!(validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--report", "r.json", "--checkpoint", "r.json"])
+    )
+    .is_err());
+}
+
+#[test]
+fn test_resume_options() {
+    use common::u_vec;
+    use mca::MCA;
+
+    let path = std::env::temp_dir().join(format!("libreca-resume-options-{}.txt", std::process::id()));
+    let path_text = path.to_str().unwrap();
+    let options = |extra: &[&str]| {
+        let mut args = vec!["exe", "-s", "2", "ignored", "-n", "--checkpoint", path_text];
+        args.extend_from_slice(extra);
+        validate_args(get_app("", "", "").get_matches_from(args)).unwrap().1
+    };
+    let parameters = u_vec![2_u8, 2, 2];
+    let mut checkpoint = options(&["--scoring", "single"]).checkpointer::<u8, u64>(1, parameters.as_slice(), 2, "ipog-single").unwrap();
+    let mut mca = MCA::<u8, u64>::new_empty();
+    mca.array.push(u_vec![0, 1, 1]);
+    mca.dont_care_locations.push(0);
+    checkpoint.save(3, &mca);
+
+    assert!(options(&["--scoring", "single", "--resume"]).checkpointer::<u8, u64>(1, parameters.as_slice(), 2, "ipog-single").is_ok());
+    for other in [&["--scoring", "double"][..], &["--scoring", "single", "--no-cycle-split"], &["--scoring", "single", "--construct"], &["--scoring", "single", "--order", "ascending"]] {
+        let mut args = other.to_vec();
+        args.push("--resume");
+        let result = options(&args).checkpointer::<u8, u64>(1, parameters.as_slice(), 2, "ipog-single");
+        assert!(result.err().unwrap().starts_with("The checkpoint was created with the options"), "{:?}", other);
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_report_layer() {
+    use common::{Phase, Progress, SharedReport, time_it};
+    use tracing_subscriber::filter::filter_fn;
+    use tracing_subscriber::prelude::*;
+    use report::{is_reported, ReportLayer};
+
+    let report = SharedReport::default();
+    let subscriber = tracing_subscriber::registry().with(ReportLayer::new(report.clone()).with_filter(filter_fn(is_reported)));
+    tracing::subscriber::with_default(subscriber, || {
+        time_it!(time_it!(0, "Inner", DEBUG), "Outer");
+        time_it!(0, "Inner", TRACE);
+        tracing::debug!(coverage_map_bytes = 64_usize, "Allocated the coverage map");
+        tracing::debug!(coverage_map_bytes = 8_usize, "Allocated the coverage map");
+        tracing::info!(unrelated = 128_usize, "Ignored");
+    });
+
+    let options = Options { report_path: Some(PathBuf::from("report.json")), report: report.clone(), ..Options::new(PathBuf::from("result.txt")) };
+    let mut observer = options.progress_observer();
+    observer.progress(&Progress { strength: 2, at_parameter: 2, parameters: 3, rows: 4, uncovered: 2, phase: Phase::Horizontal });
+    observer.progress(&Progress { strength: 2, at_parameter: 3, parameters: 3, rows: 4, uncovered: 0, phase: Phase::Done });
+
+    let report = report.lock().unwrap();
+    assert_eq!(report.phases.iter().map(|timing| (timing.phase.as_str(), timing.count)).collect::<Vec<_>>(), vec![("Inner", 2), ("Outer", 1)]);
+    assert!(report.phases[0].duration <= report.phases[1].duration || report.phases[0].count > 1);
+    assert_eq!(report.peak_coverage_map_bytes, 64);
+    assert_eq!(report.parameters.len(), 1);
+}
+
+#[test]
+fn test_solver_cache() {
+    use common::CacheHits;
+    use sut::{CachingSolver, CountingSolver, FakeSolver, parse_constrained, Solver};
+
+    let (_, options, _, _) = validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--solver-cache", "--report", "report.json"])
+    ).unwrap();
+    assert!(options.solver_cache);
+
+    let sut = parse_constrained("p1: v1, v2; p2: v1, v2;").unwrap();
+    let init = options.caching_init(CountingSolver::<FakeSolver>::default_init());
+    let mut solver = CachingSolver::<CountingSolver<FakeSolver>>::new(&sut, &init);
+    assert!(solver.check_row(&[0_usize, 1]));
+    assert!(solver.check_row(&[0_usize, 1]));
+    options.report_solver(&init);
+
+    let report = options.report.lock().unwrap();
+    assert_eq!(report.solver_cache, Some(CacheHits { hits: 1, misses: 1 }));
+    let check_row = report.solver_calls.iter().find(|calls| calls.method == "check_row").unwrap();
+    assert_eq!(check_row.calls(), 1);
+
+    let options = Options::new(PathBuf::from("result.txt"));
+    assert!(!options.solver_cache);
+    assert_eq!(options.caching_init(()).capacity, 0);
+}
+
+#[test]
+fn test_cancellation_token() {
+    let options = Options::new(PathBuf::from("result.txt"));
+    assert!(!options.cancellation_token().is_cancelled());
+
+    let options = Options { time_limit: Some(std::time::Duration::ZERO), ..options };
+    assert!(options.cancellation_token().is_cancelled());
+
+    assert!(validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--coverage-report", "result.txt"])
+    )
+    .is_err());
+}
+
+#[test]
+fn test_format_duration() {
+    use std::time::Duration;
+    use progress::format_duration;
+
+    assert_eq!(format_duration(Duration::from_millis(12_500)), "12s");
+    assert_eq!(format_duration(Duration::from_secs(125)), "2m05s");
+    assert_eq!(format_duration(Duration::from_secs(3723)), "1h02m03s");
+}
+
+#[test]
+fn test_render_progress() {
+    use std::time::Duration;
+    use common::{Phase, Progress};
+    use progress::render;
+
+    let mut progress = Progress { strength: 2, at_parameter: 2, parameters: 5, rows: 9, uncovered: 18, phase: Phase::Horizontal };
+    assert_eq!(
+        render(&progress, Duration::from_secs(1)),
+        "[>                             ]   0.0% parameter 2/5 horizontal, 9 rows, 18 uncovered, ETA ?",
+    );
+
+    // The weights of the parameters 2 and 3 are 2 and 3, so 70% is done halfway through the last parameter.
+    progress.at_parameter = 3;
+    progress.phase = Phase::Vertical;
+    progress.parameters = 4;
+    assert_eq!(
+        render(&progress, Duration::from_secs(10)),
+        format!("[{}>{}]  70.0% parameter 3/4 vertical, 9 rows, 18 uncovered, ETA 4s", "=".repeat(21), " ".repeat(8)),
+    );
+
+    progress.phase = Phase::Done;
+    assert!(render(&progress, Duration::from_secs(10)).ends_with("100.0% parameter 3/4 done, 9 rows, 18 uncovered, took 10s"));
+}
+
+#[test]
+fn test_memory_budget() {
+    assert_eq!(parse_bytes("1024"), Ok(1024));
+    assert_eq!(parse_bytes("512M"), Ok(512 << 20));
+    assert_eq!(parse_bytes("4 GiB"), Ok(4 << 30));
+    assert_eq!(parse_bytes("2t"), Ok(2 << 40));
+    assert!(parse_bytes("G").is_err());
+    assert!(parse_bytes("4X").is_err());
+    assert_eq!(parse_bytes(&format!("{}T", u128::MAX >> 40)), Ok(u128::MAX >> 40 << 40));
+    assert!(parse_bytes(&format!("{}T", (u128::MAX >> 40) + 1)).is_err());
+    assert!(parse_bytes(&format!("{}0", u128::MAX)).is_err());
+
+    let (_, options, _, _) = validate_args(
+        get_app("", "", "").get_matches_from(&["exe", "-s", "2", "ignored", "-c", "--memory-budget", "8G"])
+    ).unwrap();
+    assert_eq!(options.memory_budget, Some(8 << 30));
+
+    let parameters = UVec::from(vec![10; 20]);
+    assert!(check_memory_budget(4, &parameters, &options).is_ok());
+    let options = Options { memory_budget: Some(1 << 20), ..options };
+    assert!(check_memory_budget(4, &parameters, &options).unwrap_err().contains("exceeds the memory budget of 1.0 MiB"));
+    assert!(check_memory_budget(4, &parameters, &Options::default()).is_ok());
+
+    let estimate = estimate_sut(4, &parameters);
+    let budget = estimate.min_memory_bytes();
+    assert_eq!(coverage_map_budget(4, &parameters, &Options { memory_budget: Some(8 << 30), ..options.clone() }), Ok(None));
+    assert!(coverage_map_budget(4, &parameters, &Options { memory_budget: Some(budget), ..options.clone() }).unwrap().is_some());
+    assert!(coverage_map_budget(4, &parameters, &Options { memory_budget: Some(budget - 1), ..options.clone() }).unwrap_err().contains("processed in chunks"));
+    assert!(coverage_map_budget(4, &parameters, &Options { memory_budget: Some(budget), prioritise: true, ..options }).unwrap_err().contains("whole coverage map"));
+}
+
+#[test]
+fn test_estimate_command() {
+    let matches = get_app("", "", "").get_matches_from(&["exe", "estimate", "model.txt", "-s", "3", "--memory-budget", "1K"]);
+    let matches = matches.subcommand_matches(ESTIMATE_COMMAND).unwrap();
+    assert_eq!(matches.value_of(INPUT_FILE_ARG), Some("model.txt"));
+    assert_eq!(parse_strength(matches), Ok(3));
+    assert_eq!(parse_memory_budget(matches), Ok(Some(1024)));
+
+    assert!(get_app("", "", "").get_matches_from_safe(&["exe", "estimate", "model.txt"]).is_err());
+    assert!(get_app("", "", "").get_matches_from_safe(&["exe", "model.txt", "-s", "3"]).is_err());
+}
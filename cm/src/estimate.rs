@@ -0,0 +1,199 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::cmp::max;
+use std::fmt::{Display, Formatter};
+
+use common::Number;
+
+use crate::{coverage_map_bytes, largest_pc_bits, map_bytes, MapTypes, offsets_bytes};
+
+/// The resources a generation is expected to need, computed without allocating anything.
+///
+/// # Example
+/// ```
+/// use cm::Estimate;
+///
+/// let estimate = Estimate::new::<u8, u128>(&[4, 3, 3, 2, 2], 2);
+/// assert_eq!(estimate.pcs, 4);
+/// assert_eq!(estimate.lower_bound, 12);
+/// assert!(estimate.check_budget(1 << 20).is_ok());
+/// assert!(estimate.check_budget(64).is_err());
+/// assert_eq!(estimate.coverage_map_budget(1 << 20), Ok(None));
+/// assert!(estimate.coverage_map_budget(estimate.pc_list_bytes + estimate.min_coverage_map_bytes + estimate.min_scores_bytes - 1).is_err());
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Estimate {
+    /// The number of parameters of the SUT.
+    pub parameters: usize,
+
+    /// The strength of the MCA.
+    pub strength: usize,
+
+    /// The number of PCs in the [pc_list::PCList].
+    pub pcs: u128,
+
+    /// The number of bytes allocated by the [pc_list::PCList].
+    pub pc_list_bytes: u128,
+
+    /// The types of the [crate::CoverageMap].
+    pub map_types: MapTypes,
+
+    /// The number of bytes allocated by the [crate::CoverageMap].
+    pub coverage_map_bytes: u128,
+
+    /// The number of bytes allocated by a [crate::CoverageMap] which processes the PCs in the smallest possible chunks.
+    pub min_coverage_map_bytes: u128,
+
+    /// The number of bytes of the scores of the horizontal extension, which hold the uncovered interactions of every PC for each value.
+    pub scores_bytes: u128,
+
+    /// The number of bytes of the scores of the horizontal extension for the smallest possible chunks.
+    pub min_scores_bytes: u128,
+
+    /// The minimal number of rows of any MCA, which is the product of the largest `strength` levels.
+    pub lower_bound: u128,
+
+    /// A rough estimate of the number of rows of the MCA generated by IPOG.
+    pub rows: u128,
+
+    /// The bits of the smallest PC and the bits of the scores of a value of every PC, see [score_ratio].
+    score_ratio: (u128, u128),
+}
+
+impl Estimate {
+    /// Estimate the resources for the given parameter levels and strength.
+    ///
+    /// The `ParameterId` and `LocationsType` should be the types used for the [pc_list::PCList] of the generation.
+    /// The strength should not exceed the number of parameters.
+    pub fn new<ParameterId: Number, LocationsType: Number>(parameters: &[usize], strength: usize) -> Self {
+        let mut levels = parameters.to_vec();
+        levels.sort_unstable_by(|a, b| b.cmp(a));
+        let lower_bound = levels.iter().take(strength).fold(1u128, |product, &level| product.saturating_mul(level as u128));
+
+        // IPOG needs roughly half the natural logarithm of the number of t-combinations times the lower bound.
+        let ln_combinations: f64 = (0..strength).map(|i| ((parameters.len() - i) as f64 / (i + 1) as f64).ln()).sum();
+        let rows = (lower_bound as f64 * (ln_combinations / 2.0).max(1.0)).ceil() as u128;
+        let map_types = MapTypes::select(parameters, strength);
+        let pcs = pc_list::pc_count(strength, parameters.len());
+        let min_map_bytes = map_bytes(largest_pc_bits(parameters, strength), map_types.word_bits);
+        let score_ratio = score_ratio(&levels, strength, map_types.index_bits);
+        let scores_bytes = (levels.first().copied().unwrap_or(0) as u128).saturating_mul(pcs).saturating_mul(map_types.index_bits as u128 / 8);
+
+        Self {
+            parameters: parameters.len(),
+            strength,
+            pcs,
+            pc_list_bytes: pc_list::pc_list_bytes::<ParameterId, LocationsType>(strength, parameters.len()),
+            map_types,
+            coverage_map_bytes: coverage_map_bytes(parameters, strength),
+            min_coverage_map_bytes: min_map_bytes.saturating_add(offsets_bytes(parameters.len(), strength, map_types.index_bits)),
+            scores_bytes,
+            min_scores_bytes: chunk_scores_bytes(min_map_bytes, score_ratio).min(scores_bytes),
+            lower_bound,
+            rows,
+            score_ratio,
+        }
+    }
+
+    /// Returns the number of bytes of the [pc_list::PCList], the [crate::CoverageMap] and the scores together.
+    pub fn memory_bytes(&self) -> u128 {
+        self.pc_list_bytes.saturating_add(self.coverage_map_bytes).saturating_add(self.scores_bytes)
+    }
+
+    /// Returns the number of bytes needed when the PCs are processed in the smallest possible chunks.
+    ///
+    /// The [pc_list::PCList] and the offsets of the PCs are not split into chunks, so they are counted whole.
+    pub fn min_memory_bytes(&self) -> u128 {
+        self.pc_list_bytes.saturating_add(self.min_coverage_map_bytes).saturating_add(self.min_scores_bytes)
+    }
+
+    /// Returns an error if the [Estimate::memory_bytes] exceed the budget, in bytes.
+    pub fn check_budget(&self, budget: u128) -> Result<(), String> {
+        if self.memory_bytes() > budget {
+            Err(format!(
+                "The generation needs about {} for the PC list, coverage map and scores, which exceeds the memory budget of {}.",
+                format_bytes(self.memory_bytes()), format_bytes(budget),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the number of bytes left for the map of a [crate::CoverageMap::with_memory_budget] within the budget, in bytes.
+    ///
+    /// The [pc_list::PCList] and the offsets of the PCs are subtracted from the budget, the rest is shared by the map and the scores of a chunk.
+    /// Returns `None` if the whole [crate::CoverageMap] fits in the budget, so the PCs do not need to be processed in chunks.
+    /// Returns an error if even the smallest chunks exceed the budget.
+    pub fn coverage_map_budget(&self, budget: u128) -> Result<Option<u128>, String> {
+        if self.memory_bytes() <= budget {
+            Ok(None)
+        } else if self.min_memory_bytes() <= budget {
+            let chunk_budget = budget - self.pc_list_bytes - offsets_bytes(self.parameters, self.strength, self.map_types.index_bits);
+            let (pc_bits, score_bits) = self.score_ratio;
+            let shared = chunk_budget.saturating_mul(pc_bits) / pc_bits.saturating_add(score_bits);
+            Ok(Some(max(shared, chunk_budget.saturating_sub(self.scores_bytes))))
+        } else {
+            Err(format!(
+                "The generation needs at least {} for the PC list, a coverage map processed in chunks and its scores, which exceeds the memory budget of {}.",
+                format_bytes(self.min_memory_bytes()), format_bytes(budget),
+            ))
+        }
+    }
+}
+
+/// Returns the number of bits of the smallest PC, and the number of bits of the scores of the largest level for a single PC.
+///
+/// The scores of a chunk hold at most one index for each value of every PC in the chunk, and every PC has at least as many interactions as the smallest PC.
+/// So the scores of a chunk with a map of `n` bytes need at most `n * score_bits / pc_bits` bytes.
+fn score_ratio(descending_levels: &[usize], strength: usize, index_bits: usize) -> (u128, u128) {
+    let pc_bits = descending_levels.iter().rev().take(strength).fold(1u128, |product, &level| product.saturating_mul(level as u128));
+    let score_bits = (descending_levels.first().copied().unwrap_or(0) as u128).saturating_mul(index_bits as u128);
+    (pc_bits.max(1), score_bits)
+}
+
+/// Returns the number of bytes of the scores of a chunk with a map of `map_bytes` bytes, see [score_ratio].
+fn chunk_scores_bytes(map_bytes: u128, (pc_bits, score_bits): (u128, u128)) -> u128 {
+    map_bytes.saturating_mul(score_bits).div_ceil(pc_bits)
+}
+
+impl Display for Estimate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Parameters: {}", self.parameters)?;
+        writeln!(f, "Strength: {}", self.strength)?;
+        writeln!(f, "PCs: {}", self.pcs)?;
+        writeln!(f, "PC list: {}", format_bytes(self.pc_list_bytes))?;
+        writeln!(f, "Coverage map: {} in elements of {} bits with indices of {} bits", format_bytes(self.coverage_map_bytes), self.map_types.word_bits, self.map_types.index_bits)?;
+        writeln!(f, "Scores: {}", format_bytes(self.scores_bytes))?;
+        writeln!(f, "Coverage map in chunks: at least {} and {} for the scores", format_bytes(self.min_coverage_map_bytes), format_bytes(self.min_scores_bytes))?;
+        writeln!(f, "Memory: {}", format_bytes(self.memory_bytes()))?;
+        writeln!(f, "Lower bound: {} rows", self.lower_bound)?;
+        write!(f, "Estimated size: about {} rows", self.rows)
+    }
+}
+
+/// Format the number of bytes using the largest binary unit for which the number is at least one.
+///
+/// # Example
+/// ```
+/// use cm::format_bytes;
+///
+/// assert_eq!(format_bytes(512), "512 B");
+/// assert_eq!(format_bytes(3 << 29), "1.5 GiB");
+/// ```
+pub fn format_bytes(bytes: u128) -> String {
+    const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
@@ -6,15 +6,20 @@
 
 //! This module provides the [CoverageMap] used during each iteration of IPOG.
 //!
+//! The memory needed for the [CoverageMap] and [PCList] can be checked before the generation with an [Estimate].
+//!
 //! # Features
 //! This crate provides the following optional features:
-//!   * `score-single` Always use the naive scoring algorithm.
-//!   * `score-double` Switch between the bitwise scoring algorithm and unchecked algorithm when there are no don't-cares.
+//!   * `score-single` Use [Scoring::Single] unless the [CoverageMap::scoring] is set otherwise.
+//!   * `score-double` Use [Scoring::Double] unless the [CoverageMap::scoring] is set otherwise.
+//!   * `score-avx2` Use AVX2 gathers to extract the bits of the scores if the CPU supports it, see [score].
 //!
-//! If neither `score-single` or `score-double` are set then the algorithm uses one of the three algorithms:
+//! With [Scoring::Triple], the default if neither `score-single` or `score-double` are set, the algorithm uses one of the three algorithms:
 //!   * If no don't-cares are present: unchecked algorithm [cm::CoverageMap::get_high_score_masked_unchecked].
 //!   * If only a few don't-cares are present: naive algorithm [cm::CoverageMap::get_high_score].
 //!   * If more don't-cares are present: unchecked algorithm [cm::CoverageMap::get_high_score_masked].
+//!
+//! All algorithms extract the bits of the interactions of a PC at once, with the `score-avx2` feature for several PCs at a time.
 
 #![cfg_attr(test, feature(test))]
 #![allow(incomplete_features)]
@@ -23,54 +28,227 @@
 #![deny(missing_docs, rustdoc::missing_crate_level_docs, future_incompatible)]
 #![allow(soft_unstable)]
 
-use std::cmp::max;
+use std::cmp::{max, min};
+use std::ops::Range;
 
-use common::{Number, u_vec, UVec};
+use common::{Number, Scoring, u_vec, UVec};
 use pc_list::PCList;
 
+pub use estimate::{Estimate, format_bytes};
+
+mod estimate;
+pub mod score;
+
 #[cfg(test)]
 mod test_map;
 
 #[cfg(test)]
 mod bench_score;
 
-// TODO make this a property of the CoverageMap (maybe make it private)
-/// This the type of the elements of the map.
-pub type BitArray = u64;
+/// The type of the elements of the map of a [CoverageMap], each element stores whether [BitArray::BITS] interactions are covered.
+pub trait BitArray: Number {
+    /// The number of bits of an element.
+    const BITS: usize;
+
+    /// The mask used to get the index of the specific bit in the element from the index of an interaction.
+    const MASK: usize = Self::BITS - 1;
+
+    /// The number of bits to shift the index of an interaction to get the index of the element in the map.
+    const SHIFT: usize = Self::MASK.count_ones() as usize;
+
+    /// See [u64::trailing_zeros].
+    fn trailing_zeros(self) -> u32;
+}
+
+macro_rules! as_bit_array {
+    ($($t:ident),+) => {
+        $(
+            impl BitArray for $t {
+                const BITS: usize = $t::BITS as usize;
+
+                #[inline(always)]
+                fn trailing_zeros(self) -> u32 { self.trailing_zeros() }
+            }
+        )+
+    };
+}
+
+as_bit_array!(u32, u64, u128);
+
+/// The number of bits of the element type and the index type of the [CoverageMap] for a generation, see [MapTypes::select].
+///
+/// Use [with_map_types] to call a method with these types.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct MapTypes {
+    /// The number of bits of the elements of the map, see [BitArray].
+    pub word_bits: usize,
+
+    /// The number of bits of the indices of the interactions.
+    pub index_bits: usize,
+}
 
-// TODO type of elements of the map should not be forced to be the same as the index
-/// The mask used to get the index of the specific bit in the array. This is the [usize] version.
-pub const BIT_MASK_U: usize = std::mem::size_of::<BitArray>() * 8 - 1;
-/// The mask used to get the index of the specific bit in the array. This is the [BitArray] version.
-pub const BIT_MASK: BitArray = BIT_MASK_U as BitArray;
-/// The number of bits to shift to get the index of the element in the map.
-pub const BIT_SHIFT: usize = BIT_MASK_U.count_ones() as usize;
+impl MapTypes {
+    /// Select the types of the [CoverageMap] for the given parameter levels and strength.
+    ///
+    /// The elements are the smallest [BitArray] with at least as many bits as the largest level,
+    /// so the interactions of a PC are in at most two elements and the scores are extracted at once, see [score].
+    /// The elements are at least [u64] if the scores are extracted with AVX2 for those, see [score::uses_avx2].
+    /// The indices are [u32] if all interactions of a parameter fit, see [coverage_map_bits], which halves the memory of the scores and the offsets.
+    ///
+    /// # Example
+    /// ```
+    /// # use cm::MapTypes;
+    /// let word_bits = if cm::score::uses_avx2::<u64>() { 64 } else { 32 };
+    /// assert_eq!(MapTypes::select(&[4u8, 3, 3, 2], 2), MapTypes { word_bits, index_bits: 32 });
+    /// assert_eq!(MapTypes::select(&[40u8, 40, 40], 2), MapTypes { word_bits: 64, index_bits: 32 });
+    /// assert_eq!(MapTypes::select(&[200u8; 6], 5), MapTypes { word_bits: 128, index_bits: 64 });
+    /// ```
+    pub fn select<ValueId: Number>(parameters: &[ValueId], strength: usize) -> Self {
+        let levels: Vec<usize> = parameters.iter().map(|level| level.as_usize()).collect();
+        let largest_level = levels.iter().copied().max().unwrap_or(0);
+        let smallest_bits = if score::uses_avx2::<u64>() { u64::BITS } else { u32::BITS };
+        let word_bits = [u32::BITS, u64::BITS].into_iter()
+            .filter(|&bits| bits >= smallest_bits)
+            .map(|bits| bits as usize)
+            .find(|&bits| largest_level <= bits)
+            .unwrap_or(u128::BITS as usize);
+        let index_bits = if coverage_map_bits(&levels, strength) < u32::MAX as u128 { 32 } else { 64 };
+        Self { word_bits, index_bits }
+    }
+}
+
+/// Evaluate the expression with the type aliases `Word` and `Index` set to the types of [MapTypes::select] for the parameters and strength.
+///
+/// # Example
+/// ```
+/// # use cm::{BitArray, with_map_types};
+/// let bits = with_map_types!(&[4u8, 3, 3, 2], 2, <Word, Index> <Word as BitArray>::BITS + 8 * std::mem::size_of::<Index>());
+/// assert_eq!(bits, if cm::score::uses_avx2::<u64>() { 64 + 32 } else { 32 + 32 });
+/// ```
+#[macro_export]
+macro_rules! with_map_types {
+    ($parameters:expr, $strength:expr, <$word:ident, $index:ident> $body:expr) => {
+        match $crate::MapTypes::select($parameters, $strength) {
+            $crate::MapTypes { word_bits: 32, index_bits: 32 } => { type $word = u32; type $index = u32; $body }
+            $crate::MapTypes { word_bits: 32, .. } => { type $word = u32; type $index = u64; $body }
+            $crate::MapTypes { word_bits: 64, index_bits: 32 } => { type $word = u64; type $index = u32; $body }
+            $crate::MapTypes { word_bits: 64, .. } => { type $word = u64; type $index = u64; $body }
+            $crate::MapTypes { index_bits: 32, .. } => { type $word = u128; type $index = u32; $body }
+            _ => { type $word = u128; type $index = u64; $body }
+        }
+    };
+}
 
 /// The number of dont-cares for which the non-bitwise solution is used.
 ///
 /// Used in [CoverageMap::get_high_score_masked_triple_sub] to switch between the three `get_high_score` implementations.
 pub const DONT_CARES_FOR_NAIVE: u32 = 2;
 
+/// Returns the number of bits of the map allocated by [CoverageMap::new] for the given parameter levels and strength.
+///
+/// The map is large enough for the interactions of the parameter with the most interactions.
+/// The result saturates instead of overflowing and is computed without creating the [PCList].
+pub fn coverage_map_bits(parameters: &[usize], strength: usize) -> u128 {
+    // The sums of the products of the levels of all (k)-combinations of the parameters before the current one.
+    let mut combinations = vec![0u128; strength];
+    combinations[0] = 1;
+    let mut result = 0;
+    for (at_parameter, &level) in parameters.iter().enumerate() {
+        if at_parameter >= strength {
+            result = max(result, combinations[strength - 1].saturating_mul(level as u128));
+        }
+        for k in (1..strength).rev() {
+            combinations[k] = combinations[k].saturating_add(combinations[k - 1].saturating_mul(level as u128));
+        }
+    }
+    result
+}
+
+/// Returns the number of bits of the largest PC of any parameter for the given parameter levels and strength.
+///
+/// This is the smallest chunk a [CoverageMap::with_memory_budget] can process.
+/// The result saturates instead of overflowing and is computed without creating the [PCList].
+pub fn largest_pc_bits(parameters: &[usize], strength: usize) -> u128 {
+    // The largest `strength - 1` levels of the parameters before the current one, in descending order.
+    let mut largest = Vec::with_capacity(strength);
+    let mut result = 0;
+    for (at_parameter, &level) in parameters.iter().enumerate() {
+        if at_parameter >= strength {
+            result = max(result, largest.iter().fold(level as u128, |product: u128, &other| product.saturating_mul(other as u128)));
+        }
+        largest.insert(largest.partition_point(|&other| other >= level), level);
+        largest.truncate(strength - 1);
+    }
+    result
+}
+
+/// Returns the number of bytes [CoverageMap::new] allocates for the given parameter levels and strength with the types of [MapTypes::select].
+///
+/// This includes the map itself, see [coverage_map_bits], and the offsets of the PCs.
+pub fn coverage_map_bytes(parameters: &[usize], strength: usize) -> u128 {
+    let types = MapTypes::select(parameters, strength);
+    map_bytes(coverage_map_bits(parameters, strength), types.word_bits).saturating_add(offsets_bytes(parameters.len(), strength, types.index_bits))
+}
+
+/// Returns the number of bytes of the map needed for the given number of bits, stored in elements of `word_bits` bits.
+fn map_bytes(bits: u128, word_bits: usize) -> u128 {
+    (bits / word_bits as u128 + 1).saturating_mul(word_bits as u128 / 8)
+}
+
+/// Returns the number of bytes of the offsets of the PCs with indices of `index_bits` bits, see [CoverageMap::sizes].
+fn offsets_bytes(parameter_count: usize, strength: usize, index_bits: usize) -> u128 {
+    let element_bytes = index_bits as u128 / 8;
+    pc_list::pc_count(strength, parameter_count).saturating_add(1).saturating_mul((strength - 1) as u128 * element_bytes)
+}
+
 /// Get the highest scoring value.
 ///
 /// Ties are solved by selecting the least used value.
 /// If a tie persists then the `previous_value` is used to determine the best scoring value "closest" to this value (incrementing and cycling).
+///
+/// # Safety
+/// The `scores` and `uses` should have an entry for each value, and the `previous_value` should be one of the values.
 #[inline]
-pub unsafe fn get_highscore<ValueId: Number>(
-    scores: &UVec<UVec<BitArray>>,
+pub unsafe fn get_highscore<ValueId: Number, Index: Number>(
+    scores: &UVec<UVec<Index>>,
+    uses: &UVec<usize>,
+    previous_value: ValueId,
+) -> ValueId {
+    get_highscore_by(scores.len(), |value| scores[value].len(), uses, previous_value)
+}
+
+/// Same as [get_highscore], but adds the `partial_scores` of the other chunks of a [CoverageMap] to the scores of the current chunk.
+///
+/// # Safety
+/// Same as [get_highscore], and the `partial_scores` should have an entry for each value.
+#[inline]
+pub unsafe fn get_highscore_merged<ValueId: Number, Index: Number>(
+    partial_scores: &UVec<usize>,
+    scores: &UVec<UVec<Index>>,
+    uses: &UVec<usize>,
+    previous_value: ValueId,
+) -> ValueId {
+    debug_assert_eq!(partial_scores.len(), scores.len());
+    get_highscore_by(scores.len(), |value| partial_scores[value] + scores[value].len(), uses, previous_value)
+}
+
+#[inline]
+fn get_highscore_by<ValueId: Number>(
+    value_choices: usize,
+    score: impl Fn(usize) -> usize,
     uses: &UVec<usize>,
     mut previous_value: ValueId,
 ) -> ValueId {
-    previous_value = (previous_value + ValueId::from_usize(1)) % ValueId::from_usize(scores.len());
-    let mut high_score: usize = scores[previous_value.as_usize()].len();
+    previous_value = (previous_value + ValueId::from_usize(1)) % ValueId::from_usize(value_choices);
+    let mut high_score: usize = score(previous_value.as_usize());
     let mut high_use: usize = uses[previous_value.as_usize()];
     let mut high_value: ValueId = previous_value;
 
     // Start at previous_value + 1 and cycle through all values.
-    for value in (previous_value + ValueId::from_usize(1)..ValueId::from_usize(scores.len()))
+    for value in (previous_value + ValueId::from_usize(1)..ValueId::from_usize(value_choices))
         .chain(ValueId::from_usize(0)..previous_value)
     {
-        let value_score = scores[value.as_usize()].len();
+        let value_score = score(value.as_usize());
         let value_use = uses[value.as_usize()];
         if high_score < value_score || (high_score == value_score && value_use < high_use) {
             high_score = value_score;
@@ -83,9 +261,12 @@ pub unsafe fn get_highscore<ValueId: Number>(
 }
 
 /// Get the highest scoring value while skipping the blacklisted values.
+///
+/// # Safety
+/// The `scores`, `uses` and `blacklist` should have an entry for each value, and the `previous_value` should be a value which is not blacklisted.
 #[inline]
-pub unsafe fn get_highscore_blacklisted<ValueId: Number>(
-    scores: &UVec<UVec<BitArray>>,
+pub unsafe fn get_highscore_blacklisted<ValueId: Number, Index: Number>(
+    scores: &UVec<UVec<Index>>,
     uses: &UVec<usize>,
     previous_value: ValueId,
     blacklist: &UVec<bool>,
@@ -121,6 +302,12 @@ pub unsafe fn get_highscore_blacklisted<ValueId: Number>(
 ///
 /// Check whether all interactions are covered using [CoverageMap::is_covered].
 ///
+/// A map created with [CoverageMap::with_memory_budget] may not fit all PCs of an iteration at once.
+/// It then covers a chunk of the PCs, see [CoverageMap::chunk], and [CoverageMap::next_chunk] moves on to the next chunk.
+///
+/// The map consists of elements of the type `Word`, and the indices of the interactions and the offsets of the PCs are of the type `Index`.
+/// The `Index` should fit the [coverage_map_bits], use [MapTypes::select] to get the smallest types.
+///
 /// # Examples
 /// ```
 /// # use common::{u_vec, UVec};
@@ -135,30 +322,40 @@ pub unsafe fn get_highscore_blacklisted<ValueId: Number>(
 /// unsafe { coverage_map.set_zero_covered() };
 /// ```
 #[derive(Default, Clone)]
-pub struct CoverageMap<ValueId: Number, const STRENGTH: usize>
+pub struct CoverageMap<ValueId: Number, const STRENGTH: usize, Word: BitArray = u64, Index: Number = u64>
     where [(); STRENGTH - 1]: {
     /// This is the collection of bit arrays.
-    pub map: UVec<BitArray>,
+    pub map: UVec<Word>,
 
     /// This vector contains the values used to calculate the indices.
     ///
     /// Each PC in the [PCList] has a row at the same index.
-    /// The first element is the absolute offset for the PC.
+    /// The first element is the absolute offset for the PC, use [CoverageMap::pc_index] to get its index in the map.
     /// The next elements are the relative offsets for each value in the PC.
     /// See [CoverageMap::get_base_index] for more details.
     ///
     /// Will be generated once in [CoverageMap::new] and is reused throughout the generation.
-    pub sizes: UVec<[BitArray; STRENGTH - 1]>,
+    pub sizes: UVec<[Index; STRENGTH - 1]>,
     sizes_len: usize,
     all_sizes_len: UVec<usize>,
 
-    /// The number of PCs left to cover.
+    /// The PCs in the map, all PCs of the iteration unless the map is chunked.
+    chunk: Range<usize>,
+    /// The absolute offset of the first PC of the chunk.
+    offset: usize,
+    /// The maximal number of bits of a chunk.
+    chunk_bits: usize,
+
+    /// The number of interactions of the chunk left to cover.
     pub uncovered: usize,
+
+    /// The algorithm used by [CoverageMap::calculate_scores], set it to the [common::RunOptions::scoring] of the generation.
+    pub scoring: Scoring,
     parameters: UVec<ValueId>,
     value_choices: ValueId,
 }
 
-impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+impl<ValueId: Number, const STRENGTH: usize, Word: BitArray, Index: Number> CoverageMap<ValueId, STRENGTH, Word, Index> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
     /// Create a new [CoverageMap] for the provided parameters.
     ///
     /// It is assumed that the provided [PCList] is created using the same parameters.
@@ -169,35 +366,111 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         parameters: UVec<ValueId>,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
     ) -> Self {
-        let mut offset: BitArray = 0;
-        let mut sizes = u_vec![[0; STRENGTH - 1]; pc_list.pcs.len() + 1];
+        let sizes = Self::offsets(&parameters, pc_list);
+        let max_coverage_map = Self::max_bits(&parameters, pc_list, |pc_list_len| sizes[pc_list_len][0].as_usize());
+        Self::allocate(parameters, pc_list, sizes, max_coverage_map)
+    }
+
+    /// Create a new [CoverageMap] for the provided parameters of which the map does not exceed the budget, in bytes.
+    ///
+    /// If the interactions of a parameter do not fit in the budget, the PCs are processed in chunks that do, see [CoverageMap::next_chunk].
+    /// Returns an error if the budget is too small for the largest PC, see [largest_pc_bits].
+    ///
+    /// # Example
+    /// ```ignore
+    /// # use common::{u_vec, UVec};
+    /// # use cm::CoverageMap;
+    /// # use pc_list::PCList;
+    /// let parameters = u_vec![4, 3, 3, 3, 3, 2, 2];
+    /// let pc_list = PCList::<u8, u8, 3>::new(parameters.len());
+    /// let mut coverage_map = CoverageMap::<u8, 3>::with_memory_budget(parameters.clone(), &pc_list, 16).unwrap();
+    ///
+    /// coverage_map.initialise(6);
+    /// let mut chunks = 1;
+    /// while coverage_map.next_chunk() {
+    ///     chunks += 1;
+    /// }
+    /// assert_eq!(chunks, 3);
+    /// assert!(CoverageMap::<u8, 3>::with_memory_budget(parameters, &pc_list, 1).is_err());
+    /// ```
+    pub fn with_memory_budget<ParameterId: Number, LocationsType: Number>(
+        parameters: UVec<ValueId>,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        budget: usize,
+    ) -> Result<Self, String> {
+        let sizes = Self::offsets(&parameters, pc_list);
+        let max_coverage_map = Self::max_bits(&parameters, pc_list, |pc_list_len| sizes[pc_list_len][0].as_usize());
+        let (mut pc_id, mut largest_value_tuples) = (0, 0);
+        let largest_pc = Self::max_bits(&parameters, pc_list, |pc_list_len| {
+            for (sub_sizes, next_sizes) in sizes[pc_id..pc_list_len].iter().zip(sizes[pc_id + 1..pc_list_len + 1].iter()) {
+                largest_value_tuples = max(largest_value_tuples, next_sizes[0].as_usize() - sub_sizes[0].as_usize());
+            }
+            pc_id = max(pc_id, pc_list_len);
+            largest_value_tuples
+        });
+
+        // The map needs one element more than the number of bits, see CoverageMap::initialise.
+        let chunk_bits = ((budget / std::mem::size_of::<Word>()) << Word::SHIFT).saturating_sub(1);
+        if chunk_bits < largest_pc {
+            return Err(format!(
+                "The memory budget of {} for the coverage map is too small, the largest PC needs {}.",
+                format_bytes(budget as u128), format_bytes(map_bytes(largest_pc as u128, Word::BITS)),
+            ));
+        }
+
+        Ok(Self::allocate(parameters, pc_list, sizes, min(max_coverage_map, chunk_bits)))
+    }
+
+    /// Calculate the offsets of all PCs, see [CoverageMap::sizes].
+    fn offsets<ParameterId: Number, LocationsType: Number>(
+        parameters: &UVec<ValueId>,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+    ) -> UVec<[Index; STRENGTH - 1]> {
+        let mut offset: usize = 0;
+        let mut sizes = u_vec![[Index::default(); STRENGTH - 1]; pc_list.pcs.len() + 1];
 
         for (pc, sub_sizes) in pc_list.pcs.iter().zip(sizes.iter_mut()) {
-            sub_sizes[0] = offset;
-            let mut vec_size: BitArray = parameters[pc[STRENGTH - 2].as_usize()].as_usize() as BitArray;
+            sub_sizes[0] = Index::from_usize(offset);
+            let mut vec_size: usize = parameters[pc[STRENGTH - 2].as_usize()].as_usize();
             for pc_index in (0..STRENGTH - 2).rev() {
-                sub_sizes[pc_index + 1] = vec_size;
-                vec_size *= parameters[pc[pc_index].as_usize()].as_usize() as BitArray;
+                sub_sizes[pc_index + 1] = Index::from_usize(vec_size);
+                vec_size *= parameters[pc[pc_index].as_usize()].as_usize();
             }
 
             offset += vec_size;
         }
 
-        sizes[pc_list.pcs.len()][0] = offset;
+        debug_assert!(offset <= Index::dont_care().as_usize(), "The offsets of the PCs do not fit in the index type.");
+        sizes[pc_list.pcs.len()][0] = Index::from_usize(offset);
+        sizes
+    }
 
-        let mut max_coverage_map: usize = 0;
+    /// Returns the maximum over all iterations of the `value_tuples` of the PCs of the iteration times the number of values of the parameter.
+    ///
+    /// The `value_tuples` are requested for the number of PCs of each iteration in increasing order.
+    fn max_bits<ParameterId: Number, LocationsType: Number>(
+        parameters: &UVec<ValueId>,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        mut value_tuples: impl FnMut(usize) -> usize,
+    ) -> usize {
+        let mut result: usize = 0;
         for (value_count, pc_list_len) in parameters.iter().skip(STRENGTH).zip(pc_list.sizes.iter()) {
-            max_coverage_map = max(
-                max_coverage_map,
-                value_count.as_usize() * sizes[*pc_list_len][0].as_usize(),
-            );
+            result = max(result, value_count.as_usize() * value_tuples(*pc_list_len));
         }
+        result
+    }
 
-        let mut map = u_vec![0; (max_coverage_map >> BIT_SHIFT) + 1];
+    /// Allocate the map for chunks of at most `chunk_bits` bits.
+    fn allocate<ParameterId: Number, LocationsType: Number>(
+        parameters: UVec<ValueId>,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        sizes: UVec<[Index; STRENGTH - 1]>,
+        chunk_bits: usize,
+    ) -> Self {
+        debug_assert!(chunk_bits <= Index::dont_care().as_usize(), "The interactions do not fit in the index type.");
+        let mut map = u_vec![Word::default(); (chunk_bits >> Word::SHIFT) + 1];
 
-        if cfg!(debug_assertions) {
-            println!("Max coverage map: {} => map size: {}", max_coverage_map, map.len());
-        }
+        tracing::debug!(chunk_bits, map_size = map.len(), coverage_map_bytes = map.len() * std::mem::size_of::<Word>(), word_bits = Word::BITS, "Allocated the coverage map");
 
         unsafe { map.set_len(0); }
 
@@ -206,22 +479,64 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
             sizes,
             sizes_len: 0,
             all_sizes_len: pc_list.sizes.clone(),
+            chunk: 0..0,
+            offset: 0,
+            chunk_bits,
             uncovered: 0,
+            scoring: Scoring::default(),
             parameters,
             value_choices: ValueId::default(),
         }
     }
 
     /// Initialise the [CoverageMap] for the (next) iteration of IPOG.
+    ///
+    /// The map contains the first chunk of PCs of the iteration.
     pub fn initialise(&mut self, at_parameter: usize) {
         debug_assert!(at_parameter < self.parameters.len());
         self.value_choices = self.parameters[at_parameter];
         debug_assert!(at_parameter - STRENGTH < self.all_sizes_len.len());
         self.sizes_len = self.all_sizes_len[at_parameter - STRENGTH];
         debug_assert!(self.sizes_len < self.sizes.len());
-        self.uncovered = self.sizes[self.sizes_len][0].as_usize() * self.value_choices.as_usize();
+        self.initialise_chunk(0);
+    }
+
+    /// Move on to the next chunk of PCs of the iteration, returns false if the map already contains the last chunk.
+    ///
+    /// The new chunk is not covered at all, mark the interactions of the existing rows as covered before the extensions.
+    pub fn next_chunk(&mut self) -> bool {
+        if self.chunk.end < self.sizes_len {
+            self.initialise_chunk(self.chunk.end);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the range of PCs in the map.
+    #[inline]
+    pub fn chunk(&self) -> Range<usize> {
+        self.chunk.clone()
+    }
 
-        let length = (self.uncovered >> BIT_SHIFT) + 1;
+    /// Returns true iff the PCs of the iteration do not fit in the map at once.
+    #[inline]
+    pub fn is_chunked(&self) -> bool {
+        self.chunk.start != 0 || self.chunk.end != self.sizes_len
+    }
+
+    /// Fit as many PCs starting from `start` in the map as possible and clear the map.
+    fn initialise_chunk(&mut self, start: usize) {
+        self.offset = self.sizes[start][0].as_usize();
+        let limit = self.offset + self.chunk_bits / self.value_choices.as_usize();
+        let end = start + self.sizes[start..self.sizes_len + 1].partition_point(|sizes| sizes[0].as_usize() <= limit) - 1;
+        debug_assert!(end > start || start == self.sizes_len, "The PC {} does not fit in the map.", start);
+        self.chunk = start..end;
+        self.uncovered = (self.sizes[end][0].as_usize() - self.offset) * self.value_choices.as_usize();
+
+        tracing::trace!(start, end, pcs = self.sizes_len, uncovered = self.uncovered, "Initialised the chunk of the coverage map");
+
+        let length = (self.uncovered >> Word::SHIFT) + 1;
 
         debug_assert!(length <= self.map.capacity(), "{} <= {}; {}", length, self.map.capacity(), self.sizes_len);
 
@@ -231,12 +546,61 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         }
     }
 
+    /// Get the index of the first interaction of the PC in the map.
+    #[inline]
+    pub fn pc_index(&self, pc_id: usize) -> Index {
+        debug_assert!(self.chunk.contains(&pc_id));
+        Index::from_usize((self.sizes[pc_id][0].as_usize() - self.offset) * self.value_choices.as_usize())
+    }
+
+    /// Returns true iff the interaction at the index is covered.
+    ///
+    /// # Safety
+    /// The index should be an interaction of the chunk, so its word `index >> Word::SHIFT` is in the map.
     #[inline]
-    unsafe fn get(&self, index: BitArray) -> bool {
-        let map_index = index as usize >> BIT_SHIFT;
+    pub unsafe fn get(&self, index: Index) -> bool {
+        let index = index.as_usize();
+        let map_index = index >> Word::SHIFT;
         debug_assert!(map_index < self.map.len());
-        let bit_index = 1 << (index & BIT_MASK);
-        self.map[map_index] & bit_index != 0
+        self.map[map_index].get(index & Word::MASK)
+    }
+
+    /// Returns the index of the first uncovered interaction of the chunk at or after `from`.
+    pub fn first_uncovered(&self, from: Index) -> Option<Index> {
+        let end = (self.sizes[self.chunk.end][0].as_usize() - self.offset) * self.value_choices.as_usize();
+        let mut map_index = from.as_usize() >> Word::SHIFT;
+        let mut mask = Word::mask_high(from.as_usize() & Word::MASK);
+        while map_index << Word::SHIFT < end {
+            let uncovered = !self.map[map_index] & mask;
+            if uncovered.any() {
+                let index = (map_index << Word::SHIFT) + uncovered.trailing_zeros() as usize;
+                return if index < end { Some(Index::from_usize(index)) } else { None };
+            }
+            map_index += 1;
+            mask = Word::dont_care();
+        }
+        None
+    }
+
+    /// Returns the PC, the values for the parameters of the PC and the value for the parameter of the iteration of the interaction at the index.
+    ///
+    /// This is the inverse of [CoverageMap::get_base_index] plus the value of the parameter of the iteration.
+    pub fn interaction(&self, index: Index) -> (usize, [ValueId; STRENGTH - 1], ValueId) {
+        let value_choices = self.value_choices.as_usize();
+        let value = ValueId::from_usize(index.as_usize() % value_choices);
+        let absolute = index.as_usize() / value_choices + self.offset;
+        let pc_id = self.chunk.start + self.sizes[self.chunk.clone()].partition_point(|sizes| sizes[0].as_usize() <= absolute) - 1;
+        debug_assert!(self.chunk.contains(&pc_id));
+
+        let sizes = self.sizes[pc_id];
+        let mut relative = absolute - sizes[0].as_usize();
+        let mut values = [ValueId::default(); STRENGTH - 1];
+        for index in 1..STRENGTH - 1 {
+            values[index - 1] = ValueId::from_usize(relative / sizes[index].as_usize());
+            relative %= sizes[index].as_usize();
+        }
+        values[STRENGTH - 2] = ValueId::from_usize(relative);
+        (pc_id, values, value)
     }
 
     /// Returns true iff all PCs are covered.
@@ -246,17 +610,32 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
     }
 
     #[inline]
-    unsafe fn add_scores(&self, scores: &mut UVec<UVec<BitArray>>, mut base_index: BitArray) {
+    unsafe fn add_scores(&self, scores: &mut UVec<UVec<Index>>, mut base_index: Index) {
         for score in scores.iter_mut().take(self.value_choices.as_usize()) {
             if !self.get(base_index) {
                 score.push(base_index);
             }
 
-            base_index += 1;
+            base_index += Index::from_usize(1);
+        }
+    }
+
+    /// Add the uncovered interactions of the PCs with the base indices to the scores, see [score].
+    #[inline]
+    unsafe fn add_scores_all(&self, scores: &mut UVec<UVec<Index>>, base_indices: impl Iterator<Item=Index>) {
+        let value_choices = self.value_choices.as_usize();
+        if value_choices <= Word::BITS {
+            score::add_scores(self.map.as_slice(), scores, value_choices, base_indices);
+        } else {
+            for base_index in base_indices {
+                self.add_scores(scores, base_index);
+            }
         }
     }
 
     /// Get the list of indices covered by each value if it where chosen.
+    ///
+    /// The algorithm is selected by the [CoverageMap::scoring], all algorithms give the same scores.
     #[inline]
     pub fn calculate_scores<ParameterId: Number, LocationsType: Number>(
         &self,
@@ -265,7 +644,7 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         row: &[ValueId],
         dont_care_locations: LocationsType,
         no_dont_cares: LocationsType,
-        scores: &mut UVec<UVec<BitArray>>,
+        scores: &mut UVec<UVec<Index>>,
     ) {
         self.calculate_scores_sub(pc_list, row, dont_care_locations, no_dont_cares, scores, 0, pc_list_len);
     }
@@ -278,13 +657,13 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         row: &[ValueId],
         dont_care_locations: LocationsType,
         no_dont_cares: LocationsType,
-        scores: &mut UVec<UVec<BitArray>>,
+        scores: &mut UVec<UVec<Index>>,
         start: usize,
         end: usize,
     ) {
         unsafe {
-            if cfg!(feature="score-double") {
-                self.get_high_score_masked_sub(
+            match self.scoring {
+                Scoring::Double => self.get_high_score_masked_sub(
                     pc_list,
                     row,
                     dont_care_locations,
@@ -292,17 +671,15 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
                     scores,
                     start,
                     end,
-                );
-            } else if cfg!(feature="score-single") {
-                self.get_high_score_sub(
+                ),
+                Scoring::Single => self.get_high_score_sub(
                     pc_list,
                     row,
                     scores,
                     start,
                     end,
-                );
-            } else {
-                self.get_high_score_masked_triple_sub(
+                ),
+                Scoring::Triple => self.get_high_score_masked_triple_sub(
                     pc_list,
                     row,
                     dont_care_locations,
@@ -310,7 +687,7 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
                     scores,
                     start,
                     end,
-                );
+                ),
             }
         }
     }
@@ -322,7 +699,7 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         pc_list_len: usize,
         row: &[ValueId],
-        scores: &mut UVec<UVec<BitArray>>,
+        scores: &mut UVec<UVec<Index>>,
     ) {
         self.get_high_score_sub(pc_list, row, scores, 0, pc_list_len)
     }
@@ -333,15 +710,11 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         &self,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         row: &[ValueId],
-        scores: &mut UVec<UVec<BitArray>>,
+        scores: &mut UVec<UVec<Index>>,
         start: usize,
         end: usize,
     ) {
-        for pc_id in start..end {
-            if let Some(base_index) = self.get_base_index(pc_id, pc_list, row) {
-                self.add_scores(scores, base_index);
-            }
-        }
+        self.add_scores_all(scores, (start..end).filter_map(|pc_id| self.get_base_index(pc_id, pc_list, row)));
     }
 
     /// Get the list of indices covered by each value if it where chosen.
@@ -356,7 +729,7 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         row: &[ValueId],
         dont_care_locations: LocationsType,
         no_dont_cares: LocationsType,
-        scores: &mut UVec<UVec<BitArray>>,
+        scores: &mut UVec<UVec<Index>>,
     ) {
         self.get_high_score_masked_sub(pc_list, row, dont_care_locations, no_dont_cares, scores, 0, pc_list_len)
     }
@@ -371,7 +744,7 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         row: &[ValueId],
         dont_care_locations: LocationsType,
         no_dont_cares: LocationsType,
-        scores: &mut UVec<UVec<BitArray>>,
+        scores: &mut UVec<UVec<Index>>,
         start: usize,
         end: usize,
     ) {
@@ -398,7 +771,7 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         row: &[ValueId],
         dont_care_locations: LocationsType,
         no_dont_cares: LocationsType,
-        scores: &mut UVec<UVec<BitArray>>,
+        scores: &mut UVec<UVec<Index>>,
     ) {
         self.get_high_score_masked_triple_sub(pc_list, row, dont_care_locations, no_dont_cares, scores, 0, pc_list_len)
     }
@@ -414,7 +787,7 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         row: &[ValueId],
         dont_care_locations: LocationsType,
         no_dont_cares: LocationsType,
-        scores: &mut UVec<UVec<BitArray>>,
+        scores: &mut UVec<UVec<Index>>,
         start: usize,
         end: usize,
     ) {
@@ -446,7 +819,7 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         pc_list_len: usize,
         row: &[ValueId],
         dont_care_locations: LocationsType,
-        scores: &mut UVec<UVec<BitArray>>,
+        scores: &mut UVec<UVec<Index>>,
     ) {
         self.get_high_score_masked_checked_sub(pc_list, row, dont_care_locations, scores, 0, pc_list_len)
     }
@@ -460,15 +833,16 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         row: &[ValueId],
         dont_care_locations: LocationsType,
-        scores: &mut UVec<UVec<BitArray>>,
+        scores: &mut UVec<UVec<Index>>,
         start: usize,
         end: usize,
     ) {
-        for tid in start..end {
-            if (pc_list.locations[tid] & dont_care_locations).none() {
-                self.add_scores(scores, self.get_base_index_unchecked(tid, pc_list, row));
-            }
-        }
+        self.add_scores_all(
+            scores,
+            (start..end)
+                .filter(|&tid| (pc_list.locations[tid] & dont_care_locations).none())
+                .map(|tid| self.get_base_index_unchecked(tid, pc_list, row)),
+        );
     }
 
     /// Get the list of indices covered by each value if it where chosen.
@@ -481,7 +855,7 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         pc_list_len: usize,
         row: &[ValueId],
-        scores: &mut UVec<UVec<BitArray>>,
+        scores: &mut UVec<UVec<Index>>,
     ) {
         self.get_high_score_masked_unchecked_sub(pc_list, row, scores, 0, pc_list_len)
     }
@@ -494,13 +868,11 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         &self,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         row: &[ValueId],
-        scores: &mut UVec<UVec<BitArray>>,
+        scores: &mut UVec<UVec<Index>>,
         start: usize,
         end: usize,
     ) {
-        for tid in start..end {
-            self.add_scores(scores, self.get_base_index_unchecked(tid, pc_list, row));
-        }
+        self.add_scores_all(scores, (start..end).map(|tid| self.get_base_index_unchecked(tid, pc_list, row)));
     }
 
     /// Get the list of indices covered by each of the specified values if it where chosen for the specified PCs.
@@ -511,14 +883,14 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         row: &[ValueId],
         masked_value_choices: &[ValueId],
-        scores: &mut UVec<UVec<BitArray>>,
+        scores: &mut UVec<UVec<Index>>,
         start: usize,
         end: usize,
     ) {
         for pc_id in start..end {
             if let Some(base_index) = self.get_base_index(pc_id, pc_list, row) {
                 for &value in masked_value_choices {
-                    let index = base_index + value.as_usize() as BitArray;
+                    let index = base_index + Index::from_usize(value.as_usize());
                     if !self.get(base_index) {
                         scores[value.as_usize()].push(index);
                     }
@@ -529,13 +901,14 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
 
     /// Get the previous value and set the index as covered.
     /// Does not decrease [CoverageMap::uncovered].
-    unsafe fn get_and_set(&mut self, index: BitArray) -> bool {
-        let map_index = index as usize >> BIT_SHIFT;
+    unsafe fn get_and_set(&mut self, index: Index) -> bool {
+        let index = index.as_usize();
+        let map_index = index >> Word::SHIFT;
         debug_assert!(map_index < self.map.len());
-        let bit_index = 1 << (index & BIT_MASK);
+        let bit_index = Word::bit(index & Word::MASK);
 
         let array = &mut self.map[map_index];
-        if *array & bit_index == 0 {
+        if (*array & bit_index).none() {
             *array |= bit_index;
             false
         } else {
@@ -545,17 +918,24 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
 
     /// Set an index as covered. Assumes and expects the index not to be covered yet.
     /// Does not decrease [CoverageMap::uncovered].
+    ///
+    /// # Safety
+    /// The index should be an interaction of the chunk, so its word `index >> Word::SHIFT` is in the map.
     #[inline]
-    pub unsafe fn set(&mut self, index: BitArray) {
-        let array = &mut self.map[index as usize >> BIT_SHIFT];
-        debug_assert_eq!(*array & (1 << (index & BIT_MASK)), 0);
-        *array |= 1 << (index & BIT_MASK);
+    pub unsafe fn set(&mut self, index: Index) {
+        let index = index.as_usize();
+        let array = &mut self.map[index >> Word::SHIFT];
+        debug_assert!(!array.get(index & Word::MASK));
+        *array |= Word::bit(index & Word::MASK);
     }
 
     /// Sets the given index. If the index was already covered, then return `false`.
     /// Otherwise decrease [CoverageMap::uncovered] and return `true`.
+    ///
+    /// # Safety
+    /// The index should be an interaction of the chunk, so its word `index >> Word::SHIFT` is in the map.
     #[inline]
-    pub unsafe fn set_index(&mut self, index: BitArray) -> bool {
+    pub unsafe fn set_index(&mut self, index: Index) -> bool {
         if !self.get_and_set(index) {
             self.uncovered -= 1;
             true
@@ -566,25 +946,37 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
 
     /// Set all the indices as covered.
     /// Assumes all indices are not covered.
-    pub unsafe fn set_indices(&mut self, indices: &UVec<BitArray>) {
+    ///
+    /// # Safety
+    /// The indices should be interactions of the chunk, so the word `index >> Word::SHIFT` of each index is in the map.
+    pub unsafe fn set_indices(&mut self, indices: &UVec<Index>) {
         self.uncovered -= indices.len();
         self.set_indices_sub(indices)
     }
 
     /// Same as [CoverageMap::set_indices], but decreases the [CoverageMap::uncovered] by `indices.len() - filtered`.
-    pub unsafe fn set_indices_updated(&mut self, indices: &UVec<BitArray>, filtered: usize) {
+    ///
+    /// # Safety
+    /// The indices should be interactions of the chunk, so the word `index >> Word::SHIFT` of each index is in the map.
+    pub unsafe fn set_indices_updated(&mut self, indices: &UVec<Index>, filtered: usize) {
         self.uncovered -= indices.len() - filtered;
         self.set_indices_sub(indices)
     }
 
     /// Same as [CoverageMap::set_indices], but does not decrease the [CoverageMap::uncovered].
-    pub unsafe fn set_indices_sub(&mut self, indices: &UVec<BitArray>) {
+    ///
+    /// # Safety
+    /// The indices should be interactions of the chunk, so the word `index >> Word::SHIFT` of each index is in the map.
+    pub unsafe fn set_indices_sub(&mut self, indices: &UVec<Index>) {
         for &index in indices.iter() {
             self.set(index)
         }
     }
 
     /// Set all the interactions in the row as covered.
+    ///
+    /// # Safety
+    /// The PCs up to `pc_list_len` should be in the chunk, and the row should have a value for `at_parameter`, see [CoverageMap::get_base_index] for the other values.
     #[inline]
     pub unsafe fn set_covered_row_simple<ParameterId: Number, LocationsType: Number>(
         &mut self,
@@ -597,6 +989,9 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
     }
 
     /// Set the interactions of the specified PCs in the row as covered.
+    ///
+    /// # Safety
+    /// The PCs from `start` up to `end` should be in the chunk, and the row should have a value for `at_parameter`, see [CoverageMap::get_base_index] for the other values.
     #[inline]
     pub unsafe fn set_covered_row_simple_sub<ParameterId: Number, LocationsType: Number>(
         &mut self,
@@ -606,7 +1001,7 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         start: usize,
         end: usize,
     ) {
-        let value = row.get_unchecked(at_parameter).as_usize() as BitArray;
+        let value = Index::from_usize(row.get_unchecked(at_parameter).as_usize());
         for pc_id in start..end {
             if let Some(base_index) = self.get_base_index(pc_id, pc_list, row) {
                 self.set_index(base_index + value);
@@ -614,42 +1009,105 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
         }
     }
 
+    /// Set the interactions of the chunk in all rows with a value for the parameter as covered.
+    ///
+    /// # Safety
+    /// The `pc_list` should be the list of `at_parameter` the map was initialised with, see [CoverageMap::get_base_index] for the values of the rows.
+    pub unsafe fn set_covered_rows<ParameterId: Number, LocationsType: Number>(
+        &mut self,
+        at_parameter: usize,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        rows: &UVec<UVec<ValueId>>,
+    ) {
+        for row in rows.iter() {
+            if row[at_parameter] != ValueId::dont_care() {
+                self.set_covered_row_simple_sub(at_parameter, pc_list, row.as_slice(), self.chunk.start, self.chunk.end);
+            }
+        }
+    }
+
+    /// Count the interactions in the row that are not covered yet.
+    /// Does not change the [CoverageMap].
+    ///
+    /// # Safety
+    /// The PCs up to `pc_list_len` should be in the chunk, and the row should have a value for `at_parameter`, see [CoverageMap::get_base_index] for the other values.
+    #[inline]
+    pub unsafe fn count_uncovered_row_simple<ParameterId: Number, LocationsType: Number>(
+        &self,
+        at_parameter: usize,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        pc_list_len: usize,
+        row: &[ValueId],
+    ) -> usize {
+        self.count_uncovered_row_simple_sub(at_parameter, pc_list, row, 0, pc_list_len)
+    }
+
+    /// Count the interactions of the specified PCs in the row that are not covered yet.
+    /// Does not change the [CoverageMap].
+    ///
+    /// # Safety
+    /// The PCs from `start` up to `end` should be in the chunk, and the row should have a value for `at_parameter`, see [CoverageMap::get_base_index] for the other values.
+    #[inline]
+    pub unsafe fn count_uncovered_row_simple_sub<ParameterId: Number, LocationsType: Number>(
+        &self,
+        at_parameter: usize,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        row: &[ValueId],
+        start: usize,
+        end: usize,
+    ) -> usize {
+        let value = Index::from_usize(row.get_unchecked(at_parameter).as_usize());
+        let mut result = 0;
+        for pc_id in start..end {
+            if let Some(base_index) = self.get_base_index(pc_id, pc_list, row) {
+                if !self.get(base_index + value) {
+                    result += 1;
+                }
+            }
+        }
+        result
+    }
+
     /// Set all interactions with all zero values as covered.
     /// This method is used to handle the first row of the MCA (which is always an all zeros row).
+    ///
+    /// # Safety
+    /// The map should be initialised for the current parameter, see [CoverageMap::initialise].
     #[inline]
     pub unsafe fn set_zero_covered(&mut self) {
-        self.uncovered -= self.sizes_len;
-        let value_choices = self.value_choices.as_usize();
-        for size in self.sizes.iter().take(self.sizes_len) {
-            let index = *size.get_unchecked(0) as usize * value_choices;
-            self.map[index as usize >> BIT_SHIFT] |= 1 << (index & BIT_MASK_U);
-        }
+        self.set_zero_covered_sub(self.chunk.start, self.chunk.end)
     }
 
-    /// Same as [CoverageMap::set_zero_covered], but only sets the specified range of PCs.
-    /// Only reduces the [CoverageMap::uncovered] if `start` is zero.
+    /// Same as [CoverageMap::set_zero_covered], but only sets the specified range of PCs of the chunk.
+    /// Only reduces the [CoverageMap::uncovered] if `start` is the start of the chunk.
     /// Assumes all PCs will be handled.
+    ///
+    /// # Safety
+    /// The PCs from `start` up to `end` should be in the chunk.
     #[inline]
     pub unsafe fn set_zero_covered_sub(&mut self, start: usize, end: usize) {
-        if start == 0 {
-            self.uncovered -= self.sizes_len;
+        if start == self.chunk.start {
+            self.uncovered -= self.chunk.len();
         }
         let value_choices = self.value_choices.as_usize();
         for size in self.sizes[start..end].iter() {
-            let index = *size.get_unchecked(0) as usize * value_choices;
-            self.map[index >> BIT_SHIFT] |= 1 << (index & BIT_MASK_U);
+            let index = (size.get_unchecked(0).as_usize() - self.offset) * value_choices;
+            self.map[index >> Word::SHIFT] |= Word::bit(index & Word::MASK);
         }
     }
 
     /// Set all covered indices to zero.
     /// Be sure to call [CoverageMap::set_indices_updated]
+    ///
+    /// # Safety
+    /// The indices should be interactions of the chunk, so the word `index >> Word::SHIFT` of each index is in the map.
     #[inline]
-    pub unsafe fn update_scores(&self, new_vec: &mut UVec<u64>) -> usize {
+    pub unsafe fn update_scores(&self, new_vec: &mut UVec<Index>) -> usize {
         let mut result = 0;
 
         for index in new_vec.iter_mut() {
             if self.get(*index) {
-                *index = 0;
+                *index = Index::default();
                 result += 1;
             }
         }
@@ -658,58 +1116,64 @@ impl<ValueId: Number, const STRENGTH: usize> CoverageMap<ValueId, STRENGTH> wher
     }
 
     /// Get the base index for an interaction given a parameter_combination and a row.
+    ///
+    /// # Safety
+    /// The PC should be in the chunk, and the row should have a value below the level or a don't-care for each parameter of the PC.
     #[inline]
     pub unsafe fn get_base_index<ParameterId: Number, LocationsType: Number>(
         &self,
         pc_id: usize,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         row: &[ValueId],
-    ) -> Option<BitArray> {
+    ) -> Option<Index> {
         let sizes = self.sizes[pc_id];
         let pc = pc_list.pcs[pc_id];
 
-        let mut base_index = *sizes.get_unchecked(0);
+        let mut base_index = sizes.get_unchecked(0).as_usize() - self.offset;
         for index in 1..STRENGTH - 1 {
             let value = *row.get_unchecked(pc.get_unchecked(index - 1).as_usize());
             if value == ValueId::dont_care() {
                 return None;
             }
-            base_index += (value.as_usize() as BitArray) * *sizes.get_unchecked(index);
+            base_index += value.as_usize() * sizes.get_unchecked(index).as_usize();
         }
         let value = *row.get_unchecked(pc.get_unchecked(STRENGTH - 2).as_usize());
         if value == ValueId::dont_care() {
             return None;
         }
-        base_index += value.as_usize() as BitArray;
+        base_index += value.as_usize();
 
-        base_index *= self.value_choices.as_usize() as BitArray;
+        base_index *= self.value_choices.as_usize();
 
-        debug_assert!(base_index as usize >> BIT_SHIFT < self.map.len());
-        Some(base_index)
+        debug_assert!(base_index >> Word::SHIFT < self.map.len());
+        Some(Index::from_usize(base_index))
     }
 
     /// Get the base index for an interaction given a parameter_combination and a row.
     /// This version does not check if the values are `don't-cares`.
     /// Use [CoverageMap::get_base_index] instead if you are not sure whether the values at the parameters used are `don't-cares`.
+    ///
+    /// # Safety
+    /// The PC should be in the chunk, and the row should have a value below the level for each parameter of the PC.
     #[inline]
     pub unsafe fn get_base_index_unchecked<ParameterId: Number, LocationsType: Number>(
         &self,
         pc_id: usize,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         row: &[ValueId],
-    ) -> BitArray {
+    ) -> Index {
         let sizes = self.sizes[pc_id];
         let pc = pc_list.pcs[pc_id];
 
-        let mut base_index = *sizes.get_unchecked(0);
+        let mut base_index = sizes.get_unchecked(0).as_usize() - self.offset;
         for index in 1..STRENGTH - 1 {
-            base_index += (row.get_unchecked(pc.get_unchecked(index - 1).as_usize()).as_usize() as BitArray) * *sizes.get_unchecked(index);
+            base_index += row.get_unchecked(pc.get_unchecked(index - 1).as_usize()).as_usize() * sizes.get_unchecked(index).as_usize();
         }
-        base_index += row.get_unchecked(pc.get_unchecked(STRENGTH - 2).as_usize()).as_usize() as BitArray;
+        base_index += row.get_unchecked(pc.get_unchecked(STRENGTH - 2).as_usize()).as_usize();
 
-        base_index *= self.value_choices.as_usize() as BitArray;
+        base_index *= self.value_choices.as_usize();
 
-        debug_assert!(base_index as usize >> BIT_SHIFT < self.map.len());
-        base_index
+        debug_assert!(base_index >> Word::SHIFT < self.map.len());
+        Index::from_usize(base_index)
     }
 }
@@ -0,0 +1,176 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module contains the word-parallel scoring used by the `get_high_score` methods of the [crate::CoverageMap].
+//!
+//! The interactions of a PC with the values of the parameter of the iteration are consecutive bits of the map.
+//! Instead of testing these bits one at a time, they are extracted from the (at most two) elements containing them,
+//! after which only the uncovered interactions are visited.
+//! This requires that the number of values is at most the number of bits of an element, see [crate::BitArray::BITS].
+//!
+//! With the `score-avx2` feature the bits of [LANES] PCs are extracted at once with an AVX2 gather on `x86_64`,
+//! if the map consists of [u64] elements and the CPU supports it, which is detected at runtime. Otherwise the scalar version is used.
+//! The gather is not faster than the scalar loads on every CPU, which is why it has to be enabled.
+//! Both push the same indices in the same order as testing the bits one at a time.
+
+use common::{Number, UVec};
+
+use crate::BitArray;
+
+/// The number of PCs of which the bits are extracted at once with AVX2.
+pub const LANES: usize = 4;
+
+/// Returns true if the CPU supports AVX2.
+pub fn avx2_available() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    return is_x86_feature_detected!("avx2");
+    #[cfg(not(target_arch = "x86_64"))]
+    false
+}
+
+/// Returns true if [add_scores] extracts the bits of maps of `Word` elements with AVX2.
+///
+/// This requires the `score-avx2` feature, [u64] elements and a CPU which supports AVX2.
+pub fn uses_avx2<Word: BitArray>() -> bool {
+    cfg!(all(feature = "score-avx2", target_arch = "x86_64")) && Word::BITS == 64 && avx2_available()
+}
+
+/// Push the uncovered interactions of the PCs with the base indices to the score of their value.
+///
+/// # Safety
+/// The `value_choices` should be at most [BitArray::BITS] and the `scores` should have a list for each value.
+/// The interactions of the base indices should be in the `map`.
+pub(crate) unsafe fn add_scores<Word: BitArray, Index: Number>(
+    map: &[Word],
+    scores: &mut UVec<UVec<Index>>,
+    value_choices: usize,
+    base_indices: impl Iterator<Item=Index>,
+) {
+    debug_assert!(value_choices <= Word::BITS);
+    #[cfg(target_arch = "x86_64")]
+    if uses_avx2::<Word>() {
+        return add_scores_avx2(map, scores, value_choices, base_indices);
+    }
+    add_scores_scalar(map, scores, value_choices, base_indices)
+}
+
+/// The scalar version of [add_scores], which extracts the bits of one PC at a time.
+pub(crate) unsafe fn add_scores_scalar<Word: BitArray, Index: Number>(
+    map: &[Word],
+    scores: &mut UVec<UVec<Index>>,
+    value_choices: usize,
+    base_indices: impl Iterator<Item=Index>,
+) {
+    let mask = value_mask::<Word>(value_choices);
+    for base_index in base_indices {
+        push_uncovered(scores, base_index, extract(map, base_index, value_choices), mask);
+    }
+}
+
+/// The AVX2 version of [add_scores], which extracts the bits of [LANES] PCs at a time.
+///
+/// # Safety
+/// The elements of the map should be [u64].
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn add_scores_avx2<Word: BitArray, Index: Number>(
+    map: &[Word],
+    scores: &mut UVec<UVec<Index>>,
+    value_choices: usize,
+    mut base_indices: impl Iterator<Item=Index>,
+) {
+    debug_assert_eq!(Word::BITS, 64);
+    let mask = value_mask::<Word>(value_choices);
+    let mut batch = [Index::default(); LANES];
+    loop {
+        let mut filled = 0;
+        while filled < LANES {
+            match base_indices.next() {
+                Some(base_index) => batch[filled] = base_index,
+                None => break,
+            }
+            filled += 1;
+        }
+
+        if filled < LANES {
+            for &base_index in batch.iter().take(filled) {
+                push_uncovered(scores, base_index, extract(map, base_index, value_choices), mask);
+            }
+            return;
+        }
+
+        let covered = extract_avx2(map, &batch, value_choices);
+        for (&base_index, &covered) in batch.iter().zip(covered.iter()) {
+            push_uncovered(scores, base_index, Word::from_usize(covered as usize), mask);
+        }
+    }
+}
+
+/// Returns the `value_choices` bits of the map starting at the index as the lowest bits, the higher bits are unspecified.
+#[inline(always)]
+unsafe fn extract<Word: BitArray, Index: Number>(map: &[Word], index: Index, value_choices: usize) -> Word {
+    let index = index.as_usize();
+    let map_index = index >> Word::SHIFT;
+    let shift = index & Word::MASK;
+    let mut bits = *map.get_unchecked(map_index) >> Word::from_usize(shift);
+    if shift + value_choices > Word::BITS {
+        bits |= *map.get_unchecked(map_index + 1) << Word::from_usize(Word::BITS - shift);
+    }
+    bits
+}
+
+/// Extract the bits of the indices like [extract] with a gather of the words containing their first bits.
+///
+/// The bits continue in the next word for few of the indices, these are completed separately, so the map needs no padding.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn extract_avx2<Word: BitArray, Index: Number>(map: &[Word], indices: &[Index; LANES], value_choices: usize) -> [u64; LANES] {
+    use std::arch::x86_64::*;
+
+    let vector = _mm256_set_epi64x(
+        indices[3].as_usize() as i64,
+        indices[2].as_usize() as i64,
+        indices[1].as_usize() as i64,
+        indices[0].as_usize() as i64,
+    );
+    let map_indices = _mm256_srli_epi64::<6>(vector);
+    let shifts = _mm256_and_si256(vector, _mm256_set1_epi64x(63));
+    let low = _mm256_i64gather_epi64::<8>(map.as_ptr() as *const i64, map_indices);
+    let bits = _mm256_srlv_epi64(low, shifts);
+
+    let mut result = [0; LANES];
+    _mm256_storeu_si256(result.as_mut_ptr() as *mut __m256i, bits);
+
+    let ends = _mm256_add_epi64(shifts, _mm256_set1_epi64x(value_choices as i64));
+    let mut continues = _mm256_movemask_pd(_mm256_castsi256_pd(_mm256_cmpgt_epi64(ends, _mm256_set1_epi64x(64))));
+    while continues != 0 {
+        let lane = continues.trailing_zeros() as usize;
+        result[lane] = extract(map, indices[lane], value_choices).as_usize() as u64;
+        continues &= continues - 1;
+    }
+    result
+}
+
+/// Returns the mask of the lowest `value_choices` bits.
+#[inline(always)]
+fn value_mask<Word: BitArray>(value_choices: usize) -> Word {
+    if value_choices == Word::BITS {
+        Word::dont_care()
+    } else {
+        Word::mask_low(value_choices)
+    }
+}
+
+/// Push the indices of the values of which the bit is set in `mask` but not in `covered` to their score.
+#[inline(always)]
+unsafe fn push_uncovered<Word: BitArray, Index: Number>(scores: &mut UVec<UVec<Index>>, base_index: Index, covered: Word, mask: Word) {
+    let mut uncovered = !covered & mask;
+    while uncovered.any() {
+        let value = uncovered.trailing_zeros() as usize;
+        scores.as_slice_mut().get_unchecked_mut(value).push(base_index + Index::from_usize(value));
+        uncovered &= uncovered - Word::from_usize(1);
+    }
+}
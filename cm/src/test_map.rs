@@ -14,7 +14,7 @@ use lazy_static::lazy_static;
 use common::{u_vec, UVec};
 use pc_list::PCList;
 
-use crate::{BitArray, CoverageMap};
+use crate::{BitArray, coverage_map_bits, coverage_map_bytes, CoverageMap, Estimate, largest_pc_bits, map_bytes, MapTypes};
 
 const STRENGTH: usize = 6;
 const PARAMETERS: [usize; 15] = [6, 6, 6, 5, 4, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2];
@@ -35,9 +35,9 @@ struct Init {
     pc_list: PCList<usize, u64, STRENGTH>,
     pc_list_len: usize,
     cm: CoverageMap<usize, STRENGTH>,
-    locations: UVec<BitArray>,
-    scores: UVec<UVec<BitArray>>,
-    no_dont_cares: BitArray,
+    locations: UVec<u64>,
+    scores: UVec<UVec<u64>>,
+    no_dont_cares: u64,
 }
 
 impl Init {
@@ -47,9 +47,9 @@ impl Init {
         &PCList<usize, u64, STRENGTH>,
         usize,
         &CoverageMap<usize, STRENGTH>,
-        &UVec<BitArray>,
-        &UVec<UVec<BitArray>>,
-        BitArray,
+        &UVec<u64>,
+        &UVec<UVec<u64>>,
+        u64,
     ) {
         (
             &self.pc_list,
@@ -63,10 +63,10 @@ impl Init {
 }
 
 mod test_indices {
-    use common::{repeat_strengths, UVec, ValueGenerator};
+    use common::{Number, repeat_strengths, UVec, ValueGenerator};
     use pc_list::PCList;
 
-    use crate::{BIT_SHIFT, BitArray, CoverageMap};
+    use crate::{BitArray, CoverageMap};
 
     use super::PARAMETERS;
 
@@ -74,20 +74,22 @@ mod test_indices {
         ($strength_name:ident, $strength:expr) => {
             #[test]
             fn $strength_name() {
-                sub_test::<$strength>();
+                sub_test::<$strength, u64, u64>();
+                sub_test::<$strength, u32, u32>();
+                sub_test::<$strength, u128, u32>();
             }
         };
     }
 
     repeat_strengths!(call_sub_test);
 
-    fn sub_test<const STRENGTH: usize>() where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    fn sub_test<const STRENGTH: usize, Word: BitArray, Index: Number>() where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
         let at_parameter = PARAMETERS.len() - 2;
         let parameters = UVec::from(PARAMETERS.to_vec());
         let pc_list = PCList::<usize, u64, STRENGTH>::new(PARAMETERS.len());
         let pc_list_len = pc_list.sizes[at_parameter - STRENGTH];
 
-        let mut map = CoverageMap::<usize, STRENGTH>::new(parameters.clone(), &pc_list);
+        let mut map = CoverageMap::<usize, STRENGTH, Word, Index>::new(parameters.clone(), &pc_list);
         map.initialise(at_parameter);
 
         let mut count = 0;
@@ -114,10 +116,10 @@ mod test_indices {
             }
 
             assert_eq!(
-                sizes[0] as usize * PARAMETERS[at_parameter],
+                sizes[0].as_usize() * PARAMETERS[at_parameter],
                 count,
                 "sizes {} != count {}; tid: {}",
-                sizes[0] as usize * PARAMETERS[at_parameter],
+                sizes[0].as_usize() * PARAMETERS[at_parameter],
                 count,
                 tid
             );
@@ -130,7 +132,7 @@ mod test_indices {
             "uncovered {} != count {}; vc: {}",
             map.uncovered, count, map.value_choices
         );
-        assert_eq!(map.map.len(), 1 + (count >> BIT_SHIFT));
+        assert_eq!(map.map.len(), 1 + (count >> Word::SHIFT));
 
         unsafe {
             map.set_zero_covered();
@@ -141,7 +143,7 @@ mod test_indices {
         let mut index = 0;
         for (tid, pc) in pc_list.pcs.iter().take(pc_list_len).enumerate() {
             assert!(
-                unsafe { !map.set_index(index) },
+                unsafe { !map.set_index(Index::from_usize(index)) },
                 "zero check; count {}; index {}; tid {}; next offset: {}",
                 count,
                 index,
@@ -159,7 +161,7 @@ mod test_indices {
             );
             while generator.next_array(&mut values) {
                 assert!(
-                    unsafe { map.set_index(index) },
+                    unsafe { map.set_index(Index::from_usize(index)) },
                     "count {}; index {}; tid {}; next offset: {}",
                     count,
                     index,
@@ -171,8 +173,8 @@ mod test_indices {
                         row[*pid] = *value;
                     }
                     assert_eq!(
-                        unsafe { map.get_base_index(tid, &pc_list, &row) }.unwrap()
-                            + values[STRENGTH - 1] as BitArray,
+                        unsafe { map.get_base_index(tid, &pc_list, &row) }.unwrap().as_usize()
+                            + values[STRENGTH - 1],
                         index
                     );
                 }
@@ -182,8 +184,8 @@ mod test_indices {
 
         assert_eq!(map.uncovered, 0);
 
-        for &a in map.map.iter().take(count >> BIT_SHIFT) {
-            assert_eq!(a, BitArray::max_value());
+        for &a in map.map.iter().take(count >> Word::SHIFT) {
+            assert_eq!(a, Word::dont_care());
         }
     }
 }
@@ -203,7 +205,7 @@ fn init_bench() {
             let mut location = !0;
             for (parameter_id, &value) in row.iter().enumerate() {
                 if value != !0 {
-                    location ^= 1 << parameter_id as BitArray;
+                    location ^= 1 << parameter_id as u64;
                 }
             }
             for pid in at_parameter..parameter_count {
@@ -218,7 +220,7 @@ fn init_bench() {
             cm,
             locations,
             scores: u_vec![UVec::with_capacity(pc_list_len); VALUE_CHOICES],
-            no_dont_cares: !((!0) << at_parameter as BitArray),
+            no_dont_cares: !((!0) << at_parameter as u64),
         });
     }
 }
@@ -243,9 +245,9 @@ fn bench_naive(bencher: &mut Bencher) {
         &PCList<usize, u64, STRENGTH>,
         usize,
         &CoverageMap<usize, STRENGTH>,
-        &UVec<BitArray>,
-        &UVec<UVec<BitArray>>,
-        BitArray,
+        &UVec<u64>,
+        &UVec<UVec<u64>>,
+        u64,
     ) = init_guard.as_ref().unwrap().borrow_pc();
     let mut scores = scores.clone();
 
@@ -268,9 +270,9 @@ fn bench_masked(bencher: &mut Bencher) {
         &PCList<usize, u64, STRENGTH>,
         usize,
         &CoverageMap<usize, STRENGTH>,
-        &UVec<BitArray>,
-        &UVec<UVec<BitArray>>,
-        BitArray,
+        &UVec<u64>,
+        &UVec<UVec<u64>>,
+        u64,
     ) = init_guard.as_ref().unwrap().borrow_pc();
     let mut scores = scores.clone();
 
@@ -298,9 +300,9 @@ fn bench_unchecked_and_naive(bencher: &mut Bencher) {
         &PCList<usize, u64, STRENGTH>,
         usize,
         &CoverageMap<usize, STRENGTH>,
-        &UVec<BitArray>,
-        &UVec<UVec<BitArray>>,
-        BitArray,
+        &UVec<u64>,
+        &UVec<UVec<u64>>,
+        u64,
     ) = init_guard.as_ref().unwrap().borrow_pc();
     let mut scores = scores.clone();
 
@@ -329,9 +331,9 @@ fn bench_unchecked_and_naive_count(bencher: &mut Bencher) {
         &PCList<usize, u64, STRENGTH>,
         usize,
         &CoverageMap<usize, STRENGTH>,
-        &UVec<BitArray>,
-        &UVec<UVec<BitArray>>,
-        BitArray,
+        &UVec<u64>,
+        &UVec<UVec<u64>>,
+        u64,
     ) = init_guard.as_ref().unwrap().borrow_pc();
     let mut scores = scores.clone();
 
@@ -361,3 +363,192 @@ fn bench_count_zeros(bencher: &mut Bencher) {
         assert_eq!((a & b).count_ones(), 1);
     });
 }
+
+#[test]
+fn test_coverage_map_bits() {
+    let pc_list = PCList::<usize, u64, STRENGTH>::new(PARAMETERS.len());
+    let cm = CoverageMap::<usize, STRENGTH>::new(UVec::from(PARAMETERS.to_vec()), &pc_list);
+    let bits = coverage_map_bits(&PARAMETERS, STRENGTH);
+    assert_eq!(cm.map.capacity(), (bits >> u64::SHIFT) as usize + 1);
+    assert_eq!(coverage_map_bits(&[3, 2, 2], 2), 2 * 5);
+
+    let word_bits = if crate::score::uses_avx2::<u64>() { 64 } else { 32 };
+    assert_eq!(MapTypes::select(&PARAMETERS, STRENGTH), MapTypes { word_bits, index_bits: 32 });
+    let bytes = crate::with_map_types!(&PARAMETERS, STRENGTH, <Word, Index> {
+        let cm = CoverageMap::<usize, STRENGTH, Word, Index>::new(UVec::from(PARAMETERS.to_vec()), &pc_list);
+        cm.map.capacity() * std::mem::size_of::<Word>() + cm.sizes.len() * (STRENGTH - 1) * std::mem::size_of::<Index>()
+    });
+    assert_eq!(coverage_map_bytes(&PARAMETERS, STRENGTH), bytes as u128);
+}
+
+#[test]
+fn test_select_avx2() {
+    use crate::score::{avx2_available, uses_avx2};
+
+    let avx2 = cfg!(all(feature = "score-avx2", target_arch = "x86_64")) && avx2_available();
+    for (parameters, strength) in [(&[4, 3, 3, 2][..], 2), (&PARAMETERS[..], STRENGTH), (&[40, 40, 40][..], 2), (&[64; 5][..], 3)] {
+        let used = crate::with_map_types!(parameters, strength, <Word, Index> uses_avx2::<Word>() && std::mem::size_of::<Index>() > 0);
+        assert_eq!(used, avx2, "{:?}", parameters);
+    }
+    assert!(!crate::with_map_types!(&[65usize, 2, 2], 2, <Word, Index> uses_avx2::<Word>() && std::mem::size_of::<Index>() > 0));
+}
+
+#[test]
+fn test_chunks() {
+    const STRENGTH: usize = 3;
+    let at_parameter = 11;
+    let parameters = UVec::from(PARAMETERS.to_vec());
+    let pc_list = PCList::<usize, u64, STRENGTH>::new(PARAMETERS.len());
+    let pc_list_len = pc_list.sizes[at_parameter - STRENGTH];
+
+    assert_eq!(largest_pc_bits(&[3, 2, 2], 2), 6);
+    assert_eq!(largest_pc_bits(&[2, 3, 4, 2], 3), 24);
+    let largest_pc_bytes = ((largest_pc_bits(&PARAMETERS, STRENGTH) >> u64::SHIFT) as usize + 1) * std::mem::size_of::<u64>();
+    assert!(CoverageMap::<usize, STRENGTH>::with_memory_budget(parameters.clone(), &pc_list, largest_pc_bytes - 1).is_err());
+    assert!(CoverageMap::<usize, STRENGTH>::with_memory_budget(parameters.clone(), &pc_list, largest_pc_bytes).is_ok());
+
+    let mut dense = CoverageMap::<usize, STRENGTH>::new(parameters.clone(), &pc_list);
+    let mut chunked = CoverageMap::<usize, STRENGTH>::with_memory_budget(parameters, &pc_list, 64).unwrap();
+    assert_eq!(chunked.map.capacity(), 64 / std::mem::size_of::<u64>());
+
+    let rows: Vec<[usize; 15]> = ROWS.iter().map(|row| {
+        let mut row = *row;
+        row[at_parameter] = 1;
+        row
+    }).collect();
+
+    dense.initialise(at_parameter);
+    assert_eq!(dense.chunk(), 0..pc_list_len);
+    assert!(!dense.next_chunk());
+    for row in rows.iter() {
+        unsafe { dense.set_covered_row_simple(at_parameter, &pc_list, pc_list_len, row) };
+    }
+
+    chunked.initialise(at_parameter);
+    let (mut end, mut uncovered, mut chunks) = (0, 0, 0);
+    loop {
+        let chunk = chunked.chunk();
+        assert_eq!(chunk.start, end);
+        assert!(chunk.end > chunk.start);
+        end = chunk.end;
+
+        for row in rows.iter() {
+            unsafe { chunked.set_covered_row_simple_sub(at_parameter, &pc_list, row, chunk.start, chunk.end) };
+        }
+        uncovered += chunked.uncovered;
+        chunks += 1;
+
+        if !chunked.next_chunk() {
+            break;
+        }
+    }
+
+    assert_eq!(end, pc_list_len);
+    assert!(chunks > 1);
+    assert_eq!(uncovered, dense.uncovered);
+}
+
+#[test]
+fn test_estimate_budget() {
+    const STRENGTH: usize = 3;
+    let estimate = Estimate::new::<u8, u128>(&PARAMETERS, STRENGTH);
+    let min_map_bytes = map_bytes(largest_pc_bits(&PARAMETERS, STRENGTH), estimate.map_types.word_bits);
+    assert!(estimate.min_scores_bytes <= estimate.scores_bytes);
+    assert!(estimate.min_memory_bytes() < estimate.memory_bytes());
+    assert!(estimate.coverage_map_budget(estimate.min_memory_bytes() - 1).is_err());
+    assert_eq!(estimate.coverage_map_budget(estimate.memory_bytes()), Ok(None));
+
+    // The map of a chunk and its scores stay within the budget, after the PC list and offsets
+    let offsets = estimate.min_coverage_map_bytes - min_map_bytes;
+    for budget in [estimate.min_memory_bytes(), (estimate.min_memory_bytes() + estimate.memory_bytes()) / 2, estimate.memory_bytes() - 1] {
+        let map_budget = estimate.coverage_map_budget(budget).unwrap().unwrap();
+        assert!(map_budget >= min_map_bytes);
+        let chunk_pcs = map_budget * 8 / PARAMETERS.iter().rev().take(STRENGTH).product::<usize>() as u128;
+        let scores = (chunk_pcs * 6 * estimate.map_types.index_bits as u128 / 8).min(estimate.scores_bytes);
+        assert!(estimate.pc_list_bytes + offsets + map_budget + scores <= budget + 1, "{}", budget);
+    }
+}
+
+#[test]
+fn test_interaction() {
+    const STRENGTH: usize = 3;
+    let at_parameter = 9;
+    let parameters = UVec::from(PARAMETERS.to_vec());
+    let pc_list = PCList::<usize, u64, STRENGTH>::new(PARAMETERS.len());
+
+    let dense = CoverageMap::<usize, STRENGTH>::new(parameters.clone(), &pc_list);
+    let chunked = CoverageMap::<usize, STRENGTH>::with_memory_budget(parameters, &pc_list, 64).unwrap();
+    for mut cm in [dense, chunked] {
+        cm.initialise(at_parameter);
+        loop {
+            let (mut from, mut visited) = (0, 0);
+            let uncovered = cm.uncovered;
+            while let Some(index) = cm.first_uncovered(from) {
+                let (pc_id, values, value) = cm.interaction(index);
+                assert!(cm.chunk().contains(&pc_id));
+
+                let mut row = [!0; 15];
+                for (&parameter, &pc_value) in pc_list.pcs[pc_id].iter().zip(values.iter()) {
+                    row[parameter] = pc_value;
+                }
+                row[at_parameter] = value;
+                assert_eq!(unsafe { cm.get_base_index(pc_id, &pc_list, &row) }, Some(index - value as u64));
+
+                assert!(unsafe { cm.set_index(index) });
+                from = index + 1;
+                visited += 1;
+            }
+            assert_eq!(visited, uncovered);
+            assert!(cm.is_covered());
+
+            if !cm.next_chunk() {
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_score_extraction() {
+    use common::{Number, split_mix};
+
+    use crate::score::{add_scores_scalar, avx2_available};
+
+    fn sub_test<Word: BitArray, Index: Number>(avx2: bool) {
+        let mut state = 0x1ca;
+        let map: UVec<Word> = (0..64 * 64 / Word::BITS).map(|_| Word::from_usize(split_mix(&mut state) as usize)).collect();
+        for value_choices in [1, 2, 3, 5, 7, 10, 33, 64, 100, 128].into_iter().filter(|&value_choices| value_choices <= Word::BITS) {
+            // Skip some of the PCs, like the PCs with don't-cares are skipped.
+            let base_indices: Vec<Index> = (0..63 * 64 / value_choices)
+                .filter(|pc| pc % 3 != 1)
+                .map(|pc| Index::from_usize(pc * value_choices))
+                .collect();
+
+            let mut expected = u_vec![UVec::with_capacity(0); value_choices];
+            for &base_index in base_indices.iter() {
+                for (value, score) in expected.iter_mut().enumerate() {
+                    let index = base_index.as_usize() + value;
+                    if !map[index >> Word::SHIFT].get(index & Word::MASK) {
+                        score.push(Index::from_usize(index));
+                    }
+                }
+            }
+
+            let mut scores = u_vec![UVec::with_capacity(0); value_choices];
+            unsafe { add_scores_scalar(map.as_slice(), &mut scores, value_choices, base_indices.iter().copied()) };
+            assert!(scores == expected, "scalar scores for {} values in {} bits", value_choices, Word::BITS);
+
+            #[cfg(target_arch = "x86_64")]
+            if avx2 {
+                let mut scores = u_vec![UVec::with_capacity(0); value_choices];
+                unsafe { crate::score::add_scores_avx2(map.as_slice(), &mut scores, value_choices, base_indices.iter().copied()) };
+                assert!(scores == expected, "AVX2 scores for {} values", value_choices);
+            }
+        }
+    }
+
+    sub_test::<u64, u64>(avx2_available());
+    sub_test::<u64, u32>(avx2_available());
+    sub_test::<u32, u32>(false);
+    sub_test::<u128, u64>(false);
+}
@@ -0,0 +1,88 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::fmt::{Debug, Formatter};
+use std::iter::FromIterator;
+
+/// A growable list of bits, stored in [u64] words.
+///
+/// # Example
+/// ```
+/// use common::BitVec;
+///
+/// let bits: BitVec = [true, false, true].into_iter().collect();
+/// assert_eq!(bits.len(), 3);
+/// assert!(bits.get(2));
+/// assert_eq!(bits.count_ones(), 2);
+/// assert_eq!(bits.iter().collect::<Vec<_>>(), vec![true, false, true]);
+/// ```
+#[derive(Clone, Default, Eq, PartialEq, Hash)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    /// Create an empty list with room for `capacity` bits.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { words: Vec::with_capacity(capacity.div_ceil(64)), len: 0 }
+    }
+
+    /// Append the bit to the end of the list.
+    #[inline]
+    pub fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        self.words[self.len / 64] |= (bit as u64) << (self.len % 64);
+        self.len += 1;
+    }
+
+    /// Returns the bit at the index.
+    ///
+    /// # Panics
+    /// If the index is not less than the length.
+    #[inline]
+    pub fn get(&self, index: usize) -> bool {
+        assert!(index < self.len, "Index {} out of bounds for a BitVec of length {}.", index, self.len);
+        (self.words[index / 64] >> (index % 64)) & 1 == 1
+    }
+
+    /// Returns the number of bits.
+    #[inline]
+    pub fn len(&self) -> usize { self.len }
+
+    /// Returns true if there are no bits.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns the number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Iterate over the bits in order.
+    pub fn iter(&self) -> impl Iterator<Item=bool> + '_ {
+        (0..self.len).map(move |index| (self.words[index / 64] >> (index % 64)) & 1 == 1)
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item=bool>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut result = Self::with_capacity(iter.size_hint().0);
+        for bit in iter {
+            result.push(bit);
+        }
+        result
+    }
+}
+
+impl Debug for BitVec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
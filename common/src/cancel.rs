@@ -0,0 +1,59 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Allows for stopping the generation early, either explicitly or when a time limit has passed.
+///
+/// Clones of the token share the cancellation, so a clone can be cancelled from another thread.
+///
+/// # Example
+/// ```
+/// use common::CancellationToken;
+///
+/// let token = CancellationToken::new();
+/// let clone = token.clone();
+/// assert!(!token.is_cancelled());
+/// clone.cancel();
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    /// Create a token which is only cancelled by calling [CancellationToken::cancel].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a token which is cancelled once the time limit has passed, starting now.
+    pub fn with_time_limit(time_limit: Duration) -> Self {
+        Self { cancelled: Arc::default(), deadline: Some(Instant::now() + time_limit) }
+    }
+
+    /// Cancel the generation using this token or any of its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the token is cancelled or its time limit has passed.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return true;
+        }
+        if self.deadline.is_some_and(|deadline| deadline <= Instant::now()) {
+            self.cancel();
+            return true;
+        }
+        false
+    }
+}
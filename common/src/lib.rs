@@ -6,8 +6,15 @@
 
 //! This crate provides common features used throughout the IPOG implementations and the data-types of LibreCA.
 //!
+//! # Logging
+//! The crates of LibreCA do not print anything, they emit [tracing] events and spans instead.
+//! The phases timed by [time_it] are `time` spans, at the info level unless specified otherwise, with the name of the phase in the `phase` field.
+//! Install a [tracing] subscriber to receive them, the cli crate provides one which writes to the standard error.
+//!
 //! # Features
-//!   * `sub-time` Print the timings for all the [sub_time_it] calls.
+//!   * `sub-time` Record the [sub_time_it] calls as `time` spans at the debug level.
+//!   * `serde` Implement `Serialize` and `Deserialize` for the [UVec] and the [Phase].
+//!   * `filter-map`, `score-single`, `score-double` and `no-cycle-split` Select the defaults of the [RunOptions].
 
 #![feature(step_trait)]
 #![cfg_attr(test, feature(test))]
@@ -17,14 +24,27 @@
 #![feature(slice_as_chunks)]
 #![deny(missing_docs, rustdoc::missing_crate_level_docs, future_incompatible)]
 
+pub use bit_vec::BitVec;
+pub use cancel::CancellationToken;
 pub use number::Number;
+pub use progress::{Phase, Progress, ProgressObserver};
+pub use report::{CacheHits, ParameterReport, PhaseTiming, Report, ReportObserver, SharedReport, SolverCalls};
+pub use run_options::{RunOptions, Scoring};
 pub use u_vec::UVec;
 pub use value_generator::ValueGenerator;
 
+mod bit_vec;
+mod cancel;
 mod number;
+mod progress;
+mod report;
+mod run_options;
 mod u_vec;
 mod value_generator;
 
+#[doc(hidden)]
+pub use tracing;
+
 
 /// The minimal supported strength.
 pub const MIN_STRENGTH: usize = 2;
@@ -35,27 +55,59 @@ pub const MAX_STRENGTH: usize = 12;
 /// The text to print when a value is a don't care value.
 pub const DONT_CARE_TEXT: &str = "*";
 
-/// Print the time it took to provide the result of the provided expression.
+/// The SplitMix64 generator, which gives the same numbers for a seed on every platform.
+pub fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Move on to the next combination of indices below `length` in lexicographic order, returns false if this was the last one.
+///
+/// Start with the combination `[0, 1, ..., size - 1]`, `length` should be at least the size of the combination.
+pub fn next_combination(combination: &mut [usize], length: usize) -> bool {
+    let size = combination.len();
+    let mut index = size;
+    while index > 0 {
+        index -= 1;
+        if combination[index] < length - size + index {
+            combination[index] += 1;
+            for next in index + 1..size {
+                combination[next] = combination[next - 1] + 1;
+            }
+            return true;
+        }
+    }
+    false
+}
+
+/// Record the time it takes to evaluate the provided expression as the duration of a `time` span.
 /// Returns the result of the provided expression.
 ///
+/// The span is at the info level, unless another [tracing::Level] is provided.
+///
 /// # Example
 /// ```
 /// use common::time_it;
 ///
 /// time_it!(0 + 1, "Addition");
+/// time_it!(0 + 1, "Addition", DEBUG);
 /// ```
 #[macro_export]
 macro_rules! time_it {
-    ($code:expr, $text:expr) => {{
-        let now = std::time::Instant::now();
-        let result = $code;
-        let duration = now.elapsed();
-        println!("{} takes: {}.{:06}s", $text, duration.as_secs(), duration.subsec_micros());
-        result
+    ($code:expr, $text:expr) => {
+        $crate::time_it!($code, $text, INFO)
+    };
+    ($code:expr, $text:expr, $level:ident) => {{
+        let span = $crate::tracing::span!($crate::tracing::Level::$level, "time", phase = $text);
+        let _entered = span.enter();
+        $code
     }};
 }
 
-/// Act like [time_it] if the `sub-time` feature is set. Otherwise return the provided expression.
+/// Act like [time_it] at the debug level if the `sub-time` feature is set. Otherwise return the provided expression.
 ///
 /// # Example
 /// ```
@@ -68,16 +120,12 @@ macro_rules! time_it {
 #[cfg(feature = "sub-time")]
 #[macro_export]
 macro_rules! sub_time_it {
-    ($code:expr, $text:expr) => {{
-        let now = std::time::Instant::now();
-        let result = $code;
-        let duration = now.elapsed();
-        println!("{} takes: {}.{:06}s", $text, duration.as_secs(), duration.subsec_micros());
-        result
-    }};
+    ($code:expr, $text:expr) => {
+        $crate::time_it!($code, $text, DEBUG)
+    };
 }
 
-/// Act like [time_it] if the `sub-time` feature is set. Otherwise return the provided expression.
+/// Act like [time_it] at the debug level if the `sub-time` feature is set. Otherwise return the provided expression.
 ///
 /// # Example
 /// ```
@@ -0,0 +1,112 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+/// The phase of the IPOG algorithm a [Progress] refers to.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Phase {
+    /// The horizontal extension of the parameter is about to start.
+    Horizontal,
+
+    /// The vertical extension of the parameter is about to start.
+    Vertical,
+
+    /// The generation has finished, the [Progress::at_parameter] equals the number of parameters.
+    Done,
+}
+
+/// A snapshot of the state of the generation, provided to a [ProgressObserver].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Progress {
+    /// The strength of the MCA being generated.
+    pub strength: usize,
+
+    /// The parameter which is currently being added to the MCA.
+    pub at_parameter: usize,
+
+    /// The total number of parameters of the SUT.
+    pub parameters: usize,
+
+    /// The number of rows of the MCA so far.
+    pub rows: usize,
+
+    /// The number of interactions of the current parameter which are still uncovered.
+    pub uncovered: usize,
+
+    /// The phase of the current parameter.
+    pub phase: Phase,
+}
+
+/// Returns the binomial coefficient *n* over *k* as a float, which is used as the weight of a parameter.
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |result, i| result * (n - i) as f64 / (i + 1) as f64)
+}
+
+impl Progress {
+    /// Estimate the fraction of the work done, between 0 and 1.
+    ///
+    /// The work for a parameter is proportional to the number of parameter combinations it is part of,
+    /// which is the number of combinations of *t*-1 of the previous parameters.
+    /// The vertical extension is counted as the second half of the work of a parameter.
+    pub fn fraction(&self) -> f64 {
+        if self.phase == Phase::Done || self.parameters <= self.strength {
+            return 1.0;
+        }
+        let weight = |at_parameter: usize| binomial(at_parameter, self.strength - 1);
+        let total: f64 = (self.strength..self.parameters).map(weight).sum();
+        let done: f64 = (self.strength..self.at_parameter).map(weight).sum();
+        let current = if self.phase == Phase::Vertical { weight(self.at_parameter) / 2.0 } else { 0.0 };
+        ((done + current) / total).min(1.0)
+    }
+}
+
+/// Receives the progress of the generation of an MCA.
+///
+/// The IPOG implementations call [ProgressObserver::progress] at the start of every phase of every parameter
+/// and once with [Phase::Done] when the generation has finished.
+/// The observer is always called from the thread which started the generation.
+pub trait ProgressObserver {
+    /// Handle the progress of the generation.
+    fn progress(&mut self, progress: &Progress);
+}
+
+/// The unit type ignores the progress.
+impl ProgressObserver for () {
+    fn progress(&mut self, _progress: &Progress) {}
+}
+
+impl<O: ProgressObserver + ?Sized> ProgressObserver for Box<O> {
+    fn progress(&mut self, progress: &Progress) {
+        (**self).progress(progress)
+    }
+}
+
+/// A pair of observers passes the progress to both.
+impl<A: ProgressObserver, B: ProgressObserver> ProgressObserver for (A, B) {
+    fn progress(&mut self, progress: &Progress) {
+        self.0.progress(progress);
+        self.1.progress(progress);
+    }
+}
+
+#[cfg(test)]
+mod progress_tests {
+    use super::*;
+
+    fn progress(at_parameter: usize, phase: Phase) -> Progress {
+        Progress { strength: 2, at_parameter, parameters: 5, rows: 0, uncovered: 0, phase }
+    }
+
+    #[test]
+    fn test_fraction() {
+        // The weights of the parameters 2, 3 and 4 are 2, 3 and 4.
+        assert_eq!(progress(2, Phase::Horizontal).fraction(), 0.0);
+        assert_eq!(progress(2, Phase::Vertical).fraction(), 1.0 / 9.0);
+        assert_eq!(progress(3, Phase::Horizontal).fraction(), 2.0 / 9.0);
+        assert_eq!(progress(4, Phase::Vertical).fraction(), 7.0 / 9.0);
+        assert_eq!(progress(5, Phase::Done).fraction(), 1.0);
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{Phase, Progress, ProgressObserver};
+
+/// The total duration of all the `time` spans of a phase, see [crate::time_it].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PhaseTiming {
+    /// The name of the phase.
+    pub phase: String,
+
+    /// The number of times the phase was timed.
+    pub count: usize,
+
+    /// The sum of the durations.
+    pub duration: Duration,
+}
+
+/// The time spent on a single parameter and the rows added by its extensions.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ParameterReport {
+    /// The parameter which was added to the MCA.
+    pub parameter: usize,
+
+    /// The duration of the horizontal extension.
+    pub horizontal: Duration,
+
+    /// The number of rows added during the horizontal extension.
+    pub horizontal_rows: usize,
+
+    /// The duration of the vertical extension, zero if the horizontal extension covered all interactions.
+    pub vertical: Duration,
+
+    /// The number of rows added during the vertical extension.
+    pub vertical_rows: usize,
+}
+
+/// The number of calls of a checking method of a solver, split by their result.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SolverCalls {
+    /// The name of the method of the solver.
+    pub method: &'static str,
+
+    /// The number of calls which returned true.
+    pub sat: u64,
+
+    /// The number of calls which returned false.
+    pub unsat: u64,
+}
+
+impl SolverCalls {
+    /// Returns the total number of calls.
+    pub fn calls(&self) -> u64 {
+        self.sat + self.unsat
+    }
+
+    /// Returns the fraction of the calls which returned true, or [None] if the method was not called.
+    pub fn sat_ratio(&self) -> Option<f64> {
+        if self.calls() == 0 {
+            None
+        } else {
+            Some(self.sat as f64 / self.calls() as f64)
+        }
+    }
+}
+
+/// The number of queries of a solver answered from its cache and the number passed on to the solver.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CacheHits {
+    /// The number of queries answered from the cache.
+    pub hits: u64,
+
+    /// The number of queries passed on to the solver.
+    pub misses: u64,
+}
+
+impl CacheHits {
+    /// Returns the total number of queries.
+    pub fn queries(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    /// Returns the fraction of the queries answered from the cache, or [None] if there were no queries.
+    pub fn hit_ratio(&self) -> Option<f64> {
+        if self.queries() == 0 {
+            None
+        } else {
+            Some(self.hits as f64 / self.queries() as f64)
+        }
+    }
+}
+
+/// The statistics of a run, collected for the machine-readable report of the cli.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Report {
+    /// The timings of the phases, in the order in which they first finished.
+    pub phases: Vec<PhaseTiming>,
+
+    /// The work done per parameter, in the order in which the parameters were added.
+    pub parameters: Vec<ParameterReport>,
+
+    /// The calls of the checking methods of the solver, empty if the solver calls were not counted.
+    pub solver_calls: Vec<SolverCalls>,
+
+    /// The queries answered by the cache of the solver, [None] if the solver did not cache.
+    pub solver_cache: Option<CacheHits>,
+
+    /// The largest coverage map allocated during the run, in bytes.
+    pub peak_coverage_map_bytes: usize,
+
+    /// The number of rows of the resulting MCA.
+    pub rows: usize,
+}
+
+/// A [Report] shared between the collectors of its statistics.
+pub type SharedReport = Arc<Mutex<Report>>;
+
+impl Report {
+    /// Add the duration to the timing of the given phase.
+    pub fn add_phase_timing(&mut self, phase: &str, duration: Duration) {
+        match self.phases.iter_mut().find(|timing| timing.phase == phase) {
+            Some(timing) => {
+                timing.count += 1;
+                timing.duration += duration;
+            }
+            None => self.phases.push(PhaseTiming { phase: phase.to_string(), count: 1, duration }),
+        }
+    }
+}
+
+/// Fills the [Report::parameters] using the progress of the generation.
+///
+/// Each phase ends when the next [Progress] is received, so the duration of a phase is the time between two calls.
+///
+/// # Example
+/// ```
+/// use common::{Phase, Progress, ProgressObserver, ReportObserver, SharedReport};
+///
+/// let report = SharedReport::default();
+/// let mut observer = ReportObserver::new(report.clone());
+/// observer.progress(&Progress { strength: 2, at_parameter: 2, parameters: 3, rows: 4, uncovered: 8, phase: Phase::Horizontal });
+/// observer.progress(&Progress { strength: 2, at_parameter: 2, parameters: 3, rows: 4, uncovered: 2, phase: Phase::Vertical });
+/// observer.progress(&Progress { strength: 2, at_parameter: 3, parameters: 3, rows: 6, uncovered: 0, phase: Phase::Done });
+///
+/// let report = report.lock().unwrap();
+/// assert_eq!(report.parameters.len(), 1);
+/// assert_eq!(report.parameters[0].parameter, 2);
+/// assert_eq!(report.parameters[0].horizontal_rows, 0);
+/// assert_eq!(report.parameters[0].vertical_rows, 2);
+/// ```
+pub struct ReportObserver {
+    report: SharedReport,
+    last: Option<(Progress, Instant)>,
+}
+
+impl ReportObserver {
+    /// Create a new observer which adds the parameters to the given report.
+    pub fn new(report: SharedReport) -> Self {
+        Self { report, last: None }
+    }
+}
+
+impl ProgressObserver for ReportObserver {
+    fn progress(&mut self, progress: &Progress) {
+        let now = Instant::now();
+        if let Some((last, start)) = self.last.replace((*progress, now)) {
+            let duration = now - start;
+            let rows = progress.rows.saturating_sub(last.rows);
+            let mut report = self.report.lock().unwrap();
+            if last.phase == Phase::Horizontal {
+                report.parameters.push(ParameterReport { parameter: last.at_parameter, horizontal: duration, horizontal_rows: rows, ..ParameterReport::default() });
+            } else if let (Phase::Vertical, Some(parameter)) = (last.phase, report.parameters.last_mut()) {
+                parameter.vertical = duration;
+                parameter.vertical_rows = rows;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod report_tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn progress(at_parameter: usize, rows: usize, phase: Phase) -> Progress {
+        Progress { strength: 2, at_parameter, parameters: 4, rows, uncovered: 0, phase }
+    }
+
+    #[test]
+    fn test_add_phase_timing() {
+        let mut report = Report::default();
+        report.add_phase_timing("Parsing", Duration::from_millis(3));
+        report.add_phase_timing("Generation", Duration::from_millis(5));
+        report.add_phase_timing("Parsing", Duration::from_millis(2));
+        assert_eq!(report.phases, vec![
+            PhaseTiming { phase: "Parsing".to_string(), count: 2, duration: Duration::from_millis(5) },
+            PhaseTiming { phase: "Generation".to_string(), count: 1, duration: Duration::from_millis(5) },
+        ]);
+    }
+
+    #[test]
+    fn test_report_observer() {
+        let report = SharedReport::default();
+        let mut observer = ReportObserver::new(report.clone());
+        observer.progress(&progress(2, 9, Phase::Horizontal));
+        observer.progress(&progress(3, 9, Phase::Horizontal));
+        observer.progress(&progress(3, 9, Phase::Vertical));
+        observer.progress(&progress(4, 12, Phase::Done));
+
+        let parameters = &report.lock().unwrap().parameters;
+        assert_eq!(parameters.iter().map(|p| (p.parameter, p.horizontal_rows, p.vertical_rows)).collect::<Vec<_>>(), vec![(2, 0, 0), (3, 0, 3)]);
+        assert_eq!(parameters[0].vertical, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_sat_ratio() {
+        assert_eq!(SolverCalls { method: "check", sat: 0, unsat: 0 }.sat_ratio(), None);
+        assert_eq!(SolverCalls { method: "check", sat: 3, unsat: 1 }.sat_ratio(), Some(0.75));
+        assert_eq!(CacheHits::default().hit_ratio(), None);
+        assert_eq!(CacheHits { hits: 1, misses: 3 }.hit_ratio(), Some(0.25));
+    }
+}
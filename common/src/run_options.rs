@@ -0,0 +1,76 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+/// The algorithm used by the coverage map to calculate the scores of the values for a row.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Scoring {
+    /// Always use the naive algorithm.
+    Single,
+
+    /// Switch between the bitwise algorithm and the unchecked algorithm when there are no don't-cares.
+    Double,
+
+    /// Switch between the unchecked, naive and bitwise algorithms depending on the number of don't-cares.
+    Triple,
+}
+
+impl Scoring {
+    /// Parse the algorithm as written on the commandline, which is one of `single`, `double` or `triple`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "single" => Ok(Self::Single),
+            "double" => Ok(Self::Double),
+            "triple" => Ok(Self::Triple),
+            _ => Err(format!("Unknown scoring algorithm {}, expected single, double or triple.", text)),
+        }
+    }
+}
+
+impl Default for Scoring {
+    /// The algorithm selected by the `score-double` and `score-single` features, [Scoring::Triple] if neither is set.
+    fn default() -> Self {
+        if cfg!(feature = "score-double") {
+            Self::Double
+        } else if cfg!(feature = "score-single") {
+            Self::Single
+        } else {
+            Self::Triple
+        }
+    }
+}
+
+/// The algorithmic choices of a generation which can be compared without rebuilding.
+///
+/// The defaults are selected by the features of the same name, so a build without flags behaves as before.
+///
+/// # Example
+/// ```
+/// use common::{RunOptions, Scoring};
+///
+/// let options = RunOptions { scoring: Scoring::Single, ..RunOptions::default() };
+/// assert_eq!(options.cycle_split, RunOptions::default().cycle_split);
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct RunOptions {
+    /// Mark the interactions disallowed by the constraints as covered before beginning the extensions, the `filter-map` feature.
+    pub filter_map: bool,
+
+    /// The algorithm used to calculate the scores, the `score-single` and `score-double` features.
+    pub scoring: Scoring,
+
+    /// Cycle the division of the work between the worker threads, unless the `no-cycle-split` feature is set.
+    pub cycle_split: bool,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            filter_map: cfg!(feature = "filter-map"),
+            scoring: Scoring::default(),
+            cycle_split: !cfg!(feature = "no-cycle-split"),
+        }
+    }
+}
@@ -9,6 +9,7 @@ use std::ops::Range;
 
 /// This struct wraps a [Vec] and uses unsafe methods to access its contents if `debug_assertions` is off.
 #[derive(Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct UVec<T>(Vec<T>);
 
 impl<T> UVec<T> {
@@ -39,6 +40,9 @@ impl<T> UVec<T> {
     /// See [Vec::reserve].
     #[inline]
     pub fn reserve(&mut self, additional: usize) { self.0.reserve(additional) }
+    /// See [Vec::reserve_exact].
+    #[inline]
+    pub fn reserve_exact(&mut self, additional: usize) { self.0.reserve_exact(additional) }
     /// See [Vec::clear].
     #[inline]
     pub fn clear(&mut self) { self.0.clear() }
@@ -0,0 +1,143 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module contains the finite fields used by the constructions.
+//!
+//! An element of GF(*p*<sup>*m*</sup>) is a polynomial of degree below *m* over the integers modulo *p*,
+//! stored as the number with the coefficients as its base *p* digits.
+//! The polynomials are multiplied modulo a primitive polynomial, which is searched for when the field is created.
+
+use common::{u_vec, UVec};
+
+/// Returns the prime and the exponent of the order if it is a prime power.
+///
+/// # Example
+/// ```
+/// use constructions::galois::prime_power;
+///
+/// assert_eq!(prime_power(9), Some((3, 2)));
+/// assert_eq!(prime_power(7), Some((7, 1)));
+/// assert_eq!(prime_power(6), None);
+/// assert_eq!(prime_power(1), None);
+/// ```
+pub fn prime_power(order: usize) -> Option<(usize, u32)> {
+    if order < 2 {
+        return None;
+    }
+    let prime = (2..).take_while(|&divisor| divisor * divisor <= order).find(|&divisor| order % divisor == 0).unwrap_or(order);
+    let mut remainder = order;
+    let mut exponent = 0;
+    while remainder % prime == 0 {
+        remainder /= prime;
+        exponent += 1;
+    }
+    (remainder == 1).then_some((prime, exponent))
+}
+
+/// The finite field with a prime power number of elements, the elements are the numbers below the [GaloisField::order].
+///
+/// Zero and one are the additive and multiplicative identities.
+pub struct GaloisField {
+    order: usize,
+    prime: usize,
+
+    /// The powers of the primitive element, `exp[i]` is *x*<sup>*i*</sup>.
+    exp: UVec<usize>,
+
+    /// The inverse of [GaloisField::exp] for the nonzero elements.
+    log: UVec<usize>,
+}
+
+impl GaloisField {
+    /// Create the field with the given number of elements, [None] if it is not a prime power.
+    pub fn new(order: usize) -> Option<Self> {
+        let (prime, exponent) = prime_power(order)?;
+        let top = order / prime;
+
+        // x^m is reduced to the negation of the lower coefficients of the primitive polynomial.
+        for reduction in 1..order {
+            let mut exp = UVec::with_capacity(order - 1);
+            let mut log = u_vec![0; order];
+            let mut power = 1;
+            while exp.len() < order - 1 {
+                if power == 0 || log[power] != 0 || (power == 1 && !exp.is_empty()) {
+                    break;
+                }
+                log[power] = exp.len();
+                exp.push(power);
+                let overflow = power / top;
+                power = add_digits(prime, (power % top) * prime, negate_digits(prime, multiply_digits(prime, reduction, overflow)));
+            }
+
+            if exp.len() == order - 1 && power == 1 {
+                tracing::trace!(order, reduction, exponent, "Found a primitive polynomial");
+                return Some(Self { order, prime, exp, log });
+            }
+        }
+        unreachable!("Every finite field has a primitive polynomial")
+    }
+
+    /// Returns the number of elements of the field.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Returns the sum of the elements.
+    pub fn add(&self, first: usize, second: usize) -> usize {
+        add_digits(self.prime, first, second)
+    }
+
+    /// Returns the product of the elements.
+    pub fn multiply(&self, first: usize, second: usize) -> usize {
+        if first == 0 || second == 0 {
+            0
+        } else {
+            self.exp[(self.log[first] + self.log[second]) % (self.order - 1)]
+        }
+    }
+
+    /// Returns the value of the polynomial with the coefficients, lowest degree first, at the element.
+    pub fn evaluate(&self, coefficients: &[usize], element: usize) -> usize {
+        coefficients.iter().rev().fold(0, |value, &coefficient| self.add(self.multiply(value, element), coefficient))
+    }
+}
+
+/// Add the base `prime` digits of the numbers modulo the `prime`.
+fn add_digits(prime: usize, mut first: usize, mut second: usize) -> usize {
+    let mut result = 0;
+    let mut position = 1;
+    while first != 0 || second != 0 {
+        result += (first % prime + second % prime) % prime * position;
+        first /= prime;
+        second /= prime;
+        position *= prime;
+    }
+    result
+}
+
+/// Negate the base `prime` digits of the number modulo the `prime`.
+fn negate_digits(prime: usize, mut number: usize) -> usize {
+    let mut result = 0;
+    let mut position = 1;
+    while number != 0 {
+        result += (prime - number % prime) % prime * position;
+        number /= prime;
+        position *= prime;
+    }
+    result
+}
+
+/// Multiply the base `prime` digits of the number by the `factor` modulo the `prime`.
+fn multiply_digits(prime: usize, mut number: usize, factor: usize) -> usize {
+    let mut result = 0;
+    let mut position = 1;
+    while number != 0 {
+        result += number % prime * factor % prime * position;
+        number /= prime;
+        position *= prime;
+    }
+    result
+}
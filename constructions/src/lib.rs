@@ -0,0 +1,282 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This crate contains algebraic constructions of covering arrays, which are used instead of IPOG when the levels allow one.
+//!
+//! The constructions are:
+//!   * [orthogonal_array] The construction of Bush, which gives an orthogonal array of *q*<sup>*t*</sup> rows and *q*+1 columns if *q* is a prime power larger than *t*.
+//!     Otherwise the zero-sum array gives *t*+1 columns for any *q*.
+//!   * [binary_pairs] The construction of Kleitman and Spencer, which gives the smallest arrays of strength two with two values.
+//!   * [product] The product construction, which multiplies the number of columns of two arrays of strength two.
+//!   * [roux] The construction of Roux, which doubles the number of columns of an array of strength three with two values.
+//!
+//! If all parameters have the same level and a construction is known, [construct] returns the whole [MCA].
+//! Otherwise [seed] provides an orthogonal array for the first parameters, which is a much smaller start for IPOG than all combinations of the first *t* parameters.
+//! IPOG continues from the seed like it does from a checkpoint, see [Seeded].
+//! The values of parameters with a lower level than the array are don't-cares, which IPOG fills in during the vertical extension.
+//!
+//! The constructions ignore constraints.
+
+#![deny(missing_docs, rustdoc::missing_crate_level_docs, future_incompatible)]
+
+use common::{Number, u_vec, UVec};
+use mca::checkpoint::Checkpointer;
+use mca::MCA;
+
+use crate::galois::GaloisField;
+
+pub mod galois;
+
+#[cfg(test)]
+mod test;
+
+/// A covering array, in which every column has the same number of values.
+pub type Array = UVec<UVec<usize>>;
+
+/// Returns the number of columns of the [orthogonal_array] with the given number of values and strength.
+pub fn orthogonal_columns(order: usize, strength: usize) -> usize {
+    if strength < order && galois::prime_power(order).is_some() {
+        order + 1
+    } else {
+        strength + 1
+    }
+}
+
+/// Returns an orthogonal array of the given strength with `order`<sup>`strength`</sup> rows and [orthogonal_columns] columns.
+///
+/// Every interaction is covered exactly once, so no covering array with these values can have fewer rows.
+/// The first row consists of zeros.
+///
+/// The rows of the construction of Bush are the polynomials of degree below the strength over GF(*q*).
+/// The columns are the values of the polynomial at the elements of the field and its coefficient of the highest degree.
+/// Otherwise the first *t* columns are all combinations of values, and the last column is their sum modulo the order.
+pub fn orthogonal_array(order: usize, strength: usize) -> Array {
+    let rows = order.pow(strength as u32);
+    let mut array = UVec::with_capacity(rows);
+    let mut coefficients = u_vec![0; strength];
+    let field = if orthogonal_columns(order, strength) > strength + 1 { GaloisField::new(order) } else { None };
+
+    for _ in 0..rows {
+        array.push(match &field {
+            Some(field) => (0..order)
+                .map(|element| field.evaluate(coefficients.as_slice(), element))
+                .chain(std::iter::once(coefficients[strength - 1]))
+                .collect(),
+            None => coefficients.iter()
+                .copied()
+                .chain(std::iter::once(coefficients.iter().sum::<usize>() % order))
+                .collect(),
+        });
+
+        for coefficient in coefficients.iter_mut() {
+            *coefficient += 1;
+            if *coefficient < order {
+                break;
+            }
+            *coefficient = 0;
+        }
+    }
+    array
+}
+
+/// Returns the smallest array of strength two with two values for the number of columns.
+///
+/// The columns are distinct vectors of *N* values, which start with a zero and contain ⌈*N*/2⌉ ones.
+/// Two such columns cover all pairs, so the number of rows *N* is the smallest for which there are enough of these vectors.
+pub fn binary_pairs(columns: usize) -> Array {
+    let mut rows = 4;
+    while binomial(rows - 1, (rows + 1) / 2) < columns as u128 {
+        rows += 1;
+    }
+
+    let mut array = u_vec![u_vec![0; columns]; rows];
+    let mut ones: u128 = (1 << ((rows + 1) / 2)) - 1;
+    for column in 0..columns {
+        for (row, values) in array.iter_mut().skip(1).enumerate() {
+            values[column] = (ones >> row) as usize & 1;
+        }
+
+        // The next number with the same number of ones.
+        let lowest = ones & ones.wrapping_neg();
+        let carried = ones + lowest;
+        ones = carried | (((ones ^ carried) >> 2) / lowest);
+    }
+    array
+}
+
+/// Returns an array of strength two with the product of the number of columns of the arrays of strength two.
+///
+/// The column (*i*, *j*) is column *i* of the `first` array in its rows followed by column *j* of the `second` array in its rows.
+/// Two columns share either the first or the second index, or the first array covers their pairs.
+pub fn product(first: &Array, second: &Array) -> Array {
+    let first_columns = first[0].len();
+    let second_columns = second[0].len();
+    let mut array = UVec::with_capacity(first.len() + second.len());
+    for row in first.iter() {
+        array.push((0..first_columns * second_columns).map(|column| row[column / second_columns]).collect());
+    }
+    for row in second.iter() {
+        array.push((0..first_columns * second_columns).map(|column| row[column % second_columns]).collect());
+    }
+    array
+}
+
+/// Returns an array of strength three with two values and twice the number of columns of the arrays.
+///
+/// The rows of the `strength_three` array are repeated, and the rows of the `strength_two` array are followed by their complement.
+/// A column in one half together with its copy in the other half is then covered by the complement.
+///
+/// # Panics
+/// Panics if the arrays do not have the same number of columns.
+pub fn roux(strength_three: &Array, strength_two: &Array) -> Array {
+    assert_eq!(strength_three[0].len(), strength_two[0].len(), "The arrays should have the same number of columns.");
+    let mut array = UVec::with_capacity(strength_three.len() + strength_two.len());
+    for row in strength_three.iter() {
+        array.push(row.iter().chain(row.iter()).copied().collect());
+    }
+    for row in strength_two.iter() {
+        array.push(row.iter().copied().chain(row.iter().map(|value| 1 - value)).collect());
+    }
+    array
+}
+
+/// Returns an array with the number of columns, each with the same number of values, if one of the constructions applies.
+///
+/// The [orthogonal_array] is used if it has enough columns.
+/// Otherwise there are constructions for two values of strength two and three, and for strength two with a prime power number of values.
+pub fn uniform_array(order: usize, columns: usize, strength: usize) -> Option<Array> {
+    let mut array = if columns <= orthogonal_columns(order, strength) {
+        orthogonal_array(order, strength)
+    } else {
+        match (order, strength) {
+            (2, 2) => binary_pairs(columns),
+            (2, 3) => {
+                let mut array = orthogonal_array(2, 3);
+                while array[0].len() < columns {
+                    array = roux(&array, &binary_pairs(array[0].len()));
+                }
+                array
+            }
+            (_, 2) if galois::prime_power(order).is_some() => {
+                let orthogonal = orthogonal_array(order, 2);
+                let mut array = orthogonal.clone();
+                while array[0].len() < columns {
+                    array = product(&array, &orthogonal);
+                }
+                array
+            }
+            _ => return None,
+        }
+    };
+
+    for row in array.iter_mut() {
+        row.unwrap_ref_mut().truncate(columns);
+    }
+    Some(array)
+}
+
+/// Construct the whole MCA if all parameters have the same level and [uniform_array] knows a construction for it.
+pub fn construct<ValueId: Number, LocationsType: Number>(parameters: &UVec<ValueId>, strength: usize) -> Option<MCA<ValueId, LocationsType>> {
+    let order = parameters[0].as_usize();
+    if parameters.len() <= strength || parameters.iter().any(|level| level.as_usize() != order) {
+        return None;
+    }
+    let array = uniform_array(order, parameters.len(), strength)?;
+    tracing::debug!(rows = array.len(), "Constructed the MCA");
+
+    Some(MCA {
+        dont_care_locations: u_vec![LocationsType::default(); array.len()],
+        array: array.iter().map(|row| row.iter().map(|&value| ValueId::from_usize(value)).collect()).collect(),
+        vertical_extension_rows: UVec::with_capacity(0),
+        new_row: u_vec![ValueId::dont_care(); parameters.len()],
+        skipped: None,
+    })
+}
+
+/// Returns an MCA for the first parameters and the next parameter to add to it, [None] if it would not be smaller than the start of IPOG.
+///
+/// The first *t* parameters should have the same level, the array then has the least possible number of rows for them.
+/// It contains the following parameters with at most this level, as far as the [orthogonal_array] has columns for them.
+/// The values a parameter does not have are don't-cares, the first row consists of zeros like the start of IPOG.
+pub fn seed<ValueId: Number, LocationsType: Number>(parameters: &UVec<ValueId>, strength: usize) -> Option<(usize, MCA<ValueId, LocationsType>)> {
+    let order = parameters[0].as_usize();
+    if parameters.len() <= strength || parameters.iter().take(strength).any(|level| level.as_usize() != order) {
+        return None;
+    }
+    let columns = parameters.iter()
+        .take(orthogonal_columns(order, strength))
+        .take_while(|level| level.as_usize() <= order)
+        .count();
+    if columns <= strength {
+        return None;
+    }
+
+    let mut mca = MCA {
+        array: UVec::with_capacity(order.pow(strength as u32)),
+        dont_care_locations: UVec::with_capacity(order.pow(strength as u32)),
+        vertical_extension_rows: UVec::with_capacity(0),
+        new_row: u_vec![ValueId::dont_care(); parameters.len()],
+        skipped: None,
+    };
+    for values in orthogonal_array(order, strength).iter() {
+        let mut row = mca.new_row.clone();
+        let mut locations = LocationsType::mask_high(columns);
+        let mut set = 0;
+        for (parameter, (&value, &level)) in values.iter().zip(parameters.iter()).take(columns).enumerate() {
+            if value < level.as_usize() {
+                row[parameter] = ValueId::from_usize(value);
+                set += 1;
+            } else {
+                locations |= LocationsType::bit(parameter);
+            }
+        }
+
+        // Rows with fewer than t values do not cover any interaction.
+        if set >= strength {
+            mca.array.push(row);
+            mca.dont_care_locations.push(locations);
+        }
+    }
+
+    // The first row of the orthogonal array consists of zeros, which IPOG extends with zeros.
+    for value in mca.array[0].iter_mut() {
+        *value = ValueId::default();
+    }
+    mca.dont_care_locations[0] = LocationsType::default();
+
+    tracing::debug!(rows = mca.array.len(), parameters = columns, "Constructed the seed of the MCA");
+    Some((columns, mca))
+}
+
+/// Resumes the generation from the [seed], unless the wrapped checkpoint has a state to resume from.
+///
+/// The saves are passed on to the wrapped checkpoint.
+pub struct Seeded<'c, ValueId: Number, LocationsType: Number> {
+    seed: Option<(usize, MCA<ValueId, LocationsType>)>,
+    checkpoint: &'c mut dyn Checkpointer<ValueId, LocationsType>,
+}
+
+impl<'c, ValueId: Number, LocationsType: Number> Seeded<'c, ValueId, LocationsType> {
+    /// Wrap the checkpoint, the `seed` is usually the result of [seed].
+    pub fn new(seed: Option<(usize, MCA<ValueId, LocationsType>)>, checkpoint: &'c mut dyn Checkpointer<ValueId, LocationsType>) -> Self {
+        Self { seed, checkpoint }
+    }
+}
+
+impl<'c, ValueId: Number, LocationsType: Number> Checkpointer<ValueId, LocationsType> for Seeded<'c, ValueId, LocationsType> {
+    fn resume(&mut self) -> Option<(usize, MCA<ValueId, LocationsType>)> {
+        self.checkpoint.resume().or_else(|| self.seed.take())
+    }
+
+    fn save(&mut self, next_parameter: usize, mca: &MCA<ValueId, LocationsType>) {
+        self.checkpoint.save(next_parameter, mca);
+    }
+}
+
+/// Returns the binomial coefficient, the number of subsets of size `k` of a set of size `n`.
+fn binomial(n: usize, k: usize) -> u128 {
+    (0..k).fold(1, |result, i| result * (n - i) as u128 / (i as u128 + 1))
+}
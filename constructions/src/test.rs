@@ -0,0 +1,117 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use common::{u_vec, UVec};
+use mca::checkpoint::Checkpointer;
+use mca::min_coverage;
+
+use super::*;
+
+#[test]
+fn test_galois_field() {
+    for order in [2, 3, 4, 5, 8, 9, 16, 25, 27] {
+        let field = GaloisField::new(order).unwrap();
+        for first in 0..order {
+            assert_eq!(field.add(first, 0), first);
+            assert_eq!(field.multiply(first, 1), first);
+            assert_eq!((0..order).filter(|&second| field.add(first, second) == 0).count(), 1);
+            if first != 0 {
+                assert_eq!((1..order).filter(|&second| field.multiply(first, second) == 1).count(), 1, "{} has no inverse in GF({})", first, order);
+            }
+            for second in 0..order {
+                for third in 0..order {
+                    assert_eq!(
+                        field.multiply(first, field.add(second, third)),
+                        field.add(field.multiply(first, second), field.multiply(first, third)),
+                    );
+                }
+            }
+        }
+    }
+    assert!(GaloisField::new(6).is_none());
+}
+
+#[test]
+fn test_orthogonal_array() {
+    for (order, strength, columns) in [(2, 2, 3), (3, 2, 4), (4, 2, 5), (5, 3, 6), (4, 3, 5), (6, 2, 3), (2, 3, 4), (3, 4, 5)] {
+        let array = orthogonal_array(order, strength);
+        assert_eq!(orthogonal_columns(order, strength), columns);
+        assert_eq!(array.len(), order.pow(strength as u32));
+        assert!(array.iter().all(|row| row.len() == columns && row.iter().all(|&value| value < order)));
+        assert!(array[0].iter().all(|&value| value == 0));
+        assert_eq!(min_coverage(&vec![order; columns], &array, strength, |_, _| true), 1);
+    }
+}
+
+#[test]
+fn test_binary_pairs() {
+    for (columns, rows) in [(2, 4), (3, 4), (4, 5), (10, 6), (11, 7), (15, 7), (16, 8), (35, 8), (36, 9)] {
+        let array = binary_pairs(columns);
+        assert_eq!(array.len(), rows, "{} columns", columns);
+        assert!(min_coverage(&vec![2; columns], &array, 2, |_, _| true) > 0);
+    }
+}
+
+#[test]
+fn test_product_roux() {
+    let orthogonal = orthogonal_array(3, 2);
+    let array = product(&orthogonal, &orthogonal);
+    assert_eq!(array.len(), 18);
+    assert!(min_coverage(&[3; 16], &array, 2, |_, _| true) > 0);
+
+    let array = roux(&orthogonal_array(2, 3), &binary_pairs(4));
+    assert_eq!(array.len(), 13);
+    assert!(min_coverage(&[2; 8], &array, 3, |_, _| true) > 0);
+}
+
+#[test]
+fn test_uniform_array() {
+    for (order, columns, strength) in [(5, 6, 2), (5, 4, 3), (2, 20, 2), (2, 20, 3), (4, 12, 2), (6, 3, 2)] {
+        let array = uniform_array(order, columns, strength).unwrap();
+        assert!(array.iter().all(|row| row.len() == columns));
+        assert!(min_coverage(&vec![order; columns], &array, strength, |_, _| true) > 0);
+    }
+    assert!(uniform_array(6, 4, 2).is_none());
+    assert!(uniform_array(3, 5, 3).is_none());
+}
+
+#[test]
+fn test_construct() {
+    let mca = construct::<u8, u64>(&u_vec![7; 8], 2).unwrap();
+    assert_eq!(mca.array.len(), 49);
+    assert_eq!(mca.dont_care_locations.len(), 49);
+    assert!(mca.is_complete());
+
+    assert!(construct::<u8, u64>(&u_vec![7, 7, 7, 6], 2).is_none());
+    assert!(construct::<u8, u64>(&u_vec![3, 3, 3], 3).is_none());
+}
+
+#[test]
+fn test_seed() {
+    let parameters: UVec<usize> = u_vec![5, 5, 4, 3, 2, 5, 2, 2];
+    let (next_parameter, mca) = seed::<usize, u64>(&parameters, 2).unwrap();
+    assert_eq!(next_parameter, 6);
+    assert!(mca.array.len() <= 25);
+    assert!(mca.check_locations());
+    assert!(mca.array[0].iter().all(|&value| value == 0));
+    assert!(mca.array.iter().skip(1).all(|row| row.iter().skip(next_parameter).all(|&value| value == !0)));
+    assert!(min_coverage(&[5, 5, 4, 3, 2, 5], &mca.array, 2, |_, _| true) > 0);
+
+    assert!(seed::<usize, u64>(&u_vec![5, 4, 4, 4], 2).is_none());
+    assert!(seed::<usize, u64>(&u_vec![6, 6, 6, 6], 3).is_some());
+    assert!(seed::<usize, u64>(&u_vec![3, 3, 3], 3).is_none());
+}
+
+#[test]
+fn test_seeded() {
+    let parameters: UVec<usize> = u_vec![3, 3, 3, 2];
+    let mut checkpoint = ();
+    let mut seeded = Seeded::<usize, u64>::new(seed(&parameters, 2), &mut checkpoint);
+    let (next_parameter, mca) = seeded.resume().unwrap();
+    assert_eq!(next_parameter, 4);
+    assert_eq!(mca.array.len(), 9);
+    assert!(seeded.resume().is_none());
+}
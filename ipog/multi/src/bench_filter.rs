@@ -8,7 +8,7 @@
 
 use test::Bencher;
 
-use cm::{BIT_MASK, BIT_SHIFT, BitArray, CoverageMap};
+use cm::{BitArray, CoverageMap};
 use common::{u_vec, UVec};
 
 use crate::unconstrained::bench_horizontal::TestData;
@@ -19,8 +19,8 @@ struct FilterTestData {
     row_previous: usize,
     row_next: usize,
     value: usize,
-    scores_previous: UVec<UVec<BitArray>>,
-    scores_next: UVec<UVec<BitArray>>,
+    scores_previous: UVec<UVec<u64>>,
+    scores_next: UVec<UVec<u64>>,
     filtered: usize,
 }
 
@@ -115,10 +115,10 @@ fn filter_scores_remove(new_vec: &mut UVec<u64>, old_vec: &UVec<u64>, _coverage_
 fn filter_scores_cm_remove(new_vec: &mut UVec<u64>, _old_vec: &UVec<u64>, coverage_map: &CoverageMap<u8, 6>) -> usize {
     let previous_size = new_vec.len();
     new_vec.retain(|index| {
-        let array_id = *index as usize >> BIT_SHIFT;
+        let array_id = *index as usize >> u64::SHIFT;
         debug_assert!(array_id < coverage_map.map.len());
         let array = coverage_map.map[array_id];
-        let index = 1 << (*index & BIT_MASK);
+        let index = 1 << (*index as usize & u64::MASK);
         array & index == 0
     });
     previous_size - new_vec.len()
@@ -129,10 +129,10 @@ fn filter_scores_cm_zero(new_vec: &mut UVec<u64>, _old_vec: &UVec<u64>, coverage
     let mut result = 0;
     for index in new_vec.iter_mut() {
         if {
-            let array_id = *index as usize >> BIT_SHIFT;
+            let array_id = *index as usize >> u64::SHIFT;
             debug_assert!(array_id < coverage_map.map.len());
             let array = coverage_map.map[array_id];
-            let index = 1 << (*index & BIT_MASK);
+            let index = 1 << (*index as usize & u64::MASK);
             array & index != 0
         } {
             *index = 0;
@@ -183,9 +183,9 @@ fn cm_filter_remove_low(bencher: &mut Bencher) {
 }
 
 #[inline]
-fn bench_filter(bencher: &mut Bencher, ftd: FilterTestData, filter: impl Fn(&mut UVec<BitArray>, &UVec<BitArray>, &CoverageMap<u8, 6>) -> usize, removes: bool) {
+fn bench_filter(bencher: &mut Bencher, ftd: FilterTestData, filter: impl Fn(&mut UVec<u64>, &UVec<u64>, &CoverageMap<u8, 6>) -> usize, removes: bool) {
     bencher.iter(|| {
-        let mut new_scores: UVec<BitArray> = ftd.scores_next[ftd.value].clone();
+        let mut new_scores: UVec<u64> = ftd.scores_next[ftd.value].clone();
         let previous_len = new_scores.len();
         let score = filter(&mut new_scores, &ftd.scores_previous[ftd.value], &ftd.td.coverage_map);
         assert_eq!(previous_len, if removes { new_scores.len() + ftd.filtered } else { new_scores.len() });
@@ -14,19 +14,20 @@ use std::sync::atomic::Ordering::SeqCst;
 
 use crossbeam::utils::Backoff;
 
-use cm::{BitArray, CoverageMap};
-use common::{Number, sub_time_it, u_vec, UVec};
+use cm::{BitArray, CoverageMap, with_map_types};
+use common::{CancellationToken, Number, Phase, Progress, ProgressObserver, RunOptions, sub_time_it, u_vec, UVec};
 use ipog_single::constrained::{Extension, HorizontalExtension, VerticalExtension};
-use mca::{check_locations, MCA};
+use mca::{check_locations, MCA, Skipped};
+use mca::checkpoint::Checkpointer;
 use sut::{ConstrainedSUT, Solver, SolverImpl};
 
 use crate::{CACHE_MASK, IPOGData, Wrapper};
 use crate::threads_common::{Response, Work};
 use crate::unconstrained::threads::init_thread_pool;
 
-unsafe fn update_scores<ValueId: Number, const STRENGTH: usize>(
-    row_scores: &mut UVec<UVec<UVec<BitArray>>>,
-    cm: &CoverageMap<ValueId, STRENGTH>,
+unsafe fn update_scores<ValueId: Number, const STRENGTH: usize, Word: BitArray, Index: Number>(
+    row_scores: &mut UVec<UVec<UVec<Index>>>,
+    cm: &CoverageMap<ValueId, STRENGTH, Word, Index>,
     scores: &mut UVec<usize>,
 ) where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
     for thread_scores in row_scores.iter_mut() {
@@ -114,11 +115,11 @@ unsafe fn get_best_value<ValueId: Number, ParameterId: Number, const STRENGTH: u
 }
 
 
-pub(crate) unsafe fn horizontal_extension_threaded<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>(
+pub(crate) unsafe fn horizontal_extension_threaded<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize, Word: BitArray, Index: Number>(
     solver: &mut SolverImpl,
     senders: &[crossbeam::channel::Sender<Work<ValueId>>],
     _receivers: &[crossbeam::channel::Receiver<Response>],
-    ipog_data: &mut IPOGData<ValueId, ParameterId, LocationsType, STRENGTH>,
+    ipog_data: &mut IPOGData<ValueId, ParameterId, LocationsType, STRENGTH, Word, Index>,
     at_parameter: usize,
 ) where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
     ipog_data.at_row_main.store(0, SeqCst);
@@ -221,35 +222,79 @@ pub struct ConstrainedMCIPOG<ValueId: Number, ParameterId: Number, LocationsType
 
 impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize> ConstrainedMCIPOG<ValueId, ParameterId, LocationsType, STRENGTH> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
     /// Run the constrained version of IPOG.
-    pub fn run(sut: Arc<ConstrainedSUT<ValueId, ParameterId>>, mut solver: SolverImpl) -> MCA<ValueId, LocationsType> {
-        let mca = MCA::<ValueId, LocationsType>::new_constrained::<ParameterId, SolverImpl, STRENGTH>(
-            &sut.sub_sut.parameters,
-            &mut solver,
-        );
+    ///
+    /// The `observer` is notified by the calling thread at the start of every horizontal and vertical extension and when the generation has finished.
+    ///
+    /// If the `cancel` token is cancelled, the MCA built so far is returned with [MCA::skipped] describing the remaining work.
+    /// The threaded horizontal extension is not interrupted, the token is checked before and after it.
+    ///
+    /// The generation continues from the MCA provided by the `checkpoint`, if any, and saves the MCA to it after every parameter.
+    ///
+    /// The `options` select the scoring and whether the division of the work is cycled, [RunOptions::filter_map] is not supported.
+    pub fn run(
+        sut: Arc<ConstrainedSUT<ValueId, ParameterId>>,
+        mut solver: SolverImpl,
+        observer: &mut dyn ProgressObserver,
+        cancel: &CancellationToken,
+        checkpoint: &mut dyn Checkpointer<ValueId, LocationsType>,
+        options: &RunOptions,
+    ) -> MCA<ValueId, LocationsType> {
+        let (first_parameter, mca) = checkpoint.resume().unwrap_or_else(|| {
+            (STRENGTH, MCA::<ValueId, LocationsType>::new_constrained::<ParameterId, SolverImpl, STRENGTH>(&sut.sub_sut.parameters, &mut solver))
+        });
 
         if STRENGTH == sut.sub_sut.parameters.len() {
+            observer.progress(&Progress { strength: STRENGTH, at_parameter: STRENGTH, parameters: STRENGTH, rows: mca.array.len(), uncovered: 0, phase: Phase::Done });
             return mca;
         }
 
-        let wrapper = Wrapper::<ValueId, ParameterId, LocationsType, STRENGTH>::new(sut.sub_sut.parameters.clone(), sut.count_constraints());
+        with_map_types!(sut.sub_sut.parameters.as_slice(), STRENGTH, <Word, Index> Self::generate::<Word, Index>(sut, solver, mca, first_parameter, observer, cancel, checkpoint, options))
+    }
+
+    /// Runs the constrained version of IPOG with a [CoverageMap] with the given element and index types, see [ConstrainedMCIPOG::run].
+    #[allow(clippy::too_many_arguments)]
+    fn generate<Word: BitArray, Index: Number>(
+        sut: Arc<ConstrainedSUT<ValueId, ParameterId>>,
+        mut solver: SolverImpl,
+        mca: MCA<ValueId, LocationsType>,
+        first_parameter: usize,
+        observer: &mut dyn ProgressObserver,
+        cancel: &CancellationToken,
+        checkpoint: &mut dyn Checkpointer<ValueId, LocationsType>,
+        options: &RunOptions,
+    ) -> MCA<ValueId, LocationsType> {
+        let wrapper = Wrapper::<ValueId, ParameterId, LocationsType, STRENGTH, Word, Index>::new(sut.sub_sut.parameters.clone(), sut.count_constraints(), *options);
         unsafe { replace(&mut (*wrapper.data.get()).mca, mca); }
         let (senders, receivers) = sub_time_it!(init_thread_pool(wrapper.clone()), "T init");
         let ipog_data = unsafe { &mut *wrapper.data.get() };
 
-        for at_parameter in STRENGTH..sut.sub_sut.parameters.len() {
+        for at_parameter in first_parameter..sut.sub_sut.parameters.len() {
             ipog_data.at_parameter_main.store(at_parameter, SeqCst);
             let pc_list_len = ipog_data.pc_list.sizes[at_parameter - STRENGTH];
             ipog_data.pc_list_len = pc_list_len;
             ipog_data.cm.initialise(at_parameter);
 
+            if cancel.is_cancelled() {
+                ipog_data.mca.skipped = Some(Skipped { at_parameter, parameters: sut.sub_sut.parameters.len(), phase: Phase::Horizontal, uncovered: ipog_data.cm.uncovered, completion_rows: None });
+                break;
+            }
+
+            observer.progress(&Progress { strength: STRENGTH, at_parameter, parameters: sut.sub_sut.parameters.len(), rows: ipog_data.mca.array.len(), uncovered: ipog_data.cm.uncovered, phase: Phase::Horizontal });
             if ipog_data.lower_limit <= pc_list_len {
                 sub_time_it!(unsafe { horizontal_extension_threaded(&mut solver, &senders, &receivers, ipog_data, at_parameter) }, "HMulti  ");
             } else {
-                sub_time_it!( unsafe { HorizontalExtension::extend(&mut solver, &ipog_data.parameters, at_parameter, &ipog_data.pc_list, pc_list_len, &mut ipog_data.mca, &mut ipog_data.cm) }, "HSingle");
+                sub_time_it!( unsafe { HorizontalExtension::extend(&mut solver, &ipog_data.parameters, at_parameter, &ipog_data.pc_list, pc_list_len, &mut ipog_data.mca, &mut ipog_data.cm, cancel) }, "HSingle");
             }
 
-            if !ipog_data.cm.is_covered() {
-                sub_time_it!( unsafe { VerticalExtension::extend(&mut solver, &sut.sub_sut.parameters, at_parameter, &ipog_data.pc_list, pc_list_len, &mut ipog_data.mca, &mut ipog_data.cm) }, "vertical");
+            let mut skipped_phase = None;
+            if !ipog_data.cm.is_covered() && cancel.is_cancelled() {
+                skipped_phase = Some(Phase::Horizontal);
+            } else if !ipog_data.cm.is_covered() {
+                observer.progress(&Progress { strength: STRENGTH, at_parameter, parameters: sut.sub_sut.parameters.len(), rows: ipog_data.mca.array.len(), uncovered: ipog_data.cm.uncovered, phase: Phase::Vertical });
+                sub_time_it!( unsafe { VerticalExtension::extend(&mut solver, &sut.sub_sut.parameters, at_parameter, &ipog_data.pc_list, pc_list_len, &mut ipog_data.mca, &mut ipog_data.cm, cancel) }, "vertical");
+                if !ipog_data.cm.is_covered() && cancel.is_cancelled() {
+                    skipped_phase = Some(Phase::Vertical);
+                }
             }
 
             if ipog_data.lower_limit <= pc_list_len {
@@ -257,8 +302,16 @@ impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH
                     while receiver.recv().unwrap() != Response::Done {}
                 }
             }
+
+            if let Some(phase) = skipped_phase {
+                ipog_data.mca.skipped = Some(Skipped { at_parameter, parameters: sut.sub_sut.parameters.len(), phase, uncovered: ipog_data.cm.uncovered, completion_rows: None });
+                break;
+            }
+
+            checkpoint.save(at_parameter + 1, &ipog_data.mca);
         }
 
+        observer.progress(&Progress { strength: STRENGTH, at_parameter: sut.sub_sut.parameters.len(), parameters: sut.sub_sut.parameters.len(), rows: ipog_data.mca.array.len(), uncovered: ipog_data.cm.uncovered, phase: Phase::Done });
         ipog_data.get_mca()
     }
 }
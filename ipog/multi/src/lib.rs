@@ -8,7 +8,7 @@
 //!
 //! # Features
 //! The following feature is provided by this crate:
-//!   * `no-cycle-split` Do not cycle the division of work between the worker threads.
+//!   * `no-cycle-split` Do not cycle the division of work between the worker threads by default, see [common::RunOptions::cycle_split].
 
 #![allow(incomplete_features)]
 #![feature(adt_const_params)]
@@ -25,7 +25,7 @@ use std::sync::Arc;
 use std::sync::atomic::AtomicUsize;
 
 use cm::{BitArray, CoverageMap};
-use common::{Number, u_vec, UVec};
+use common::{Number, RunOptions, time_it, u_vec, UVec};
 use mca::MCA;
 use pc_list::PCList;
 
@@ -52,7 +52,7 @@ pub(crate) const CACHE_MASK: usize = CACHE_SIZE - 1;
 pub(crate) const CONSTRAINTS_SWITCH: usize = 40;
 
 /// This is the data passed to the new threads.
-pub struct IPOGData<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+pub struct IPOGData<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize, Word: BitArray = u64, Index: Number = u64> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
     /// The number of worker threads that work on the solution.
     pub thread_count: usize,
 
@@ -75,7 +75,7 @@ pub struct IPOGData<ValueId: Number, ParameterId: Number, LocationsType: Number,
     pub at_row_worker: UVec<AtomicUsize>,
 
     /// The scores collected by the worker threads.
-    pub scores: UVec<UVec<UVec<UVec<BitArray>>>>,
+    pub scores: UVec<UVec<UVec<UVec<Index>>>>,
 
     /// The parameters in the SUT.
     pub parameters: UVec<ValueId>,
@@ -90,20 +90,25 @@ pub struct IPOGData<ValueId: Number, ParameterId: Number, LocationsType: Number,
     pub pc_list_len: usize,
 
     /// The coverage-map used during generation.
-    pub cm: CoverageMap<ValueId, STRENGTH>,
+    pub cm: CoverageMap<ValueId, STRENGTH, Word, Index>,
+
+    /// The options of the generation.
+    pub options: RunOptions,
 }
 
-impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize> IPOGData<ValueId, ParameterId, LocationsType, STRENGTH> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
-    /// Create a new struct for the given parameters.
-    pub fn new(parameters: UVec<ValueId>, constraints: usize) -> Self {
-        let pc_list = PCList::new(parameters.len());
-        let cm = CoverageMap::new(parameters.clone(), &pc_list);
+impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize, Word: BitArray, Index: Number> IPOGData<ValueId, ParameterId, LocationsType, STRENGTH, Word, Index> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    /// Create a new struct for the given parameters, the coverage map calculates the scores with the [RunOptions::scoring] of the `options`.
+    pub fn new(parameters: UVec<ValueId>, constraints: usize, options: RunOptions) -> Self {
+        let pc_list = time_it!(PCList::new(parameters.len()), "PCList generation", DEBUG);
+        let mut cm: CoverageMap<ValueId, STRENGTH, Word, Index> = time_it!(CoverageMap::new(parameters.clone(), &pc_list), "Coverage map generation", DEBUG);
+        cm.scoring = options.scoring;
         let thread_count = if CONSTRAINTS_SWITCH <= constraints && num_cpus::get_physical() != num_cpus::get() {
             num_cpus::get_physical()
         } else {
-            num_cpus::get() - 1
+            // The work is split between at least two worker threads, also on a single CPU
+            (num_cpus::get() - 1).max(2)
         };
-        println!("tc={}", thread_count);
+        tracing::debug!(thread_count, "Starting the worker threads");
         let mut at_parameter_worker = UVec::with_capacity(thread_count);
         let mut at_row_worker = UVec::with_capacity(thread_count);
         for _ in 0..thread_count {
@@ -139,6 +144,7 @@ impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH
             pc_list,
             pc_list_len: 0,
             cm,
+            options,
         }
     }
 
@@ -152,25 +158,25 @@ impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH
 ///
 /// It allows for concurrent writes to the data without any checks, so it is super unsafe.
 /// Do not use this if you care about your sanity.
-pub struct Wrapper<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
-    data: UnsafeCell<IPOGData<ValueId, ParameterId, LocationsType, STRENGTH>>,
+pub struct Wrapper<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize, Word: BitArray = u64, Index: Number = u64> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    data: UnsafeCell<IPOGData<ValueId, ParameterId, LocationsType, STRENGTH, Word, Index>>,
 }
 
 // It is ''safe'' to move this to other threads...
-unsafe impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize> Sync for Wrapper<ValueId, ParameterId, LocationsType, STRENGTH> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {}
+unsafe impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize, Word: BitArray, Index: Number> Sync for Wrapper<ValueId, ParameterId, LocationsType, STRENGTH, Word, Index> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {}
 
-impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize> Wrapper<ValueId, ParameterId, LocationsType, STRENGTH> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize, Word: BitArray, Index: Number> Wrapper<ValueId, ParameterId, LocationsType, STRENGTH, Word, Index> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
     /// Create an [Arc], which wraps around this wrapper, which wraps around the [IPOGData].
     ///
-    /// The argument is passed directly to [IPOGData::new] to construct a new instance of the data struct.
-    pub fn new(parameters: UVec<ValueId>, constraints: usize) -> Arc<Self> {
+    /// The arguments are passed directly to [IPOGData::new] to construct a new instance of the data struct.
+    pub fn new(parameters: UVec<ValueId>, constraints: usize, options: RunOptions) -> Arc<Self> {
         Arc::new(Self {
-            data: UnsafeCell::new(IPOGData::new(parameters, constraints)),
+            data: UnsafeCell::new(IPOGData::new(parameters, constraints, options)),
         })
     }
 
     /// This method returns the [IPOGData] wrapped by this struct.
-    pub unsafe fn get_data(&self) -> &mut IPOGData<ValueId, ParameterId, LocationsType, STRENGTH> {
+    pub unsafe fn get_data(&self) -> &mut IPOGData<ValueId, ParameterId, LocationsType, STRENGTH, Word, Index> {
         &mut *self.data.get()
     }
 }
@@ -98,8 +98,8 @@ pub fn multiple_split(thread_count: usize, thread_id: usize, total_size: usize)
 }
 
 /// Split the work concerning `total_size` items in `thread_count` parts. Returns an iterator returning the parts for which `thread_id` is responsible.
-/// If the `no-cycle-split` feature is set, then the parts are not cycled amongst all threads.
-pub fn cycling_split(thread_count: usize, thread_id: usize, total_size: usize) -> impl Iterator<Item=(usize, usize)> {
+/// If `cycle` is false, then the parts are not cycled amongst all threads, see [common::RunOptions::cycle_split].
+pub fn cycling_split(thread_count: usize, thread_id: usize, total_size: usize, cycle: bool) -> impl Iterator<Item=(usize, usize)> {
     debug_assert!(thread_count >= 2);
     let mut result = UVec::with_capacity(thread_count);
     let mut previous = 0;
@@ -115,7 +115,7 @@ pub fn cycling_split(thread_count: usize, thread_id: usize, total_size: usize) -
 
     result.push((previous, total_size));
 
-    if cfg!(feature="no-cycle-split") {
+    if !cycle {
         vec![result.into_iter().cycle().skip(thread_id).next().unwrap()].into_iter().cycle().skip(0)
     } else {
         result.into_iter().cycle().skip(thread_id)
@@ -39,6 +39,7 @@ fn test_split_5_6_7() { test_split(5, 6, 7) }
 fn test_split(strength: usize, at_parameter: usize, thread_count: usize) {
     test_multiple_split(strength, at_parameter, thread_count);
     test_cycling_split(strength, at_parameter, thread_count);
+    test_fixed_split(strength, at_parameter, thread_count);
 }
 fn test_multiple_split(strength: usize, at_parameter: usize, thread_count: usize) {
     let length = calculate_length(strength, at_parameter);
@@ -91,7 +92,7 @@ fn test_cycling_split(strength: usize, at_parameter: usize, thread_count: usize)
     let mut split_results = UVec::with_capacity(thread_count);
 
     for thread_id in 0..thread_count {
-        split_results.push(cycling_split(thread_count, thread_id, length));
+        split_results.push(cycling_split(thread_count, thread_id, length, true));
     }
 
     println!("s={} p={} tl={} tc={}", strength, at_parameter, length, thread_count);
@@ -120,3 +121,20 @@ fn test_cycling_split(strength: usize, at_parameter: usize, thread_count: usize)
         assert!(temp.iter().any(|(_, end)| *end == length), "No end?");
     }
 }
+
+fn test_fixed_split(strength: usize, at_parameter: usize, thread_count: usize) {
+    let length = calculate_length(strength, at_parameter);
+    let mut mid = 0;
+
+    for thread_id in 0..thread_count {
+        let mut parts = cycling_split(thread_count, thread_id, length, false);
+        let part = parts.next().unwrap();
+        assert_eq!(part.0, mid, "start != previous end?");
+        for _ in 0..SUB_SPLIT {
+            assert_eq!(parts.next(), Some(part), "The part of thread {} changed", thread_id);
+        }
+        mid = part.1;
+    }
+
+    assert_eq!(mid, length, "No end?");
+}
@@ -87,7 +87,7 @@ fn get_basic_mca(basic_mca: &[[u8; PARAMETERS.len()]; 4]) -> MCA<u8, u64> {
         dont_care_locations.push(locations);
     }
 
-    let result = MCA { array, dont_care_locations, vertical_extension_rows: UVec::with_capacity(0), new_row: UVec::with_capacity(0) };
+    let result = MCA { array, dont_care_locations, vertical_extension_rows: UVec::with_capacity(0), new_row: UVec::with_capacity(0), skipped: None };
     debug_assert!(result.check_locations());
     result
 }
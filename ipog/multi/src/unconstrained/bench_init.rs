@@ -6,7 +6,7 @@
 
 use test::Bencher;
 
-use common::{u_vec, UVec};
+use common::{RunOptions, u_vec, UVec};
 use mca::MCA;
 
 use crate::unconstrained::threads::init_thread_pool;
@@ -39,7 +39,7 @@ fn bench_initial_single(bencher: &mut Bencher) {
 #[bench]
 fn bench_initial_thread(bencher: &mut Bencher) {
     let length = mca_size();
-    let mut ipog_data = crate::IPOGData::<u8, u8, 5, 6>::new(UVec::from(PARAMETERS.to_vec()), 0);
+    let mut ipog_data = crate::IPOGData::<u8, u8, 5, 6>::new(UVec::from(PARAMETERS.to_vec()), 0, RunOptions::default());
     ipog_data.thread_count = THREAD_COUNT;
     let (_start, end) = crate::threads_common::split(THREAD_COUNT, 0, length);
     let parameters = UVec::from(PARAMETERS.to_vec());
@@ -61,13 +61,13 @@ fn bench_initial_alloc(bencher: &mut Bencher) {
     let capacity = length * PARAMETERS.len() * 6;
 
     bencher.iter(|| {
-        let _mca = MCA::<u8, u64> { array: UVec::with_capacity(capacity), dont_care_locations: UVec::with_capacity(capacity), vertical_extension_rows: UVec::with_capacity(capacity), new_row: UVec::with_capacity(PARAMETERS.len()) };
+        let _mca = MCA::<u8, u64> { array: UVec::with_capacity(capacity), dont_care_locations: UVec::with_capacity(capacity), vertical_extension_rows: UVec::with_capacity(capacity), new_row: UVec::with_capacity(PARAMETERS.len()), skipped: None };
     })
 }
 
 #[test]
 fn uninitialised_mca() {
-    let mut mca = MCA::<u8, u64> { array: UVec::with_capacity(100), dont_care_locations: UVec::with_capacity(100), vertical_extension_rows: UVec::with_capacity(0), new_row: UVec::with_capacity(0) };
+    let mut mca = MCA::<u8, u64> { array: UVec::with_capacity(100), dont_care_locations: UVec::with_capacity(100), vertical_extension_rows: UVec::with_capacity(0), new_row: UVec::with_capacity(0), skipped: None };
     unsafe { mca.array.set_len(10); }
     let mut pointer = mca.array.as_mut_ptr();
     let row = u_vec![1, 2, 3, 4, 5];
@@ -123,7 +123,7 @@ fn bench_initial_thread_init(bencher: &mut Bencher) {
 #[bench]
 fn bench_initial_thread_full(bencher: &mut Bencher) {
     bencher.iter(|| {
-        let ipog_data_arc = Wrapper::<u8, u8, u64, 5, 6>::new(UVec::from(PARAMETERS.to_vec()), 0);
+        let ipog_data_arc = Wrapper::<u8, u8, u64, 5, 6>::new(UVec::from(PARAMETERS.to_vec()), 0, RunOptions::default());
         let ipog_data = unsafe { &mut *ipog_data_arc.data.get() };
         ipog_data.thread_count = THREAD_COUNT;
 
@@ -140,7 +140,7 @@ fn bench_initial_thread_full(bencher: &mut Bencher) {
 #[bench]
 fn bench_initial_start_one_thread(bencher: &mut Bencher) {
     bencher.iter(|| {
-        let ipog_data_arc = Wrapper::<u8, u8, u64, 6>::new(UVec::from(PARAMETERS.to_vec()), 0);
+        let ipog_data_arc = Wrapper::<u8, u8, u64, 6>::new(UVec::from(PARAMETERS.to_vec()), 0, RunOptions::default());
         let ipog_data = unsafe { &mut *ipog_data_arc.data.get() };
         ipog_data.thread_count = 1;
         let (_senders, _receivers) = init_thread_pool(ipog_data_arc.clone());
@@ -150,7 +150,7 @@ fn bench_initial_start_one_thread(bencher: &mut Bencher) {
 #[bench]
 fn bench_initial_start_threads(bencher: &mut Bencher) {
     bencher.iter(|| {
-        let ipog_data_arc = Wrapper::<u8, u8, u64, 6>::new(UVec::from(PARAMETERS.to_vec()), 0);
+        let ipog_data_arc = Wrapper::<u8, u8, u64, 6>::new(UVec::from(PARAMETERS.to_vec()), 0, RunOptions::default());
         let (_senders, _receivers) = init_thread_pool(ipog_data_arc.clone());
     });
 }
@@ -158,6 +158,6 @@ fn bench_initial_start_threads(bencher: &mut Bencher) {
 #[bench]
 fn bench_initial_data_init(bencher: &mut Bencher) {
     bencher.iter(|| {
-        let _ipog_data_arc = Wrapper::<u8, u8, u64, 6>::new(UVec::from(PARAMETERS.to_vec()), 0);
+        let _ipog_data_arc = Wrapper::<u8, u8, u64, 6>::new(UVec::from(PARAMETERS.to_vec()), 0, RunOptions::default());
     });
 }
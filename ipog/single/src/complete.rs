@@ -0,0 +1,160 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module contains the methods for completing an [MCA] of which the generation was cancelled.
+//!
+//! The completion does not try to keep the MCA small: it appends one row for each interaction that is still uncovered.
+//! This is cheap, so the result is a valid covering array shortly after the time limit has passed.
+
+use cm::CoverageMap;
+use common::{Number, UVec, ValueGenerator};
+use mca::MCA;
+use pc_list::PCList;
+use sut::Solver;
+
+/// Decides whether the interaction of the values for the PC and parameter should be covered.
+type IsValid<'v, ValueId, ParameterId> = dyn FnMut(&[ParameterId], usize, &[ValueId]) -> bool + 'v;
+
+/// Append a row for every uncovered interaction of the parameters skipped by the generation, if `valid` accepts it.
+fn complete<
+    ValueId: Number,
+    ParameterId: Number,
+    LocationsType: Number,
+    const STRENGTH: usize,
+>(
+    parameters: &UVec<ValueId>,
+    mca: &mut MCA<ValueId, LocationsType>,
+    coverage_map_budget: Option<usize>,
+    valid: &mut IsValid<'_, ValueId, ParameterId>,
+) where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    let at_parameter = match &mca.skipped {
+        Some(skipped) if skipped.completion_rows.is_none() => skipped.at_parameter,
+        _ => return,
+    };
+    let initial_rows = mca.array.len();
+
+    let pc_list = PCList::<ParameterId, LocationsType, STRENGTH>::new(parameters.len());
+    let mut coverage_map = match coverage_map_budget {
+        Some(budget) => CoverageMap::<ValueId, STRENGTH>::with_memory_budget(parameters.clone(), &pc_list, budget).unwrap_or_else(|e| panic!("{}", e)),
+        None => CoverageMap::<ValueId, STRENGTH>::new(parameters.clone(), &pc_list),
+    };
+
+    for at_parameter in at_parameter..parameters.len() {
+        let parameter_mask = LocationsType::bit(at_parameter);
+        coverage_map.initialise(at_parameter);
+
+        loop {
+            let chunk = coverage_map.chunk();
+            for row in mca.array.iter() {
+                if row[at_parameter] != ValueId::dont_care() {
+                    unsafe { coverage_map.set_covered_row_simple_sub(at_parameter, &pc_list, row.as_slice(), chunk.start, chunk.end) };
+                }
+            }
+
+            for pc_id in chunk {
+                let pc = &pc_list.pcs[pc_id];
+                let value_generator = ValueGenerator::<ValueId, STRENGTH>::new(parameters, at_parameter, pc);
+                let mut values = [ValueId::default(); STRENGTH];
+                let mut map_index = coverage_map.pc_index(pc_id);
+
+                loop {
+                    if unsafe { coverage_map.set_index(map_index) } && valid(pc, at_parameter, &values) {
+                        mca.append_row(at_parameter, pc, &values, !(pc_list.locations[pc_id] | parameter_mask));
+                    }
+
+                    map_index += 1;
+                    if !value_generator.next_array(&mut values) {
+                        break;
+                    }
+                }
+            }
+
+            if !coverage_map.next_chunk() {
+                break;
+            }
+        }
+    }
+
+    if let Some(skipped) = &mut mca.skipped {
+        skipped.completion_rows = Some(mca.array.len() - initial_rows);
+    }
+}
+
+/// Complete the unconstrained [MCA] returned by a cancelled generation, see [MCA::skipped].
+///
+/// Does nothing if the generation was not cancelled.
+/// The `coverage_map_budget` bounds the coverage map as in [crate::unconstrained::UnconstrainedIPOG::run].
+pub fn complete_unconstrained<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>(
+    parameters: &UVec<ValueId>,
+    mca: &mut MCA<ValueId, LocationsType>,
+    coverage_map_budget: Option<usize>,
+) where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    complete::<ValueId, ParameterId, LocationsType, STRENGTH>(parameters, mca, coverage_map_budget, &mut |_, _, _| true)
+}
+
+/// Complete the constrained [MCA] returned by a cancelled generation, see [MCA::skipped].
+///
+/// Only the interactions allowed by the constraints are added. Does nothing if the generation was not cancelled.
+pub fn complete_constrained<'a, ValueId: Number, ParameterId: Number, LocationsType: Number, S: Solver<'a>, const STRENGTH: usize>(
+    solver: &mut S,
+    parameters: &UVec<ValueId>,
+    mca: &mut MCA<ValueId, LocationsType>,
+) where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    complete::<ValueId, ParameterId, LocationsType, STRENGTH>(parameters, mca, None, &mut |pc, at_parameter, values| {
+        solver.push_and_assert_interaction(pc, at_parameter, values);
+        solver.check_and_pop_all(1)
+    })
+}
+
+#[cfg(test)]
+mod complete_tests {
+    use common::{CancellationToken, RunOptions};
+    use mca::min_coverage;
+    use sut::{parse_unconstrained, SUT};
+
+    use crate::unconstrained::{HorizontalExtension, UnconstrainedIPOG, VerticalExtension};
+
+    use super::complete_unconstrained;
+
+    type IPOG = UnconstrainedIPOG<usize, usize, u64, HorizontalExtension<usize, usize, u64, 2>, VerticalExtension<usize, usize, u64, 2>, 2>;
+
+    fn sut() -> SUT<usize, usize> {
+        parse_unconstrained("p0: v0, v1, v2;p1: v0, v1, v2;p2: v0, v1;p3: v0, v1;p4: v0, v1;p5: v0, v1;").unwrap()
+    }
+
+    #[test]
+    fn test_cancelled_and_completed() {
+        let mut sut = sut();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let mut mca = IPOG::run(&mut sut, &mut (), &cancel, &mut (), None, &RunOptions::default());
+        let skipped = mca.skipped.clone().expect("The generation should be cancelled.");
+        assert_eq!(skipped.at_parameter, 2);
+        assert_eq!(skipped.parameters, 6);
+        assert_eq!(skipped.completion_rows, None);
+        assert!(!mca.is_complete());
+        assert_eq!(min_coverage(sut.parameters.as_slice(), &mca.array, 2, |_, _| true), 0);
+
+        let rows = mca.array.len();
+        complete_unconstrained::<usize, usize, u64, 2>(&sut.parameters, &mut mca, None);
+        assert_eq!(mca.skipped.as_ref().unwrap().completion_rows, Some(mca.array.len() - rows));
+        assert!(mca.is_complete());
+        assert!(min_coverage(sut.parameters.as_slice(), &mca.array, 2, |_, _| true) > 0);
+    }
+
+    #[test]
+    fn test_not_cancelled() {
+        let mut sut = sut();
+        let mut mca = IPOG::run(&mut sut, &mut (), &CancellationToken::new(), &mut (), None, &RunOptions::default());
+        assert_eq!(mca.skipped, None);
+
+        let rows = mca.array.len();
+        complete_unconstrained::<usize, usize, u64, 2>(&sut.parameters, &mut mca, None);
+        assert_eq!(mca.array.len(), rows);
+        assert!(min_coverage(sut.parameters.as_slice(), &mca.array, 2, |_, _| true) > 0);
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module contains the IPOG-F horizontal extension for [sut::ConstrainedSUT]s.
+
+use std::marker::PhantomData;
+
+use cm::{BitArray, CoverageMap};
+use common::{CancellationToken, Number, u_vec, UVec};
+use mca::{check_locations, MCA};
+use pc_list::PCList;
+use sut::Solver;
+
+use crate::constrained::Extension;
+use crate::gain::GainQueue;
+
+/// The horizontal extension of IPOG-F, which extends the row with the highest gain first instead of the rows in order.
+///
+/// The gain of a row is the number of uncovered interactions covered by its best valid value.
+/// This usually results in a smaller MCA, but takes longer and calls the solver more often than the [super::HorizontalExtension].
+pub struct HorizontalExtension<
+    ValueId: Number,
+    ParameterId: Number,
+    LocationsType: Number,
+    const STRENGTH: usize,
+> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    value_id: PhantomData<ValueId>,
+    parameter_id: PhantomData<ParameterId>,
+    locations_type: PhantomData<LocationsType>,
+}
+
+impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
+HorizontalExtension<ValueId, ParameterId, LocationsType, STRENGTH> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:
+{
+    /// Returns the best valid value for the row and its gain, the `scores` contain the interactions covered by each value.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn best_value<'a, S: Solver<'a>, Word: BitArray, Index: Number>(
+        solver: &mut S,
+        at_parameter: usize,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        pc_list_len: usize,
+        mca: &MCA<ValueId, LocationsType>,
+        coverage_map: &CoverageMap<ValueId, STRENGTH, Word, Index>,
+        row_id: usize,
+        scores: &mut UVec<UVec<Index>>,
+        blacklist: &mut UVec<bool>,
+        uses: &mut UVec<usize>,
+        previous_value: ValueId,
+    ) -> (ValueId, usize) {
+        let row = mca.array[row_id].as_slice();
+
+        for score in scores.iter_mut() {
+            score.clear();
+        }
+
+        for b in blacklist.iter_mut() {
+            *b = false;
+        }
+
+        coverage_map.calculate_scores(pc_list, pc_list_len, row, mca.dont_care_locations[row_id], LocationsType::mask_low(at_parameter), scores);
+
+        if scores.iter().all(UVec::is_empty) {
+            return (ValueId::default(), 0);
+        }
+
+        solver.push_and_assert_row(&row[..at_parameter]);
+        let value = super::HorizontalExtension::<ValueId, ParameterId, LocationsType, STRENGTH>::get_best_value(
+            solver,
+            at_parameter,
+            previous_value,
+            ValueId::from_usize(scores.len()),
+            scores,
+            blacklist,
+            uses,
+        );
+        solver.pop(1); // Pop row
+
+        match value {
+            Some(value) => (value, scores[value.as_usize()].len()),
+            None => (ValueId::default(), 0),
+        }
+    }
+}
+
+impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
+Extension<ValueId, ParameterId, LocationsType, STRENGTH>
+for HorizontalExtension<ValueId, ParameterId, LocationsType, STRENGTH> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:
+{
+    const NAME: &'static str = "F HE";
+
+    unsafe fn extend<'a, S: Solver<'a>, Word: BitArray, Index: Number>(
+        solver: &mut S,
+        parameters: &UVec<ValueId>,
+        at_parameter: usize,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        pc_list_len: usize,
+        mca: &mut MCA<ValueId, LocationsType>,
+        coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
+        cancel: &CancellationToken,
+    ) {
+        let dont_care_mask = !LocationsType::bit(at_parameter);
+
+        coverage_map.set_zero_covered();
+        let mut previous_value = ValueId::default();
+
+        let value_choices = parameters[at_parameter];
+        let mut scores = u_vec![UVec::with_capacity(pc_list_len); value_choices.as_usize()];
+        let mut blacklist = u_vec![false; value_choices.as_usize()];
+        let mut uses = u_vec![0; value_choices.as_usize()];
+        uses[0] = 1;
+
+        let mut queue = GainQueue::with_capacity(mca.array.len());
+        for row_id in 1..mca.array.len() {
+            if cancel.is_cancelled() {
+                return;
+            }
+            let (_, gain) = Self::best_value(solver, at_parameter, pc_list, pc_list_len, mca, coverage_map, row_id, &mut scores, &mut blacklist, &mut uses, previous_value);
+            queue.push(gain, row_id);
+        }
+
+        let mut value = ValueId::default();
+        while let Some(row_id) = queue.pop(|row_id| {
+            let gain;
+            (value, gain) = Self::best_value(solver, at_parameter, pc_list, pc_list_len, mca, coverage_map, row_id, &mut scores, &mut blacklist, &mut uses, previous_value);
+            gain
+        }) {
+            if cancel.is_cancelled() {
+                return;
+            }
+
+            let row = mca.array[row_id].as_slice_mut();
+            let dont_care_locations = &mut mca.dont_care_locations[row_id];
+            *row.get_unchecked_mut(at_parameter) = value;
+            uses[value.as_usize()] += 1;
+            *dont_care_locations &= dont_care_mask;
+            previous_value = value;
+            debug_assert!(check_locations(row, *dont_care_locations));
+
+            coverage_map.set_indices(&scores[value.as_usize()]);
+
+            if coverage_map.is_covered() {
+                return;
+            }
+        }
+    }
+}
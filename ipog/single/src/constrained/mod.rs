@@ -10,9 +10,10 @@
 
 use std::marker::PhantomData;
 
-use cm::{BIT_MASK, BIT_SHIFT, BitArray, CoverageMap, get_highscore_blacklisted};
-use common::{Number, sub_time_it, u_vec, UVec, ValueGenerator};
-use mca::{check_locations, MCA};
+use cm::{BitArray, CoverageMap, get_highscore_blacklisted, with_map_types};
+use common::{CancellationToken, Number, Phase, Progress, ProgressObserver, RunOptions, sub_time_it, time_it, u_vec, UVec, ValueGenerator};
+use mca::{check_locations, MCA, Skipped};
+use mca::checkpoint::Checkpointer;
 use pc_list::PCList;
 use sut::{ConstrainedSUT, Solver};
 
@@ -22,14 +23,17 @@ pub trait Extension<ValueId: Number, ParameterId: Number, LocationsType: Number,
     const NAME: &'static str;
 
     /// Does the extension for the specified strength.
-    unsafe fn extend<'a, S: Solver<'a>>(
+    ///
+    /// The extension returns early when the `cancel` token is cancelled, leaving the interactions of the parameter partially uncovered.
+    unsafe fn extend<'a, S: Solver<'a>, Word: BitArray, Index: Number>(
         solver: &mut S,
         parameters: &UVec<ValueId>,
         at_parameter: usize,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         pc_list_len: usize,
         mca: &mut MCA<ValueId, LocationsType>,
-        coverage_map: &mut CoverageMap<ValueId, STRENGTH>,
+        coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
+        cancel: &CancellationToken,
     );
 }
 
@@ -39,14 +43,15 @@ pub struct NOOPExtension<const STRENGTH: usize>;
 impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize> Extension<ValueId, ParameterId, LocationsType, STRENGTH> for NOOPExtension<STRENGTH> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
     const NAME: &'static str = "NOOP";
 
-    unsafe fn extend<'a, S: Solver<'a>>(
+    unsafe fn extend<'a, S: Solver<'a>, Word: BitArray, Index: Number>(
         _solver: &mut S,
         _parameters: &UVec<ValueId>,
         _at_parameter: usize,
         _pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         _pc_list_len: usize,
         _mca: &mut MCA<ValueId, LocationsType>,
-        _coverage_map: &mut CoverageMap<ValueId, STRENGTH>,
+        _coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
+        _cancel: &CancellationToken,
     ) {}
 }
 
@@ -75,14 +80,15 @@ for TimedExtension<ValueId, ParameterId, LocationsType, SubExtension, STRENGTH>
 {
     const NAME: &'static str = SubExtension::NAME;
 
-    unsafe fn extend<'a, S: Solver<'a>>(
+    unsafe fn extend<'a, S: Solver<'a>, Word: BitArray, Index: Number>(
         solver: &mut S,
         parameters: &UVec<ValueId>,
         at_parameter: usize,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         pc_list_len: usize,
         mca: &mut MCA<ValueId, LocationsType>,
-        coverage_map: &mut CoverageMap<ValueId, STRENGTH>,
+        coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
+        cancel: &CancellationToken,
     ) {
         sub_time_it!(
             SubExtension::extend(
@@ -92,7 +98,8 @@ for TimedExtension<ValueId, ParameterId, LocationsType, SubExtension, STRENGTH>
                 pc_list,
                 pc_list_len,
                 mca,
-                coverage_map
+                coverage_map,
+                cancel
             ),
             Self::NAME
         );
@@ -115,12 +122,12 @@ pub struct HorizontalExtension<
 impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
 HorizontalExtension<ValueId, ParameterId, LocationsType, STRENGTH> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:
 {
-    unsafe fn get_best_value<'a, S: Solver<'a>>(
+    unsafe fn get_best_value<'a, S: Solver<'a>, Index: Number>(
         solver: &mut S,
         at_parameter: usize,
         mut previous_value: ValueId,
         value_choices: ValueId,
-        scores: &mut UVec<UVec<BitArray>>,
+        scores: &mut UVec<UVec<Index>>,
         blacklist: &mut UVec<bool>,
         uses: &mut UVec<usize>,
     ) -> Option<ValueId> {
@@ -164,14 +171,15 @@ for HorizontalExtension<ValueId, ParameterId, LocationsType, STRENGTH> where [()
 {
     const NAME: &'static str = "B HE";
 
-    unsafe fn extend<'a, S: Solver<'a>>(
+    unsafe fn extend<'a, S: Solver<'a>, Word: BitArray, Index: Number>(
         solver: &mut S,
         parameters: &UVec<ValueId>,
         at_parameter: usize,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         pc_list_len: usize,
         mca: &mut MCA<ValueId, LocationsType>,
-        coverage_map: &mut CoverageMap<ValueId, STRENGTH>,
+        coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
+        cancel: &CancellationToken,
     ) {
         let dont_care_mask = !LocationsType::bit(at_parameter);
         let no_dont_cares = LocationsType::mask_low(at_parameter);
@@ -186,6 +194,10 @@ for HorizontalExtension<ValueId, ParameterId, LocationsType, STRENGTH> where [()
         uses[0] = 1;
 
         'row: for row_id in 1..mca.array.len() {
+            if cancel.is_cancelled() {
+                return;
+            }
+
             let row = &mut mca.array[row_id].as_slice_mut();
             let dont_care_locations = &mut mca.dont_care_locations[row_id];
 
@@ -250,18 +262,52 @@ impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH
 VerticalExtension<ValueId, ParameterId, LocationsType, STRENGTH>
     where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:
 {
-    fn pc_valid<'a, S: Solver<'a>>(
-        solver: &mut S,
+    /// Push the uncovered interactions of the PC to the `interactions`, in the order of the [CoverageMap].
+    unsafe fn uncovered_interactions<'p, Word: BitArray, Index: Number>(
+        parameters: &UVec<ValueId>,
         at_parameter: usize,
-        pc: &[ParameterId; STRENGTH - 1],
-        values: &[ValueId; STRENGTH],
-    ) -> bool {
-        solver.push_and_assert_interaction(pc, at_parameter, values);
-        let result = solver.check();
-        if !result {
-            solver.pop_all(1);
+        pc: &'p [ParameterId; STRENGTH - 1],
+        pc_id: usize,
+        coverage_map: &CoverageMap<ValueId, STRENGTH, Word, Index>,
+        interactions: &mut Vec<(&'p [ParameterId; STRENGTH - 1], [ValueId; STRENGTH])>,
+    ) {
+        let value_choices = parameters[at_parameter].as_usize();
+        let mut values = [ValueId::default(); STRENGTH];
+        let value_generator = ValueGenerator::<ValueId, STRENGTH>::new(
+            &parameters, at_parameter, pc,
+        );
+        let mut map_index = (coverage_map.sizes[pc_id][0].as_usize() * value_choices) + 1;
+
+        'sup_index: loop {
+            let map_sub_index = map_index & Word::MASK;
+            let mut map_array = coverage_map.map[map_index >> Word::SHIFT] >> Word::from_usize(map_sub_index);
+
+            // Skip block if the block is covered
+            if map_array == Word::dont_care() {
+                if value_generator
+                    .skip_array(&mut values, ValueId::from_usize(Word::BITS))
+                {
+                    map_index += Word::BITS;
+                    continue 'sup_index;
+                } else {
+                    break 'sup_index;
+                }
+            }
+
+            for _ in map_sub_index..=Word::MASK {
+                if value_generator.next_array(&mut values) {
+                    if (map_array & Word::from_usize(1)).none() {
+                        interactions.push((pc, values));
+                    }
+
+                    map_index += 1;
+                    map_array >>= Word::from_usize(1);
+                } else {
+                    break 'sup_index;
+                }
+            }
+            debug_assert_eq!(map_index & Word::MASK, 0);
         }
-        result
     }
 
     #[inline]
@@ -310,13 +356,13 @@ VerticalExtension<ValueId, ParameterId, LocationsType, STRENGTH>
         }
     }
 
-    unsafe fn fit_in_row<'a, S: Solver<'a>>(
+    unsafe fn fit_in_row<'a, S: Solver<'a>, Word: BitArray, Index: Number>(
         solver: &mut S,
         at_parameter: usize,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         pc_list_len: usize,
         mca: &mut MCA<ValueId, LocationsType>,
-        coverage_map: &mut CoverageMap<ValueId, STRENGTH>,
+        coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
         pc: &[ParameterId; STRENGTH - 1],
         values: &[ValueId; STRENGTH],
         pc_id: usize,
@@ -373,100 +419,80 @@ for VerticalExtension<ValueId, ParameterId, LocationsType, STRENGTH> where [();
 {
     const NAME: &'static str = "B VE";
 
-    unsafe fn extend<'a, S: Solver<'a>>(
+    unsafe fn extend<'a, S: Solver<'a>, Word: BitArray, Index: Number>(
         solver: &mut S,
         parameters: &UVec<ValueId>,
         at_parameter: usize,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         pc_list_len: usize,
         mca: &mut MCA<ValueId, LocationsType>,
-        coverage_map: &mut CoverageMap<ValueId, STRENGTH>,
+        coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
+        cancel: &CancellationToken,
     ) {
         debug_assert!(
-            ((BIT_MASK + 1) as usize) < (ValueId::dont_care().as_usize()),
+            Word::BITS < ValueId::dont_care().as_usize(),
             "Bitarray and ValueId are incompatible."
         );
-        let value_choices = parameters[at_parameter].as_usize() as BitArray;
-
         let locations_mask = mca.set_vertical_extension_rows(at_parameter);
 
         debug_assert!(mca.check_all(at_parameter));
 
         let parameter_mask = LocationsType::bit(at_parameter);
+        let mut interactions = Vec::new();
 
         for pc_id in 0..pc_list.pcs.len() {
+            if cancel.is_cancelled() {
+                return;
+            }
+
             let pc = &pc_list.pcs[pc_id];
 
-            let mut values = [ValueId::default(); STRENGTH];
-            let value_generator = ValueGenerator::<ValueId, STRENGTH>::new(
-                &parameters, at_parameter, pc,
-            );
-            let mut map_index = (coverage_map.sizes[pc_id][0] * value_choices) + 1;
-            let mut pc_locations_option: Option<(LocationsType, LocationsType)> = None;
-
-            'sup_index: loop {
-                let map_sub_index = map_index & BIT_MASK;
-                let mut map_array = coverage_map.map[map_index as usize >> BIT_SHIFT] >> map_sub_index;
-
-                // Skip block if the block is covered
-                if map_array == !0 {
-                    if value_generator
-                        .skip_array(&mut values, ValueId::from_usize(BIT_MASK as usize + 1))
-                    {
-                        map_index += BIT_MASK + 1;
-                        continue 'sup_index;
-                    } else {
-                        break 'sup_index;
+            // The interactions of this PC are not covered by fitting the others, so they are all checked at once
+            interactions.clear();
+            Self::uncovered_interactions(parameters, at_parameter, pc, pc_id, coverage_map, &mut interactions);
+            if interactions.is_empty() {
+                continue;
+            }
+
+            let valid = solver.check_interactions(at_parameter, &interactions);
+            let pc_locations_tuple = (pc_list.locations[pc_id], !(pc_list.locations[pc_id] | parameter_mask));
+
+            for ((_, values), valid) in interactions.iter().zip(valid.iter()) {
+                coverage_map.uncovered -= 1;
+
+                if valid {
+                    solver.push_and_assert_interaction(pc, at_parameter, values);
+
+                    if !Self::fit_in_row(
+                        solver,
+                        at_parameter,
+                        pc_list,
+                        pc_list_len,
+                        mca,
+                        coverage_map,
+                        pc,
+                        values,
+                        pc_id,
+                        &pc_locations_tuple,
+                        locations_mask,
+                    ) {
+                        mca.append_row(at_parameter, &pc, values, pc_locations_tuple.1);
                     }
+
+                    solver.pop_all(1);
                 }
 
-                for _ in map_sub_index..=BIT_MASK {
-                    if value_generator.next_array(&mut values) {
-                        if map_array & 1 == 0 {
-                            coverage_map.uncovered -= 1;
-
-                            if Self::pc_valid(solver, at_parameter, pc, &values) {
-                                let pc_locations_tuple = pc_locations_option.get_or_insert_with(|| {
-                                    (pc_list.locations[pc_id], !(pc_list.locations[pc_id] | parameter_mask))
-                                });
-
-                                if !Self::fit_in_row(
-                                    solver,
-                                    at_parameter,
-                                    pc_list,
-                                    pc_list_len,
-                                    mca,
-                                    coverage_map,
-                                    pc,
-                                    &values,
-                                    pc_id,
-                                    pc_locations_tuple,
-                                    locations_mask,
-                                ) {
-                                    mca.append_row(at_parameter, &pc, &values, pc_locations_tuple.1);
-                                }
-
-                                solver.pop_all(1);
-                            }
-
-                            if coverage_map.is_covered() {
-                                return;
-                            }
-                        }
-
-                        map_index += 1;
-                        map_array >>= 1;
-                    } else {
-                        break 'sup_index;
-                    }
+                if coverage_map.is_covered() {
+                    return;
                 }
-                debug_assert_eq!(map_index & BIT_MASK, 0);
             }
         }
     }
 }
 
 /// Filter the [CoverageMap]. Sets all disallowed interactions as covered.
+///
+/// The interactions of every PC are checked in a single [Solver::check_interactions] batch, except the first which is covered by the first row.
 pub unsafe fn filter_map<
     'a,
     ValueId: Number,
@@ -474,6 +500,8 @@ pub unsafe fn filter_map<
     LocationsType: Number,
     S: Solver<'a>,
     const STRENGTH: usize,
+    Word: BitArray,
+    Index: Number,
 >(
     solver: &mut S,
     parameters: &UVec<ValueId>,
@@ -481,60 +509,25 @@ pub unsafe fn filter_map<
     pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
     start: usize,
     end: usize,
-    coverage_map: &mut CoverageMap<ValueId, STRENGTH>,
+    coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
 ) where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
     let mut map_index = 0;
+    let mut interactions = Vec::new();
 
     for pc in pc_list.pcs[start..end].iter() {
-        let mut values = [ValueId::default(); STRENGTH - 1];
-        let mut max_values = [ValueId::default(); STRENGTH - 1];
-        for (max_value, parameter_id) in max_values.iter_mut().zip(pc.iter()) {
-            solver.push_and_assert_eq(*parameter_id, ValueId::default());
-            *max_value = parameters[parameter_id.as_usize()];
+        let value_generator = ValueGenerator::<ValueId, STRENGTH>::new(parameters, at_parameter, pc);
+        let mut values = [ValueId::default(); STRENGTH];
+        interactions.clear();
+        while value_generator.next_array(&mut values) {
+            interactions.push((pc, values));
         }
-        let value_choices = parameters[at_parameter];
 
         map_index += 1;
-        let mut first_iteration = true;
-
-        'value_loop: loop {
-            for value in ValueId::default()..value_choices {
-                if first_iteration {
-                    first_iteration = false;
-                    continue;
-                }
-
-                solver.push_and_assert_eq(ParameterId::from_usize(at_parameter), value);
-
-                if !solver.check() {
-                    coverage_map.set_index(map_index);
-                }
-
-                solver.pop(1);
-                map_index += 1;
-            }
-
-            let mut value_index = STRENGTH - 2;
-            values[value_index] += ValueId::from_usize(1);
-
-            while 0 < value_index && values[value_index] == max_values[value_index] {
-                values[value_index] = ValueId::default();
-                values[value_index - 1] += ValueId::from_usize(1);
-                value_index -= 1;
-            }
-
-            solver.pop((STRENGTH - value_index - 1) as u32);
-            if 0 == value_index && values[0] == max_values[0] {
-                break 'value_loop;
-            }
-
-            solver.push_and_assert_eq(pc[value_index], values[value_index]);
-
-            while value_index < STRENGTH - 2 {
-                value_index += 1;
-
-                solver.push_and_assert_eq(pc[value_index], values[value_index]);
+        for valid in solver.check_interactions(at_parameter, &interactions).iter() {
+            if !valid {
+                coverage_map.set_index(Index::from_usize(map_index));
             }
+            map_index += 1;
         }
     }
 }
@@ -568,39 +561,81 @@ impl<
     where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:
 {
     /// Run the constrained version of IPOG.
+    ///
+    /// The `observer` is notified at the start of every horizontal and vertical extension and when the generation has finished.
+    ///
+    /// If the `cancel` token is cancelled, the MCA built so far is returned with [MCA::skipped] describing the remaining work.
+    /// Use [crate::complete::complete_constrained] to turn it into a valid MCA.
+    ///
+    /// The generation continues from the MCA provided by the `checkpoint`, if any, and saves the MCA to it after every parameter.
+    ///
+    /// The `options` select the scoring of the horizontal extension and whether the disallowed interactions are filtered from the [CoverageMap], see [RunOptions::filter_map].
     pub fn run(
         sut: &mut ConstrainedSUT<ValueId, ParameterId>,
         solver_init: &'a S::Init,
+        observer: &mut dyn ProgressObserver,
+        cancel: &CancellationToken,
+        checkpoint: &mut dyn Checkpointer<ValueId, LocationsType>,
+        options: &RunOptions,
     ) -> MCA<ValueId, LocationsType> {
-        let mut solver = sut.get_solver::<S>(&solver_init);
-        let mut mca = MCA::<ValueId, LocationsType>::new_constrained::<ParameterId, S, STRENGTH>(
-            &sut.sub_sut.parameters,
-            &mut solver,
-        );
+        let mut solver = time_it!(sut.get_solver::<S>(&solver_init), "Solver setup", DEBUG);
+        let (first_parameter, mca) = checkpoint.resume().unwrap_or_else(|| {
+            (STRENGTH, MCA::<ValueId, LocationsType>::new_constrained::<ParameterId, S, STRENGTH>(&sut.sub_sut.parameters, &mut solver))
+        });
 
         if STRENGTH == sut.sub_sut.parameters.len() {
+            observer.progress(&Progress { strength: STRENGTH, at_parameter: STRENGTH, parameters: STRENGTH, rows: mca.array.len(), uncovered: 0, phase: Phase::Done });
             return mca;
         }
 
-        let pc_list = sub_time_it!(
+        let pc_list = time_it!(
             PCList::<ParameterId, LocationsType, STRENGTH>::new(sut.sub_sut.parameters.len()),
-            "PCList generation"
+            "PCList generation",
+            DEBUG
         );
-        let mut coverage_map = CoverageMap::<ValueId, STRENGTH>::new(
-            sut.sub_sut.parameters.clone(),
+        with_map_types!(sut.sub_sut.parameters.as_slice(), STRENGTH, <Word, Index> Self::extend_parameters::<Word, Index>(
+            &mut solver,
+            &sut.sub_sut.parameters,
             &pc_list,
+            first_parameter,
+            mca,
+            observer,
+            cancel,
+            checkpoint,
+            options,
+        ))
+    }
+
+    /// Extends the MCA with the parameters from `first_parameter` on, using a [CoverageMap] with the given element and index types.
+    #[allow(clippy::too_many_arguments)]
+    fn extend_parameters<Word: BitArray, Index: Number>(
+        solver: &mut S,
+        parameters: &UVec<ValueId>,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        first_parameter: usize,
+        mut mca: MCA<ValueId, LocationsType>,
+        observer: &mut dyn ProgressObserver,
+        cancel: &CancellationToken,
+        checkpoint: &mut dyn Checkpointer<ValueId, LocationsType>,
+        options: &RunOptions,
+    ) -> MCA<ValueId, LocationsType> {
+        let mut coverage_map = time_it!(
+            CoverageMap::<ValueId, STRENGTH, Word, Index>::new(parameters.clone(), pc_list),
+            "Coverage map generation",
+            DEBUG
         );
-        for at_parameter in STRENGTH..sut.sub_sut.parameters.len() {
+        coverage_map.scoring = options.scoring;
+        for at_parameter in first_parameter..parameters.len() {
             let pc_list_len = pc_list.sizes[at_parameter - STRENGTH];
             coverage_map.initialise(at_parameter);
 
-            if cfg!(feature = "filter-map") {
+            if options.filter_map {
                 unsafe {
                     filter_map(
-                        &mut solver,
-                        &sut.sub_sut.parameters,
+                        solver,
+                        parameters,
                         at_parameter,
-                        &pc_list,
+                        pc_list,
                         0,
                         pc_list_len,
                         &mut coverage_map,
@@ -608,37 +643,65 @@ impl<
                 }
             }
 
+            if cancel.is_cancelled() {
+                mca.skipped = Some(Skipped { at_parameter, parameters: parameters.len(), phase: Phase::Horizontal, uncovered: coverage_map.uncovered, completion_rows: None });
+                break;
+            }
+
             debug_assert!(mca.check_locations());
 
+            observer.progress(&Progress { strength: STRENGTH, at_parameter, parameters: parameters.len(), rows: mca.array.len(), uncovered: coverage_map.uncovered, phase: Phase::Horizontal });
             unsafe {
                 TimedExtension::<ValueId, ParameterId, LocationsType, HorizontalExtension, STRENGTH>::extend(
-                    &mut solver,
-                    &sut.sub_sut.parameters,
+                    solver,
+                    parameters,
                     at_parameter,
-                    &pc_list,
+                    pc_list,
                     pc_list_len,
                     &mut mca,
                     &mut coverage_map,
+                    cancel,
                 )
             };
+
+            if !coverage_map.is_covered() && cancel.is_cancelled() {
+                mca.skipped = Some(Skipped { at_parameter, parameters: parameters.len(), phase: Phase::Horizontal, uncovered: coverage_map.uncovered, completion_rows: None });
+                break;
+            }
+
             if !coverage_map.is_covered() {
                 debug_assert!(mca.check_locations());
+                observer.progress(&Progress { strength: STRENGTH, at_parameter, parameters: parameters.len(), rows: mca.array.len(), uncovered: coverage_map.uncovered, phase: Phase::Vertical });
                 unsafe {
                     TimedExtension::<ValueId, ParameterId, LocationsType, VerticalExtension, STRENGTH>::extend(
-                        &mut solver,
-                        &sut.sub_sut.parameters,
+                        solver,
+                        parameters,
                         at_parameter,
-                        &pc_list,
+                        pc_list,
                         pc_list_len,
                         &mut mca,
                         &mut coverage_map,
+                        cancel,
                     );
                 }
+
+                if !coverage_map.is_covered() && cancel.is_cancelled() {
+                    mca.skipped = Some(Skipped { at_parameter, parameters: parameters.len(), phase: Phase::Vertical, uncovered: coverage_map.uncovered, completion_rows: None });
+                    break;
+                }
             }
+
+            checkpoint.save(at_parameter + 1, &mca);
         }
+        observer.progress(&Progress { strength: STRENGTH, at_parameter: parameters.len(), parameters: parameters.len(), rows: mca.array.len(), uncovered: coverage_map.uncovered, phase: Phase::Done });
         mca
     }
 }
 
+pub mod ipog_f;
+
 #[cfg(all(test, feature = "sut/constraints-common"))]
 mod test;
+
+#[cfg(test)]
+mod test_predicate;
@@ -0,0 +1,112 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::collections::HashMap;
+
+use cm::CoverageMap;
+use common::{CancellationToken, RunOptions, UVec};
+use mca::{min_coverage, MCA};
+use pc_list::PCList;
+use sut::{ConstrainedSUT, parse_constrained, Predicate, PredicateSolver};
+
+use super::*;
+
+const MODEL: &str = "p0: a, b, c; p1: a, b, c; p2: x, y; p3: x, y; p4: 0, 1, 2;";
+
+fn predicate(row: &HashMap<&str, &str>) -> bool {
+    (row["p0"] != "a" || row["p4"] != "0") && (row["p1"] != row["p0"] || row["p2"] == "y") && (row["p3"] != "x" || row["p4"] != "2")
+}
+
+/// Returns every full row allowed by the [predicate], independent of the solver.
+fn allowed_rows(sut: &ConstrainedSUT<usize, usize>) -> Vec<UVec<usize>> {
+    let levels = &sut.sub_sut.parameters;
+    (0..levels.iter().product()).map(|mut index: usize| levels.iter().map(|&level| {
+        let value = index % level;
+        index /= level;
+        value
+    }).collect::<UVec<usize>>()).filter(|row| {
+        predicate(&row.iter().enumerate().map(|(parameter_id, &value_id)| {
+            (sut.sub_sut.parameter_names[parameter_id].as_str(), sut.sub_sut.values[parameter_id][value_id].as_str())
+        }).collect())
+    }).collect()
+}
+
+/// Returns true iff one of the rows has the values for the parameters.
+fn extends(rows: &[UVec<usize>], parameters: impl Iterator<Item=usize> + Clone, values: &[usize]) -> bool {
+    rows.iter().any(|row| parameters.clone().zip(values.iter()).all(|(parameter_id, &value_id)| row[parameter_id] == value_id))
+}
+
+fn check_filter_map<const STRENGTH: usize>() where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    let mut sut = parse_constrained(MODEL).unwrap();
+    let mut solver = sut.get_solver::<PredicateSolver>(&(predicate as Predicate));
+    let rows = allowed_rows(&sut);
+    let parameters = sut.sub_sut.parameters.clone();
+    let pc_list = PCList::<usize, u64, STRENGTH>::new(parameters.len());
+    let mut coverage_map = CoverageMap::<usize, STRENGTH>::new(parameters.clone(), &pc_list);
+
+    let mut filtered = 0;
+    for at_parameter in STRENGTH..parameters.len() {
+        coverage_map.initialise(at_parameter);
+        let total = coverage_map.uncovered;
+        unsafe { filter_map(&mut solver, &parameters, at_parameter, &pc_list, 0, pc_list.sizes[at_parameter - STRENGTH], &mut coverage_map) };
+
+        let mut disallowed = 0;
+        for index in (0..total).map(Number::from_usize) {
+            let (pc_id, values, value) = coverage_map.interaction(index);
+            let pc = pc_list.pcs[pc_id];
+            let mut interaction = values.to_vec();
+            interaction.push(value);
+            let allowed = extends(&rows, pc.iter().copied().chain([at_parameter]), &interaction);
+            assert_eq!(unsafe { coverage_map.get(index) }, !allowed, "{:?} {:?} at {}", pc, interaction, at_parameter);
+            disallowed += !allowed as usize;
+        }
+        assert_eq!(coverage_map.uncovered, total - disallowed);
+        filtered += disallowed;
+    }
+    assert!(filtered > 0);
+}
+
+#[test]
+fn test_filter_map() {
+    check_filter_map::<2>();
+    check_filter_map::<3>();
+}
+
+fn check_generation<HorizontalExtension: Extension<usize, usize, u64, STRENGTH>, const STRENGTH: usize>(filter_map: bool)
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    let mut sut = parse_constrained(MODEL).unwrap();
+    let options = RunOptions { filter_map, ..RunOptions::default() };
+    let init: Predicate = predicate;
+    let mca: MCA<usize, u64> = ConstrainedIPOG::<usize, usize, u64, PredicateSolver, HorizontalExtension, VerticalExtension<usize, usize, u64, STRENGTH>, STRENGTH>::run(
+        &mut sut, &init, &mut (), &CancellationToken::new(), &mut (), &options,
+    );
+    assert_eq!(mca.skipped, None);
+    assert!(mca.check_locations());
+
+    let rows = allowed_rows(&sut);
+    for row in mca.array.iter() {
+        let assigned: Vec<usize> = (0..row.len()).filter(|&parameter_id| row[parameter_id] != !0).collect();
+        let values: Vec<usize> = assigned.iter().map(|&parameter_id| row[parameter_id]).collect();
+        assert!(extends(&rows, assigned.into_iter(), &values), "The row {:?} is not allowed", row);
+    }
+    assert!(min_coverage(sut.sub_sut.parameters.as_slice(), &mca.array, STRENGTH, |pc, values| extends(&rows, pc.iter().copied(), values)) > 0);
+}
+
+#[test]
+fn test_generation_filter_map() {
+    for filter_map in [false, true] {
+        check_generation::<HorizontalExtension<usize, usize, u64, 2>, 2>(filter_map);
+        check_generation::<HorizontalExtension<usize, usize, u64, 3>, 3>(filter_map);
+    }
+}
+
+#[test]
+fn test_ipog_f() {
+    for filter_map in [false, true] {
+        check_generation::<ipog_f::HorizontalExtension<usize, usize, u64, 2>, 2>(filter_map);
+        check_generation::<ipog_f::HorizontalExtension<usize, usize, u64, 3>, 3>(filter_map);
+    }
+}
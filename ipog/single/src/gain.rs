@@ -0,0 +1,47 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module contains the queue used by the IPOG-F horizontal extensions to find the row with the highest gain.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// A queue of rows ordered by their gain, which is the number of uncovered interactions the best value for the row covers.
+///
+/// The gain of a row can only decrease when other rows are extended.
+/// So the gains in the queue are upper bounds, which are only recalculated for the row at the front of the queue.
+pub(crate) struct GainQueue {
+    heap: BinaryHeap<(usize, Reverse<usize>)>,
+}
+
+impl GainQueue {
+    /// Create an empty queue for the given number of rows.
+    pub(crate) fn with_capacity(rows: usize) -> Self {
+        Self { heap: BinaryHeap::with_capacity(rows) }
+    }
+
+    /// Add the row with the given gain, rows without gain are ignored.
+    pub(crate) fn push(&mut self, gain: usize, row_id: usize) {
+        if gain != 0 {
+            self.heap.push((gain, Reverse(row_id)));
+        }
+    }
+
+    /// Remove and return a row with the highest gain, or [None] if no row has any gain.
+    ///
+    /// The `gain` of a row is recalculated before it is returned, so the caller can reuse the scores calculated for the returned row.
+    pub(crate) fn pop(&mut self, mut gain: impl FnMut(usize) -> usize) -> Option<usize> {
+        while let Some((_, Reverse(row_id))) = self.heap.pop() {
+            let current = gain(row_id);
+            match self.heap.peek() {
+                Some(&(next, _)) if current < next => self.push(current, row_id),
+                _ if current != 0 => return Some(row_id),
+                _ => {}
+            }
+        }
+        None
+    }
+}
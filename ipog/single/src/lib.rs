@@ -7,10 +7,11 @@
 //! This crate provides a single-threaded implementation of IPOG.
 //! Currently it provides support for generating Mixed-level Covering Arrays (MCA)
 //! for both unconstrained Systems Under Test (SUT) and constrained SUTs.
+//! Both variants provide the horizontal extension of IPOG-F as an alternative, see [unconstrained::ipog_f] and [constrained::ipog_f].
 //!
 //! # Features
 //! This crate provides the following optional feature:
-//!   * `filter-map` Mark interactions disallowed by the constraints as covered in the [cm::CoverageMap] before beginning the extensions.
+//!   * `filter-map` Mark interactions disallowed by the constraints as covered in the [cm::CoverageMap] before beginning the extensions by default, see [common::RunOptions::filter_map].
 
 #![allow(incomplete_features)]
 #![feature(adt_const_params)]
@@ -20,3 +21,7 @@
 pub mod unconstrained;
 
 pub mod constrained;
+
+pub mod complete;
+
+mod gain;
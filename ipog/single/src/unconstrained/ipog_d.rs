@@ -0,0 +1,274 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module contains IPOG-D for unconstrained [SUT]s, which doubles an MCA for half of the parameters.
+
+use std::cmp::max;
+use std::marker::PhantomData;
+
+use cm::{BitArray, CoverageMap, with_map_types};
+use common::{CancellationToken, Number, Phase, Progress, ProgressObserver, repeat_strengths, RunOptions, time_it, u_vec, UVec};
+use mca::{MCA, Skipped};
+use mca::checkpoint::Checkpointer;
+use pc_list::PCList;
+use sut::SUT;
+
+use crate::unconstrained::{Extension, TimedExtension, UnconstrainedIPOG};
+
+/// The minimum number of parameters for which IPOG-D doubles an MCA, fewer parameters are generated with IPOG directly.
+///
+/// The doubling also requires at least twice the strength in parameters.
+pub const MIN_DOUBLING_PARAMETERS: usize = 8;
+
+/// The toplevel of IPOG-D.
+///
+/// The MCA for the first half of the parameters is generated recursively and copied into the second half of the parameters.
+/// This covers all interactions of which the parameters are in different positions of the halves.
+/// For a strength of 3 or more, shifted copies of an MCA of one strength lower for the half also cover the interactions with exactly two parameters in the same position.
+/// The remaining interactions are covered parameter by parameter with the vertical extension.
+/// This is much faster than [UnconstrainedIPOG] for many parameters, but usually results in a larger MCA.
+pub struct UnconstrainedIPOGD<
+    ValueId: Number,
+    ParameterId: Number,
+    LocationsType: Number,
+    HorizontalExtension: Extension<ValueId, ParameterId, LocationsType, STRENGTH>,
+    VerticalExtension: Extension<ValueId, ParameterId, LocationsType, STRENGTH>,
+    const STRENGTH: usize,
+> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    value_id: PhantomData<ValueId>,
+    parameter_id: PhantomData<ParameterId>,
+    locations_type: PhantomData<LocationsType>,
+
+    horizontal_extension: PhantomData<HorizontalExtension>,
+    vertical_extension: PhantomData<VerticalExtension>,
+}
+
+impl<ValueId: Number, ParameterId: Number, LocationsType: Number, HorizontalExtension: Extension<ValueId, ParameterId, LocationsType, STRENGTH>, VerticalExtension: Extension<ValueId, ParameterId, LocationsType, STRENGTH>, const STRENGTH: usize>
+UnconstrainedIPOGD<ValueId, ParameterId, LocationsType, HorizontalExtension, VerticalExtension, STRENGTH>
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    /// Performs the IPOG-D algorithm, the smallest halves are generated by [UnconstrainedIPOG] with the specified extension types.
+    ///
+    /// The `observer`, `cancel` token, `checkpoint`, `coverage_map_budget` and `options` are used in the same way as by [UnconstrainedIPOG::run].
+    /// The `checkpoint` is only saved while covering the remaining interactions of the largest doubling.
+    ///
+    /// # Panics
+    /// Panics if the `coverage_map_budget` is too small for the largest PC, see [cm::largest_pc_bits].
+    pub fn run(
+        sut: &mut SUT<ValueId, ParameterId>,
+        observer: &mut dyn ProgressObserver,
+        cancel: &CancellationToken,
+        checkpoint: &mut dyn Checkpointer<ValueId, LocationsType>,
+        coverage_map_budget: Option<usize>,
+        options: &RunOptions,
+    ) -> MCA<ValueId, LocationsType> {
+        Self::generate(&sut.parameters, observer, cancel, checkpoint, coverage_map_budget, options)
+    }
+
+    fn generate(
+        parameters: &UVec<ValueId>,
+        observer: &mut dyn ProgressObserver,
+        cancel: &CancellationToken,
+        checkpoint: &mut dyn Checkpointer<ValueId, LocationsType>,
+        coverage_map_budget: Option<usize>,
+        options: &RunOptions,
+    ) -> MCA<ValueId, LocationsType> {
+        if parameters.len() < max(MIN_DOUBLING_PARAMETERS, 2 * STRENGTH) {
+            return UnconstrainedIPOG::<ValueId, ParameterId, LocationsType, HorizontalExtension, VerticalExtension, STRENGTH>::generate(
+                parameters, observer, cancel, checkpoint, coverage_map_budget, options,
+            );
+        }
+
+        let (first_parameter, mca) = match checkpoint.resume() {
+            Some(resumed) => resumed,
+            None => {
+                let middle = parameters.len().div_ceil(2);
+                let half_parameters: UVec<ValueId> = parameters.iter().take(middle).copied().collect();
+                let half = Self::generate(
+                    &half_parameters,
+                    &mut HalfObserver { observer, parameters: parameters.len() },
+                    cancel,
+                    &mut (),
+                    coverage_map_budget,
+                    options,
+                );
+                let lower = if STRENGTH > 2 && half.skipped.is_none() {
+                    time_it!(
+                        generate_lower::<ValueId, ParameterId, LocationsType>(STRENGTH - 1, &half_parameters, cancel, coverage_map_budget, options),
+                        "Lower strength generation",
+                        DEBUG
+                    )
+                } else {
+                    MCA::new_empty()
+                };
+                let mca = time_it!(double(parameters, middle, half, lower), "Doubling", DEBUG);
+                if mca.skipped.is_some() {
+                    return mca;
+                }
+                (middle, mca)
+            }
+        };
+
+        tracing::debug!(rows = mca.array.len(), first_parameter, "Doubled the MCA");
+
+        let pc_list = time_it!(PCList::<ParameterId, LocationsType, STRENGTH>::new(parameters.len()), "PCList generation", DEBUG);
+        with_map_types!(parameters.as_slice(), STRENGTH, <Word, Index> Self::cover_remaining::<Word, Index>(
+            parameters,
+            &pc_list,
+            first_parameter,
+            mca,
+            observer,
+            cancel,
+            checkpoint,
+            coverage_map_budget,
+        ))
+    }
+
+    /// Covers the remaining interactions of the parameters from `first_parameter` on, using a [CoverageMap] with the given element and index types.
+    #[allow(clippy::too_many_arguments)]
+    fn cover_remaining<Word: BitArray, Index: Number>(
+        parameters: &UVec<ValueId>,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        first_parameter: usize,
+        mut mca: MCA<ValueId, LocationsType>,
+        observer: &mut dyn ProgressObserver,
+        cancel: &CancellationToken,
+        checkpoint: &mut dyn Checkpointer<ValueId, LocationsType>,
+        coverage_map_budget: Option<usize>,
+    ) -> MCA<ValueId, LocationsType> {
+        let mut coverage_map = time_it!(match coverage_map_budget {
+            Some(budget) => CoverageMap::<ValueId, STRENGTH, Word, Index>::with_memory_budget(parameters.clone(), pc_list, budget).unwrap_or_else(|e| panic!("{}", e)),
+            None => CoverageMap::<ValueId, STRENGTH, Word, Index>::new(parameters.clone(), pc_list),
+        }, "Coverage map generation", DEBUG);
+
+        for at_parameter in first_parameter..parameters.len() {
+            let pc_list_len = pc_list.sizes[at_parameter - STRENGTH];
+            coverage_map.initialise(at_parameter);
+
+            if cancel.is_cancelled() {
+                mca.skipped = Some(Skipped { at_parameter, parameters: parameters.len(), phase: Phase::Horizontal, uncovered: coverage_map.uncovered, completion_rows: None });
+                break;
+            }
+
+            // The doubled values take the place of the horizontal extension, the vertical extension covers the chunks itself
+            observer.progress(&Progress { strength: STRENGTH, at_parameter, parameters: parameters.len(), rows: mca.array.len(), uncovered: coverage_map.uncovered, phase: Phase::Horizontal });
+            if !coverage_map.is_chunked() {
+                unsafe { coverage_map.set_covered_rows(at_parameter, pc_list, &mca.array) };
+            }
+
+            if !coverage_map.is_covered() || coverage_map.is_chunked() {
+                debug_assert!(mca.check_locations());
+                observer.progress(&Progress { strength: STRENGTH, at_parameter, parameters: parameters.len(), rows: mca.array.len(), uncovered: coverage_map.uncovered, phase: Phase::Vertical });
+                unsafe {
+                    TimedExtension::<ValueId, ParameterId, LocationsType, VerticalExtension, STRENGTH>::extend(
+                        parameters,
+                        at_parameter,
+                        pc_list,
+                        pc_list_len,
+                        &mut mca,
+                        &mut coverage_map,
+                        cancel,
+                    );
+                }
+
+                if !coverage_map.is_covered() && cancel.is_cancelled() {
+                    mca.skipped = Some(Skipped { at_parameter, parameters: parameters.len(), phase: Phase::Vertical, uncovered: coverage_map.uncovered, completion_rows: None });
+                    break;
+                }
+                debug_assert!(mca.check_all(at_parameter));
+            }
+
+            checkpoint.save(at_parameter + 1, &mca);
+        }
+        observer.progress(&Progress { strength: STRENGTH, at_parameter: parameters.len(), parameters: parameters.len(), rows: mca.array.len(), uncovered: coverage_map.uncovered, phase: Phase::Done });
+        mca
+    }
+}
+
+/// Generate an MCA of the given `strength` with [UnconstrainedIPOG], the strength is only known at runtime.
+fn generate_lower<ValueId: Number, ParameterId: Number, LocationsType: Number>(
+    strength: usize,
+    parameters: &UVec<ValueId>,
+    cancel: &CancellationToken,
+    coverage_map_budget: Option<usize>,
+    options: &RunOptions,
+) -> MCA<ValueId, LocationsType> {
+    macro_rules! generate {
+        ($name:ident, $strength:expr) => {
+            if strength == $strength {
+                return UnconstrainedIPOG::<
+                    ValueId,
+                    ParameterId,
+                    LocationsType,
+                    super::HorizontalExtension<ValueId, ParameterId, LocationsType, $strength>,
+                    super::VerticalExtension<ValueId, ParameterId, LocationsType, $strength>,
+                    $strength,
+                >::generate(parameters, &mut (), cancel, &mut (), coverage_map_budget, options);
+            }
+        };
+    }
+    repeat_strengths!(generate);
+    unreachable!("The strength {} is not supported.", strength)
+}
+
+/// Copy the rows of the MCA for the parameters before `middle` into the parameters from `middle`.
+///
+/// The rows of the `lower` MCA, which has one strength lower, are added with the copied values shifted by every value except 0.
+/// These cover the interactions with exactly two parameters in the same position of the halves.
+/// The parameters are not necessarily sorted on descending level, so a value which does not fit the parameter it is copied to is folded with the modulo.
+/// The interactions this misses, like those with the values of a larger level that are never copied, are covered afterwards by the vertical extension.
+/// If the generation of the half was cancelled, the parameters from the skipped one are not covered in either half.
+fn double<ValueId: Number, LocationsType: Number>(
+    parameters: &UVec<ValueId>,
+    middle: usize,
+    half: MCA<ValueId, LocationsType>,
+    lower: MCA<ValueId, LocationsType>,
+) -> MCA<ValueId, LocationsType> {
+    let shifts = parameters[middle].as_usize();
+    let capacity = half.array.capacity() + lower.array.len() * shifts;
+    let mut result = MCA {
+        array: UVec::with_capacity(capacity),
+        dont_care_locations: UVec::with_capacity(capacity),
+        vertical_extension_rows: UVec::with_capacity(capacity),
+        new_row: u_vec![ValueId::dont_care(); parameters.len()],
+        skipped: half.skipped.map(|skipped| Skipped { parameters: parameters.len(), ..skipped }),
+    };
+
+    let rows = half.array.iter().zip(half.dont_care_locations.iter()).map(|row| (0, row));
+    let shifted_rows = (1..shifts).flat_map(|shift| lower.array.iter().zip(lower.dont_care_locations.iter()).map(move |row| (shift, row)));
+    for (shift, (row, &locations)) in rows.chain(shifted_rows) {
+        let mut new_row = u_vec![ValueId::dont_care(); parameters.len()];
+        let mut new_locations = locations & LocationsType::mask_low(middle);
+        for column in 0..middle {
+            new_row[column] = row[column];
+        }
+        for column in middle..parameters.len() {
+            let value = row[column - middle];
+            if value == ValueId::dont_care() {
+                new_locations |= LocationsType::bit(column);
+            } else {
+                new_row[column] = ValueId::from_usize((value.as_usize() + shift) % parameters[column].as_usize());
+            }
+        }
+        result.array.push(new_row);
+        result.dont_care_locations.push(new_locations);
+    }
+
+    result
+}
+
+/// Reports the progress of the generation of a half as part of the generation of all parameters.
+struct HalfObserver<'o> {
+    observer: &'o mut dyn ProgressObserver,
+    parameters: usize,
+}
+
+impl ProgressObserver for HalfObserver<'_> {
+    fn progress(&mut self, progress: &Progress) {
+        if progress.phase != Phase::Done {
+            self.observer.progress(&Progress { parameters: self.parameters, ..*progress });
+        }
+    }
+}
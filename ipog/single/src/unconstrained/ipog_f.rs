@@ -0,0 +1,137 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module contains the IPOG-F horizontal extension for unconstrained [sut::SUT]s.
+
+use std::marker::PhantomData;
+
+use cm::{BitArray, CoverageMap, get_highscore};
+use common::{CancellationToken, Number, u_vec, UVec};
+use mca::{check_locations, MCA};
+use pc_list::PCList;
+
+use crate::gain::GainQueue;
+use crate::unconstrained::Extension;
+
+/// The horizontal extension of IPOG-F, which extends the row with the highest gain first instead of the rows in order.
+///
+/// The gain of a row is the number of uncovered interactions covered by its best value.
+/// This usually results in a smaller MCA, but takes longer than the [super::HorizontalExtension].
+/// If the [CoverageMap] is chunked, the [super::HorizontalExtension] is used instead.
+pub struct HorizontalExtension<
+    ValueId: Number,
+    ParameterId: Number,
+    LocationsType: Number,
+    const STRENGTH: usize,
+> {
+    value_id: PhantomData<ValueId>,
+    parameter_id: PhantomData<ParameterId>,
+    locations_type: PhantomData<LocationsType>,
+}
+
+impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
+HorizontalExtension<ValueId, ParameterId, LocationsType, STRENGTH>
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:
+{
+    /// Returns the best value for the row and its gain, the `scores` contain the interactions covered by each value.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn best_value<Word: BitArray, Index: Number>(
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        pc_list_len: usize,
+        mca: &MCA<ValueId, LocationsType>,
+        coverage_map: &CoverageMap<ValueId, STRENGTH, Word, Index>,
+        row_id: usize,
+        no_dont_cares: LocationsType,
+        scores: &mut UVec<UVec<Index>>,
+        uses: &UVec<usize>,
+        previous_value: ValueId,
+    ) -> (ValueId, usize) {
+        for score in scores.iter_mut() {
+            score.clear();
+        }
+
+        coverage_map.calculate_scores(
+            pc_list,
+            pc_list_len,
+            mca.array[row_id].as_slice(),
+            mca.dont_care_locations[row_id],
+            no_dont_cares,
+            scores,
+        );
+
+        let value: ValueId = get_highscore(scores, uses, previous_value);
+        (value, scores[value.as_usize()].len())
+    }
+}
+
+impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
+Extension<ValueId, ParameterId, LocationsType, STRENGTH>
+for HorizontalExtension<ValueId, ParameterId, LocationsType, STRENGTH>
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:
+{
+    const NAME: &'static str = "F HE";
+
+    unsafe fn extend<Word: BitArray, Index: Number>(
+        parameters: &UVec<ValueId>,
+        at_parameter: usize,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        pc_list_len: usize,
+        mca: &mut MCA<ValueId, LocationsType>,
+        coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
+        cancel: &CancellationToken,
+    ) {
+        debug_assert!(!mca.dont_care_locations.is_empty());
+
+        if coverage_map.is_chunked() {
+            return super::HorizontalExtension::<ValueId, ParameterId, LocationsType, STRENGTH>::extend(
+                parameters, at_parameter, pc_list, pc_list_len, mca, coverage_map, cancel,
+            );
+        }
+
+        let dont_care_mask = !LocationsType::bit(at_parameter);
+        let no_dont_cares = LocationsType::mask_low(at_parameter);
+        let value_choices = parameters[at_parameter];
+        let mut scores = u_vec![UVec::with_capacity(pc_list_len); value_choices.as_usize()];
+        let mut previous_value: ValueId = ValueId::default();
+        let mut uses = u_vec![0; value_choices.as_usize()];
+        uses[0] = 1;
+        coverage_map.set_zero_covered();
+
+        let mut queue = GainQueue::with_capacity(mca.array.len());
+        for row_id in 1..mca.array.len() {
+            if cancel.is_cancelled() {
+                return;
+            }
+            let (_, gain) = Self::best_value(pc_list, pc_list_len, mca, coverage_map, row_id, no_dont_cares, &mut scores, &uses, previous_value);
+            queue.push(gain, row_id);
+        }
+
+        let mut value = ValueId::default();
+        while let Some(row_id) = queue.pop(|row_id| {
+            let gain;
+            (value, gain) = Self::best_value(pc_list, pc_list_len, mca, coverage_map, row_id, no_dont_cares, &mut scores, &uses, previous_value);
+            gain
+        }) {
+            if cancel.is_cancelled() {
+                return;
+            }
+
+            let row = mca.array[row_id].as_slice_mut();
+            let dont_care_locations = &mut mca.dont_care_locations[row_id];
+            *row.get_unchecked_mut(at_parameter) = value;
+            uses[value.as_usize()] += 1;
+            *dont_care_locations &= dont_care_mask;
+            previous_value = value;
+            debug_assert!(check_locations(row, *dont_care_locations));
+
+            coverage_map.set_indices(&scores[value.as_usize()]);
+
+            if coverage_map.is_covered() {
+                return;
+            }
+        }
+    }
+}
@@ -6,11 +6,13 @@
 
 //! This module contains the IPOG algorithm for unconstrained [SUT]s.
 
+use std::cmp::{max, min};
 use std::marker::PhantomData;
 
-use cm::{BIT_MASK, BIT_SHIFT, BitArray, CoverageMap, get_highscore};
-use common::{Number, sub_time_it, u_vec, UVec, ValueGenerator};
-use mca::{check_locations, MCA};
+use cm::{BitArray, CoverageMap, get_highscore, get_highscore_merged, with_map_types};
+use common::{CancellationToken, Number, Phase, Progress, ProgressObserver, RunOptions, sub_time_it, time_it, u_vec, UVec, ValueGenerator};
+use mca::{check_locations, MCA, Skipped};
+use mca::checkpoint::Checkpointer;
 use pc_list::PCList;
 use sut::SUT;
 
@@ -20,13 +22,16 @@ pub trait Extension<ValueId: Number, ParameterId: Number, LocationsType: Number,
     const NAME: &'static str;
 
     /// Does the extension for the specified strength.
-    unsafe fn extend(
+    ///
+    /// The extension returns early when the `cancel` token is cancelled, leaving the interactions of the parameter partially uncovered.
+    unsafe fn extend<Word: BitArray, Index: Number>(
         parameters: &UVec<ValueId>,
         at_parameter: usize,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         pc_list_len: usize,
         mca: &mut MCA<ValueId, LocationsType>,
-        coverage_map: &mut CoverageMap<ValueId, STRENGTH>,
+        coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
+        cancel: &CancellationToken,
     );
 }
 
@@ -40,13 +45,14 @@ Extension<ValueId, ParameterId, LocationsType, STRENGTH> for NOOPExtension<STREN
 {
     const NAME: &'static str = "NOOP";
 
-    unsafe fn extend(
+    unsafe fn extend<Word: BitArray, Index: Number>(
         _parameters: &UVec<ValueId>,
         _at_parameter: usize,
         _pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         _pc_list_len: usize,
         _mca: &mut MCA<ValueId, LocationsType>,
-        _coverage_map: &mut CoverageMap<ValueId, STRENGTH>,
+        _coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
+        _cancel: &CancellationToken,
     ) {}
 }
 
@@ -76,13 +82,14 @@ for TimedExtension<ValueId, ParameterId, LocationsType, SubExtension, STRENGTH>
 {
     const NAME: &'static str = SubExtension::NAME;
 
-    unsafe fn extend(
+    unsafe fn extend<Word: BitArray, Index: Number>(
         parameters: &UVec<ValueId>,
         at_parameter: usize,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         pc_list_len: usize,
         mca: &mut MCA<ValueId, LocationsType>,
-        coverage_map: &mut CoverageMap<ValueId, STRENGTH>,
+        coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
+        cancel: &CancellationToken,
     ) {
         sub_time_it!(
             SubExtension::extend(
@@ -91,14 +98,23 @@ for TimedExtension<ValueId, ParameterId, LocationsType, SubExtension, STRENGTH>
                 pc_list,
                 pc_list_len,
                 mca,
-                coverage_map
+                coverage_map,
+                cancel
             ),
             Self::NAME
         );
     }
 }
 
+/// The number of blocks in which the [HorizontalExtension] extends the rows if the [CoverageMap] is chunked.
+///
+/// Each block processes all chunks once, more blocks give an MCA closer to the one of a map without chunks.
+pub const HORIZONTAL_BLOCKS: usize = 16;
+
 /// This horizontal extension will use bitwise operations to speed up the generation of the MCA.
+///
+/// If the [CoverageMap] is chunked, the rows are extended in blocks, see [HORIZONTAL_BLOCKS].
+/// The scores of the rows in a block are summed over the chunks, after which the values are chosen in the last chunk.
 pub struct HorizontalExtension<
     ValueId: Number,
     ParameterId: Number,
@@ -110,6 +126,114 @@ pub struct HorizontalExtension<
     locations_type: PhantomData<LocationsType>,
 }
 
+impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
+HorizontalExtension<ValueId, ParameterId, LocationsType, STRENGTH>
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:
+{
+    /// Does the horizontal extension for a chunked [CoverageMap].
+    ///
+    /// The scores of all but the last chunk only consider the rows before the block.
+    /// The scores hold at most one interaction of every PC of a chunk for each value, so they are sized per chunk.
+    unsafe fn extend_chunked<Word: BitArray, Index: Number>(
+        parameters: &UVec<ValueId>,
+        at_parameter: usize,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        pc_list_len: usize,
+        mca: &mut MCA<ValueId, LocationsType>,
+        coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
+        cancel: &CancellationToken,
+    ) {
+        let dont_care_mask = !LocationsType::bit(at_parameter);
+        let no_dont_cares = LocationsType::mask_low(at_parameter);
+        let value_choices = parameters[at_parameter];
+        let mut scores = u_vec![UVec::with_capacity(0); value_choices.as_usize()];
+        let mut previous_value: ValueId = ValueId::default();
+        let mut uses = u_vec![0; value_choices.as_usize()];
+        uses[0] = 1;
+
+        let block_size = max(1, mca.array.len() / HORIZONTAL_BLOCKS);
+        let mut partial_scores = u_vec![u_vec![0; value_choices.as_usize()]; block_size];
+
+        for block_start in (1..mca.array.len()).step_by(block_size) {
+            let block = block_start..min(block_start + block_size, mca.array.len());
+            for partial in partial_scores.iter_mut() {
+                partial.iter_mut().for_each(|score| *score = 0);
+            }
+
+            coverage_map.initialise(at_parameter);
+            loop {
+                if cancel.is_cancelled() {
+                    return;
+                }
+
+                coverage_map.set_covered_rows(at_parameter, pc_list, &mca.array);
+                let chunk = coverage_map.chunk();
+                for score in scores.iter_mut() {
+                    score.clear();
+                    score.reserve_exact(chunk.len());
+                }
+                if chunk.end == pc_list_len {
+                    break;
+                }
+
+                for (row_id, partial) in block.clone().zip(partial_scores.iter_mut()) {
+                    for score in scores.iter_mut() {
+                        score.clear();
+                    }
+
+                    coverage_map.calculate_scores_sub(
+                        pc_list,
+                        mca.array[row_id].as_slice(),
+                        mca.dont_care_locations[row_id],
+                        no_dont_cares,
+                        &mut scores,
+                        chunk.start,
+                        chunk.end,
+                    );
+
+                    for (partial_score, score) in partial.iter_mut().zip(scores.iter()) {
+                        *partial_score += score.len();
+                    }
+                }
+
+                coverage_map.next_chunk();
+            }
+
+            let chunk = coverage_map.chunk();
+            for (row_id, partial) in block.zip(partial_scores.iter()) {
+                let row = mca.array[row_id].as_slice_mut();
+                let dont_care_locations = &mut mca.dont_care_locations[row_id];
+
+                for score in scores.iter_mut() {
+                    score.clear();
+                }
+
+                coverage_map.calculate_scores_sub(
+                    pc_list,
+                    row,
+                    *dont_care_locations,
+                    no_dont_cares,
+                    &mut scores,
+                    chunk.start,
+                    chunk.end,
+                );
+
+                let value: ValueId = get_highscore_merged(partial, &scores, &uses, previous_value);
+
+                if partial[value.as_usize()] + scores[value.as_usize()].len() != 0 {
+                    *row.get_unchecked_mut(at_parameter) = value;
+                    uses[value.as_usize()] += 1;
+                    *dont_care_locations &= dont_care_mask;
+                    previous_value = value;
+                    debug_assert!(check_locations(row, *dont_care_locations));
+
+                    coverage_map.set_indices(&scores[value.as_usize()]);
+                }
+            }
+        }
+    }
+}
+
 impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
 Extension<ValueId, ParameterId, LocationsType, STRENGTH>
 for HorizontalExtension<ValueId, ParameterId, LocationsType, STRENGTH>
@@ -117,16 +241,21 @@ for HorizontalExtension<ValueId, ParameterId, LocationsType, STRENGTH>
 {
     const NAME: &'static str = "B HE";
 
-    unsafe fn extend(
+    unsafe fn extend<Word: BitArray, Index: Number>(
         parameters: &UVec<ValueId>,
         at_parameter: usize,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         pc_list_len: usize,
         mca: &mut MCA<ValueId, LocationsType>,
-        coverage_map: &mut CoverageMap<ValueId, STRENGTH>,
+        coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
+        cancel: &CancellationToken,
     ) {
         debug_assert!(!mca.dont_care_locations.is_empty());
 
+        if coverage_map.is_chunked() {
+            return Self::extend_chunked(parameters, at_parameter, pc_list, pc_list_len, mca, coverage_map, cancel);
+        }
+
         let dont_care_mask = !LocationsType::bit(at_parameter);
         let no_dont_cares = LocationsType::mask_low(at_parameter);
         let value_choices = parameters[at_parameter];
@@ -137,6 +266,10 @@ for HorizontalExtension<ValueId, ParameterId, LocationsType, STRENGTH>
         coverage_map.set_zero_covered();
 
         for row_id in 1..mca.array.len() {
+            if cancel.is_cancelled() {
+                return;
+            }
+
             let row = mca.array[row_id].as_slice_mut();
             let dont_care_locations = &mut mca.dont_care_locations[row_id];
 
@@ -227,12 +360,12 @@ VerticalExtension<ValueId, ParameterId, LocationsType, STRENGTH>
 
     /// Try to fit the interaction in the existing rows by setting dont_care values.
     #[inline]
-    unsafe fn fit_in_row(
+    unsafe fn fit_in_row<Word: BitArray, Index: Number>(
         at_parameter: usize,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
-        pc_list_len: usize,
+        chunk_end: usize,
         mca: &mut MCA<ValueId, LocationsType>,
-        coverage_map: &mut CoverageMap<ValueId, STRENGTH>,
+        coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
         pc: &[ParameterId; STRENGTH - 1],
         values: &[ValueId; STRENGTH],
         pc_id: usize,
@@ -270,7 +403,7 @@ VerticalExtension<ValueId, ParameterId, LocationsType, STRENGTH>
                     &pc_list,
                     row,
                     pc_id + 1, // TODO max(pc_id + 1, pc_list.sizes[first_parameter_changed - STRENGTH])
-                    pc_list_len,
+                    chunk_end,
                 );
 
                 // Done with this interaction, so stop iterating
@@ -280,60 +413,61 @@ VerticalExtension<ValueId, ParameterId, LocationsType, STRENGTH>
 
         false
     }
-}
 
-impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
-Extension<ValueId, ParameterId, LocationsType, STRENGTH>
-for VerticalExtension<ValueId, ParameterId, LocationsType, STRENGTH> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:
-{
-    const NAME: &'static str = "B VE";
-
-    unsafe fn extend(
+    /// Does the vertical extension for the current chunk of the [CoverageMap].
+    unsafe fn extend_chunk<Word: BitArray, Index: Number>(
         parameters: &UVec<ValueId>,
         at_parameter: usize,
         pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
         pc_list_len: usize,
         mca: &mut MCA<ValueId, LocationsType>,
-        coverage_map: &mut CoverageMap<ValueId, STRENGTH>,
+        coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
+        cancel: &CancellationToken,
     ) {
         debug_assert!(
-            ((BIT_MASK + 1) as usize) < (ValueId::dont_care().as_usize()),
+            Word::BITS < ValueId::dont_care().as_usize(),
             "Bitarray and ValueId are incompatible."
         );
-        let value_choices = parameters[at_parameter].as_usize() as BitArray;
 
         let dont_care_mask = mca.set_vertical_extension_rows(at_parameter);
 
         debug_assert!(mca.check_all(at_parameter));
 
         let parameter_mask = LocationsType::bit(at_parameter);
+        let chunk = coverage_map.chunk();
+        debug_assert!(chunk.end <= pc_list_len);
 
-        // Ignore the fact that pc_list should be bounded by the pc_list_len, because the uncovered interactions will be covered before getting to the out of bound PCs.
+        // Ignore the fact that pc_list should be bounded by the chunk, because the uncovered interactions will be covered before getting to the out of bound PCs.
         for (pc_id, (pc, dont_care_locations)) in pc_list
             .pcs
             .iter()
             .zip(pc_list.locations.iter())
             .enumerate()
+            .skip(chunk.start)
         {
+            if cancel.is_cancelled() {
+                return;
+            }
+
             let mut values = [ValueId::default(); STRENGTH];
             let value_generator = ValueGenerator::<ValueId, STRENGTH>::new(
                 &parameters,
                 at_parameter,
                 pc,
             );
-            let mut map_index = (coverage_map.sizes[pc_id][0] * value_choices) + 1;
+            let mut map_index = coverage_map.pc_index(pc_id).as_usize() + 1;
             let mut pc_locations_option: Option<(LocationsType, LocationsType)> = None;
 
             'sup_index: loop {
-                let map_sub_index = map_index & BIT_MASK;
-                let mut map_array = coverage_map.map[map_index as usize >> BIT_SHIFT] >> map_sub_index;
+                let map_sub_index = map_index & Word::MASK;
+                let mut map_array = coverage_map.map[map_index >> Word::SHIFT] >> Word::from_usize(map_sub_index);
 
                 // Skip block if the block is covered
-                if map_array == BitArray::max_value() {
+                if map_array == Word::dont_care() {
                     if value_generator
-                        .skip_array(&mut values, ValueId::from_usize(BIT_MASK as usize + 1))
+                        .skip_array(&mut values, ValueId::from_usize(Word::BITS))
                     {
-                        map_index += BIT_MASK + 1;
+                        map_index += Word::BITS;
                         continue 'sup_index;
                     } else {
                         break 'sup_index;
@@ -341,10 +475,10 @@ for VerticalExtension<ValueId, ParameterId, LocationsType, STRENGTH> where [();
                 }
 
                 // Loop through every bit in the block
-                for _ in map_sub_index..=BIT_MASK {
+                for _ in map_sub_index..=Word::MASK {
                     if value_generator.next_array(&mut values) {
                         // Updates the values and checks if we are done
-                        if map_array & 1 == 0 {
+                        if (map_array & Word::from_usize(1)).none() {
                             // Check if values need covering
                             coverage_map.uncovered -= 1;
 
@@ -355,7 +489,7 @@ for VerticalExtension<ValueId, ParameterId, LocationsType, STRENGTH> where [();
                             if !Self::fit_in_row(
                                 at_parameter,
                                 pc_list,
-                                pc_list_len,
+                                chunk.end,
                                 mca,
                                 coverage_map,
                                 pc,
@@ -373,14 +507,51 @@ for VerticalExtension<ValueId, ParameterId, LocationsType, STRENGTH> where [();
                         }
 
                         map_index += 1;
-                        map_array >>= 1;
+                        map_array >>= Word::from_usize(1);
                     } else {
                         // According to the value generator we are done, so break:
                         break 'sup_index;
                     }
                 }
 
-                debug_assert_eq!(map_index & BIT_MASK, 0);
+                debug_assert_eq!(map_index & Word::MASK, 0);
+            }
+        }
+    }
+}
+
+impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
+Extension<ValueId, ParameterId, LocationsType, STRENGTH>
+for VerticalExtension<ValueId, ParameterId, LocationsType, STRENGTH> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:
+{
+    const NAME: &'static str = "B VE";
+
+    unsafe fn extend<Word: BitArray, Index: Number>(
+        parameters: &UVec<ValueId>,
+        at_parameter: usize,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        pc_list_len: usize,
+        mca: &mut MCA<ValueId, LocationsType>,
+        coverage_map: &mut CoverageMap<ValueId, STRENGTH, Word, Index>,
+        cancel: &CancellationToken,
+    ) {
+        if !coverage_map.is_chunked() {
+            return Self::extend_chunk(parameters, at_parameter, pc_list, pc_list_len, mca, coverage_map, cancel);
+        }
+
+        // The horizontal extension ends in the last chunk, so start over to cover the chunks one by one
+        coverage_map.initialise(at_parameter);
+        loop {
+            coverage_map.set_covered_rows(at_parameter, pc_list, &mca.array);
+            if !coverage_map.is_covered() {
+                Self::extend_chunk(parameters, at_parameter, pc_list, pc_list_len, mca, coverage_map, cancel);
+                if !coverage_map.is_covered() {
+                    return;
+                }
+            }
+
+            if !coverage_map.next_chunk() {
+                return;
             }
         }
     }
@@ -407,59 +578,149 @@ impl<ValueId: Number, ParameterId: Number, LocationsType: Number, HorizontalExte
 UnconstrainedIPOG<ValueId, ParameterId, LocationsType, HorizontalExtension, VerticalExtension, STRENGTH>
     where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
     /// Performs the IPOG algorithm using the specified extension types.
-    pub fn run(sut: &mut SUT<ValueId, ParameterId>) -> MCA<ValueId, LocationsType> {
-        let mut mca = MCA::<ValueId, LocationsType>::new_unconstrained::<ParameterId, STRENGTH>(&sut.parameters);
-
-        if cfg!(debug_assertions) {
-            println!("Initial: {:?}", mca.array.len());
-        }
+    ///
+    /// The `observer` is notified at the start of every horizontal and vertical extension and when the generation has finished.
+    ///
+    /// If the `cancel` token is cancelled, the MCA built so far is returned with [MCA::skipped] describing the remaining work.
+    /// Use [crate::complete::complete_unconstrained] to turn it into a valid MCA.
+    ///
+    /// The generation continues from the MCA provided by the `checkpoint`, if any, and saves the MCA to it after every parameter.
+    ///
+    /// If a `coverage_map_budget` is given, the map of the [CoverageMap] does not exceed this number of bytes.
+    /// The PCs of a parameter which do not fit are then processed in chunks.
+    /// The [PCList] is not split and the scores of the horizontal extension grow with the chunks, [cm::Estimate::coverage_map_budget] leaves room for both.
+    /// The horizontal extension extends the rows in blocks with their scores summed over the chunks and the vertical extension covers the chunks one by one, so the MCA is usually somewhat larger than without a budget.
+    ///
+    /// The scores of the horizontal extension are calculated with the [RunOptions::scoring] of the `options`.
+    ///
+    /// # Panics
+    /// Panics if the `coverage_map_budget` is too small for the largest PC, see [cm::largest_pc_bits].
+    pub fn run(
+        sut: &mut SUT<ValueId, ParameterId>,
+        observer: &mut dyn ProgressObserver,
+        cancel: &CancellationToken,
+        checkpoint: &mut dyn Checkpointer<ValueId, LocationsType>,
+        coverage_map_budget: Option<usize>,
+        options: &RunOptions,
+    ) -> MCA<ValueId, LocationsType> {
+        Self::generate(&sut.parameters, observer, cancel, checkpoint, coverage_map_budget, options)
+    }
 
-        if STRENGTH == sut.parameters.len() {
+    /// Performs the IPOG algorithm for the given parameter levels, see [UnconstrainedIPOG::run].
+    pub(crate) fn generate(
+        parameters: &UVec<ValueId>,
+        observer: &mut dyn ProgressObserver,
+        cancel: &CancellationToken,
+        checkpoint: &mut dyn Checkpointer<ValueId, LocationsType>,
+        coverage_map_budget: Option<usize>,
+        options: &RunOptions,
+    ) -> MCA<ValueId, LocationsType> {
+        let (first_parameter, mca) = checkpoint.resume().unwrap_or_else(|| {
+            (STRENGTH, MCA::<ValueId, LocationsType>::new_unconstrained::<ParameterId, STRENGTH>(parameters))
+        });
+
+        tracing::debug!(rows = mca.array.len(), "Created the initial MCA");
+
+        if STRENGTH == parameters.len() {
+            observer.progress(&Progress { strength: STRENGTH, at_parameter: STRENGTH, parameters: STRENGTH, rows: mca.array.len(), uncovered: 0, phase: Phase::Done });
             return mca;
         }
 
-        let pc_list = sub_time_it!(PCList::<ParameterId, LocationsType, STRENGTH>::new(sut.parameters.len()), "PCList generation");
-        let mut coverage_map = CoverageMap::<ValueId, STRENGTH>::new(sut.parameters.clone(), &pc_list);
+        let pc_list = time_it!(PCList::<ParameterId, LocationsType, STRENGTH>::new(parameters.len()), "PCList generation", DEBUG);
+        with_map_types!(parameters.as_slice(), STRENGTH, <Word, Index> Self::extend_parameters::<Word, Index>(
+            parameters,
+            &pc_list,
+            first_parameter,
+            mca,
+            observer,
+            cancel,
+            checkpoint,
+            coverage_map_budget,
+            options,
+        ))
+    }
 
-        for at_parameter in STRENGTH..sut.parameters.len() {
+    /// Extends the MCA with the parameters from `first_parameter` on, using a [CoverageMap] with the given element and index types.
+    #[allow(clippy::too_many_arguments)]
+    fn extend_parameters<Word: BitArray, Index: Number>(
+        parameters: &UVec<ValueId>,
+        pc_list: &PCList<ParameterId, LocationsType, STRENGTH>,
+        first_parameter: usize,
+        mut mca: MCA<ValueId, LocationsType>,
+        observer: &mut dyn ProgressObserver,
+        cancel: &CancellationToken,
+        checkpoint: &mut dyn Checkpointer<ValueId, LocationsType>,
+        coverage_map_budget: Option<usize>,
+        options: &RunOptions,
+    ) -> MCA<ValueId, LocationsType> {
+        let mut coverage_map = time_it!(match coverage_map_budget {
+            Some(budget) => CoverageMap::<ValueId, STRENGTH, Word, Index>::with_memory_budget(parameters.clone(), pc_list, budget).unwrap_or_else(|e| panic!("{}", e)),
+            None => CoverageMap::<ValueId, STRENGTH, Word, Index>::new(parameters.clone(), pc_list),
+        }, "Coverage map generation", DEBUG);
+        coverage_map.scoring = options.scoring;
+
+        for at_parameter in first_parameter..parameters.len() {
             let pc_list_len = pc_list.sizes[at_parameter - STRENGTH];
             coverage_map.initialise(at_parameter);
 
-            if cfg!(debug_assertions) {
-                println!("Uncovered: {:?}", coverage_map.uncovered);
+            tracing::trace!(at_parameter, uncovered = coverage_map.uncovered, "Initialised the coverage map");
+
+            if cancel.is_cancelled() {
+                mca.skipped = Some(Skipped { at_parameter, parameters: parameters.len(), phase: Phase::Horizontal, uncovered: coverage_map.uncovered, completion_rows: None });
+                break;
             }
 
             debug_assert!(mca.check_locations());
 
+            observer.progress(&Progress { strength: STRENGTH, at_parameter, parameters: parameters.len(), rows: mca.array.len(), uncovered: coverage_map.uncovered, phase: Phase::Horizontal });
             unsafe {
                 TimedExtension::<ValueId, ParameterId, LocationsType, HorizontalExtension, STRENGTH>::extend(
-                    &sut.parameters,
+                    parameters,
                     at_parameter,
-                    &pc_list,
+                    pc_list,
                     pc_list_len,
                     &mut mca,
                     &mut coverage_map,
+                    cancel,
                 );
             }
 
-            if !coverage_map.is_covered() {
+            if !coverage_map.is_covered() && cancel.is_cancelled() {
+                mca.skipped = Some(Skipped { at_parameter, parameters: parameters.len(), phase: Phase::Horizontal, uncovered: coverage_map.uncovered, completion_rows: None });
+                break;
+            }
+
+            if !coverage_map.is_covered() || coverage_map.is_chunked() {
                 debug_assert!(mca.check_locations());
+                observer.progress(&Progress { strength: STRENGTH, at_parameter, parameters: parameters.len(), rows: mca.array.len(), uncovered: coverage_map.uncovered, phase: Phase::Vertical });
                 unsafe {
                     TimedExtension::<ValueId, ParameterId, LocationsType, VerticalExtension, STRENGTH>::extend(
-                        &sut.parameters,
+                        parameters,
                         at_parameter,
-                        &pc_list,
+                        pc_list,
                         pc_list_len,
                         &mut mca,
                         &mut coverage_map,
+                        cancel,
                     );
                 }
+
+                if !coverage_map.is_covered() && cancel.is_cancelled() {
+                    mca.skipped = Some(Skipped { at_parameter, parameters: parameters.len(), phase: Phase::Vertical, uncovered: coverage_map.uncovered, completion_rows: None });
+                    break;
+                }
                 debug_assert!(mca.check_all(at_parameter));
             }
+
+            checkpoint.save(at_parameter + 1, &mca);
         }
+        observer.progress(&Progress { strength: STRENGTH, at_parameter: parameters.len(), parameters: parameters.len(), rows: mca.array.len(), uncovered: coverage_map.uncovered, phase: Phase::Done });
         mca
     }
 }
 
+pub mod ipog_d;
+pub mod ipog_f;
+
 #[cfg(test)]
 mod test;
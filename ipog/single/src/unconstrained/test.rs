@@ -6,9 +6,14 @@
 
 use std::collections::HashSet;
 
-use sut::parse_unconstrained;
+use common::{CancellationToken, RunOptions, Scoring};
+use mca::checkpoint::Checkpointer;
+use sut::{parse_unconstrained, SUT};
 
-use mca::MCA;
+use mca::{min_coverage, MCA};
+
+use crate::complete::complete_unconstrained;
+use crate::unconstrained::{HorizontalExtension, ipog_d, ipog_f, UnconstrainedIPOG, VerticalExtension};
 
 #[test]
 fn test_coverage_map() {
@@ -66,3 +71,130 @@ fn test_big() {
 
     assert_eq!(mca.array.len(), 7 * 6 * 5 * 5);
 }
+
+/// Keeps the saved checkpoints in memory and resumes from the first one.
+#[derive(Default)]
+struct MemoryCheckpointer {
+    saved: Vec<(usize, MCA<usize, u64>)>,
+    resume: Option<(usize, MCA<usize, u64>)>,
+}
+
+impl Checkpointer<usize, u64> for MemoryCheckpointer {
+    fn resume(&mut self) -> Option<(usize, MCA<usize, u64>)> {
+        self.resume.take()
+    }
+
+    fn save(&mut self, next_parameter: usize, mca: &MCA<usize, u64>) {
+        self.saved.push((next_parameter, mca.clone()));
+    }
+}
+
+#[test]
+fn test_resume() {
+    type IPOG = UnconstrainedIPOG<usize, usize, u64, HorizontalExtension<usize, usize, u64, 2>, VerticalExtension<usize, usize, u64, 2>, 2>;
+    let model = "p0: v0, v1, v2;p1: v0, v1;p2: v0, v1, v2;p3: v0, v1;p4: v0, v1, v2, v3;p5: v0, v1;";
+
+    let mut checkpointer = MemoryCheckpointer::default();
+    let mca = IPOG::run(&mut parse_unconstrained(model).unwrap(), &mut (), &CancellationToken::new(), &mut checkpointer, None, &RunOptions::default());
+    assert_eq!(checkpointer.saved.iter().map(|(next_parameter, _)| *next_parameter).collect::<Vec<_>>(), vec![3, 4, 5, 6]);
+
+    let mut resumed = MemoryCheckpointer { resume: Some(checkpointer.saved[1].clone()), ..MemoryCheckpointer::default() };
+    let resumed_mca = IPOG::run(&mut parse_unconstrained(model).unwrap(), &mut (), &CancellationToken::new(), &mut resumed, None, &RunOptions::default());
+    assert_eq!(resumed_mca.array, mca.array);
+    assert_eq!(resumed_mca.dont_care_locations, mca.dont_care_locations);
+    assert_eq!(resumed.saved.iter().map(|(next_parameter, _)| *next_parameter).collect::<Vec<_>>(), vec![5, 6]);
+}
+
+#[test]
+fn test_coverage_map_budget() {
+    type IPOG = UnconstrainedIPOG<usize, usize, u64, HorizontalExtension<usize, usize, u64, 2>, VerticalExtension<usize, usize, u64, 2>, 2>;
+    let model = "p0: 0, 1, 2, 3;p1: 0, 1, 2, 3;p2: 0, 1, 2, 3;p3: 0, 1, 2, 3;p4: 0, 1, 2, 3;p5: 0, 1, 2, 3;p6: 0, 1, 2, 3;p7: 0, 1, 2, 3;p8: 0, 1, 2, 3;p9: 0, 1, 2, 3;";
+    let mut sut = parse_unconstrained(model).unwrap();
+
+    // A single element of the map fits 3 PCs of 16 interactions, the last parameter has 9 PCs.
+    let mca = IPOG::run(&mut sut, &mut (), &CancellationToken::new(), &mut (), Some(8), &RunOptions::default());
+    assert_eq!(mca.skipped, None);
+    assert!(mca.check_locations());
+    assert!(min_coverage(sut.parameters.as_slice(), &mca.array, 2, |_, _| true) > 0);
+
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let mut mca = IPOG::run(&mut sut, &mut (), &cancel, &mut (), Some(8), &RunOptions::default());
+    complete_unconstrained::<usize, usize, u64, 2>(&sut.parameters, &mut mca, Some(8));
+    assert!(min_coverage(sut.parameters.as_slice(), &mca.array, 2, |_, _| true) > 0);
+}
+
+#[test]
+fn test_ipog_f() {
+    type IPOG = UnconstrainedIPOG<usize, usize, u64, ipog_f::HorizontalExtension<usize, usize, u64, 2>, VerticalExtension<usize, usize, u64, 2>, 2>;
+    let model = "p0: 0, 1, 2, 3, 4;p1: 0, 1, 2, 3;p2: 0, 1, 2, 3;p3: 0, 1, 2;p4: 0, 1, 2;p5: 0, 1;p6: 0, 1;p7: 0, 1;";
+    let mut sut = parse_unconstrained(model).unwrap();
+
+    let mca = IPOG::run(&mut sut, &mut (), &CancellationToken::new(), &mut (), None, &RunOptions::default());
+    assert_eq!(mca.skipped, None);
+    assert!(mca.check_locations());
+    assert!(min_coverage(sut.parameters.as_slice(), &mca.array, 2, |_, _| true) > 0);
+
+    // A chunked coverage map falls back to the horizontal extension of IPOG.
+    let mca = IPOG::run(&mut sut, &mut (), &CancellationToken::new(), &mut (), Some(8), &RunOptions::default());
+    assert_eq!(mca.skipped, None);
+    assert!(mca.check_locations());
+    assert!(min_coverage(sut.parameters.as_slice(), &mca.array, 2, |_, _| true) > 0);
+}
+
+#[test]
+fn test_ipog_d() {
+    type IPOGD = ipog_d::UnconstrainedIPOGD<usize, usize, u64, HorizontalExtension<usize, usize, u64, 2>, VerticalExtension<usize, usize, u64, 2>, 2>;
+    let model = "p0: 0, 1, 2, 3;p1: 0, 1, 2;p2: 0, 1, 2;p3: 0, 1, 2;p4: 0, 1, 2;p5: 0, 1;p6: 0, 1;p7: 0, 1;p8: 0, 1;p9: 0, 1;\
+        p10: 0, 1;p11: 0, 1;p12: 0, 1;p13: 0, 1;p14: 0, 1;p15: 0, 1;p16: 0, 1;p17: 0, 1;";
+    let mut sut = parse_unconstrained(model).unwrap();
+
+    let mut checkpointer = MemoryCheckpointer::default();
+    let mca = IPOGD::run(&mut sut, &mut (), &CancellationToken::new(), &mut checkpointer, None, &RunOptions::default());
+    assert_eq!(mca.skipped, None);
+    assert!(mca.check_locations());
+    assert!(min_coverage(sut.parameters.as_slice(), &mca.array, 2, |_, _| true) > 0);
+    assert_eq!(checkpointer.saved.first().map(|(next_parameter, _)| *next_parameter), Some(10));
+
+    let mca = IPOGD::run(&mut sut, &mut (), &CancellationToken::new(), &mut (), Some(8), &RunOptions::default());
+    assert_eq!(mca.skipped, None);
+    assert!(min_coverage(sut.parameters.as_slice(), &mca.array, 2, |_, _| true) > 0);
+
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let mut mca = IPOGD::run(&mut sut, &mut (), &cancel, &mut (), None, &RunOptions::default());
+    assert_eq!(mca.skipped.as_ref().map(|skipped| skipped.parameters), Some(sut.parameters.len()));
+    complete_unconstrained::<usize, usize, u64, 2>(&sut.parameters, &mut mca, None);
+    assert!(min_coverage(sut.parameters.as_slice(), &mca.array, 2, |_, _| true) > 0);
+}
+
+#[test]
+fn test_ipog_d_strength_3() {
+    type IPOGD = ipog_d::UnconstrainedIPOGD<usize, usize, u64, HorizontalExtension<usize, usize, u64, 3>, VerticalExtension<usize, usize, u64, 3>, 3>;
+    let model = "p0: 0, 1, 2;p1: 0, 1, 2;p2: 0, 1, 2;p3: 0, 1;p4: 0, 1;p5: 0, 1;p6: 0, 1, 2;p7: 0, 1;p8: 0, 1;p9: 0, 1;p10: 0, 1;p11: 0, 1;p12: 0, 1;";
+    let mut sut = parse_unconstrained(model).unwrap();
+
+    let mca = IPOGD::run(&mut sut, &mut (), &CancellationToken::new(), &mut (), None, &RunOptions::default());
+    assert_eq!(mca.skipped, None);
+    assert!(mca.check_locations());
+    assert!(min_coverage(sut.parameters.as_slice(), &mca.array, 3, |_, _| true) > 0);
+}
+
+fn check_scoring<const STRENGTH: usize>(model: &str) where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    type IPOG<const STRENGTH: usize> = UnconstrainedIPOG<usize, usize, u64, HorizontalExtension<usize, usize, u64, STRENGTH>, VerticalExtension<usize, usize, u64, STRENGTH>, STRENGTH>;
+    let mut sut = parse_unconstrained(model).unwrap();
+
+    let run = |sut: &mut SUT<usize, usize>, scoring| IPOG::<STRENGTH>::run(sut, &mut (), &CancellationToken::new(), &mut (), None, &RunOptions { scoring, ..RunOptions::default() });
+    let mca = run(&mut sut, Scoring::Single);
+    assert!(mca.array.iter().any(|row| row.iter().any(|&value| value == !0)));
+    assert!(min_coverage(sut.parameters.as_slice(), &mca.array, STRENGTH, |_, _| true) > 0);
+    for scoring in [Scoring::Double, Scoring::Triple] {
+        assert_eq!(run(&mut sut, scoring).array, mca.array, "{:?}", scoring);
+    }
+}
+
+#[test]
+fn test_scoring() {
+    check_scoring::<2>("p0: 0, 1, 2, 3;p1: 0, 1, 2;p2: 0, 1, 2;p3: 0, 1, 2;p4: 0, 1;p5: 0, 1;p6: 0, 1;p7: 0, 1;p8: 0, 1, 2;p9: 0, 1;");
+    check_scoring::<3>("p0: 0, 1, 2;p1: 0, 1, 2;p2: 0, 1, 2;p3: 0, 1;p4: 0, 1;p5: 0, 1;p6: 0, 1, 2;p7: 0, 1;p8: 0, 1;p9: 0, 1;p10: 0, 1;");
+}
@@ -0,0 +1,306 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module contains the checkpoints which allow a generation to be resumed after it was interrupted.
+//!
+//! After a parameter has been added, the state of IPOG consists of only the [MCA] and the next parameter to add.
+//! The list of PCs and the coverage map are rebuilt for every parameter, so they are not stored.
+
+use std::fs::{File, rename};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+
+use common::{DONT_CARE_TEXT, Number, u_vec, UVec};
+
+use crate::MCA;
+
+/// The first line of a checkpoint file.
+const CHECKPOINT_LINE: &str = "# LibreCA checkpoint";
+
+/// Receives the state of the generation after every parameter, and provides the state to resume from.
+pub trait Checkpointer<ValueId: Number, LocationsType: Number> {
+    /// Take the MCA of a previous generation and the next parameter to add to it, [None] to start from scratch.
+    fn resume(&mut self) -> Option<(usize, MCA<ValueId, LocationsType>)>;
+
+    /// Store the MCA after all the parameters before `next_parameter` have been added to it.
+    fn save(&mut self, next_parameter: usize, mca: &MCA<ValueId, LocationsType>);
+}
+
+/// The unit type never resumes and ignores the saves.
+impl<ValueId: Number, LocationsType: Number> Checkpointer<ValueId, LocationsType> for () {
+    fn resume(&mut self) -> Option<(usize, MCA<ValueId, LocationsType>)> {
+        None
+    }
+
+    fn save(&mut self, _next_parameter: usize, _mca: &MCA<ValueId, LocationsType>) {}
+}
+
+/// Identifies the generation a checkpoint belongs to, a generation can only be resumed from a checkpoint with the same header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CheckpointHeader {
+    /// The fingerprint of the SUT, see `sut::SUT::fingerprint`.
+    pub sut: u64,
+
+    /// The strength of the MCA.
+    pub strength: usize,
+
+    /// The name of the algorithm generating the MCA.
+    pub generator: String,
+
+    /// The options which change the generated MCA, written on a single line.
+    ///
+    /// Resuming with other options would not give the result of an uninterrupted generation.
+    pub options: String,
+}
+
+impl CheckpointHeader {
+    /// Returns an error describing the first difference with the header of the checkpoint.
+    fn check(&self, checkpoint: &CheckpointHeader) -> Result<(), String> {
+        if self.sut != checkpoint.sut {
+            Err("The checkpoint was created for a different SUT.".to_string())
+        } else if self.strength != checkpoint.strength {
+            Err(format!("The checkpoint was created for strength {} instead of {}.", checkpoint.strength, self.strength))
+        } else if self.generator != checkpoint.generator {
+            Err(format!("The checkpoint was created by {} instead of {}.", checkpoint.generator, self.generator))
+        } else if self.options != checkpoint.options {
+            Err(format!("The checkpoint was created with the options {} instead of {}.", checkpoint.options, self.options))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Saves the state of the generation to a file after every parameter, and optionally resumes from that file.
+///
+/// The file is replaced atomically, so an interrupted save leaves the previous checkpoint intact.
+pub struct CheckpointFile<ValueId: Number, LocationsType: Number> {
+    path: PathBuf,
+    header: CheckpointHeader,
+    resumed: Option<(usize, MCA<ValueId, LocationsType>)>,
+}
+
+impl<ValueId: Number, LocationsType: Number> CheckpointFile<ValueId, LocationsType> {
+    /// Create a new checkpoint file for the generation identified by the header.
+    ///
+    /// If `resume` is set, the checkpoint is read immediately and an error is returned if it can not be used.
+    /// The rows of the checkpoint are checked against the levels of the `parameters`.
+    pub fn new(path: PathBuf, header: CheckpointHeader, parameters: &[ValueId], resume: bool) -> Result<Self, String> {
+        let resumed = if resume {
+            let file = File::open(&path).map_err(|e| format!("Could not open the checkpoint {}: {}", path.display(), e))?;
+            let (checkpoint_header, next_parameter, mca) = read_checkpoint(BufReader::new(file), parameters)?;
+            header.check(&checkpoint_header)?;
+            Some((next_parameter, mca))
+        } else {
+            None
+        };
+        Ok(Self { path, header, resumed })
+    }
+
+    fn write(&self, next_parameter: usize, mca: &MCA<ValueId, LocationsType>) -> std::io::Result<()> {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let mut file = BufWriter::new(File::create(&temporary)?);
+        write_checkpoint(&mut file, &self.header, next_parameter, mca)?;
+        file.into_inner()?.sync_all()?;
+        rename(&temporary, &self.path)
+    }
+}
+
+impl<ValueId: Number, LocationsType: Number> Checkpointer<ValueId, LocationsType> for CheckpointFile<ValueId, LocationsType> {
+    fn resume(&mut self) -> Option<(usize, MCA<ValueId, LocationsType>)> {
+        let resumed = self.resumed.take();
+        if let Some((next_parameter, mca)) = &resumed {
+            tracing::info!("Resuming at parameter {} with {} rows", next_parameter, mca.array.len());
+        }
+        resumed
+    }
+
+    fn save(&mut self, next_parameter: usize, mca: &MCA<ValueId, LocationsType>) {
+        match self.write(next_parameter, mca) {
+            Ok(()) => tracing::debug!(next_parameter, rows = mca.array.len(), "Saved the checkpoint"),
+            Err(e) => tracing::warn!("Could not save the checkpoint {}: {}", self.path.display(), e),
+        }
+    }
+}
+
+/// Write the header, the next parameter and the rows of the MCA with their don't-care locations.
+fn write_checkpoint<ValueId: Number, LocationsType: Number, W: Write>(
+    writer: &mut W,
+    header: &CheckpointHeader,
+    next_parameter: usize,
+    mca: &MCA<ValueId, LocationsType>,
+) -> std::io::Result<()> {
+    writeln!(writer, "{}", CHECKPOINT_LINE)?;
+    writeln!(writer, "sut: {:016x}", header.sut)?;
+    writeln!(writer, "strength: {}", header.strength)?;
+    writeln!(writer, "generator: {}", header.generator)?;
+    writeln!(writer, "options: {}", header.options)?;
+    writeln!(writer, "next_parameter: {}", next_parameter)?;
+    writeln!(writer, "rows: {}", mca.array.len())?;
+    for (row, locations) in mca.array.iter().zip(mca.dont_care_locations.iter()) {
+        for (index, &value) in row.iter().enumerate() {
+            if index != 0 {
+                writer.write_all(b",")?;
+            }
+            if value == ValueId::dont_care() {
+                writer.write_all(DONT_CARE_TEXT.as_bytes())?;
+            } else {
+                write!(writer, "{}", value)?;
+            }
+        }
+        writeln!(writer, ";{:b}", locations)?;
+    }
+    Ok(())
+}
+
+/// Read the value of the next header line, which should start with the given key.
+fn read_field<R: BufRead>(lines: &mut std::io::Lines<R>, key: &str) -> Result<String, String> {
+    let line = lines.next().ok_or_else(|| format!("The checkpoint ends before the {}.", key))?.map_err(|e| e.to_string())?;
+    line.strip_prefix(key)
+        .and_then(|rest| rest.strip_prefix(": "))
+        .map(str::to_string)
+        .ok_or_else(|| format!("Expected the {} in the checkpoint, found: {}", key, line))
+}
+
+/// Parse a number of the checkpoint, naming the field in the error.
+fn parse_field<N: std::str::FromStr>(text: &str, key: &str) -> Result<N, String> {
+    text.parse().map_err(|_| format!("The {} in the checkpoint is not a number: {}", key, text))
+}
+
+/// Parse the don't-care locations written in binary.
+fn parse_locations<LocationsType: Number>(text: &str) -> Result<LocationsType, String> {
+    if text.is_empty() || text.len() > 8 * std::mem::size_of::<LocationsType>() {
+        return Err(format!("Invalid don't-care locations in the checkpoint: {}", text));
+    }
+    let mut locations = LocationsType::default();
+    for c in text.chars() {
+        locations <<= LocationsType::from_usize(1);
+        match c {
+            '0' => {}
+            '1' => locations |= LocationsType::from_usize(1),
+            _ => return Err(format!("Invalid don't-care locations in the checkpoint: {}", text)),
+        }
+    }
+    Ok(locations)
+}
+
+/// Read a checkpoint written by [write_checkpoint] for an SUT with the levels of the `parameters`.
+///
+/// The file is not trusted, so the next parameter and every value are checked before the generation uses them.
+fn read_checkpoint<ValueId: Number, LocationsType: Number, R: BufRead>(
+    reader: R,
+    parameters: &[ValueId],
+) -> Result<(CheckpointHeader, usize, MCA<ValueId, LocationsType>), String> {
+    let mut lines = reader.lines();
+    match lines.next() {
+        Some(Ok(line)) if line == CHECKPOINT_LINE => {}
+        _ => return Err("The file is not a LibreCA checkpoint.".to_string()),
+    }
+    let sut = u64::from_str_radix(&read_field(&mut lines, "sut")?, 16).map_err(|_| "The sut in the checkpoint is not a fingerprint.".to_string())?;
+    let strength: usize = parse_field(&read_field(&mut lines, "strength")?, "strength")?;
+    let generator = read_field(&mut lines, "generator")?;
+    let options = read_field(&mut lines, "options")?;
+    let next_parameter: usize = parse_field(&read_field(&mut lines, "next_parameter")?, "next_parameter")?;
+    if next_parameter < strength || next_parameter > parameters.len() {
+        return Err(format!("The next parameter {} in the checkpoint is not between the strength {} and the {} parameters.", next_parameter, strength, parameters.len()));
+    }
+    let rows: usize = parse_field(&read_field(&mut lines, "rows")?, "rows")?;
+
+    // The rows are not reserved up front, as their number is only checked after reading them
+    let mut mca = MCA::new_empty();
+    for line in lines {
+        let line = line.map_err(|e| e.to_string())?;
+        if mca.array.len() == rows {
+            return Err(format!("The checkpoint contains more than {} rows.", rows));
+        }
+        let (values, locations) = line.split_once(';').ok_or_else(|| format!("Invalid row in the checkpoint: {}", line))?;
+        let mut row = UVec::with_capacity(parameters.len());
+        for value in values.split(',') {
+            row.push(if value == DONT_CARE_TEXT {
+                ValueId::dont_care()
+            } else {
+                let value_id = parse_field::<usize>(value, "value")?;
+                match parameters.get(row.len()) {
+                    Some(level) if value_id < level.as_usize() => ValueId::from_usize(value_id),
+                    _ => return Err(format!("The value {} does not fit the parameter in the row of the checkpoint: {}", value, line)),
+                }
+            });
+        }
+        if row.len() != parameters.len() {
+            return Err(format!("The row in the checkpoint has the wrong length: {}", line));
+        }
+        mca.array.push(row);
+        mca.dont_care_locations.push(parse_locations(locations)?);
+    }
+    if mca.array.len() != rows {
+        return Err(format!("The checkpoint should contain {} rows, but contains {}.", rows, mca.array.len()));
+    }
+    mca.new_row = u_vec![ValueId::dont_care(); parameters.len()];
+
+    Ok((CheckpointHeader { sut, strength, generator, options }, next_parameter, mca))
+}
+
+#[cfg(test)]
+mod checkpoint_tests {
+    use common::u_vec;
+
+    use super::*;
+
+    fn header() -> CheckpointHeader {
+        CheckpointHeader { sut: 0x1234_abcd, strength: 2, generator: "ipog-single".to_string(), options: "scoring=Triple".to_string() }
+    }
+
+    #[test]
+    fn test_write_read() {
+        let mut mca = MCA::<u8, u128>::new_unconstrained::<u8, 2>(&u_vec![3, 2, 2]);
+        mca.array[1][2] = 1;
+        mca.dont_care_locations[1] = u128::mask_high(3);
+        let mut text = Vec::new();
+        write_checkpoint(&mut text, &header(), 3, &mca).unwrap();
+
+        let (read_header, next_parameter, read_mca) = read_checkpoint::<u8, u128, _>(text.as_slice(), &[3, 2, 2]).unwrap();
+        assert_eq!(read_header, header());
+        assert_eq!(next_parameter, 3);
+        assert_eq!(read_mca.array, mca.array);
+        assert_eq!(read_mca.dont_care_locations, mca.dont_care_locations);
+        assert_eq!(read_mca.new_row, mca.new_row);
+        assert_eq!(read_mca.skipped, None);
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let mca = MCA::<u8, u64>::new_unconstrained::<u8, 2>(&u_vec![2, 2, 2]);
+        let mut text = Vec::new();
+        write_checkpoint(&mut text, &header(), 2, &mca).unwrap();
+        let text = String::from_utf8(text).unwrap();
+
+        let read = |text: &str| read_checkpoint::<u8, u64, _>(text.as_bytes(), &[2, 2, 2]);
+        assert!(read(&text).is_ok());
+        assert!(read("result").is_err());
+        assert!(read(&text.replace("rows: 4", "rows: 5")).is_err());
+        assert!(read(&text.replace("0,1,*;", "0,1;")).is_err());
+        assert!(read(&text.replace("strength: 2", "strength: two")).is_err());
+        assert!(read(&text.lines().take(4).collect::<Vec<_>>().join("\n")).is_err());
+        assert!(read(&text.replace("rows: 4", "rows: 3")).is_err());
+        assert!(read(&text.replace("rows: 4", "rows: 18446744073709551615")).is_err());
+        assert!(read(&text.replace("next_parameter: 2", "next_parameter: 1")).is_err());
+        assert!(read(&text.replace("next_parameter: 2", "next_parameter: 4")).is_err());
+        assert!(read(&text.replace("0,1,*;", "0,2,*;")).is_err());
+        assert!(read(&text.replace("0,1,*;", "0,1,*,*;")).is_err());
+    }
+
+    #[test]
+    fn test_check_header() {
+        assert_eq!(header().check(&header()), Ok(()));
+        assert!(header().check(&CheckpointHeader { sut: 0, ..header() }).is_err());
+        assert_eq!(
+            header().check(&CheckpointHeader { strength: 3, ..header() }),
+            Err("The checkpoint was created for strength 3 instead of 2.".to_string()),
+        );
+        assert!(header().check(&CheckpointHeader { generator: "ipog-multi".to_string(), ..header() }).is_err());
+        assert!(header().check(&CheckpointHeader { options: "scoring=Single".to_string(), ..header() }).is_err());
+    }
+}
@@ -5,6 +5,9 @@
 // modified, or distributed except according to those terms.
 
 //! This module contains the [MCA] and various methods for manipulating it.
+//!
+//! # Features
+//!   * `serde` Implement `Serialize` and `Deserialize` for the [MCA] and [Skipped].
 
 #![cfg_attr(test, feature(test))]
 #![cfg_attr(test, feature(is_sorted))]
@@ -14,14 +17,19 @@
 #![feature(generic_const_exprs)]
 #![deny(missing_docs, rustdoc::missing_crate_level_docs, future_incompatible)]
 
-use common::{Number, u_vec, UVec, ValueGenerator};
+use std::fmt::{Display, Formatter};
+
+use common::{next_combination, Number, Phase, u_vec, UVec, ValueGenerator};
 use sut::Solver;
 
+pub mod checkpoint;
+
 #[cfg(test)]
 mod bench_types;
 
 /// This struct represents the Mixed-level Covering Array generated by the algorithm.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MCA<ValueId: Number, LocationsType: Number> {
     /// The actual array.
     pub array: UVec<UVec<ValueId>>,
@@ -34,6 +42,60 @@ pub struct MCA<ValueId: Number, LocationsType: Number> {
 
     /// Used when creating new rows. Clone is faster than allocating new vectors.
     pub new_row: UVec<ValueId>,
+
+    /// Describes the work which was skipped when the generation was cancelled, [None] if it ran to completion.
+    pub skipped: Option<Skipped>,
+}
+
+/// The part of the generation which was skipped because the generation was cancelled.
+///
+/// # Example
+/// ```
+/// use common::Phase;
+/// use mca::Skipped;
+///
+/// let skipped = Skipped { at_parameter: 4, parameters: 6, phase: Phase::Vertical, uncovered: 12, completion_rows: None };
+/// assert_eq!(
+///     skipped.to_string(),
+///     "cancelled during the vertical extension of parameter 4/6 with 12 uncovered interactions; parameters 4 to 5 are missing, the MCA is incomplete",
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Skipped {
+    /// The parameter which was being added when the generation was cancelled.
+    pub at_parameter: usize,
+
+    /// The total number of parameters of the SUT.
+    pub parameters: usize,
+
+    /// The phase of [Skipped::at_parameter] which was skipped.
+    pub phase: Phase,
+
+    /// The number of interactions of [Skipped::at_parameter] which were uncovered when the generation was cancelled.
+    /// If the coverage map was processed in chunks, only the interactions of the current chunk are counted.
+    pub uncovered: usize,
+
+    /// The number of rows appended to cover the remaining interactions, [None] if the MCA was not completed.
+    pub completion_rows: Option<usize>,
+}
+
+impl Display for Skipped {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let phase = match self.phase {
+            Phase::Horizontal => "horizontal extension",
+            Phase::Vertical => "vertical extension",
+            Phase::Done => "generation",
+        };
+        write!(
+            f, "cancelled during the {} of parameter {}/{} with {} uncovered interactions",
+            phase, self.at_parameter, self.parameters, self.uncovered,
+        )?;
+        match self.completion_rows {
+            Some(rows) => write!(f, "; appended {} rows to cover the interactions of parameters {} to {}", rows, self.at_parameter, self.parameters - 1),
+            None => write!(f, "; parameters {} to {} are missing, the MCA is incomplete", self.at_parameter, self.parameters - 1),
+        }
+    }
 }
 
 impl<ValueId: Number, LocationsType: Number> MCA<ValueId, LocationsType> {
@@ -79,7 +141,7 @@ impl<ValueId: Number, LocationsType: Number> MCA<ValueId, LocationsType> {
         if STRENGTH < parameters.len() {
             capacity += parameters[0].as_usize() * parameters.len() * STRENGTH * STRENGTH * STRENGTH;
         }
-        println!("capacity: {}", capacity);
+        tracing::debug!(capacity, "Allocated the MCA");
         let mut array = UVec::with_capacity(capacity);
         array.push(u_vec![ValueId::default(); parameters.len()]);
 
@@ -111,6 +173,7 @@ impl<ValueId: Number, LocationsType: Number> MCA<ValueId, LocationsType> {
             dont_care_locations,
             vertical_extension_rows: UVec::with_capacity(capacity),
             new_row: u_vec![ValueId::dont_care(); parameters.len()],
+            skipped: None,
         }
     }
 
@@ -131,7 +194,7 @@ impl<ValueId: Number, LocationsType: Number> MCA<ValueId, LocationsType> {
         }
 
         capacity += parameters[0].as_usize() * parameters.len() * STRENGTH * STRENGTH * STRENGTH;
-        println!("capacity: {}", capacity);
+        tracing::debug!(capacity, "Allocated the MCA");
         let mut array = UVec::with_capacity(capacity);
         array.push(u_vec![ValueId::default(); parameters.len()]);
 
@@ -150,9 +213,15 @@ impl<ValueId: Number, LocationsType: Number> MCA<ValueId, LocationsType> {
             STRENGTH - 1,
             &pc,
         );
+        let mut rows = Vec::new();
         while generator.next_vector_inverse(&mut values) {
-            if solver.check_row(&values[..STRENGTH]) {
-                array.push(values.clone());
+            rows.push(values.clone());
+        }
+        let prefixes: Vec<&[ValueId]> = rows.iter().map(|row| &row[..STRENGTH]).collect();
+        let valid = solver.check_rows_batch(&prefixes);
+        for (row, valid) in rows.into_iter().zip(valid.iter()) {
+            if valid {
+                array.push(row);
             }
         }
 
@@ -164,6 +233,7 @@ impl<ValueId: Number, LocationsType: Number> MCA<ValueId, LocationsType> {
             dont_care_locations,
             vertical_extension_rows: UVec::with_capacity(capacity),
             new_row: u_vec![ValueId::dont_care(); parameters.len()],
+            skipped: None,
         }
     }
 
@@ -174,9 +244,15 @@ impl<ValueId: Number, LocationsType: Number> MCA<ValueId, LocationsType> {
             dont_care_locations: UVec::with_capacity(0),
             vertical_extension_rows: UVec::with_capacity(0),
             new_row: UVec::with_capacity(0),
+            skipped: None,
         }
     }
 
+    /// Returns true if the generation of this MCA was not cancelled, or if it was completed afterwards.
+    pub fn is_complete(&self) -> bool {
+        self.skipped.as_ref().is_none_or(|skipped| skipped.completion_rows.is_some())
+    }
+
     /// Set the [MCA::vertical_extension_rows] with all rows that the vertical extension should consider.
     pub fn set_vertical_extension_rows(&mut self, at_parameter: usize) -> LocationsType {
         // The mask to check for any dont_care in the cells before the at_parameter
@@ -327,3 +403,61 @@ pub fn check_locations<ValueId: Number, LocationsType: Number>(row: &[ValueId],
     }
     true
 }
+
+/// Returns the number of rows covering the least covered *t*-way interaction allowed by the constraints, don't-cares cover nothing.
+///
+/// The `allowed` function receives the parameters and values of each interaction, returns [usize::MAX] if no interaction is allowed.
+/// Every row is compared to every interaction, so this method is only meant for checking the generators on small SUTs.
+pub fn min_coverage<ValueId: Number>(
+    parameters: &[ValueId],
+    array: &UVec<UVec<ValueId>>,
+    strength: usize,
+    mut allowed: impl FnMut(&[usize], &[ValueId]) -> bool,
+) -> usize {
+    let mut minimum = usize::MAX;
+    let mut pc: Vec<usize> = (0..strength).collect();
+    loop {
+        let mut values = vec![ValueId::default(); strength];
+        'values: loop {
+            if allowed(&pc, &values) {
+                minimum = minimum.min(array.iter().filter(|row| pc.iter().zip(values.iter()).all(|(&p, &v)| row[p] == v)).count());
+            }
+            for (value, &parameter) in values.iter_mut().zip(pc.iter()) {
+                *value += ValueId::from_usize(1);
+                if *value < parameters[parameter] {
+                    continue 'values;
+                }
+                *value = ValueId::default();
+            }
+            break;
+        }
+
+        if !next_combination(&mut pc, parameters.len()) {
+            return minimum;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use common::{Phase, u_vec, UVec};
+
+    use super::{MCA, Skipped};
+
+    #[test]
+    fn test_round_trip() {
+        let mut mca = MCA::<u8, u16>::new_empty();
+        mca.array.push(u_vec![0, 1, u8::MAX]);
+        mca.array.push(u_vec![1, 0, 2]);
+        mca.dont_care_locations.push(0b100);
+        mca.dont_care_locations.push(0);
+        mca.skipped = Some(Skipped { at_parameter: 2, parameters: 3, phase: Phase::Vertical, uncovered: 1, completion_rows: Some(1) });
+
+        let json = serde_json::to_string(&mca).unwrap();
+        let result: MCA<u8, u16> = serde_json::from_str(&json).unwrap();
+        assert_eq!(result.array, mca.array);
+        assert_eq!(result.dont_care_locations, mca.dont_care_locations);
+        assert_eq!(result.skipped, mca.skipped);
+        assert_eq!(serde_json::to_string(&result).unwrap(), json);
+    }
+}
@@ -110,6 +110,20 @@ PCList<ParameterId, LocationsType, STRENGTH> where [(); STRENGTH - 1]:, [(); STR
         Self { pcs: pc, locations, sizes }
     }
 
+    /// Returns the index of the PC in [PCList::pcs] without searching the list.
+    ///
+    /// The PCs are grouped by their last parameter, each group contains the combinations of the earlier parameters in lexicographic order.
+    /// The PC must be sorted in increasing order.
+    pub fn pc_id(pc: &[ParameterId; STRENGTH - 1]) -> usize {
+        let last = pc[STRENGTH - 2].as_usize();
+        let earlier = STRENGTH - 2;
+        let mut result = binomial(last, earlier + 1) + binomial(last, earlier) - 1;
+        for (index, parameter_id) in pc.iter().take(earlier).enumerate() {
+            result -= binomial(last - 1 - parameter_id.as_usize(), earlier - index);
+        }
+        result
+    }
+
     fn pc_to_locations(pc: &[ParameterId; STRENGTH - 1]) -> LocationsType {
         let mut location = LocationsType::default();
         for &parameter_id in pc.iter() {
@@ -134,3 +148,46 @@ pub fn calculate_length(mut strength: usize, at_parameter: usize) -> usize {
     }
     res
 }
+
+/// The binomial coefficient, zero if `k` exceeds `n`.
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k.min(n - k)).fold(1, |result, i| result * (n - i) / (i + 1))
+}
+
+/// Returns the number of PCs [PCList::new] creates for the given strength and number of parameters.
+///
+/// Unlike [calculate_length] the result saturates instead of overflowing, so it can be used to check huge inputs.
+/// There are no PCs if the strength is zero or exceeds the number of parameters.
+pub fn pc_count(strength: usize, parameter_count: usize) -> u128 {
+    if strength == 0 || strength > parameter_count {
+        return 0;
+    }
+    let (n, mut k) = ((parameter_count - 1) as u128, (strength - 1) as u128);
+    if k > n - k {
+        k = n - k;
+    }
+    let mut res: u128 = 1;
+    for i in 0..k {
+        res = match res.checked_mul(n - i) {
+            Some(product) => product / (i + 1),
+            None => return u128::MAX,
+        };
+    }
+    res
+}
+
+/// Returns the number of bytes the [PCList] for the given strength and number of parameters allocates.
+///
+/// The list is not created, so this can be checked before the generation starts.
+/// Nothing is allocated if the strength is zero or exceeds the number of parameters.
+pub fn pc_list_bytes<ParameterId: Number, LocationsType: Number>(strength: usize, parameter_count: usize) -> u128 {
+    if strength == 0 || strength > parameter_count {
+        return 0;
+    }
+    let pc_bytes = ((strength - 1) * std::mem::size_of::<ParameterId>() + std::mem::size_of::<LocationsType>()) as u128;
+    let sizes_bytes = ((parameter_count - strength) * std::mem::size_of::<usize>()) as u128;
+    pc_count(strength, parameter_count).saturating_mul(pc_bytes).saturating_add(sizes_bytes)
+}
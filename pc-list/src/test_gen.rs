@@ -161,3 +161,30 @@ fn bench_generation_struct(bencher: &mut Bencher) {
         assert_eq!(pc_list.pcs.len(), max_pc_count);
     })
 }
+
+#[test]
+fn test_pc_count() {
+    for (strength, parameter_count) in [(2, 5), (3, 5), (3, 12), (5, 12), (6, 6)] {
+        assert_eq!(pc_count(strength, parameter_count), calculate_length(strength, parameter_count - 1) as u128);
+    }
+    assert_eq!(pc_count(6, 1_000_000_000), u128::MAX);
+    assert_eq!(pc_list_bytes::<u8, u64>(3, 5), 6 * (2 + 8) + 2 * 8);
+    for (strength, parameter_count) in [(3, 2), (1, 0), (0, 4)] {
+        assert_eq!(pc_count(strength, parameter_count), 0);
+        assert_eq!(pc_list_bytes::<u8, u64>(strength, parameter_count), 0);
+    }
+}
+
+#[test]
+fn test_pc_id() {
+    fn check<const STRENGTH: usize>(parameter_count: usize) where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+        let pc_list = PCList::<usize, u64, STRENGTH>::new(parameter_count);
+        for (pc_id, pc) in pc_list.pcs.iter().enumerate() {
+            assert_eq!(PCList::<usize, u64, STRENGTH>::pc_id(pc), pc_id);
+        }
+    }
+    check::<2>(7);
+    check::<3>(9);
+    check::<4>(10);
+    check::<6>(12);
+}
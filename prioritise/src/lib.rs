@@ -0,0 +1,247 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This crate contains the methods for reordering the rows of an [MCA] such that the interactions are covered as early as possible.
+//!
+//! IPOG appends the rows in the order in which they are constructed.
+//! When only a prefix of the test suite can be executed, it is better to start with the rows covering the most interactions.
+//! [prioritise] greedily picks the row covering the most new *t*-way interactions and optionally uses the (*t*-1)-way interactions to break ties.
+//! Both [prioritise] and [coverage_report] return a [CoverageReport] with the cumulative coverage of each row.
+//!
+//! The coverage is computed in the same way as the `check-mca` binary does:
+//! a dummy parameter with a single value is appended, such that a [CoverageMap] of strength *t*+1 initialised at the dummy parameter contains all the *t*-way interactions.
+//!
+//! The constraints are not considered, so the total number of interactions includes the interactions disallowed by the constraints.
+
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+#![feature(generic_const_exprs)]
+#![deny(missing_docs, rustdoc::missing_crate_level_docs, future_incompatible)]
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+use cm::CoverageMap;
+use common::{Number, UVec};
+use mca::MCA;
+use pc_list::PCList;
+
+#[cfg(test)]
+mod test;
+
+/// The coverage added by a single row of the [MCA].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CoverageStep {
+    /// The index of the row before prioritisation.
+    pub original_row: usize,
+
+    /// The number of *t*-way interactions this row covers which are not covered by the previous rows.
+    pub new: usize,
+
+    /// The number of *t*-way interactions covered by this row and the previous rows.
+    pub covered: usize,
+
+    /// Same as [CoverageStep::new] for the (*t*-1)-way interactions. Zero if these are not tracked.
+    pub lower_new: usize,
+
+    /// Same as [CoverageStep::covered] for the (*t*-1)-way interactions. Zero if these are not tracked.
+    pub lower_covered: usize,
+}
+
+/// The cumulative coverage of the rows of an [MCA].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CoverageReport {
+    /// The strength *t* of the interactions.
+    pub strength: usize,
+
+    /// The total number of *t*-way interactions in the SUT.
+    pub total: usize,
+
+    /// The total number of (*t*-1)-way interactions in the SUT if these are tracked.
+    pub lower_total: Option<usize>,
+
+    /// The coverage for each row in the (new) order of the [MCA].
+    pub steps: UVec<CoverageStep>,
+}
+
+/// Keeps track of the interactions covered so far for a single strength.
+struct Tracker<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    pc_list: Option<PCList<ParameterId, LocationsType, STRENGTH>>,
+    cm: CoverageMap<ValueId, STRENGTH>,
+    at_parameter: usize,
+    total: usize,
+
+    /// Only used if there are no other parameters than the ones in the interactions.
+    exhaustive: HashSet<UVec<ValueId>>,
+}
+
+impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
+Tracker<ValueId, ParameterId, LocationsType, STRENGTH> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    /// Track the (STRENGTH-1)-way interactions of the provided parameters, of which the last is the dummy parameter.
+    fn new(parameters: &UVec<ValueId>) -> Self {
+        let at_parameter = parameters.len() - 1;
+        if STRENGTH < parameters.len() {
+            let pc_list = PCList::<ParameterId, LocationsType, STRENGTH>::new(parameters.len());
+            let mut cm = CoverageMap::<ValueId, STRENGTH>::new(parameters.clone(), &pc_list);
+            cm.initialise(at_parameter);
+            Self { total: cm.uncovered, pc_list: Some(pc_list), cm, at_parameter, exhaustive: HashSet::new() }
+        } else {
+            let total = parameters.iter().map(|p| p.as_usize()).product();
+            Self { pc_list: None, cm: CoverageMap::default(), at_parameter, total, exhaustive: HashSet::new() }
+        }
+    }
+
+    /// Returns the number of uncovered interactions in the row.
+    fn count(&self, row: &UVec<ValueId>) -> usize {
+        if let Some(pc_list) = &self.pc_list {
+            let pc_list_len = pc_list.pcs.len();
+            unsafe { self.cm.count_uncovered_row_simple(self.at_parameter, pc_list, pc_list_len, row.as_slice()) }
+        } else if row.iter().all(|&v| v != ValueId::dont_care()) && !self.exhaustive.contains(row) {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Set the interactions in the row as covered and return the number of newly covered interactions.
+    fn cover(&mut self, row: &UVec<ValueId>) -> usize {
+        if let Some(pc_list) = &self.pc_list {
+            let uncovered = self.cm.uncovered;
+            let pc_list_len = pc_list.pcs.len();
+            unsafe { self.cm.set_covered_row_simple(self.at_parameter, pc_list, pc_list_len, row.as_slice()) };
+            uncovered - self.cm.uncovered
+        } else {
+            let new = self.count(row);
+            if new != 0 {
+                self.exhaustive.insert(row.clone());
+            }
+            new
+        }
+    }
+}
+
+/// Contains the trackers for both strengths and builds the [CoverageReport].
+struct Reporter<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:,
+          [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    tracker: Tracker<ValueId, ParameterId, LocationsType, { STRENGTH + 1 }>,
+    lower: Option<Tracker<ValueId, ParameterId, LocationsType, STRENGTH>>,
+    rows: UVec<UVec<ValueId>>,
+    report: CoverageReport,
+}
+
+impl<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>
+Reporter<ValueId, ParameterId, LocationsType, STRENGTH>
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:,
+          [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    fn new(parameters: &UVec<ValueId>, mca: &MCA<ValueId, LocationsType>, lower_strength: bool) -> Self {
+        let mut parameters = parameters.clone();
+        parameters.push(ValueId::from_usize(1));
+
+        let tracker = Tracker::new(&parameters);
+        let lower = if lower_strength { Some(Tracker::new(&parameters)) } else { None };
+
+        let rows = mca.array.iter().map(|row| {
+            let mut row = row.clone();
+            row.push(ValueId::default());
+            row
+        }).collect();
+
+        let report = CoverageReport {
+            strength: STRENGTH,
+            total: tracker.total,
+            lower_total: lower.as_ref().map(|l| l.total),
+            steps: UVec::with_capacity(mca.array.len()),
+        };
+
+        Self { tracker, lower, rows, report }
+    }
+
+    /// Returns the number of uncovered interactions for both strengths.
+    fn count(&self, row_id: usize) -> (usize, usize) {
+        let row = &self.rows[row_id];
+        (self.tracker.count(row), self.lower.as_ref().map_or(0, |l| l.count(row)))
+    }
+
+    /// Cover the row and add it to the report.
+    fn cover(&mut self, row_id: usize) {
+        let row = &self.rows[row_id];
+        let new = self.tracker.cover(row);
+        let lower_new = self.lower.as_mut().map_or(0, |l| l.cover(row));
+        let (covered, lower_covered) = self.report.steps.unwrap_ref().last()
+            .map_or((0, 0), |s| (s.covered, s.lower_covered));
+        self.report.steps.push(CoverageStep {
+            original_row: row_id,
+            new,
+            covered: covered + new,
+            lower_new,
+            lower_covered: lower_covered + lower_new,
+        });
+    }
+}
+
+/// Calculate the cumulative coverage of the rows of the [MCA] in their current order.
+///
+/// If `lower_strength` is set, the (*t*-1)-way interactions are counted as well.
+pub fn coverage_report<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>(
+    parameters: &UVec<ValueId>,
+    mca: &MCA<ValueId, LocationsType>,
+    lower_strength: bool,
+) -> CoverageReport
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:,
+          [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    let mut reporter = Reporter::<ValueId, ParameterId, LocationsType, STRENGTH>::new(parameters, mca, lower_strength);
+    for row_id in 0..mca.array.len() {
+        reporter.cover(row_id);
+    }
+    reporter.report
+}
+
+/// Reorder the rows of the [MCA] such that each next row covers the most new *t*-way interactions.
+///
+/// If `lower_strength` is set, ties are broken using the number of new (*t*-1)-way interactions.
+/// Remaining ties are broken by keeping the original order.
+/// The rows are selected greedily, the scores of the rows are only recalculated when a row reaches the top of the queue,
+/// which is valid as the score of a row can only decrease.
+///
+/// The [MCA::vertical_extension_rows] are cleared as they are no longer valid after the reordering.
+pub fn prioritise<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>(
+    parameters: &UVec<ValueId>,
+    mca: &mut MCA<ValueId, LocationsType>,
+    lower_strength: bool,
+) -> CoverageReport
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:,
+          [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    let mut reporter = Reporter::<ValueId, ParameterId, LocationsType, STRENGTH>::new(parameters, mca, lower_strength);
+
+    let mut queue: BinaryHeap<(usize, usize, Reverse<usize>)> = (0..mca.array.len()).map(|row_id| {
+        let (new, lower_new) = reporter.count(row_id);
+        (new, lower_new, Reverse(row_id))
+    }).collect();
+
+    while let Some((new, lower_new, Reverse(row_id))) = queue.pop() {
+        let scores = reporter.count(row_id);
+        if scores == (new, lower_new) {
+            reporter.cover(row_id);
+        } else {
+            queue.push((scores.0, scores.1, Reverse(row_id)));
+        }
+    }
+
+    let report = reporter.report;
+    let mut array = UVec::with_capacity(mca.array.len());
+    let mut dont_care_locations = UVec::with_capacity(mca.dont_care_locations.len());
+    for step in report.steps.iter() {
+        array.push(std::mem::take(&mut mca.array[step.original_row]));
+        dont_care_locations.push(mca.dont_care_locations[step.original_row]);
+    }
+    mca.array = array;
+    mca.dont_care_locations = dont_care_locations;
+    mca.vertical_extension_rows.clear();
+
+    report
+}
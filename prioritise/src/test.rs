@@ -0,0 +1,89 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use common::{u_vec, UVec};
+
+use super::*;
+
+fn exhaustive(parameters: &UVec<u8>) -> MCA<u8, u64> {
+    let mut array = u_vec![u_vec![0; parameters.len()]];
+    let mut row = u_vec![0; parameters.len()];
+    'outer: loop {
+        for (value, &level) in row.iter_mut().zip(parameters.iter()) {
+            *value += 1;
+            if *value < level {
+                array.push(row.clone());
+                continue 'outer;
+            }
+            *value = 0;
+        }
+        break;
+    }
+
+    MCA {
+        dont_care_locations: u_vec![0; array.len()],
+        array,
+        vertical_extension_rows: UVec::default(),
+        new_row: u_vec![!0; parameters.len()],
+        skipped: None,
+    }
+}
+
+#[test]
+fn test_coverage_report() {
+    let parameters = u_vec![2, 2, 2];
+    let mca = exhaustive(&parameters);
+
+    let report = coverage_report::<u8, u8, u64, 2>(&parameters, &mca, true);
+    assert_eq!(report.strength, 2);
+    assert_eq!(report.total, 12);
+    assert_eq!(report.lower_total, Some(6));
+    assert_eq!(report.steps.len(), 8);
+    assert_eq!(report.steps[0], CoverageStep { original_row: 0, new: 3, covered: 3, lower_new: 3, lower_covered: 3 });
+    assert_eq!(report.steps[7].covered, 12);
+    assert_eq!(report.steps[7].lower_covered, 6);
+    for (row_id, step) in report.steps.iter().enumerate() {
+        assert_eq!(step.original_row, row_id);
+    }
+}
+
+#[test]
+fn test_prioritise() {
+    let parameters = u_vec![2, 2, 2];
+    let mut mca = exhaustive(&parameters);
+
+    let report = prioritise::<u8, u8, u64, 2>(&parameters, &mut mca, false);
+    assert_eq!(report.lower_total, None);
+    assert_eq!(mca.array.len(), 8);
+    assert_eq!(mca.array[0], vec![0, 0, 0]);
+    assert_eq!(mca.array[1], vec![1, 1, 0]);
+    assert_eq!(mca.array[2], vec![1, 0, 1]);
+    assert_eq!(mca.array[3], vec![0, 1, 1]);
+    assert_eq!(report.steps[1].original_row, 3);
+    assert_eq!(report.steps[7].covered, report.total);
+    for steps in report.steps.unwrap_ref().windows(2) {
+        assert!(steps[0].new >= steps[1].new);
+        assert_eq!(steps[0].covered + steps[1].new, steps[1].covered);
+    }
+    assert_eq!(report.steps.iter().position(|s| s.new == 0), Some(4));
+
+    let again = coverage_report::<u8, u8, u64, 2>(&parameters, &mca, false);
+    for (step, original) in again.steps.iter().zip(report.steps.iter()) {
+        assert_eq!(step.covered, original.covered);
+    }
+}
+
+#[test]
+fn test_prioritise_all_parameters() {
+    let parameters = u_vec![3, 2];
+    let mut mca = exhaustive(&parameters);
+
+    let report = prioritise::<u8, u8, u64, 2>(&parameters, &mut mca, true);
+    assert_eq!(report.total, 6);
+    assert_eq!(report.lower_total, Some(5));
+    assert!(report.steps.iter().all(|s| s.new == 1));
+    assert_eq!(report.steps[5].lower_covered, 5);
+}
@@ -10,14 +10,16 @@
 #![feature(adt_const_params)]
 #![feature(generic_const_exprs)]
 
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
 
-use libreca::cm::{BIT_MASK, BIT_SHIFT};
+use libreca::cli::Options;
+use libreca::cm::BitArray;
 use libreca::common::{DONT_CARE_TEXT, Number, u_vec, UVec, ValueGenerator};
 use libreca::main;
 use libreca::sut::{ConstrainedSUT, FakeSolver, Solver, SolverImpl, SUT};
+use libreca::writer::{CLASS_SUFFIX, split_line};
 
 /// Converts an [std::io::Error] to a [String].
 fn ioe<V>(result: std::io::Result<V>) -> Result<V, String> {
@@ -28,25 +30,33 @@ fn ioe<V>(result: std::io::Result<V>) -> Result<V, String> {
 ///
 /// Call with the [FakeSolver] to check SUTs without constraints.
 fn check_mca<'a, S: Solver<'a>, ValueId: Number, ParameterId: Number, const STRENGTH: usize>(
-    mut sut: ConstrainedSUT<ValueId, ParameterId>, output_path: PathBuf, solver_init: &'a S::Init,
+    mut sut: ConstrainedSUT<ValueId, ParameterId>, options: Options, solver_init: &'a S::Init,
 ) -> Result<(), String> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
-    let mut lines = BufReader::new(ioe(File::open(output_path))?).lines().enumerate().skip_while(|(_, l)| match l {
+    let mut lines = BufReader::new(ioe(File::open(options.output_path))?).lines().enumerate().skip_while(|(_, l)| match l {
         Ok(l) => l.starts_with("#"),
         Err(_) => false,
     });
-    let parameter_line = ioe(lines.next().ok_or("No parameter names line?")?.1)?;
-    for (parameter_id, parameter_name) in parameter_line.split(",").enumerate() {
-        let old_parameter_id = sut.parameter_to_id[parameter_name];
+    let header = split_line(&ioe(lines.next().ok_or("No parameter names line?")?.1)?);
+    // Skip the columns containing the classes of the values of numeric range parameters.
+    let columns: Vec<bool> = header.iter()
+        .map(|c| sut.parameter_to_id.contains_key(c) || !c.ends_with(CLASS_SUFFIX))
+        .collect();
+    let parameter_names: Vec<&String> = header.iter().zip(columns.iter()).filter(|(_, &keep)| keep).map(|(c, _)| c).collect();
+    for (parameter_id, &parameter_name) in parameter_names.iter().enumerate() {
+        let old_parameter_id = *sut.parameter_to_id.get(parameter_name).ok_or_else(|| format!("Unknown parameter {}", parameter_name))?;
         if parameter_id != old_parameter_id {
             sut.parameter_to_id.insert(parameter_name.to_string(), parameter_id);
             sut.parameter_to_id.insert(sut.sub_sut.parameter_names[parameter_id].to_string(), old_parameter_id);
             sut.sub_sut.parameters.swap(parameter_id, old_parameter_id);
             sut.sub_sut.parameter_names.swap(parameter_id, old_parameter_id);
             sut.sub_sut.values.swap(parameter_id, old_parameter_id);
+            sut.sub_sut.value_classes.swap(parameter_id, old_parameter_id);
+            sut.sub_sut.value_labels.swap(parameter_id, old_parameter_id);
             sut.value_to_id.swap(parameter_id, old_parameter_id);
         }
     }
 
+    let parameter_line = parameter_names.iter().map(|p| p.as_str()).collect::<Vec<&str>>().join(",");
     let expected = sut.sub_sut.parameter_names.unwrap_ref().join(",");
     if &parameter_line != &expected {
         return Err(format!("Parameters incorrect:\n{}\n{}", parameter_line, expected));
@@ -57,6 +67,13 @@ fn check_mca<'a, S: Solver<'a>, ValueId: Number, ParameterId: Number, const STRE
 
     let mut solver: S = sut.get_solver::<S>(solver_init);
 
+    // The output contains the labels of the values instead of their names if they have one.
+    let display_to_id: Vec<HashMap<&str, usize>> = (0..at_parameter).map(|parameter_id| {
+        (0..sut.sub_sut.values[parameter_id].len())
+            .map(|value_id| (sut.sub_sut.display_value(parameter_id, value_id).unwrap(), value_id))
+            .collect()
+    }).collect();
+
     let pc_list = libreca::pc_list::PCList::<ParameterId, u128, STRENGTH>::new(sut.sub_sut.parameters.len());
     let mut cm = libreca::cm::CoverageMap::<ValueId, STRENGTH>::new(sut.sub_sut.parameters.clone(), &pc_list);
     cm.initialise(at_parameter);
@@ -65,9 +82,12 @@ fn check_mca<'a, S: Solver<'a>, ValueId: Number, ParameterId: Number, const STRE
 
     for (line_number, line) in lines {
         let line = ioe(line)?;
-        for (parameter_id, value) in line.split(",").enumerate() {
+        let fields = split_line(&line);
+        let values = fields.iter().zip(columns.iter()).filter(|(_, &keep)| keep).map(|(value, _)| value);
+        for (parameter_id, value) in values.enumerate() {
             row[parameter_id] = if value != DONT_CARE_TEXT {
-                ValueId::from_usize(sut.value_to_id[parameter_id][value])
+                ValueId::from_usize(*display_to_id[parameter_id].get(value.as_str())
+                    .ok_or_else(|| format!("Unknown value {} on line {}: {}", value, line_number, line))?)
             } else { ValueId::dont_care() };
         }
         if !solver.check_row(row.as_slice()) { return Err(format!("Invalid row on line {}: {}", line_number, line)); }
@@ -77,8 +97,8 @@ fn check_mca<'a, S: Solver<'a>, ValueId: Number, ParameterId: Number, const STRE
     if cm.is_covered() { return Ok(()); }
 
     let base_index = cm.sizes[pc_list.pcs.len()][0];
-    let block = &mut cm.map[(base_index >> BIT_SHIFT) as usize];
-    let mut bit = 1 << (base_index & BIT_MASK);
+    let block = &mut cm.map[base_index as usize >> u64::SHIFT];
+    let mut bit = 1 << (base_index as usize & u64::MASK);
     while bit != 0 {
         *block |= bit;
         bit <<= 1;
@@ -87,7 +107,7 @@ fn check_mca<'a, S: Solver<'a>, ValueId: Number, ParameterId: Number, const STRE
     let mut at_pc = 0;
     for (block_count, mut block) in cm.map.into_iter().enumerate() {
         if block != !0 {
-            let mut base_index = block_count << BIT_SHIFT;
+            let mut base_index = block_count << u64::SHIFT;
             for _ in 0..block.count_zeros() {
                 while block & 1 == 1 {
                     base_index += 1;
@@ -103,8 +123,7 @@ fn check_mca<'a, S: Solver<'a>, ValueId: Number, ParameterId: Number, const STRE
 
                 solver.push_and_assert_interaction(&pc_list.pcs[at_pc], at_parameter, &values);
                 if solver.check() {
-                    println!("{}", parameter_line);
-                    println!("[{:?}, {}] {:?}", pc_list.pcs[at_pc], at_parameter, values);
+                    tracing::error!(parameters = %parameter_line, "Interaction [{:?}, {}] {:?} is not covered", pc_list.pcs[at_pc], at_parameter, values);
                     return Err(format!("Interaction not covered!"));
                 }
                 solver.pop_all(1);
@@ -119,19 +138,19 @@ fn check_mca<'a, S: Solver<'a>, ValueId: Number, ParameterId: Number, const STRE
 
 /// This is the method checking MCAs for SUTs without constraints.
 fn unconstrained<ValueId: Number, ParameterId: Number, const STRENGTH: usize>(
-    sut: SUT<ValueId, ParameterId>, output_path: PathBuf,
+    sut: SUT<ValueId, ParameterId>, options: Options,
 ) -> Result<(), String> where [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
     let constrained_sut = ConstrainedSUT::wrap_sut(sut);
     let solver_init = FakeSolver::default_init();
-    check_mca::<FakeSolver, ValueId, ParameterId, { STRENGTH + 1 }>(constrained_sut, output_path, &solver_init)
+    check_mca::<FakeSolver, ValueId, ParameterId, { STRENGTH + 1 }>(constrained_sut, options, &solver_init)
 }
 
 /// This is the method checking MCAs for SUTs using constraints.
 fn constrained<ValueId: Number, ParameterId: Number, const STRENGTH: usize>(
-    constrained_sut: ConstrainedSUT<ValueId, ParameterId>, output_path: PathBuf,
+    constrained_sut: ConstrainedSUT<ValueId, ParameterId>, options: Options,
 ) -> Result<(), String> where [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
     let solver_init = SolverImpl::default_init();
-    check_mca::<SolverImpl, ValueId, ParameterId, { STRENGTH + 1 }>(constrained_sut, output_path, &solver_init)
+    check_mca::<SolverImpl, ValueId, ParameterId, { STRENGTH + 1 }>(constrained_sut, options, &solver_init)
 }
 
 main!(
@@ -0,0 +1,93 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This crate contains a binary calling the AETG-style generator provided in [aetg].
+
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+#![feature(generic_const_exprs)]
+
+use libreca::aetg::AETG;
+use libreca::anneal;
+use libreca::cli::Options;
+use libreca::common::{Number, time_it};
+use libreca::ipog_single::complete;
+use libreca::{main, write_mca};
+use libreca::sut::{CachingSolver, ConstrainedSUT, CountingSolver, Solver, SolverImpl, SUT};
+
+/// Returns an error for the options which only apply to IPOG.
+fn check_options(options: &Options) -> Result<(), String> {
+    if options.ipog_f || options.ipog_d {
+        return Err("IPOG-F and IPOG-D are not available in the AETG generation.".into());
+    }
+    if options.construct {
+        return Err("The constructions are only available in the IPOG generation.".into());
+    }
+    if options.checkpoint.is_some() {
+        return Err("The AETG generation does not support checkpoints.".into());
+    }
+    if options.coverage_map_budget.is_some() {
+        return Err("The coverage map exceeds the memory budget, only the single-threaded IPOG can process it in chunks.".into());
+    }
+    Ok(())
+}
+
+/// Run the AETG-style generation for a SUT without constraints.
+fn unconstrained<ValueId: Number, ParameterId: Number, const STRENGTH: usize>(
+    sut: SUT<ValueId, ParameterId>, options: Options,
+) -> Result<(), String> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:, [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    check_options(&options)?;
+    let mut observer = options.progress_observer();
+    let cancel = options.cancellation_token();
+    let mut mca = time_it!(
+        AETG::<ValueId, ParameterId, u128, STRENGTH>::run(&sut, observer.as_mut(), &cancel),
+        "Generation"
+    );
+    if options.complete && mca.skipped.is_some() {
+        time_it!(complete::complete_unconstrained::<ValueId, ParameterId, u128, STRENGTH>(&sut.parameters, &mut mca, None), "Completion");
+    }
+    if let Some(time_limit) = options.anneal {
+        time_it!(anneal::anneal::<ValueId, ParameterId, u128, STRENGTH>(&sut.parameters, &mut mca, time_limit), "Annealing");
+    }
+    write_mca::<ValueId, ParameterId, u128, STRENGTH>(&sut, mca, &options)
+}
+
+/// Run the AETG-style generation for a SUT with constraints.
+///
+/// The calls of the solver are counted for the report.
+fn constrained<ValueId: Number, ParameterId: Number, const STRENGTH: usize>(
+    mut sut: ConstrainedSUT<ValueId, ParameterId>, options: Options,
+) -> Result<(), String> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:, [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    check_options(&options)?;
+    let solver_init = options.caching_init(CountingSolver::<SolverImpl>::default_init());
+    let mut observer = options.progress_observer();
+    let cancel = options.cancellation_token();
+    let mut mca = time_it!(
+        AETG::<ValueId, ParameterId, u128, STRENGTH>::run_constrained::<CachingSolver<CountingSolver<SolverImpl>>>(&mut sut, &solver_init, observer.as_mut(), &cancel),
+        "Generation"
+    );
+    if options.complete && mca.skipped.is_some() {
+        let mut solver = CachingSolver::<CountingSolver<SolverImpl>>::new(&sut, &solver_init);
+        time_it!(
+            complete::complete_constrained::<ValueId, ParameterId, u128, CachingSolver<CountingSolver<SolverImpl>>, STRENGTH>(&mut solver, &sut.sub_sut.parameters, &mut mca),
+            "Completion"
+        );
+    }
+    if let Some(time_limit) = options.anneal {
+        let mut solver = CachingSolver::<CountingSolver<SolverImpl>>::new(&sut, &solver_init);
+        time_it!(
+            anneal::anneal_constrained::<ValueId, ParameterId, u128, CachingSolver<CountingSolver<SolverImpl>>, STRENGTH>(&mut solver, &sut.sub_sut.parameters, &mut mca, time_limit),
+            "Annealing"
+        );
+    }
+    options.report_solver(&solver_init);
+    write_mca::<ValueId, ParameterId, u128, STRENGTH>(&sut.sub_sut, mca, &options)
+}
+
+main!(
+    /// Run the AETG-style generation for the given command line arguments.
+    unconstrained, constrained
+);
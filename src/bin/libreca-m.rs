@@ -10,53 +10,105 @@
 #![feature(adt_const_params)]
 #![feature(generic_const_exprs)]
 
-use std::path::PathBuf;
 use std::sync::Arc;
 
+use libreca::anneal;
+use libreca::cli::Options;
 use libreca::common::{Number, time_it};
-use libreca::main;
-use libreca::writer::write_result;
+use libreca::constructions::{self, Seeded};
+use libreca::ipog_single::complete;
+use libreca::{main, write_mca};
 use libreca::sut::{ConstrainedSUT, Solver, SolverImpl, SUT};
 
 /// Run the multithreaded IPOG for a SUT without constraints.
 fn unconstrained<ValueId: Number, ParameterId: Number, const STRENGTH: usize>(
-    mut sut: SUT<ValueId, ParameterId>, output_file: PathBuf,
-) -> Result<(), String> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
-    let mca = time_it!(
-        ipog_multi::unconstrained::UnconstrainedMCIPOG::<
-            ValueId,
-            ParameterId,
-            u128,
-            STRENGTH,
-        >::run(&mut sut),
-        "Generation"
-    );
-    time_it!(
-        write_result(&sut, mca, output_file).map_err(|e| e.to_string()),
-        "Writing"
-    )
+    mut sut: SUT<ValueId, ParameterId>, options: Options,
+) -> Result<(), String> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:, [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    if options.coverage_map_budget.is_some() {
+        return Err("The coverage map exceeds the memory budget, only the single-threaded generation can process it in chunks.".into());
+    }
+    if options.ipog_f || options.ipog_d {
+        return Err("IPOG-F and IPOG-D are only available in the single-threaded generation.".into());
+    }
+    let mut checkpoint = options.checkpointer(sut.fingerprint(), sut.parameters.as_slice(), STRENGTH, "ipog-multi")?;
+    let mut observer = options.progress_observer();
+    let cancel = options.cancellation_token();
+    let constructed = if options.construct { constructions::construct(&sut.parameters, STRENGTH) } else { None };
+    let mut mca = match constructed {
+        Some(mca) => mca,
+        None => {
+            let seed = if options.construct { constructions::seed(&sut.parameters, STRENGTH) } else { None };
+            time_it!(
+                ipog_multi::unconstrained::UnconstrainedMCIPOG::<
+                    ValueId,
+                    ParameterId,
+                    u128,
+                    STRENGTH,
+                >::run(&mut sut, observer.as_mut(), &cancel, &mut Seeded::new(seed, checkpoint.as_mut()), &options.run_options),
+                "Generation"
+            )
+        }
+    };
+    if options.complete && mca.skipped.is_some() {
+        time_it!(complete::complete_unconstrained::<ValueId, ParameterId, u128, STRENGTH>(&sut.parameters, &mut mca, None), "Completion");
+    }
+    if let Some(time_limit) = options.anneal {
+        time_it!(anneal::anneal::<ValueId, ParameterId, u128, STRENGTH>(&sut.parameters, &mut mca, time_limit), "Annealing");
+    }
+    write_mca::<ValueId, ParameterId, u128, STRENGTH>(&sut, mca, &options)
 }
 
 /// Run the multithreaded IPOG for a SUT with constraints.
 fn constrained<ValueId: Number, ParameterId: Number, const STRENGTH: usize>(
-    mut sut: ConstrainedSUT<ValueId, ParameterId>, output_file: PathBuf,
-) -> Result<(), String> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    mut sut: ConstrainedSUT<ValueId, ParameterId>, mut options: Options,
+) -> Result<(), String> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:, [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    if options.ipog_f || options.ipog_d {
+        return Err("IPOG-F and IPOG-D are only available in the single-threaded generation.".into());
+    }
+    if options.construct {
+        return Err("The constructions are only available for models without constraints.".into());
+    }
+    if options.filter_map_flag {
+        return Err("Filtering the coverage map is only available in the single-threaded generation.".into());
+    }
+    // The default of the `filter-map` feature only applies to the single-threaded generation
+    options.run_options.filter_map = false;
+    if options.solver_cache {
+        return Err("The solver cache is only available in the single-threaded generation.".into());
+    }
+    if options.report_path.is_some() {
+        return Err("The report of a model with constraints, which includes the solver calls, is only available in the single-threaded generation.".into());
+    }
     let solver_init = SolverImpl::default_init();
-    let solver = sut.get_solver::<SolverImpl>(&solver_init);
+    let mut checkpoint = options.checkpointer(sut.fingerprint(), sut.sub_sut.parameters.as_slice(), STRENGTH, "ipog-multi")?;
+    let solver = time_it!(sut.get_solver::<SolverImpl>(&solver_init), "Solver setup", DEBUG);
     let sut = Arc::new(sut);
-    let mca = time_it!(
+    let mut observer = options.progress_observer();
+    let cancel = options.cancellation_token();
+    let mut mca = time_it!(
         ipog_multi::constrained::ConstrainedMCIPOG::<
             ValueId,
             ParameterId,
             u128,
             STRENGTH,
-        >::run(sut.clone(), solver),
+        >::run(sut.clone(), solver, observer.as_mut(), &cancel, checkpoint.as_mut(), &options.run_options),
         "Generation"
     );
-    time_it!(
-        write_result(&sut.sub_sut, mca, output_file).map_err(|e| e.to_string()),
-        "Writing"
-    )
+    if options.complete && mca.skipped.is_some() {
+        let mut solver = SolverImpl::new(&sut, &solver_init);
+        time_it!(
+            complete::complete_constrained::<ValueId, ParameterId, u128, SolverImpl, STRENGTH>(&mut solver, &sut.sub_sut.parameters, &mut mca),
+            "Completion"
+        );
+    }
+    if let Some(time_limit) = options.anneal {
+        let mut solver = SolverImpl::new(&sut, &solver_init);
+        time_it!(
+            anneal::anneal_constrained::<ValueId, ParameterId, u128, SolverImpl, STRENGTH>(&mut solver, &sut.sub_sut.parameters, &mut mca, time_limit),
+            "Annealing"
+        );
+    }
+    write_mca::<ValueId, ParameterId, u128, STRENGTH>(&sut.sub_sut, mca, &options)
 }
 
 main!(
@@ -10,56 +10,155 @@
 #![feature(adt_const_params)]
 #![feature(generic_const_exprs)]
 
-use std::path::PathBuf;
+use libreca::anneal;
+use libreca::cli::Options;
+use libreca::common::{CancellationToken, Number, ProgressObserver, time_it};
+use libreca::constructions::{self, Seeded};
+use libreca::ipog_single::{complete, constrained, unconstrained};
+use libreca::mca::MCA;
+use libreca::mca::checkpoint::Checkpointer;
+use libreca::{main, write_mca};
+use libreca::sut::{CachingSolver, ConstrainedSUT, CountingSolver, Solver, SolverImpl, SUT};
 
-use libreca::common::{Number, time_it};
-use libreca::ipog_single::{constrained, unconstrained};
-use libreca::main;
-use libreca::sut::{ConstrainedSUT, Solver, SolverImpl, SUT};
-use libreca::writer::write_result;
+/// Returns the generator of the checkpoint, a checkpoint can only be resumed with the same variant of IPOG.
+fn generator(options: &Options) -> &'static str {
+    match (options.ipog_d, options.ipog_f) {
+        (true, true) => "ipog-d-f",
+        (true, false) => "ipog-d",
+        (false, true) => "ipog-f",
+        (false, false) => "ipog-single",
+    }
+}
 
-/// Run the single-threaded IPOG for a SUT without constraints.
-fn unconstrained<ValueId: Number, ParameterId: Number, const STRENGTH: usize>(
-    mut sut: SUT<ValueId, ParameterId>, output_path: PathBuf,
-) -> Result<(), String> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
-    let mca = time_it!(
+/// Run IPOG, or IPOG-D if requested, with the given horizontal extension for a SUT without constraints.
+///
+/// With [Options::construct] the MCA is constructed algebraically if possible, otherwise the generation starts from a seed if there is one.
+fn generate<
+    ValueId: Number,
+    ParameterId: Number,
+    Horizontal: unconstrained::Extension<ValueId, ParameterId, u128, STRENGTH>,
+    const STRENGTH: usize,
+>(
+    sut: &mut SUT<ValueId, ParameterId>,
+    options: &Options,
+    observer: &mut dyn ProgressObserver,
+    cancel: &CancellationToken,
+    checkpoint: &mut dyn Checkpointer<ValueId, u128>,
+) -> MCA<ValueId, u128> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
+    let seed = if options.construct {
+        if let Some(mca) = constructions::construct(&sut.parameters, STRENGTH) {
+            return mca;
+        }
+        constructions::seed(&sut.parameters, STRENGTH)
+    } else {
+        None
+    };
+    let checkpoint = &mut Seeded::new(seed, checkpoint);
+    if options.ipog_d {
+        unconstrained::ipog_d::UnconstrainedIPOGD::<
+            ValueId,
+            ParameterId,
+            u128,
+            Horizontal,
+            unconstrained::VerticalExtension<ValueId, ParameterId, u128, STRENGTH>,
+            STRENGTH,
+        >::run(sut, observer, cancel, checkpoint, options.coverage_map_budget, &options.run_options)
+    } else {
         unconstrained::UnconstrainedIPOG::<
             ValueId,
             ParameterId,
             u128,
-            unconstrained::HorizontalExtension<ValueId, ParameterId, u128, STRENGTH>,
+            Horizontal,
             unconstrained::VerticalExtension<ValueId, ParameterId, u128, STRENGTH>,
             STRENGTH,
-        >::run(&mut sut),
+        >::run(sut, observer, cancel, checkpoint, options.coverage_map_budget, &options.run_options)
+    }
+}
+
+/// Run the single-threaded IPOG for a SUT without constraints.
+fn unconstrained<ValueId: Number, ParameterId: Number, const STRENGTH: usize>(
+    mut sut: SUT<ValueId, ParameterId>, options: Options,
+) -> Result<(), String> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:, [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    let mut checkpoint = options.checkpointer(sut.fingerprint(), sut.parameters.as_slice(), STRENGTH, generator(&options))?;
+    let mut observer = options.progress_observer();
+    let cancel = options.cancellation_token();
+    let mut mca = time_it!(
+        if options.ipog_f {
+            generate::<ValueId, ParameterId, unconstrained::ipog_f::HorizontalExtension<ValueId, ParameterId, u128, STRENGTH>, STRENGTH>(
+                &mut sut, &options, observer.as_mut(), &cancel, checkpoint.as_mut(),
+            )
+        } else {
+            generate::<ValueId, ParameterId, unconstrained::HorizontalExtension<ValueId, ParameterId, u128, STRENGTH>, STRENGTH>(
+                &mut sut, &options, observer.as_mut(), &cancel, checkpoint.as_mut(),
+            )
+        },
         "Generation"
     );
-    time_it!(
-        write_result(&sut, mca, output_path).map_err(|e| e.to_string()),
-        "Writing"
-    )
+    if options.complete && mca.skipped.is_some() {
+        time_it!(complete::complete_unconstrained::<ValueId, ParameterId, u128, STRENGTH>(&sut.parameters, &mut mca, options.coverage_map_budget), "Completion");
+    }
+    if let Some(time_limit) = options.anneal {
+        time_it!(anneal::anneal::<ValueId, ParameterId, u128, STRENGTH>(&sut.parameters, &mut mca, time_limit), "Annealing");
+    }
+    write_mca::<ValueId, ParameterId, u128, STRENGTH>(&sut, mca, &options)
 }
 
 /// Run the single-threaded IPOG for a SUT with constraints.
+///
+/// The calls of the solver are counted for the report.
 fn constrained<ValueId: Number, ParameterId: Number, const STRENGTH: usize>(
-    mut sut: ConstrainedSUT<ValueId, ParameterId>, output_path: PathBuf,
-) -> Result<(), String> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]: {
-    let solver_init = SolverImpl::default_init();
-    let mca = time_it!(
-        constrained::ConstrainedIPOG::<
-            ValueId,
-            ParameterId,
-            u128,
-            SolverImpl,
-            constrained::HorizontalExtension<ValueId, ParameterId, u128, STRENGTH>,
-            constrained::VerticalExtension<ValueId, ParameterId, u128, STRENGTH>,
-            STRENGTH,
-        >::run(&mut sut, &solver_init),
+    mut sut: ConstrainedSUT<ValueId, ParameterId>, options: Options,
+) -> Result<(), String> where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:, [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    if options.ipog_d {
+        return Err("IPOG-D is only available for models without constraints.".into());
+    }
+    if options.construct {
+        return Err("The constructions are only available for models without constraints.".into());
+    }
+    let solver_init = options.caching_init(CountingSolver::<SolverImpl>::default_init());
+    let mut checkpoint = options.checkpointer(sut.fingerprint(), sut.sub_sut.parameters.as_slice(), STRENGTH, generator(&options))?;
+    let mut observer = options.progress_observer();
+    let cancel = options.cancellation_token();
+    let mut mca = time_it!(
+        if options.ipog_f {
+            constrained::ConstrainedIPOG::<
+                ValueId,
+                ParameterId,
+                u128,
+                CachingSolver<CountingSolver<SolverImpl>>,
+                constrained::ipog_f::HorizontalExtension<ValueId, ParameterId, u128, STRENGTH>,
+                constrained::VerticalExtension<ValueId, ParameterId, u128, STRENGTH>,
+                STRENGTH,
+            >::run(&mut sut, &solver_init, observer.as_mut(), &cancel, checkpoint.as_mut(), &options.run_options)
+        } else {
+            constrained::ConstrainedIPOG::<
+                ValueId,
+                ParameterId,
+                u128,
+                CachingSolver<CountingSolver<SolverImpl>>,
+                constrained::HorizontalExtension<ValueId, ParameterId, u128, STRENGTH>,
+                constrained::VerticalExtension<ValueId, ParameterId, u128, STRENGTH>,
+                STRENGTH,
+            >::run(&mut sut, &solver_init, observer.as_mut(), &cancel, checkpoint.as_mut(), &options.run_options)
+        },
         "Generation"
     );
-    time_it!(
-        write_result(&sut.sub_sut, mca, output_path).map_err(|e| e.to_string()),
-        "Writing"
-    )
+    if options.complete && mca.skipped.is_some() {
+        let mut solver = CachingSolver::<CountingSolver<SolverImpl>>::new(&sut, &solver_init);
+        time_it!(
+            complete::complete_constrained::<ValueId, ParameterId, u128, CachingSolver<CountingSolver<SolverImpl>>, STRENGTH>(&mut solver, &sut.sub_sut.parameters, &mut mca),
+            "Completion"
+        );
+    }
+    if let Some(time_limit) = options.anneal {
+        let mut solver = CachingSolver::<CountingSolver<SolverImpl>>::new(&sut, &solver_init);
+        time_it!(
+            anneal::anneal_constrained::<ValueId, ParameterId, u128, CachingSolver<CountingSolver<SolverImpl>>, STRENGTH>(&mut solver, &sut.sub_sut.parameters, &mut mca, time_limit),
+            "Annealing"
+        );
+    }
+    options.report_solver(&solver_init);
+    write_mca::<ValueId, ParameterId, u128, STRENGTH>(&sut.sub_sut, mca, &options)
 }
 
 main!(
@@ -9,6 +9,10 @@
 //!   * [ipog_multi] A multithreaded implementation of IPOG.
 //!
 //! The other crates included provide the data-types used in these two implementations.
+//! The [aetg] crate provides an AETG-style generator, which builds one complete row at a time, to compare against IPOG.
+//! The [anneal] crate can remove rows from a generated MCA with simulated annealing.
+//! The [constructions] crate contains algebraic constructions, which replace or seed IPOG when the levels allow one.
+//! The [prioritise] crate can reorder the resulting MCA such that the interactions are covered as early as possible.
 //!
 //! # Features
 //! This crate provides the following optional features:
@@ -18,29 +22,78 @@
 //!   * `constraints-z3` Add support for the Z3 solver.
 //!   * `constraints` Implies `constraints-minisat`.
 //!   * `no-sort` Do not sort the parameters based on descending level. IPOG runs better on a sorted SUT.
-//!   * `sub-time` Print the timings for all the [common::sub_time_it] calls.
+//!     The output uses the declaration order of the parameters either way, unless `--internal-order` is given.
+//!   * `serde` Implement `Serialize` and `Deserialize` for the [sut::SUT], [sut::ConstrainedSUT] and [mca::MCA].
+//!   * `sub-time` Record the [common::sub_time_it] calls as debug spans, shown with `-v`.
 //!   * `no-cycle-split` Do not cycle the division of work between the worker threads in the multithreaded implementation of IPOG.
 //!   * `filter-map` Mark interactions disallowed by the constraints as covered in the [cm::CoverageMap] before beginning the extensions.
 //!   * `score-single` Always use the naive scoring algorithm.
 //!   * `score-double` Switch between the bitwise scoring algorithm and unchecked algorithm when there are no don't-cares.
+//!   * `score-avx2` Use AVX2 gathers to extract the bits of the scores if the CPU supports it, see [cm::score].
+//!
+//! The `no-cycle-split`, `filter-map`, `score-single` and `score-double` features only select the defaults of the [common::RunOptions].
+//! The binaries override them with `--cycle-split`, `--no-cycle-split`, `--filter-map`, `--no-filter-map` and `--scoring <algorithm>`.
 //!
 //! If neither `score-single` or `score-double` are set then the algorithm uses one of the three algorithms:
 //!   * If no don't-cares are present: unchecked algorithm [cm::CoverageMap::get_high_score_masked_unchecked].
 //!   * If only a few don't-cares are present: naive algorithm [cm::CoverageMap::get_high_score].
 //!   * If more don't-cares are present: unchecked algorithm [cm::CoverageMap::get_high_score_masked].
 
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+#![feature(generic_const_exprs)]
 #![deny(missing_docs, rustdoc::missing_crate_level_docs, future_incompatible)]
 
+use common::{Number, time_it};
+
+pub use aetg;
+pub use anneal;
 pub use cli;
 pub use cm;
 pub use common;
+pub use constructions;
 pub use ipog_multi;
 pub use ipog_single;
 pub use mca;
 pub use pc_list;
+pub use prioritise;
 pub use sut;
 pub use writer;
 
+/// Write the [mca::MCA] to the output path after applying the post-processing requested in the [cli::Options].
+///
+/// If requested, the rows are reordered using [prioritise::prioritise] and the cumulative coverage is written to the coverage report.
+/// The size of the MCA is added to the [cli::Options::report].
+/// The columns are written in the order in which the parameters were declared, unless [cli::Options::internal_order] is set.
+pub fn write_mca<ValueId: Number, ParameterId: Number, LocationsType: Number, const STRENGTH: usize>(
+    sut: &sut::SUT<ValueId, ParameterId>, mut mca: mca::MCA<ValueId, LocationsType>, options: &cli::Options,
+) -> Result<(), String>
+    where [(); STRENGTH - 1]:, [(); STRENGTH - 2]:,
+          [(); STRENGTH + 1]:, [(); { STRENGTH + 1 } - 1]:, [(); { STRENGTH + 1 } - 2]: {
+    let report = if options.prioritise {
+        Some(time_it!(
+            prioritise::prioritise::<ValueId, ParameterId, LocationsType, STRENGTH>(&sut.parameters, &mut mca, options.lower_strength),
+            "Prioritising"
+        ))
+    } else if options.coverage_report.is_some() {
+        Some(time_it!(
+            prioritise::coverage_report::<ValueId, ParameterId, LocationsType, STRENGTH>(&sut.parameters, &mca, options.lower_strength),
+            "Coverage"
+        ))
+    } else { None };
+
+    if let (Some(report), Some(path)) = (report, &options.coverage_report) {
+        writer::write_coverage_report(&report, path.clone()).map_err(|e| e.to_string())?;
+    }
+
+    options.update_report(|report| report.rows = mca.array.len());
+
+    time_it!(
+        writer::write_result(sut, mca, options.output_path.clone(), options.internal_order).map_err(|e| e.to_string()),
+        "Writing"
+    )
+}
+
 /// Create a main method which calls the specified methods with the correct arguments, depending on the provided [sut::SUT].
 ///
 /// # Examples
@@ -52,18 +105,17 @@ pub use writer;
 /// #![feature(adt_const_params)]
 /// #![feature(generic_const_exprs)]
 ///
-/// use std::path::PathBuf;
-/// use cli::SUTWrapper;
+/// use cli::{Options, SUTWrapper};
 /// use sut::{ConstrainedSUT, SUT};
 /// use common::Number;
 /// use libreca::main;
 ///
-/// fn unconstrained_method<ValueId: Number, ParameterId: Number,  const STRENGTH: usize>(sut: SUT<ValueId, ParameterId>, _output_path: PathBuf) -> Result<(), String> {
+/// fn unconstrained_method<ValueId: Number, ParameterId: Number,  const STRENGTH: usize>(sut: SUT<ValueId, ParameterId>, _options: Options) -> Result<(), String> {
 ///     println!("Calling unconstrained IPOG, t={}", STRENGTH);
 ///     Ok(())
 /// }
 ///
-/// fn constrained_method<ValueId: Number, ParameterId: Number,  const STRENGTH: usize>(sut: ConstrainedSUT<ValueId, ParameterId>, _output_path: PathBuf) -> Result<(), String> {
+/// fn constrained_method<ValueId: Number, ParameterId: Number,  const STRENGTH: usize>(sut: ConstrainedSUT<ValueId, ParameterId>, _options: Options) -> Result<(), String> {
 ///     println!("Calling constrained IPOG, t={}", STRENGTH);
 ///     Ok(())
 /// }
@@ -79,24 +131,24 @@ pub use writer;
 /// #![feature(generic_const_exprs)]
 ///
 /// use std::path::PathBuf;
-/// use cli::SUTWrapper;
+/// use cli::{Options, SUTWrapper};
 /// use sut::{SUT, ConstrainedSUT, parse_unconstrained};
 /// use common::Number;
 /// use libreca::main;
 ///
-/// fn unconstrained_method<ValueId: Number, ParameterId: Number,  const STRENGTH: usize>(sut: SUT<ValueId, ParameterId>, _output_path: PathBuf) {
+/// fn unconstrained_method<ValueId: Number, ParameterId: Number,  const STRENGTH: usize>(sut: SUT<ValueId, ParameterId>, _options: Options) {
 ///     println!("Calling unconstrained IPOG, t={}", STRENGTH);
 /// }
 ///
-/// fn constrained_method<ValueId: Number, ParameterId: Number,  const STRENGTH: usize>(sut: ConstrainedSUT<ValueId, ParameterId>, _output_path: PathBuf) {
+/// fn constrained_method<ValueId: Number, ParameterId: Number,  const STRENGTH: usize>(sut: ConstrainedSUT<ValueId, ParameterId>, _options: Options) {
 ///     println!("Calling constrained IPOG, t={}", STRENGTH);
 /// }
 ///
 /// let sut_wrapper = SUTWrapper::Unconstrained(parse_unconstrained("p1: v1, v2, v3;p2: v1, v2;p3: v1, v2;").unwrap());
-/// let output_path = PathBuf::from("result.txt");
+/// let options = Options::new(PathBuf::from("result.txt"));
 ///
 /// // Call the correct method for the given strength and SUTWrapper
-/// main!(call(sut_wrapper, output_path, 2, unconstrained_method, constrained_method));
+/// main!(call(sut_wrapper, options, 2, unconstrained_method, constrained_method));
 /// ```
 ///
 /// ## Provide your own SUT
@@ -106,64 +158,73 @@ pub use writer;
 /// #![feature(generic_const_exprs)]
 ///
 /// use std::path::PathBuf;
+/// use cli::Options;
 /// use sut::{SUT, ConstrainedSUT, parse_unconstrained};
 /// use common::Number;
 /// use libreca::main;
 ///
-/// fn unconstrained_method<ValueId: Number, ParameterId: Number,  const STRENGTH: usize>(sut: SUT<ValueId, ParameterId>, _output_path: PathBuf) {
+/// fn unconstrained_method<ValueId: Number, ParameterId: Number,  const STRENGTH: usize>(sut: SUT<ValueId, ParameterId>, _options: Options) {
 ///     println!("Calling unconstrained IPOG, t={}", STRENGTH);
 /// }
 ///
 /// let sut = parse_unconstrained("p1: v1, v2, v3;p2: v1, v2;p3: v1, v2;").unwrap();
-/// let output_path = PathBuf::from("result.txt");
+/// let options = Options::new(PathBuf::from("result.txt"));
 ///
 /// // Call the correct method for the given SUTWrapper and strength
-/// main!(call_constraints(sut, output_path, 2, unconstrained_method));
+/// main!(call_constraints(sut, options, 2, unconstrained_method));
 /// ```
 #[macro_export]
 macro_rules! main {
-    (call_constraints($sut:expr, $output_path:expr, $strength_variable:expr, $method:ident)) => {
-        common::repeat_strengths!(main, $sut, $output_path, $strength_variable, $method);
+    (call_constraints($sut:expr, $options:expr, $strength_variable:expr, $method:ident)) => {
+        common::repeat_strengths!(main, $sut, $options, $strength_variable, $method);
         panic!("Support for the given strength and/or SUT is not precompiled in this version.");
     };
 
-    (call_parameters<$v:tt, {$($ps:tt),+}>($strength:expr, $sut:expr, $output_path:expr, $method:ident)) => {
+    (call_parameters<$v:tt, {$($ps:tt),+}>($strength:expr, $sut:expr, $options:expr, $method:ident)) => {
         $(
-            if $sut.parameters_fit::<$ps>().is_ok() { return $method::<$v, $ps, $strength>($sut.mutate(), $output_path); }
+            if $sut.parameters_fit::<$ps>().is_ok() { return $method::<$v, $ps, $strength>($sut.mutate(), $options); }
         )+
     };
-    (call_strength<{$v:tt}, {$($ps:tt),+}>($strength:expr, $sut:expr, $output_path:expr, $method:ident)) => {
+    (call_strength<{$v:tt}, {$($ps:tt),+}>($strength:expr, $sut:expr, $options:expr, $method:ident)) => {
         if $sut.values_fit::<$v>().is_ok() {
-            main!(call_parameters<$v, {$($ps),+}>($strength, $sut, $output_path, $method));
+            main!(call_parameters<$v, {$($ps),+}>($strength, $sut, $options, $method));
         }
     };
-    (call_strength<{$v:tt, $($vs:tt),*}, {$($ps:tt),+}>($strength:expr, $sut:expr, $output_path:expr, $method:ident)) => {
-        main!(call_strength<{$v}, {$($ps),+}>($strength, $sut, $output_path, $method));
-        main!(call_strength<{$($vs:tt),*}, {$($ps),+}>($strength, $sut, $output_path, $method));
+    (call_strength<{$v:tt, $($vs:tt),*}, {$($ps:tt),+}>($strength:expr, $sut:expr, $options:expr, $method:ident)) => {
+        main!(call_strength<{$v}, {$($ps),+}>($strength, $sut, $options, $method));
+        main!(call_strength<{$($vs:tt),*}, {$($ps),+}>($strength, $sut, $options, $method));
     };
 
-    (call($sut_wrapper:expr, $output_path:expr, $strength_variable:expr, $unconstrained:ident, $constrained:ident)) => {
+    (call($sut_wrapper:expr, $options:expr, $strength_variable:expr, $unconstrained:ident, $constrained:ident)) => {
         match $sut_wrapper {
             cli::SUTWrapper::Unconstrained(sut) => {
-                main!(call_constraints(sut, $output_path, $strength_variable, $unconstrained));
+                main!(call_constraints(sut, $options, $strength_variable, $unconstrained));
             }
             cli::SUTWrapper::Constrained(sut) => {
-                main!(call_constraints(sut, $output_path, $strength_variable, $constrained));
+                main!(call_constraints(sut, $options, $strength_variable, $constrained));
             }
         }
     };
 
-    ($strength_name:ident, $strength:expr, $sut:expr, $output_path:expr, $strength_variable:expr, $method:ident) => {
+    ($strength_name:ident, $strength:expr, $sut:expr, $options:expr, $strength_variable:expr, $method:ident) => {
         if $strength == $strength_variable {
-            main!(call_strength<{u8}, {u8}>($strength, $sut, $output_path, $method));
+            main!(call_strength<{u8}, {u8}>($strength, $sut, $options, $method));
         }
     };
 
     ($(#[$outer:meta])* $unconstrained:ident, $constrained:ident) => {
         $(#[$outer])*
         fn main() -> Result<(), String> {
-            let (sut_wrapper, output_path, strength) = common::time_it!(cli::parse_arguments(file!(), cli::crate_version!()), "Parsing")?;
-            main!(call(sut_wrapper, output_path, strength, $unconstrained, $constrained));
+            let (sut_wrapper, options, strength) = cli::parse_arguments(file!(), cli::crate_version!())?;
+            let (report_path, report) = (options.report_path.clone(), options.report.clone());
+            let run = || -> Result<(), String> {
+                main!(call(sut_wrapper, options, strength, $unconstrained, $constrained));
+            };
+            run()?;
+            if let Some(report_path) = report_path {
+                writer::write_report(&report.lock().unwrap(), report_path).map_err(|e| e.to_string())?;
+            }
+            Ok(())
         }
     };
 }
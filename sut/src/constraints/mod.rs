@@ -16,6 +16,9 @@ pub(crate) mod solver_z3;
 
 pub(crate) mod solver_not_implemented;
 pub(crate) mod solver_fake;
+pub(crate) mod solver_predicate;
+pub(crate) mod solver_counting;
+pub(crate) mod solver_caching;
 
 
 pub(crate) fn find_problem<'i, Solver: solver::Solver<'i>, ValueId: Number>(solver: &mut Solver, row: &[ValueId], mut start: usize, mut end: usize) -> usize {
@@ -4,7 +4,7 @@
 // MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
 // modified, or distributed except according to those terms.
 
-use common::Number;
+use common::{BitVec, Number};
 use crate::{ConstrainedSUT};
 
 /// This trait represents any type of solver and allows for switching between backends without too much effort.
@@ -60,6 +60,50 @@ pub trait Solver<'i>: std::fmt::Display {
         self.check_and_pop_all(2)
     }
 
+    /// Check the given interactions, each the values of a PC followed by the value of `at_parameter`.
+    ///
+    /// Returns a bit for every interaction, in the same order, which is set if the interaction is valid.
+    /// The values of the PC stay pushed while the next interactions share them, so only the value of `at_parameter` is pushed and popped for those.
+    /// Backends may override this with a faster strategy.
+    ///
+    /// Requires an empty stack and leaves an empty stack.
+    fn check_interactions<ValueId: Number, ParameterId: Number, PC: AsRef<[ParameterId]>, Values: AsRef<[ValueId]>>(&mut self, at_parameter: usize, interactions: &[(PC, Values)]) -> BitVec {
+        // Every value of the PC is pushed separately, so only the ones which differ from the previous interaction are popped
+        let mut pushed: Vec<(ParameterId, ValueId)> = Vec::new();
+        let result = interactions.iter().map(|(pc, values)| {
+            let (pc, values) = (pc.as_ref(), values.as_ref());
+            debug_assert_eq!(pc.len() + 1, values.len());
+            let shared = pushed.iter().zip(pc.iter().zip(values.iter()))
+                .take_while(|(&(pushed_parameter, pushed_value), (&parameter, &value))| pushed_parameter == parameter && pushed_value == value)
+                .count();
+            if shared < pushed.len() {
+                self.pop((pushed.len() - shared) as u32);
+                pushed.truncate(shared);
+            }
+            for (&parameter, &value) in pc.iter().zip(values.iter()).skip(shared) {
+                self.push_and_assert_eq(parameter, value);
+                pushed.push((parameter, value));
+            }
+
+            self.push_and_assert_eq(ParameterId::from_usize(at_parameter), values[pc.len()]);
+            self.check_and_pop(1)
+        }).collect();
+        if !pushed.is_empty() {
+            self.pop_all(pushed.len() as u32);
+        }
+        result
+    }
+
+    /// Check the given rows, like [Solver::check_row].
+    ///
+    /// Returns a bit for every row, in the same order, which is set if the row is valid.
+    /// Checks every row separately, backends may override this with a faster strategy.
+    ///
+    /// Requires an empty stack and leaves an empty stack.
+    fn check_rows_batch<ValueId: Number, Row: AsRef<[ValueId]>>(&mut self, rows: &[Row]) -> BitVec {
+        rows.iter().map(|row| self.check_row(row.as_ref())).collect()
+    }
+
     /// Push the current state to the stack.
     ///
     /// This method should probably not be called directly.
@@ -0,0 +1,303 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use common::{BitVec, CacheHits, Number};
+
+use crate::{ConstrainedSUT, Solver};
+
+/// The number of results a [CachingSolver] keeps by default, its cache is cleared once it is full.
+pub const DEFAULT_CACHE_CAPACITY: usize = 1 << 20;
+
+/// A partial assignment as the sorted (parameter, value) pairs without duplicates.
+type Key = Vec<(usize, usize)>;
+
+/// The number of queries answered by the caches of all the [CachingSolver]s sharing it.
+#[derive(Debug, Default)]
+pub struct CacheStatistics {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheStatistics {
+    /// Returns the number of queries answered from the cache and the number passed on to the wrapped solver.
+    pub fn hits(&self) -> CacheHits {
+        CacheHits { hits: self.hits.load(Ordering::Relaxed), misses: self.misses.load(Ordering::Relaxed) }
+    }
+}
+
+/// The [Solver::Init] of the [CachingSolver], which contains the statistics.
+pub struct CachingInit<I> {
+    /// The init of the wrapped solver.
+    pub init: I,
+
+    /// The statistics of all the solvers created with this init.
+    pub statistics: CacheStatistics,
+
+    /// The maximum number of results cached per solver, zero disables the cache.
+    pub capacity: usize,
+}
+
+impl<I> CachingInit<I> {
+    /// Create the init for solvers which cache at most `capacity` results around solvers created with the `init`.
+    pub fn new(init: I, capacity: usize) -> Self {
+        Self { init, statistics: CacheStatistics::default(), capacity }
+    }
+}
+
+/// This solver wraps another solver and caches the results of its checking methods.
+///
+/// The result is cached by the partial assignment which is checked, sorted on the parameters and without the values which the solvers ignore, such as don't-cares.
+/// So [Solver::check_row] and a stack with the same assignments pushed in another order share their result.
+/// The assignments on the stack are kept next to the wrapped solver, which remain correct under [Solver::push] and [Solver::pop].
+/// The batch methods only pass the queries which are not cached on to the wrapped solver, as a single batch.
+/// Every solver has its own cache, only the [CacheStatistics] in the [CachingInit] are shared.
+///
+/// # Example
+/// ```
+/// use sut::{CachingSolver, FakeSolver, parse_constrained, Solver};
+///
+/// let sut = parse_constrained("p1: v1, v2; p2: v1, v2;").unwrap();
+/// let init = CachingSolver::<FakeSolver>::default_init();
+/// let mut solver = CachingSolver::<FakeSolver>::new(&sut, &init);
+/// assert!(solver.check_row(&[0_usize, 1]));
+///
+/// solver.push_and_assert_eq(1_usize, 1_usize);
+/// solver.push_and_assert_eq(0_usize, 0_usize);
+/// assert!(solver.check_and_pop_all(2));
+///
+/// let hits = init.statistics.hits();
+/// assert_eq!((hits.hits, hits.misses), (1, 1));
+/// ```
+pub struct CachingSolver<'i, S> {
+    solver: S,
+    levels: Vec<usize>,
+    assignments: Vec<(usize, usize)>,
+    way_points: Vec<usize>,
+    cache: HashMap<Key, bool>,
+    capacity: usize,
+    statistics: &'i CacheStatistics,
+}
+
+impl<S> CachingSolver<'_, S> {
+    /// Returns the key of the assignments, or [None] if the cache is disabled.
+    fn key(&self, assignments: impl Iterator<Item=(usize, usize)>) -> Option<Key> {
+        if self.capacity == 0 {
+            return None;
+        }
+        let mut key: Key = assignments.filter(|&(parameter, value)| self.levels.get(parameter).is_some_and(|&level| value < level)).collect();
+        key.sort_unstable();
+        key.dedup();
+        Some(key)
+    }
+
+    /// Returns the key of the assignments on the stack.
+    fn stack_key(&self) -> Option<Key> {
+        self.key(self.assignments.iter().copied())
+    }
+
+    /// Returns the cached result for the key, otherwise the result of the `query` of the wrapped solver, which is then cached.
+    ///
+    /// The `on_hit` is applied to the wrapped solver instead of the `query`, so it ends up in the same state.
+    fn cached(&mut self, key: Option<Key>, query: impl FnOnce(&mut S) -> bool, on_hit: impl FnOnce(&mut S)) -> bool {
+        let key = match key {
+            Some(key) => key,
+            None => return query(&mut self.solver),
+        };
+        if let Some(&result) = self.cache.get(&key) {
+            self.statistics.hits.fetch_add(1, Ordering::Relaxed);
+            on_hit(&mut self.solver);
+            return result;
+        }
+        self.statistics.misses.fetch_add(1, Ordering::Relaxed);
+        let result = query(&mut self.solver);
+        self.insert(key, result);
+        result
+    }
+
+    /// Returns the cached results for the keys, the others are the results of the `query` of the wrapped solver, which are then cached.
+    ///
+    /// The `query` is given the indices of the keys which are not cached, in order, and should return a result for each.
+    fn cached_batch(&mut self, keys: Vec<Key>, query: impl FnOnce(&mut S, &[usize]) -> BitVec) -> BitVec {
+        let mut results: Vec<Option<bool>> = keys.iter().map(|key| self.cache.get(key).copied()).collect();
+        let misses: Vec<usize> = (0..results.len()).filter(|&index| results[index].is_none()).collect();
+        self.statistics.hits.fetch_add((results.len() - misses.len()) as u64, Ordering::Relaxed);
+        self.statistics.misses.fetch_add(misses.len() as u64, Ordering::Relaxed);
+
+        if !misses.is_empty() {
+            let answers = query(&mut self.solver, &misses);
+            debug_assert_eq!(answers.len(), misses.len());
+            let mut answers = answers.iter();
+            for (key, result) in keys.into_iter().zip(results.iter_mut()) {
+                if result.is_none() {
+                    let answer = answers.next().unwrap();
+                    *result = Some(answer);
+                    self.insert(key, answer);
+                }
+            }
+        }
+        results.into_iter().map(|result| result.unwrap()).collect()
+    }
+
+    /// Cache the result, the cache is cleared first if it is full.
+    fn insert(&mut self, key: Key, result: bool) {
+        if self.cache.len() >= self.capacity {
+            self.cache.clear();
+        }
+        self.cache.insert(key, result);
+    }
+
+    /// Pop the given number of elements from the assignments on the stack.
+    fn pop_assignments(&mut self, num: u32) {
+        debug_assert!(self.way_points.len() >= num as usize);
+        let new_len = self.way_points.len() - num as usize;
+        self.assignments.truncate(self.way_points.get(new_len).copied().unwrap_or(0));
+        self.way_points.truncate(new_len);
+    }
+}
+
+impl<S: Display> Display for CachingSolver<'_, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.solver.fmt(f)
+    }
+}
+
+impl<'i, S: Solver<'i>> Solver<'i> for CachingSolver<'i, S> {
+    type Init = CachingInit<S::Init>;
+
+    fn default_init() -> Self::Init {
+        CachingInit::new(S::default_init(), DEFAULT_CACHE_CAPACITY)
+    }
+
+    fn new<ValueId: Number, ParameterId: Number>(sut: &ConstrainedSUT<ValueId, ParameterId>, args: &'i Self::Init) -> Self {
+        Self {
+            solver: S::new(sut, &args.init),
+            levels: sut.sub_sut.parameters.iter().map(|level| level.as_usize()).collect(),
+            assignments: Vec::with_capacity(sut.sub_sut.parameters.len()),
+            way_points: Vec::with_capacity(sut.sub_sut.parameters.len()),
+            cache: HashMap::new(),
+            capacity: args.capacity,
+            statistics: &args.statistics,
+        }
+    }
+
+    fn check(&mut self) -> bool {
+        let key = self.stack_key();
+        self.cached(key, |solver| solver.check(), |_| ())
+    }
+
+    fn check_and_pop(&mut self, num: u32) -> bool {
+        let key = self.stack_key();
+        let result = self.cached(key, |solver| solver.check_and_pop(num), |solver| solver.pop(num));
+        self.pop_assignments(num);
+        result
+    }
+
+    fn check_and_pop_all(&mut self, num: u32) -> bool {
+        let key = self.stack_key();
+        let result = self.cached(key, |solver| solver.check_and_pop_all(num), |solver| solver.pop_all(num));
+        self.pop_assignments(num);
+        result
+    }
+
+    fn check_row<ValueId: Number>(&mut self, row: &[ValueId]) -> bool {
+        debug_assert!(self.way_points.is_empty());
+        let key = self.key(row.iter().enumerate().map(|(parameter, value)| (parameter, value.as_usize())));
+        self.cached(key, |solver| solver.check_row(row), |_| ())
+    }
+
+    fn check_row_overrides<ValueId: Number, ParameterId: Number>(&mut self, row: &[ValueId], pc: &[ParameterId], at_parameter: usize, values: &[ValueId]) -> bool {
+        debug_assert!(self.way_points.is_empty());
+        let key = self.key(masked_row(row, pc, at_parameter).chain(interaction(pc, at_parameter, values)));
+        self.cached(key, |solver| solver.check_row_overrides(row, pc, at_parameter, values), |_| ())
+    }
+
+    fn check_interactions<ValueId: Number, ParameterId: Number, PC: AsRef<[ParameterId]>, Values: AsRef<[ValueId]>>(&mut self, at_parameter: usize, interactions: &[(PC, Values)]) -> BitVec {
+        debug_assert!(self.way_points.is_empty());
+        let keys: Option<Vec<Key>> = interactions.iter().map(|(pc, values)| self.key(interaction(pc.as_ref(), at_parameter, values.as_ref()))).collect();
+        match keys {
+            Some(keys) => self.cached_batch(keys, |solver, misses| {
+                let misses: Vec<(&[ParameterId], &[ValueId])> = misses.iter().map(|&index| (interactions[index].0.as_ref(), interactions[index].1.as_ref())).collect();
+                solver.check_interactions(at_parameter, &misses)
+            }),
+            None => self.solver.check_interactions(at_parameter, interactions),
+        }
+    }
+
+    fn check_rows_batch<ValueId: Number, Row: AsRef<[ValueId]>>(&mut self, rows: &[Row]) -> BitVec {
+        debug_assert!(self.way_points.is_empty());
+        let keys: Option<Vec<Key>> = rows.iter().map(|row| self.key(row.as_ref().iter().enumerate().map(|(parameter, value)| (parameter, value.as_usize())))).collect();
+        match keys {
+            Some(keys) => self.cached_batch(keys, |solver, misses| {
+                let misses: Vec<&[ValueId]> = misses.iter().map(|&index| rows[index].as_ref()).collect();
+                solver.check_rows_batch(&misses)
+            }),
+            None => self.solver.check_rows_batch(rows),
+        }
+    }
+
+    fn push(&mut self) {
+        self.way_points.push(self.assignments.len());
+        self.solver.push()
+    }
+
+    fn push_and_assert_eq<ValueId: Number, ParameterId: Number>(&mut self, parameter_id: ParameterId, value_id: ValueId) {
+        self.way_points.push(self.assignments.len());
+        self.assignments.push((parameter_id.as_usize(), value_id.as_usize()));
+        self.solver.push_and_assert_eq(parameter_id, value_id)
+    }
+
+    fn push_and_assert_row<ValueId: Number>(&mut self, row: &[ValueId]) {
+        self.way_points.push(self.assignments.len());
+        self.assignments.extend(row.iter().enumerate().map(|(parameter, value)| (parameter, value.as_usize())));
+        self.solver.push_and_assert_row(row)
+    }
+
+    fn push_and_assert_row_masked<ValueId: Number, ParameterId: Number>(&mut self, row: &[ValueId], pc: &[ParameterId], at_parameter: usize) {
+        self.way_points.push(self.assignments.len());
+        self.assignments.extend(masked_row(row, pc, at_parameter));
+        self.solver.push_and_assert_row_masked(row, pc, at_parameter)
+    }
+
+    fn push_and_assert_interaction<ValueId: Number, ParameterId: Number>(&mut self, pc: &[ParameterId], at_parameter: usize, values: &[ValueId]) {
+        self.way_points.push(self.assignments.len());
+        self.assignments.extend(interaction(pc, at_parameter, values));
+        self.solver.push_and_assert_interaction(pc, at_parameter, values)
+    }
+
+    fn pop(&mut self, num: u32) {
+        self.pop_assignments(num);
+        self.solver.pop(num)
+    }
+
+    fn pop_all(&mut self, num: u32) {
+        debug_assert_eq!(self.way_points.len(), num as usize);
+        self.assignments.clear();
+        self.way_points.clear();
+        self.solver.pop_all(num)
+    }
+}
+
+/// Returns the assignments of [Solver::push_and_assert_row_masked], the parameters of the row before `at_parameter` which are not in the sorted `pc`.
+fn masked_row<'a, ValueId: Number, ParameterId: Number>(row: &'a [ValueId], pc: &'a [ParameterId], at_parameter: usize) -> impl Iterator<Item=(usize, usize)> + 'a {
+    let mut pc_values = pc.iter().peekable();
+    row.iter().take(at_parameter).enumerate().filter_map(move |(parameter_id, value)| {
+        if pc_values.next_if(|&&parameter| parameter.as_usize() == parameter_id).is_some() {
+            None
+        } else {
+            Some((parameter_id, value.as_usize()))
+        }
+    })
+}
+
+/// Returns the assignments of [Solver::push_and_assert_interaction], the last value is that of `at_parameter`.
+fn interaction<'a, ValueId: Number, ParameterId: Number>(pc: &'a [ParameterId], at_parameter: usize, values: &'a [ValueId]) -> impl Iterator<Item=(usize, usize)> + 'a {
+    debug_assert_eq!(pc.len() + 1, values.len());
+    pc.iter().map(|parameter| parameter.as_usize()).chain(std::iter::once(at_parameter)).zip(values.iter().map(|value| value.as_usize()))
+}
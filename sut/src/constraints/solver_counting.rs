@@ -0,0 +1,153 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use common::{BitVec, Number, SolverCalls};
+
+use crate::{ConstrainedSUT, Solver};
+
+/// The names of the checking methods of [Solver], in the order of the counts in [SolverStatistics].
+///
+/// The batch methods count every interaction or row they check.
+const METHODS: [&str; 7] = ["check", "check_and_pop", "check_and_pop_all", "check_row", "check_row_overrides", "check_interactions", "check_rows_batch"];
+
+/// The number of satisfiable and unsatisfiable results of the checking methods of all the [CountingSolver]s sharing it.
+#[derive(Debug, Default)]
+pub struct SolverStatistics {
+    counts: [[AtomicU64; 2]; METHODS.len()],
+}
+
+impl SolverStatistics {
+    #[inline]
+    fn count(&self, method: usize, result: bool) -> bool {
+        self.counts[method][result as usize].fetch_add(1, Ordering::Relaxed);
+        result
+    }
+
+    #[inline]
+    fn count_batch(&self, method: usize, results: BitVec) -> BitVec {
+        let sat = results.count_ones();
+        self.counts[method][1].fetch_add(sat as u64, Ordering::Relaxed);
+        self.counts[method][0].fetch_add((results.len() - sat) as u64, Ordering::Relaxed);
+        results
+    }
+
+    /// Returns the number of calls of every checking method.
+    pub fn calls(&self) -> Vec<SolverCalls> {
+        METHODS.iter().zip(self.counts.iter()).map(|(&method, [unsat, sat])| SolverCalls {
+            method,
+            sat: sat.load(Ordering::Relaxed),
+            unsat: unsat.load(Ordering::Relaxed),
+        }).collect()
+    }
+}
+
+/// The [Solver::Init] of the [CountingSolver], which contains the statistics.
+pub struct CountingInit<I> {
+    /// The init of the wrapped solver.
+    pub init: I,
+
+    /// The statistics of all the solvers created with this init.
+    pub statistics: SolverStatistics,
+}
+
+/// This solver wraps another solver and counts the results of its checking methods.
+///
+/// The counts are kept in the [CountingInit], so all the solvers created with the same init share them.
+///
+/// # Example
+/// ```
+/// use sut::{CountingSolver, FakeSolver, parse_constrained, Solver};
+///
+/// let sut = parse_constrained("p1: v1, v2; p2: v1, v2;").unwrap();
+/// let init = CountingSolver::<FakeSolver>::default_init();
+/// let mut solver = CountingSolver::<FakeSolver>::new(&sut, &init);
+/// assert!(solver.check_row(&[0_usize, 1]));
+///
+/// let check_row = init.statistics.calls().into_iter().find(|calls| calls.method == "check_row").unwrap();
+/// assert_eq!(check_row.sat, 1);
+/// assert_eq!(check_row.unsat, 0);
+/// ```
+pub struct CountingSolver<'i, S> {
+    solver: S,
+    statistics: &'i SolverStatistics,
+}
+
+impl<S: Display> Display for CountingSolver<'_, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.solver.fmt(f)
+    }
+}
+
+impl<'i, S: Solver<'i>> Solver<'i> for CountingSolver<'i, S> {
+    type Init = CountingInit<S::Init>;
+
+    fn default_init() -> Self::Init {
+        CountingInit { init: S::default_init(), statistics: SolverStatistics::default() }
+    }
+
+    fn new<ValueId: Number, ParameterId: Number>(sut: &ConstrainedSUT<ValueId, ParameterId>, args: &'i Self::Init) -> Self {
+        Self { solver: S::new(sut, &args.init), statistics: &args.statistics }
+    }
+
+    fn check(&mut self) -> bool {
+        self.statistics.count(0, self.solver.check())
+    }
+
+    fn check_and_pop(&mut self, num: u32) -> bool {
+        self.statistics.count(1, self.solver.check_and_pop(num))
+    }
+
+    fn check_and_pop_all(&mut self, num: u32) -> bool {
+        self.statistics.count(2, self.solver.check_and_pop_all(num))
+    }
+
+    fn check_row<ValueId: Number>(&mut self, row: &[ValueId]) -> bool {
+        self.statistics.count(3, self.solver.check_row(row))
+    }
+
+    fn check_row_overrides<ValueId: Number, ParameterId: Number>(&mut self, row: &[ValueId], pc: &[ParameterId], at_parameter: usize, values: &[ValueId]) -> bool {
+        self.statistics.count(4, self.solver.check_row_overrides(row, pc, at_parameter, values))
+    }
+
+    fn check_interactions<ValueId: Number, ParameterId: Number, PC: AsRef<[ParameterId]>, Values: AsRef<[ValueId]>>(&mut self, at_parameter: usize, interactions: &[(PC, Values)]) -> BitVec {
+        self.statistics.count_batch(5, self.solver.check_interactions(at_parameter, interactions))
+    }
+
+    fn check_rows_batch<ValueId: Number, Row: AsRef<[ValueId]>>(&mut self, rows: &[Row]) -> BitVec {
+        self.statistics.count_batch(6, self.solver.check_rows_batch(rows))
+    }
+
+    fn push(&mut self) {
+        self.solver.push()
+    }
+
+    fn push_and_assert_eq<ValueId: Number, ParameterId: Number>(&mut self, parameter_id: ParameterId, value_id: ValueId) {
+        self.solver.push_and_assert_eq(parameter_id, value_id)
+    }
+
+    fn push_and_assert_row<ValueId: Number>(&mut self, row: &[ValueId]) {
+        self.solver.push_and_assert_row(row)
+    }
+
+    fn push_and_assert_row_masked<ValueId: Number, ParameterId: Number>(&mut self, row: &[ValueId], pc: &[ParameterId], at_parameter: usize) {
+        self.solver.push_and_assert_row_masked(row, pc, at_parameter)
+    }
+
+    fn push_and_assert_interaction<ValueId: Number, ParameterId: Number>(&mut self, pc: &[ParameterId], at_parameter: usize, values: &[ValueId]) {
+        self.solver.push_and_assert_interaction(pc, at_parameter, values)
+    }
+
+    fn pop(&mut self, num: u32) {
+        self.solver.pop(num)
+    }
+
+    fn pop_all(&mut self, num: u32) {
+        self.solver.pop_all(num)
+    }
+}
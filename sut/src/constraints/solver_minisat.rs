@@ -8,7 +8,7 @@ use std::fmt::{Debug, Display};
 
 use minisat::Bool;
 use std::fmt::{Error, Formatter};
-use common::Number;
+use common::{BitVec, Number};
 
 use crate::{ConstrainedSUT, Solver};
 
@@ -51,11 +51,52 @@ impl<'i> Solver<'i> for MiniSatSolver {
     }
 
     fn check(&mut self) -> bool {
-        if !self.values.is_empty() {
-            self.solver.solve_under_borrowed_assumptions(&self.values).is_ok()
-        } else {
-            self.solver.solve().is_ok()
-        }
+        Self::solve(&mut self.solver, &self.values)
+    }
+
+    /// The assumptions of the values of the PC are shared by the interactions which only differ in the value of `at_parameter`.
+    fn check_interactions<ValueId: Number, ParameterId: Number, PC: AsRef<[ParameterId]>, Values: AsRef<[ValueId]>>(&mut self, at_parameter: usize, interactions: &[(PC, Values)]) -> BitVec {
+        debug_assert!(self.way_points.is_empty());
+        let mut assumptions = Vec::with_capacity(self.parameters.len());
+        let mut previous: Option<(&[ParameterId], &[ValueId])> = None;
+        interactions.iter().map(|(pc, values)| {
+            let (pc, values) = (pc.as_ref(), values.as_ref());
+            debug_assert_eq!(pc.len() + 1, values.len());
+            let (value, pc_values) = values.split_last().unwrap();
+            if previous != Some((pc, pc_values)) {
+                assumptions.clear();
+                assumptions.extend(pc.iter().zip(pc_values.iter()).map(|(&parameter, &value)| self.parameters[parameter.as_usize()][value.as_usize()]));
+                previous = Some((pc, pc_values));
+            }
+
+            assumptions.push(self.parameters[at_parameter][value.as_usize()]);
+            let valid = Self::solve(&mut self.solver, &assumptions);
+            assumptions.pop();
+            valid
+        }).collect()
+    }
+
+    /// The assumptions of the leading values which a row shares with the previous row are kept, like the stack of [Solver::check_row].
+    fn check_rows_batch<ValueId: Number, Row: AsRef<[ValueId]>>(&mut self, rows: &[Row]) -> BitVec {
+        debug_assert!(self.way_points.is_empty());
+        let mut assumptions = Vec::with_capacity(self.parameters.len());
+        // The number of assumptions after each parameter of the previous row
+        let mut lengths: Vec<usize> = Vec::with_capacity(self.parameters.len());
+        let mut previous: &[ValueId] = &[];
+        rows.iter().map(|row| {
+            let row = row.as_ref();
+            let shared = previous.iter().zip(row.iter()).take_while(|(previous, value)| previous == value).count().min(lengths.len());
+            lengths.truncate(shared);
+            assumptions.truncate(lengths.last().copied().unwrap_or(0));
+            for (value, testers) in row.iter().zip(self.parameters.iter()).skip(shared) {
+                if let Some(&tester) = testers.get(value.as_usize()) {
+                    assumptions.push(tester);
+                }
+                lengths.push(assumptions.len());
+            }
+            previous = row;
+            Self::solve(&mut self.solver, &assumptions)
+        }).collect()
     }
 
     fn push(&mut self) {
@@ -123,6 +164,17 @@ impl<'i> Solver<'i> for MiniSatSolver {
     }
 }
 
+impl MiniSatSolver {
+    /// Solve under the assumptions, if there are any.
+    fn solve(solver: &mut minisat::Solver, assumptions: &[Bool]) -> bool {
+        if !assumptions.is_empty() {
+            solver.solve_under_borrowed_assumptions(assumptions).is_ok()
+        } else {
+            solver.solve().is_ok()
+        }
+    }
+}
+
 impl Display for MiniSatSolver {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         Debug::fmt(&self.solver, f)
@@ -0,0 +1,136 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use common::Number;
+
+use crate::{ConstrainedSUT, Solver};
+
+/// The constraints of a [PredicateSolver], which receives the value of every parameter by name and returns whether the row is allowed.
+pub type Predicate = fn(&HashMap<&str, &str>) -> bool;
+
+/// This solver checks the assertions against every row of the SUT allowed by a [Predicate], ignoring the constraints of the SUT.
+///
+/// To be used for testing with constraints without a SAT solver, so only for small SUTs.
+///
+/// # Example
+/// ```
+/// use sut::{parse_constrained, PredicateSolver, Solver};
+///
+/// let mut sut = parse_constrained("p1: v1, v2; p2: v1, v2;").unwrap();
+/// let init: sut::Predicate = |row| row["p1"] != "v2" || row["p2"] != "v2";
+/// let mut solver = sut.get_solver::<PredicateSolver>(&init);
+/// assert!(solver.check_row(&[0_usize, 1]));
+/// assert!(!solver.check_row(&[1_usize, 1]));
+/// ```
+pub struct PredicateSolver {
+    levels: Vec<usize>,
+    allowed_rows: Vec<Vec<usize>>,
+    values: Vec<(usize, usize)>,
+    way_points: Vec<usize>,
+}
+
+impl Display for PredicateSolver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<PredicateSolver with {} allowed rows>", self.allowed_rows.len())
+    }
+}
+
+impl PredicateSolver {
+    #[inline]
+    fn assert_value(&mut self, parameter_id: usize, value_id: usize) {
+        if value_id < self.levels[parameter_id] {
+            self.values.push((parameter_id, value_id));
+        }
+    }
+}
+
+impl<'i> Solver<'i> for PredicateSolver {
+    type Init = Predicate;
+
+    fn default_init() -> Self::Init {
+        |_| true
+    }
+
+    fn new<ValueId: Number, ParameterId: Number>(sut: &ConstrainedSUT<ValueId, ParameterId>, args: &'i Self::Init) -> Self {
+        let levels: Vec<usize> = sut.sub_sut.parameters.iter().map(|level| level.as_usize()).collect();
+        let mut allowed_rows = Vec::new();
+        let mut row = vec![0; levels.len()];
+        let mut names = HashMap::with_capacity(levels.len());
+        'rows: loop {
+            for (parameter_id, &value_id) in row.iter().enumerate() {
+                names.insert(sut.sub_sut.parameter_names[parameter_id].as_str(), sut.sub_sut.values[parameter_id][value_id].as_str());
+            }
+            if args(&names) {
+                allowed_rows.push(row.clone());
+            }
+
+            for (value_id, &level) in row.iter_mut().zip(levels.iter()) {
+                *value_id += 1;
+                if *value_id < level {
+                    continue 'rows;
+                }
+                *value_id = 0;
+            }
+            break;
+        }
+
+        Self { levels, allowed_rows, values: Vec::new(), way_points: Vec::new() }
+    }
+
+    fn check(&mut self) -> bool {
+        self.allowed_rows.iter().any(|row| self.values.iter().all(|&(parameter_id, value_id)| row[parameter_id] == value_id))
+    }
+
+    fn push(&mut self) {
+        self.way_points.push(self.values.len());
+    }
+
+    fn push_and_assert_eq<ValueId: Number, ParameterId: Number>(&mut self, parameter_id: ParameterId, value_id: ValueId) {
+        self.push();
+        self.assert_value(parameter_id.as_usize(), value_id.as_usize());
+    }
+
+    fn push_and_assert_row<ValueId: Number>(&mut self, row: &[ValueId]) {
+        self.push();
+        for (parameter_id, value_id) in row.iter().enumerate().take(self.levels.len()) {
+            self.assert_value(parameter_id, value_id.as_usize());
+        }
+    }
+
+    fn push_and_assert_row_masked<ValueId: Number, ParameterId: Number>(&mut self, row: &[ValueId], pc: &[ParameterId], at_parameter: usize) {
+        self.push();
+        for (parameter_id, value_id) in row.iter().enumerate().take(at_parameter) {
+            if !pc.contains(&ParameterId::from_usize(parameter_id)) {
+                self.assert_value(parameter_id, value_id.as_usize());
+            }
+        }
+    }
+
+    fn push_and_assert_interaction<ValueId: Number, ParameterId: Number>(&mut self, pc: &[ParameterId], at_parameter: usize, values: &[ValueId]) {
+        debug_assert_eq!(pc.len() + 1, values.len());
+        self.push();
+        for (parameter_id, value_id) in pc.iter().map(|parameter| parameter.as_usize()).chain(std::iter::once(at_parameter)).zip(values.iter()) {
+            self.assert_value(parameter_id, value_id.as_usize());
+        }
+    }
+
+    fn pop(&mut self, num: u32) {
+        debug_assert_ne!(num, 0);
+        debug_assert!(self.way_points.len() >= num as usize);
+        self.way_points.truncate(self.way_points.len() - num as usize + 1);
+        let new_len = self.way_points.pop().unwrap_or(0);
+        self.values.truncate(new_len);
+    }
+
+    fn pop_all(&mut self, num: u32) {
+        debug_assert_eq!(self.way_points.len(), num as usize);
+        self.values.clear();
+        self.way_points.clear();
+    }
+}
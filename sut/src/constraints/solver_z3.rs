@@ -11,7 +11,7 @@ use std::fmt::Display;
 use z3::ast::Bool;
 use z3::SatResult;
 
-use common::Number;
+use common::{BitVec, Number};
 
 use crate::{ConstrainedSUT, Solver};
 use crate::expr::expr_z3::CHelpers;
@@ -92,6 +92,51 @@ impl<'ctx> Solver<'ctx> for Z3Solver<'ctx> {
         result != SatResult::Unsat
     }
 
+    /// The values are checked as assumptions instead of on the stack.
+    /// Those of the PC are shared by the interactions which only differ in the value of `at_parameter`.
+    fn check_interactions<ValueId: Number, ParameterId: Number, PC: AsRef<[ParameterId]>, Values: AsRef<[ValueId]>>(&mut self, at_parameter: usize, interactions: &[(PC, Values)]) -> BitVec {
+        let mut assumptions = Vec::with_capacity(self.parameters.len());
+        let mut previous: Option<(&[ParameterId], &[ValueId])> = None;
+        interactions.iter().map(|(pc, values)| {
+            let (pc, values) = (pc.as_ref(), values.as_ref());
+            debug_assert_eq!(pc.len() + 1, values.len());
+            let (value, pc_values) = values.split_last().unwrap();
+            if previous != Some((pc, pc_values)) {
+                assumptions.clear();
+                assumptions.extend(pc.iter().zip(pc_values.iter()).map(|(&parameter, &value)| self.parameters[parameter.as_usize()][value.as_usize()].clone()));
+                previous = Some((pc, pc_values));
+            }
+
+            assumptions.push(self.parameters[at_parameter][value.as_usize()].clone());
+            let valid = self.check_assumptions(&assumptions);
+            assumptions.pop();
+            valid
+        }).collect()
+    }
+
+    /// The values are checked as assumptions instead of on the stack.
+    /// Those of the leading values which a row shares with the previous row are kept.
+    fn check_rows_batch<ValueId: Number, Row: AsRef<[ValueId]>>(&mut self, rows: &[Row]) -> BitVec {
+        let mut assumptions = Vec::with_capacity(self.parameters.len());
+        // The number of assumptions after each parameter of the previous row
+        let mut lengths: Vec<usize> = Vec::with_capacity(self.parameters.len());
+        let mut previous: &[ValueId] = &[];
+        rows.iter().map(|row| {
+            let row = row.as_ref();
+            let shared = previous.iter().zip(row.iter()).take_while(|(previous, value)| previous == value).count().min(lengths.len());
+            lengths.truncate(shared);
+            assumptions.truncate(lengths.last().copied().unwrap_or(0));
+            for (value, testers) in row.iter().zip(self.parameters.iter()).skip(shared) {
+                if let Some(tester) = testers.get(value.as_usize()) {
+                    assumptions.push(tester.clone());
+                }
+                lengths.push(assumptions.len());
+            }
+            previous = row;
+            self.check_assumptions(&assumptions)
+        }).collect()
+    }
+
     fn push(&mut self) {
         self.solver.push();
     }
@@ -145,7 +190,13 @@ impl<'ctx> Solver<'ctx> for Z3Solver<'ctx> {
     }
 }
 
-impl Z3Solver<'_> {
+impl<'ctx> Z3Solver<'ctx> {
+    fn check_assumptions(&self, assumptions: &[Bool<'ctx>]) -> bool {
+        let result = self.solver.check_assumptions(assumptions);
+        debug_assert_ne!(result, SatResult::Unknown);
+        result != SatResult::Unsat
+    }
+
     fn save_assert_eq(&mut self, parameter_id: usize, value_id: usize) {
         if let Some(tester) = self.parameters[parameter_id].get(value_id) {
             self.solver.assert(&tester);
@@ -28,3 +28,207 @@ fn test_threads() {
         assert!(solver.check_row(&[0_usize, 0, 0, 0, 0]));
     }).join().unwrap();
 }
+
+#[test]
+fn test_relations() {
+    let mut sut = parse_constrained("\
+    cores: 1, 2, 4;\
+    threads: 1, 2, 4, 8;\
+    mem: int[1..8];\
+    vms: int[1..4];\
+    $assert cores <= threads;\
+    $assert mem >= 2 * vms;").expect("Parsing went wrong?");
+    let mut solver = sut.get_solver::<MiniSatSolver>(&());
+
+    let value = |sut: &ConstrainedSUT<usize, usize>, parameter: &str, value: &str| {
+        let parameter_id = sut.parameter_to_id[parameter];
+        (parameter_id, sut.value_to_id[parameter_id][value])
+    };
+    let row = |sut: &ConstrainedSUT<usize, usize>, values: &[(&str, &str)]| {
+        let mut row = vec![!0_usize; sut.sub_sut.parameters.len()];
+        for (parameter, v) in values {
+            let (parameter_id, value_id) = value(sut, parameter, v);
+            row[parameter_id] = value_id;
+        }
+        row
+    };
+
+    assert!(solver.check_row(&row(&sut, &[("cores", "2"), ("threads", "2")])));
+    assert!(solver.check_row(&row(&sut, &[("cores", "2"), ("threads", "8")])));
+    assert!(!solver.check_row(&row(&sut, &[("cores", "4"), ("threads", "2")])));
+    assert!(solver.check_row(&row(&sut, &[("mem", "8"), ("vms", "4")])));
+    assert!(!solver.check_row(&row(&sut, &[("mem", "4"), ("vms", "4")])));
+}
+
+#[test]
+fn test_relation_limits() {
+    // The products do not fit in an i64, but the relation is evaluated without overflowing
+    let mut sut = parse_constrained("p: 9223372036854775807, -9223372036854775808; q: 1, 2; $assert 2 * p > q;").expect("Parsing went wrong?");
+    let mut solver = sut.get_solver::<MiniSatSolver>(&());
+    let p = sut.parameter_to_id["p"];
+    let mut row = vec![0_usize; 2];
+    row[p] = sut.value_to_id[p]["9223372036854775807"];
+    assert!(solver.check_row(&row));
+    row[p] = sut.value_to_id[p]["-9223372036854775808"];
+    assert!(!solver.check_row(&row));
+
+    let values = (0..128).map(|value| value.to_string()).collect::<Vec<_>>().join(", ");
+    let model = format!("a: {0}; b: {0}; c: {0}; $assert a + b <= c;", values);
+    assert!(parse_constrained(&model).unwrap_err().contains("MiniSat supports at most"));
+    assert!(parse_constrained(&format!("a: {0}; b: {0}; c: {0}; $assert a <= c;", values)).is_ok());
+}
+
+#[test]
+fn test_parameter_equality() {
+    let mut sut = parse_constrained("\
+    p1: a, b, c;\
+    p2: c, b, a, d;\
+    $assert p1 = p2;").expect("Parsing went wrong?");
+    let mut solver = sut.get_solver::<MiniSatSolver>(&());
+
+    let p1 = sut.parameter_to_id["p1"];
+    let p2 = sut.parameter_to_id["p2"];
+    for (v1, v2, expected) in [("a", "a", true), ("b", "b", true), ("a", "c", false), ("c", "d", false)] {
+        let mut row = vec![!0_usize; 2];
+        row[p1] = sut.value_to_id[p1][v1];
+        row[p2] = sut.value_to_id[p2][v2];
+        assert_eq!(solver.check_row(&row), expected, "{} = {}", v1, v2);
+    }
+}
+
+#[test]
+fn test_caching_solver() {
+    use common::split_mix;
+
+    use crate::CachingSolver;
+
+    let mut sut = parse_constrained("\
+    p0: v0, v1;\
+    p1: v0, v1, v2;\
+    p2: v0, v1, v2;\
+    p3: v0, v1;\
+    p4: v0, v1;\
+    $assert p1=v0 => p2=v1;\
+    $assert p3=v1 => !(p0=v0 || p4=v1);").expect("Parsing went wrong?");
+    let init = CachingSolver::<MiniSatSolver>::default_init();
+    let mut cached = sut.get_solver::<CachingSolver<MiniSatSolver>>(&init);
+    let mut solver = sut.get_solver::<MiniSatSolver>(&());
+    let levels = sut.sub_sut.parameters.clone();
+
+    let mut state = 0x5a7;
+    let mut random = |bound: usize| split_mix(&mut state) as usize % bound;
+    let mut stack = 0;
+    for step in 0..2000 {
+        match random(6) {
+            0 | 1 => {
+                // Values past the level are ignored by the solvers, like don't-cares.
+                let parameter = random(levels.len());
+                let value = random(levels[parameter] + 1);
+                cached.push_and_assert_eq(parameter, value);
+                solver.push_and_assert_eq(parameter, value);
+                stack += 1;
+            }
+            2 => assert_eq!(cached.check(), solver.check(), "step {}", step),
+            3 if stack > 0 => {
+                let num = 1 + random(stack) as u32;
+                assert_eq!(cached.check_and_pop(num), solver.check_and_pop(num), "step {}", step);
+                stack -= num as usize;
+            }
+            4 if stack > 0 => {
+                let num = 1 + random(stack) as u32;
+                cached.pop(num);
+                solver.pop(num);
+                stack -= num as usize;
+            }
+            _ if stack > 0 => {
+                assert_eq!(cached.check_and_pop_all(stack as u32), solver.check_and_pop_all(stack as u32), "step {}", step);
+                stack = 0;
+            }
+            _ => {
+                let row: Vec<usize> = levels.iter().map(|&level| if random(3) == 0 { !0 } else { random(level) }).collect();
+                assert_eq!(cached.check_row(&row), solver.check_row(&row), "step {} {:?}", step, row);
+                let values = [random(levels[1]), random(levels[3]), random(levels[4])];
+                assert_eq!(cached.check_row_overrides(&row, &[1_usize, 3], 4, &values), solver.check_row_overrides(&row, &[1_usize, 3], 4, &values), "step {} {:?}", step, row);
+            }
+        }
+    }
+
+    let hits = init.statistics.hits();
+    assert!(hits.hits > 0 && hits.misses > 0, "{:?}", hits);
+}
+
+#[test]
+fn test_batches() {
+    use crate::{CachingSolver, Predicate, PredicateSolver};
+
+    let mut sut = parse_constrained("\
+    p0: v0, v1;\
+    p1: v0, v1, v2;\
+    p2: v0, v1, v2;\
+    p3: v0, v1;\
+    $assert p1=v0 => p2=v1;\
+    $assert p3=v1 => p0=v1;").expect("Parsing went wrong?");
+    let init = CachingSolver::<MiniSatSolver>::default_init();
+    let mut cached = sut.get_solver::<CachingSolver<MiniSatSolver>>(&init);
+    let mut solver = sut.get_solver::<MiniSatSolver>(&());
+    let predicate: Predicate = |row| (row["p1"] != "v0" || row["p2"] == "v1") && (row["p3"] != "v1" || row["p0"] == "v1");
+    let mut predicate_solver = sut.get_solver::<PredicateSolver>(&predicate);
+
+    // The constraints evaluated on the names, as get_solver may have swapped the values.
+    let is = |row: &[usize], parameter: &str, value: &str| {
+        let parameter_id = sut.parameter_to_id[parameter];
+        sut.sub_sut.values[parameter_id][row[parameter_id]] == value
+    };
+    let allowed = |row: &[usize]| (!is(row, "p1", "v0") || is(row, "p2", "v1")) && (!is(row, "p3", "v1") || is(row, "p0", "v1"));
+    let levels = sut.sub_sut.parameters.clone();
+    let rows: Vec<Vec<usize>> = (0..levels.iter().product()).map(|mut index: usize| levels.iter().map(|&level| {
+        let value = index % level;
+        index /= level;
+        value
+    }).collect()).collect();
+
+    let pcs = [[0_usize, 1], [1, 2], [0, 2]];
+    let mut interactions = Vec::new();
+    for pc in pcs.iter() {
+        for first in 0..levels[pc[0]] {
+            for second in 0..levels[pc[1]] {
+                for last in 0..levels[3] {
+                    interactions.push((pc, [first, second, last]));
+                }
+            }
+        }
+    }
+    let expected: Vec<bool> = interactions.iter().map(|(pc, values)| {
+        rows.iter().any(|row| allowed(row) && row[pc[0]] == values[0] && row[pc[1]] == values[1] && row[3] == values[2])
+    }).collect();
+    assert!(expected.contains(&false) && expected.contains(&true));
+    assert_eq!(solver.check_interactions(3, &interactions).iter().collect::<Vec<_>>(), expected);
+    assert_eq!(predicate_solver.check_interactions(3, &interactions).iter().collect::<Vec<_>>(), expected);
+    let before = init.statistics.hits();
+    assert_eq!(cached.check_interactions(3, &interactions).iter().collect::<Vec<_>>(), expected);
+    assert_eq!(cached.check_interactions(3, &interactions).iter().collect::<Vec<_>>(), expected);
+    let hits = init.statistics.hits();
+    assert_eq!(hits.queries() - before.queries(), 2 * interactions.len() as u64);
+    assert!(hits.hits - before.hits >= interactions.len() as u64, "{:?}", hits);
+
+    let expected: Vec<bool> = rows.iter().map(|row| allowed(row)).collect();
+    assert!(expected.contains(&false) && expected.contains(&true));
+    assert_eq!(solver.check_rows_batch(&rows).iter().collect::<Vec<_>>(), expected);
+    assert_eq!(predicate_solver.check_rows_batch(&rows).iter().collect::<Vec<_>>(), expected);
+    assert_eq!(cached.check_rows_batch(&rows).iter().collect::<Vec<_>>(), expected);
+    assert_eq!(cached.check_rows_batch(&rows[..7]).iter().collect::<Vec<_>>(), expected[..7]);
+
+    // Sorted rows share their leading values, which the solvers may keep between the rows, also with don't-cares.
+    let mut sorted = rows.clone();
+    sorted.sort();
+    let partial: Vec<Vec<usize>> = sorted.iter().flat_map(|row| {
+        let mut dont_care = row.clone();
+        dont_care[1] = !0;
+        [row.clone(), dont_care]
+    }).collect();
+    let expected: Vec<bool> = partial.iter().map(|partial_row| {
+        rows.iter().any(|row| allowed(row) && partial_row.iter().zip(row.iter()).all(|(&partial_value, &value)| partial_value == !0 || partial_value == value))
+    }).collect();
+    assert_eq!(solver.check_rows_batch(&partial).iter().collect::<Vec<_>>(), expected);
+    assert_eq!(predicate_solver.check_rows_batch(&partial).iter().collect::<Vec<_>>(), expected);
+}
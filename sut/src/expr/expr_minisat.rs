@@ -10,7 +10,7 @@ use minisat::{Bool, Solver};
 
 use common::{Number, UVec};
 
-use crate::expr::{BinOp, BOp, Eq, False, Not, True};
+use crate::expr::{BinOp, BOp, Eq, False, Not, Relation, True};
 
 pub(crate) trait ApplyMiniSat {
     fn apply_minisat(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>, solver: &mut Solver, parameters: &[Vec<Bool>]) -> Bool;
@@ -47,9 +47,30 @@ impl ApplyMiniSat for BinOp {
 }
 
 impl ApplyMiniSat for Eq {
-    fn apply_minisat(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>, _solver: &mut Solver, parameters: &[Vec<Bool>]) -> Bool {
+    fn apply_minisat(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>, solver: &mut Solver, parameters: &[Vec<Bool>]) -> Bool {
+        if let Some((parameter_id, other_id, pairs)) = self.parameter_pairs(parameter_to_id, value_to_id) {
+            let clauses: Vec<Bool> = pairs.into_iter()
+                .map(|(value_id, other_value_id)| solver.and_literal(vec![parameters[parameter_id][value_id], parameters[other_id][other_value_id]]))
+                .collect();
+            return solver.or_literal(clauses);
+        }
         let parameter_id = parameter_to_id.get(&self.parameter).expect("Unknown parameter!");
         let value_id = value_to_id[(*parameter_id).as_usize()].get(&self.value).expect("Unknown value!");
         parameters[(*parameter_id).as_usize()][(*value_id).as_usize()]
     }
 }
+
+impl ApplyMiniSat for Relation {
+    fn apply_minisat(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>, solver: &mut Solver, parameters: &[Vec<Bool>]) -> Bool {
+        let (parameter_ids, assignments) = self.satisfying_assignments(parameter_to_id, value_to_id);
+        let clauses: Vec<Bool> = assignments.into_iter()
+            .map(|assignment| {
+                let testers: Vec<Bool> = parameter_ids.iter().zip(assignment)
+                    .map(|(&parameter_id, value_id)| parameters[parameter_id][value_id])
+                    .collect();
+                solver.and_literal(testers)
+            })
+            .collect();
+        solver.or_literal(clauses)
+    }
+}
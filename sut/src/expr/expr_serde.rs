@@ -0,0 +1,74 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use serde::{Deserialize, Serialize};
+
+use crate::expr::{BinOp, BOp, Eq, Expr, False, Linear, Not, Relation, ROp, True};
+
+/// The serializable form of an [Expr], with the same structure as the constraint.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SerdeExpr {
+    False,
+    True,
+    Not { sub: Box<SerdeExpr> },
+    BinOp { left: Box<SerdeExpr>, op: BOp, right: Box<SerdeExpr> },
+    Eq { parameter: String, value: String },
+    Relation { left: Linear, op: ROp, right: Linear },
+}
+
+impl From<SerdeExpr> for Box<dyn Expr> {
+    fn from(expr: SerdeExpr) -> Self {
+        match expr {
+            SerdeExpr::False => Box::new(False),
+            SerdeExpr::True => Box::new(True),
+            SerdeExpr::Not { sub } => Box::new(Not { sub: (*sub).into() }),
+            SerdeExpr::BinOp { left, op, right } => Box::new(BinOp { left: (*left).into(), op, right: (*right).into() }),
+            SerdeExpr::Eq { parameter, value } => Box::new(Eq { parameter, value }),
+            SerdeExpr::Relation { left, op, right } => Box::new(Relation { left, op, right }),
+        }
+    }
+}
+
+pub(crate) trait ApplySerde {
+    fn to_serde(&self) -> SerdeExpr;
+}
+
+impl ApplySerde for False {
+    fn to_serde(&self) -> SerdeExpr {
+        SerdeExpr::False
+    }
+}
+
+impl ApplySerde for True {
+    fn to_serde(&self) -> SerdeExpr {
+        SerdeExpr::True
+    }
+}
+
+impl ApplySerde for Not {
+    fn to_serde(&self) -> SerdeExpr {
+        SerdeExpr::Not { sub: Box::new(self.sub.to_serde()) }
+    }
+}
+
+impl ApplySerde for BinOp {
+    fn to_serde(&self) -> SerdeExpr {
+        SerdeExpr::BinOp { left: Box::new(self.left.to_serde()), op: self.op, right: Box::new(self.right.to_serde()) }
+    }
+}
+
+impl ApplySerde for Eq {
+    fn to_serde(&self) -> SerdeExpr {
+        SerdeExpr::Eq { parameter: self.parameter.clone(), value: self.value.clone() }
+    }
+}
+
+impl ApplySerde for Relation {
+    fn to_serde(&self) -> SerdeExpr {
+        SerdeExpr::Relation { left: self.left.clone(), op: self.op, right: self.right.clone() }
+    }
+}
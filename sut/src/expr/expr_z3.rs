@@ -6,11 +6,11 @@
 
 use std::collections::HashMap;
 
-use z3::ast::Bool;
+use z3::ast::{Ast, Bool, Int};
 
 use common::{Number, UVec};
 
-use crate::expr::{BinOp, BOp, Eq, False, Not, True};
+use crate::expr::{BinOp, BOp, Eq, False, Linear, Not, numeric_values, Relation, ROp, True};
 
 pub(crate) struct CHelpers<'ctx, 'sut> {
     pub(crate) context: &'ctx z3::Context,
@@ -55,8 +55,54 @@ impl ApplyZ3 for BinOp {
 
 impl ApplyZ3 for Eq {
     fn apply_z3<'ctx, 'sut>(&self, helpers: &CHelpers<'ctx, 'sut>) -> Bool<'ctx> {
+        if let Some((parameter_id, other_id, pairs)) = self.parameter_pairs(helpers.parameter_to_id, helpers.value_to_id) {
+            let clauses: Vec<Bool<'ctx>> = pairs.into_iter()
+                .map(|(value_id, other_value_id)| Bool::and(helpers.context, &[
+                    &helpers.value_testers[parameter_id][value_id],
+                    &helpers.value_testers[other_id][other_value_id],
+                ]))
+                .collect();
+            return Bool::or(helpers.context, &clauses.iter().collect::<Vec<&Bool<'ctx>>>());
+        }
         let parameter_id = helpers.parameter_to_id.get(&self.parameter).expect("Unknown parameter!");
         let value_id = helpers.value_to_id[(*parameter_id).as_usize()].get(&self.value).expect("Unknown value!");
         helpers.value_testers[(*parameter_id).as_usize()][(*value_id).as_usize()].clone()
     }
 }
+
+impl<'ctx, 'sut> CHelpers<'ctx, 'sut> {
+    /// Returns the integer value of the parameter as a nested if-then-else over the value testers.
+    fn integer(&self, parameter: &str) -> Int<'ctx> {
+        let parameter_id = *self.parameter_to_id.get(parameter).expect("Unknown parameter!");
+        let numbers = numeric_values(&self.value_to_id[parameter_id]).expect("Relations are checked when the constraints are loaded!");
+        let testers = &self.value_testers[parameter_id];
+        let mut result = Int::from_i64(self.context, numbers[numbers.len() - 1]);
+        for value_id in (0..numbers.len() - 1).rev() {
+            result = testers[value_id].ite(&Int::from_i64(self.context, numbers[value_id]), &result);
+        }
+        result
+    }
+
+    fn linear(&self, linear: &Linear) -> Int<'ctx> {
+        let mut terms: Vec<Int<'ctx>> = linear.terms.iter()
+            .map(|(coefficient, parameter)| Int::mul(self.context, &[&Int::from_i64(self.context, *coefficient), &self.integer(parameter)]))
+            .collect();
+        terms.push(Int::from_i64(self.context, linear.constant));
+        Int::add(self.context, &terms.iter().collect::<Vec<&Int<'ctx>>>())
+    }
+}
+
+impl ApplyZ3 for Relation {
+    fn apply_z3<'ctx, 'sut>(&self, helpers: &CHelpers<'ctx, 'sut>) -> Bool<'ctx> {
+        let left = helpers.linear(&self.left);
+        let right = helpers.linear(&self.right);
+        match self.op {
+            ROp::Eq => left._eq(&right),
+            ROp::Ne => left._eq(&right).not(),
+            ROp::Lt => left.lt(&right),
+            ROp::Le => left.le(&right),
+            ROp::Gt => left.gt(&right),
+            ROp::Ge => left.ge(&right),
+        }
+    }
+}
@@ -4,8 +4,13 @@
 // MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
 // modified, or distributed except according to those terms.
 
+use std::collections::HashMap;
 use std::fmt::{Debug, Error, Formatter};
 
+use common::UVec;
+
+use crate::parser::escape;
+
 #[cfg(feature = "constraints-z3")]
 pub(crate) mod expr_z3;
 
@@ -24,6 +29,8 @@ mod expr_z3 {
     impl ApplyZ3 for BinOp {}
 
     impl ApplyZ3 for Eq {}
+
+    impl ApplyZ3 for Relation {}
 }
 
 #[cfg(feature = "constraints-minisat")]
@@ -44,10 +51,35 @@ mod expr_minisat {
     impl ApplyMiniSat for BinOp {}
 
     impl ApplyMiniSat for Eq {}
+
+    impl ApplyMiniSat for Relation {}
+}
+
+#[cfg(feature = "serde")]
+pub(crate) mod expr_serde;
+
+#[cfg(not(feature = "serde"))]
+mod expr_serde {
+    use super::*;
+
+    pub(crate) trait ApplySerde {}
+
+    impl ApplySerde for False {}
+
+    impl ApplySerde for True {}
+
+    impl ApplySerde for Not {}
+
+    impl ApplySerde for BinOp {}
+
+    impl ApplySerde for Eq {}
+
+    impl ApplySerde for Relation {}
 }
 
 
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
 pub(crate) enum BOp {
     And,
     Or,
@@ -64,8 +96,15 @@ impl std::fmt::Debug for BOp {
     }
 }
 
-pub(crate) trait Expr: Debug + Send + Sync + expr_z3::ApplyZ3 + expr_minisat::ApplyMiniSat {
+pub(crate) trait Expr: Debug + Send + Sync + expr_z3::ApplyZ3 + expr_minisat::ApplyMiniSat + expr_serde::ApplySerde {
     fn fmt_no_parenthesis(&self, f: &mut Formatter<'_>) -> Result<(), Error>;
+
+    /// Adds the ids of the parameters used in this expression to the result, which may contain duplicates.
+    fn parameter_ids(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>, result: &mut Vec<usize>);
+
+    /// Returns an error if this expression uses a parameter or value which is not part of the SUT,
+    /// or relates a parameter which has values that are not integers.
+    fn check(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>) -> Result<(), String>;
 }
 
 pub(crate) struct False;
@@ -74,6 +113,12 @@ impl Expr for False {
     fn fmt_no_parenthesis(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         self.fmt(f)
     }
+
+    fn parameter_ids(&self, _parameter_to_id: &HashMap<String, usize>, _value_to_id: &UVec<HashMap<String, usize>>, _result: &mut Vec<usize>) {}
+
+    fn check(&self, _parameter_to_id: &HashMap<String, usize>, _value_to_id: &UVec<HashMap<String, usize>>) -> Result<(), String> {
+        Ok(())
+    }
 }
 
 impl Debug for False {
@@ -88,6 +133,12 @@ impl Expr for True {
     fn fmt_no_parenthesis(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         self.fmt(f)
     }
+
+    fn parameter_ids(&self, _parameter_to_id: &HashMap<String, usize>, _value_to_id: &UVec<HashMap<String, usize>>, _result: &mut Vec<usize>) {}
+
+    fn check(&self, _parameter_to_id: &HashMap<String, usize>, _value_to_id: &UVec<HashMap<String, usize>>) -> Result<(), String> {
+        Ok(())
+    }
 }
 
 impl Debug for True {
@@ -106,6 +157,14 @@ impl Expr for Not {
             .and_then(|_| self.sub.fmt_no_parenthesis(f))
             .and_then(|_| f.write_str(")"))
     }
+
+    fn parameter_ids(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>, result: &mut Vec<usize>) {
+        self.sub.parameter_ids(parameter_to_id, value_to_id, result);
+    }
+
+    fn check(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>) -> Result<(), String> {
+        self.sub.check(parameter_to_id, value_to_id)
+    }
 }
 
 impl Debug for Not {
@@ -126,6 +185,16 @@ impl Expr for BinOp {
             .and_then(|_| self.op.fmt(f))
             .and_then(|_| self.right.fmt(f))
     }
+
+    fn parameter_ids(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>, result: &mut Vec<usize>) {
+        self.left.parameter_ids(parameter_to_id, value_to_id, result);
+        self.right.parameter_ids(parameter_to_id, value_to_id, result);
+    }
+
+    fn check(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>) -> Result<(), String> {
+        self.left.check(parameter_to_id, value_to_id)
+            .and_then(|_| self.right.check(parameter_to_id, value_to_id))
+    }
 }
 
 impl Debug for BinOp {
@@ -145,12 +214,236 @@ impl Expr for Eq {
     fn fmt_no_parenthesis(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         self.fmt(f)
     }
+
+    fn parameter_ids(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>, result: &mut Vec<usize>) {
+        match self.parameter_pairs(parameter_to_id, value_to_id) {
+            Some((parameter_id, other_id, _)) => result.extend([parameter_id, other_id]),
+            None => result.push(*parameter_to_id.get(&self.parameter).expect("Unknown parameter!")),
+        }
+    }
+
+    fn check(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>) -> Result<(), String> {
+        let parameter_id = *parameter_to_id.get(&self.parameter).ok_or_else(|| format!("Unknown parameter {} in the constraint {:?}", self.parameter, self))?;
+        if value_to_id[parameter_id].contains_key(&self.value) || parameter_to_id.contains_key(&self.value) {
+            Ok(())
+        } else {
+            Err(format!("Unknown value {} of parameter {} in the constraint {:?}", self.value, self.parameter, self))
+        }
+    }
 }
 
 impl Debug for Eq {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        f.write_str(&self.parameter)
+        f.write_str(&escape(&self.parameter))
             .and_then(|_| f.write_str("="))
-            .and_then(|_| f.write_str(&self.value))
+            .and_then(|_| f.write_str(&escape(&self.value)))
+    }
+}
+
+/// The ids of the two parameters compared by an [Eq], and the pairs of their value ids with the same name.
+pub(crate) type ParameterPairs = (usize, usize, Vec<(usize, usize)>);
+
+impl Eq {
+    /// Returns the pairs of value ids with the same name if this [Eq] compares two parameters.
+    ///
+    /// Returns [None] if the value is one of the values of the parameter, which takes precedence over parameter names.
+    pub(crate) fn parameter_pairs(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>) -> Option<ParameterPairs> {
+        let parameter_id = *parameter_to_id.get(&self.parameter).expect("Unknown parameter!");
+        if value_to_id[parameter_id].contains_key(&self.value) {
+            return None;
+        }
+        let other_id = *parameter_to_id.get(&self.value).expect("Unknown value!");
+        let mut pairs = Vec::new();
+        for (name, &value_id) in value_to_id[parameter_id].iter() {
+            if let Some(&other_value_id) = value_to_id[other_id].get(name) {
+                pairs.push((value_id, other_value_id));
+            }
+        }
+        pairs.sort_unstable();
+        Some((parameter_id, other_id, pairs))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub(crate) enum ROp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl ROp {
+    #[cfg(feature = "constraints-common")]
+    pub(crate) fn holds(self, left: i128, right: i128) -> bool {
+        match self {
+            ROp::Eq => left == right,
+            ROp::Ne => left != right,
+            ROp::Lt => left < right,
+            ROp::Le => left <= right,
+            ROp::Gt => left > right,
+            ROp::Ge => left >= right,
+        }
+    }
+}
+
+impl Debug for ROp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.write_str(match self {
+            ROp::Eq => " = ",
+            ROp::Ne => " != ",
+            ROp::Lt => " < ",
+            ROp::Le => " <= ",
+            ROp::Gt => " > ",
+            ROp::Ge => " >= ",
+        })
+    }
+}
+
+/// A linear combination of parameters with integer values plus a constant.
+#[derive(Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Linear {
+    pub(crate) terms: Vec<(i64, String)>,
+    pub(crate) constant: i64,
+}
+
+impl Linear {
+    /// Evaluates the sum for the values, the products of the coefficients and values always fit in an [i128].
+    #[cfg(feature = "constraints-common")]
+    pub(crate) fn evaluate(&self, values: &HashMap<&str, i64>) -> i128 {
+        self.terms.iter()
+            .map(|(coefficient, parameter)| *coefficient as i128 * values[parameter.as_str()] as i128)
+            .fold(self.constant as i128, i128::saturating_add)
+    }
+}
+
+impl Debug for Linear {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        for (index, (coefficient, parameter)) in self.terms.iter().enumerate() {
+            if index != 0 {
+                f.write_str(if *coefficient < 0 { " - " } else { " + " })?;
+            } else if *coefficient < 0 {
+                f.write_str("-")?;
+            }
+            if coefficient.unsigned_abs() != 1 {
+                write!(f, "{} * ", coefficient.unsigned_abs())?;
+            }
+            f.write_str(&escape(parameter))?;
+        }
+        if self.terms.is_empty() {
+            write!(f, "{}", self.constant)
+        } else if self.constant != 0 {
+            write!(f, "{}{}", if self.constant < 0 { " - " } else { " + " }, self.constant.unsigned_abs())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The maximal number of combinations of values of the parameters of a [Relation] when MiniSat is compiled.
+///
+/// MiniSat has no integers, so every combination of values of the parameters is checked while the constraint is added.
+#[cfg(feature = "constraints-minisat")]
+pub(crate) const MAX_RELATION_ASSIGNMENTS: usize = 1 << 20;
+
+/// Compares two linear combinations of parameters, the values of these parameters should be integers.
+pub(crate) struct Relation {
+    pub(crate) left: Linear,
+    pub(crate) op: ROp,
+    pub(crate) right: Linear,
+}
+
+impl Relation {
+    /// Returns the parameters used in this [Relation] without duplicates.
+    pub(crate) fn parameters(&self) -> Vec<&str> {
+        let mut result: Vec<&str> = Vec::new();
+        for (_, parameter) in self.left.terms.iter().chain(self.right.terms.iter()) {
+            if !result.contains(&parameter.as_str()) {
+                result.push(parameter);
+            }
+        }
+        result
+    }
+
+    /// Returns the ids of the parameters and the combinations of value ids for which the [Relation] holds.
+    ///
+    /// Every combination of values of the parameters is evaluated, see [MAX_RELATION_ASSIGNMENTS].
+    #[cfg(feature = "constraints-common")]
+    pub(crate) fn satisfying_assignments(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>) -> (Vec<usize>, Vec<Vec<usize>>) {
+        let parameters = self.parameters();
+        let parameter_ids: Vec<usize> = parameters.iter().map(|p| *parameter_to_id.get(*p).expect("Unknown parameter!")).collect();
+        let numbers: Vec<Vec<i64>> = parameter_ids.iter().map(|&id| numeric_values(&value_to_id[id]).expect("Relations are checked when the constraints are loaded!")).collect();
+
+        let mut result = Vec::new();
+        let mut assignment = vec![0; parameters.len()];
+        let mut values: HashMap<&str, i64> = HashMap::with_capacity(parameters.len());
+        'outer: loop {
+            for ((parameter, numbers), &value_id) in parameters.iter().zip(numbers.iter()).zip(assignment.iter()) {
+                values.insert(parameter, numbers[value_id]);
+            }
+            if self.op.holds(self.left.evaluate(&values), self.right.evaluate(&values)) {
+                result.push(assignment.clone());
+            }
+
+            for (value_id, numbers) in assignment.iter_mut().zip(numbers.iter()) {
+                *value_id += 1;
+                if *value_id < numbers.len() {
+                    continue 'outer;
+                }
+                *value_id = 0;
+            }
+            break;
+        }
+
+        (parameter_ids, result)
+    }
+}
+
+impl Expr for Relation {
+    fn fmt_no_parenthesis(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        self.fmt(f)
+    }
+
+    fn parameter_ids(&self, parameter_to_id: &HashMap<String, usize>, _value_to_id: &UVec<HashMap<String, usize>>, result: &mut Vec<usize>) {
+        result.extend(self.parameters().into_iter().map(|p| *parameter_to_id.get(p).expect("Unknown parameter!")));
+    }
+
+    fn check(&self, parameter_to_id: &HashMap<String, usize>, value_to_id: &UVec<HashMap<String, usize>>) -> Result<(), String> {
+        for parameter in self.parameters() {
+            let parameter_id = *parameter_to_id.get(parameter).ok_or_else(|| format!("Unknown parameter {} in the constraint {:?}", parameter, self))?;
+            numeric_values(&value_to_id[parameter_id])
+                .map_err(|value| format!("The constraint {:?} uses parameter {}, which has the value {} that is not an integer", self, parameter, value))?;
+        }
+        #[cfg(feature = "constraints-minisat")]
+        let assignments = self.parameters().into_iter()
+            .fold(1usize, |product, parameter| product.saturating_mul(value_to_id[parameter_to_id[parameter]].len()));
+        #[cfg(feature = "constraints-minisat")]
+        if assignments > MAX_RELATION_ASSIGNMENTS {
+            return Err(format!(
+                "The constraint {:?} relates {} combinations of values, MiniSat supports at most {}",
+                self, assignments, MAX_RELATION_ASSIGNMENTS,
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Debug for Relation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        self.left.fmt(f)
+            .and_then(|_| self.op.fmt(f))
+            .and_then(|_| self.right.fmt(f))
+    }
+}
+
+/// Returns the integer value of each value id of a parameter, or a value which is not an integer.
+pub(crate) fn numeric_values(value_to_id: &HashMap<String, usize>) -> Result<Vec<i64>, &str> {
+    let mut result = vec![0; value_to_id.len()];
+    for (name, &value_id) in value_to_id.iter() {
+        result[value_id] = name.parse().map_err(|_| name.as_str())?;
     }
+    Ok(result)
 }
@@ -15,12 +15,57 @@
 //!   * `constraints-z3` Add support for the Z3 solver.
 //!   * `constraints` Implies `constraints-minisat`.
 //!   * `no-sort` Do not sort the parameters based on descending level. IPOG runs better on a sorted SUT.
+//!     The parameters can also be ordered at runtime, see [ParameterOrder].
+//!   * `serde` Implement `Serialize` and `Deserialize` for the [SUT] and [ConstrainedSUT].
+//!     The constraints are stored as a tree of expressions, not as text.
 //!
 //! # System Under Test
 //! There are two System Under Test (SUT) structures, namely the [SUT] and [ConstrainedSUT].
 //! [SUT] is an unconstrained SUT.
 //! The [ConstrainedSUT] has a unconstrained variant as one of its fields.
 //!
+//! # Names and comments
+//! Names of parameters and values consist of letters, digits, `-`, `_` and `.`.
+//! Other names are written between double quotes, in which `\"` and `\\` represent a quote and a backslash.
+//! A value can be given a display label, which is used instead of its name in the output:
+//! ```text
+//! # Line comments start with '#' or '//'.
+//! "operating system": win10 = "Windows 10", linux, "mac os";
+//! version: 1.2.3, 2.0.0; // Versions are plain names.
+//! ```
+//! The labels are stored in [SUT::value_labels], the [Debug] output of a [ConstrainedSUT] quotes names where needed and can be parsed again.
+//!
+//! # Numeric ranges
+//! Instead of a list of values, a parameter can be declared as a range of integers which is divided into partitions:
+//! ```text
+//! timeout: int[0..3600] partitions {0, 1..59, 60..3599, 3600};
+//! ```
+//! Each partition is expanded into its boundary values and a representative value in between.
+//! The partition a value was taken from is stored as its class in [SUT::value_classes].
+//! If the partitions are omitted, the entire range is used as the only partition.
+//!
+//! # Composition
+//! Models can be composed from multiple files and reuse lists of values:
+//! ```text
+//! $include "common/os.cocoa";
+//! $set BROWSER = firefox, chrome, safari;
+//! browser: $BROWSER;
+//! ```
+//! Included paths are relative to the directory of the file containing the `$include`,
+//! use [parse_constrained_path] or [parse_unconstrained_path] to parse a file.
+//! Each file is included at most once and cyclic includes are reported as an error.
+//! Errors name the file and line at which they occur.
+//!
+//! # Relations
+//! Besides comparing a parameter with one of its values, constraints can compare sums of parameters with integer values:
+//! ```text
+//! $assert cores <= threads;
+//! $assert mem >= 2 * vms + 1;
+//! ```
+//! The supported operators are `=`, `!=`, `<`, `<=`, `>` and `>=`.
+//! A constraint `p1 = p2` compares the names of the values if `p2` is not a value of `p1`, so it can be used for any two parameters with the same values.
+//! The [MiniSatSolver] lists the combinations of values a relation forbids, so it rejects relations of more than 2^20 combinations of values, Z3 has no such limit.
+//!
 //! # Solvers
 //! There currently are two solvers supported:
 //!   * [MiniSatSolver], which is the frontend of both MiniSat and Glucose.
@@ -29,6 +74,7 @@
 //!     Bindings to this SAT solver are provided by [z3].
 //!
 //! There also is a Solver called [NotASolver], which is used as a placeholder when no solvers are compiled (see the features).
+//! The [PredicateSolver] checks the rows against a Rust function instead of the constraints, which allows testing the constrained generation without a SAT solver.
 //!
 //! # Example
 //! ```
@@ -71,19 +117,23 @@ use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::env::args;
 use std::fmt::{Debug, Error, Formatter};
-use std::fs::read_to_string;
 use std::marker::PhantomData;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use common::{Number, UVec};
+use common::{Number, u_vec, UVec};
 use constraints::find_problem;
+use parser::model::Loader;
 pub use constraints::solver::Solver;
+pub use constraints::solver_caching::{CacheStatistics, CachingInit, CachingSolver, DEFAULT_CACHE_CAPACITY};
+pub use constraints::solver_counting::{CountingInit, CountingSolver, SolverStatistics};
 pub use constraints::solver_fake::FakeSolver;
 #[cfg(feature = "constraints-minisat")]
 pub use constraints::solver_minisat::MiniSatSolver;
 pub use constraints::solver_not_implemented::NotASolver;
+pub use constraints::solver_predicate::{Predicate, PredicateSolver};
 #[cfg(feature = "constraints-z3")]
 pub use constraints::solver_z3::Z3Solver;
+pub use order::ParameterOrder;
 
 #[cfg(all(feature = "constraints-z3", not(feature = "constraints-minisat")))]
 /// This type points to the default Solver, which currently is [Z3Solver].
@@ -114,12 +164,17 @@ pub type SolverImpl<'ctx> = NotASolver;
 
 mod constraints;
 mod expr;
+mod order;
 mod parser;
+#[cfg(feature = "serde")]
+mod serialization;
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 struct TemporaryParameter {
     name: String,
     values: UVec<String>,
+    labels: UVec<Option<String>>,
+    classes: Option<UVec<String>>,
 }
 
 /// Error indicating overflow returned when the [SUT] can not be converted to the specified types.
@@ -136,6 +191,9 @@ pub enum OverflowError {
 }
 
 /// This struct represents the System Under Test (SUT) for which to generate an MCA.
+///
+/// Deserialization fails if the fields do not describe the same parameters and values, e.g. if their lengths differ.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SUT<ValueId: Number, ParameterId: Number> {
     /// The parameter levels of the SUT.
     pub parameters: UVec<ValueId>,
@@ -148,24 +206,47 @@ pub struct SUT<ValueId: Number, ParameterId: Number> {
     /// The outer vector is indexed by the parameter ID, and the inner vector is indexed by the value ID.
     /// So `sut.values[parameter_id][value_id]`.
     pub values: UVec<UVec<String>>,
+
+    /// The equivalence classes of the values of parameters declared as a numeric range.
+    ///
+    /// Indexed in the same way as [SUT::values], parameters declared with a list of values have no classes.
+    pub value_classes: UVec<Option<UVec<String>>>,
+
+    /// The optional display labels of the values, which are used instead of the names of the values in the output.
+    ///
+    /// Indexed in the same way as [SUT::values].
+    pub value_labels: UVec<UVec<Option<String>>>,
+
+    /// The IDs of the parameters in the order in which they were declared in the model.
+    ///
+    /// The parameters are sorted on descending level unless the `no-sort` feature is set, the output uses this order instead.
+    pub declaration_order: UVec<usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     parameter_id: PhantomData<ParameterId>,
 }
 
 impl SUT<usize, usize> {
-    fn new(mut parameters: Vec<TemporaryParameter>) -> Self {
+    fn new(parameters: Vec<TemporaryParameter>) -> Self {
         let mut result = SUT {
             parameters: UVec::with_capacity(parameters.len()),
             parameter_names: UVec::with_capacity(parameters.len()),
             values: UVec::with_capacity(parameters.len()),
+            value_classes: UVec::with_capacity(parameters.len()),
+            value_labels: UVec::with_capacity(parameters.len()),
+            declaration_order: u_vec![0; parameters.len()],
             parameter_id: PhantomData,
         };
+        let mut parameters: Vec<(usize, TemporaryParameter)> = parameters.into_iter().enumerate().collect();
         if cfg!(not(feature = "no-sort")) {
-            parameters.sort_by_key(|p| !p.values.len());
+            parameters.sort_by_key(|(_, p)| !p.values.len());
         }
-        for p in parameters.into_iter() {
+        for (parameter_id, (declared_at, p)) in parameters.into_iter().enumerate() {
+            result.declaration_order[declared_at] = parameter_id;
             result.parameters.push(p.values.len());
             result.parameter_names.push(p.name);
             result.values.push(p.values);
+            result.value_classes.push(p.classes);
+            result.value_labels.push(p.labels);
         }
         result
     }
@@ -194,6 +275,9 @@ impl SUT<usize, usize> {
             parameters: self.parameters.into_iter().map(ValueId::from_usize).collect(),
             parameter_names: self.parameter_names,
             values: self.values,
+            value_classes: self.value_classes,
+            value_labels: self.value_labels,
+            declaration_order: self.declaration_order,
             parameter_id: PhantomData,
         }
     }
@@ -211,11 +295,88 @@ impl<'sut, ValueId: Number, ParameterId: Number> TryFrom<&'sut SUT<usize, usize>
             parameters: other.parameters.iter().map(|&e| ValueId::from_usize(e)).collect(),
             parameter_names: other.parameter_names.clone(),
             values: other.values.clone(),
+            value_classes: other.value_classes.clone(),
+            value_labels: other.value_labels.clone(),
+            declaration_order: other.declaration_order.clone(),
             parameter_id: PhantomData,
         })
     }
 }
 
+impl<ValueId: Number, ParameterId: Number> SUT<ValueId, ParameterId> {
+    /// Order the parameters using the given strategy, the [SUT::declaration_order] is updated accordingly.
+    ///
+    /// All parameters are equal according to [ParameterOrder::ConstraintDensity], use [ConstrainedSUT::order_parameters] to consider the constraints.
+    pub fn order_parameters(&mut self, order: &ParameterOrder) -> Result<(), String> {
+        let permutation = order.permutation(self, &vec![0; self.parameters.len()])?;
+        self.permute(&permutation);
+        Ok(())
+    }
+
+    /// Reorder the parameters such that the new parameter `i` is the old parameter `permutation[i]`.
+    fn permute(&mut self, permutation: &[usize]) {
+        fn apply<T: Clone>(items: &UVec<T>, permutation: &[usize]) -> UVec<T> {
+            permutation.iter().map(|&id| items[id].clone()).collect()
+        }
+
+        let mut new_ids = vec![0; permutation.len()];
+        for (new_id, &old_id) in permutation.iter().enumerate() {
+            new_ids[old_id] = new_id;
+        }
+
+        self.parameters = apply(&self.parameters, permutation);
+        self.parameter_names = apply(&self.parameter_names, permutation);
+        self.values = apply(&self.values, permutation);
+        self.value_classes = apply(&self.value_classes, permutation);
+        self.value_labels = apply(&self.value_labels, permutation);
+        for old_id in self.declaration_order.iter_mut() {
+            *old_id = new_ids[*old_id];
+        }
+    }
+
+    /// Returns the name of the value as it is written in the output, which is its label if it has one.
+    ///
+    /// Returns [None] if the parameter does not have this value, e.g. for the don't care value.
+    pub fn display_value(&self, parameter_id: usize, value_id: usize) -> Option<&str> {
+        let value = self.values[parameter_id].get(value_id)?;
+        Some(self.value_labels[parameter_id][value_id].as_ref().unwrap_or(value))
+    }
+
+    /// Returns a hash of the names of the parameters and their values, in the order of the parameters.
+    /// The classes and labels of the values are included, as they change the output.
+    ///
+    /// The hash is the same for every build, so it can identify the SUT of a stored MCA.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = FNV_OFFSET;
+        for parameter_id in 0..self.parameters.len() {
+            hash = fnv1a(hash, self.parameter_names[parameter_id].as_bytes());
+            for value in self.values[parameter_id].iter() {
+                hash = fnv1a(hash, value.as_bytes());
+            }
+            // The byte 0xfe never occurs in UTF-8, so a missing class or label differs from every text
+            match &self.value_classes[parameter_id] {
+                Some(classes) => hash = classes.iter().fold(hash, |hash, class| fnv1a(hash, class.as_bytes())),
+                None => hash = fnv1a(hash, &[0xfe]),
+            }
+            for label in self.value_labels[parameter_id].iter() {
+                hash = fnv1a(hash, label.as_ref().map_or(&[0xfe][..], |label| label.as_bytes()));
+            }
+        }
+        hash
+    }
+}
+
+/// The initial value of the FNV-1a hash.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Add the bytes to the FNV-1a hash, followed by a separator so the concatenation of two texts is not ambiguous.
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes.iter().chain(std::iter::once(&0xff)) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
 
 /// Represents a [SUT] with constraints.
 ///
@@ -234,11 +395,15 @@ pub struct ConstrainedSUT<ValueId: Number, ParameterId: Number> {
 
 impl ConstrainedSUT<usize, usize> {
     /// Create a new ConstrainedSUT using the temporary parameters and constraints.
-    fn new(parameters: Vec<TemporaryParameter>, constraints: Vec<Box<dyn expr::Expr>>) -> Self {
+    ///
+    /// Returns an error if the constraints can not be used with the parameters, see [ConstrainedSUT::check_constraints].
+    fn new(parameters: Vec<TemporaryParameter>, constraints: Vec<Box<dyn expr::Expr>>) -> Result<Self, String> {
         let sub_sut = SUT::new(parameters);
         let parameter_to_id = get_parameter_to_id(&sub_sut.parameter_names);
         let value_to_id = get_value_to_id(&sub_sut.values);
-        Self { sub_sut, constraints, parameter_to_id, value_to_id }
+        let result = Self { sub_sut, constraints, parameter_to_id, value_to_id };
+        result.check_constraints()?;
+        Ok(result)
     }
 
     /// Check if the parameters fit the given ParameterId type.
@@ -287,9 +452,13 @@ impl<ValueId: Number, ParameterId: Number> ConstrainedSUT<ValueId, ParameterId>
             debug_assert!(solver.check_row(&row[..end - 1]));
             debug_assert!(solver.check_row(&row));
 
-            for (value, (values, value_to_id)) in row.into_iter().zip(self.sub_sut.values.iter_mut().zip(self.value_to_id.iter_mut())) {
+            for (parameter_id, (value, (values, value_to_id))) in row.into_iter().zip(self.sub_sut.values.iter_mut().zip(self.value_to_id.iter_mut())).enumerate() {
                 if value != ValueId::default() {
                     values.swap(0, value.as_usize());
+                    if let Some(classes) = self.sub_sut.value_classes[parameter_id].as_mut() {
+                        classes.swap(0, value.as_usize());
+                    }
+                    self.sub_sut.value_labels[parameter_id].swap(0, value.as_usize());
                     unsafe { std::ptr::swap(value_to_id.get_mut(&values[0]).unwrap(), value_to_id.get_mut(&values[value.as_usize()]).unwrap()); }
                 }
             }
@@ -308,6 +477,39 @@ impl<ValueId: Number, ParameterId: Number> ConstrainedSUT<ValueId, ParameterId>
         Self { sub_sut, constraints: vec![], parameter_to_id, value_to_id }
     }
 
+    /// Returns an error if one of the constraints uses a parameter or value which is not part of the [SUT],
+    /// or relates a parameter which has values that are not integers.
+    pub(crate) fn check_constraints(&self) -> Result<(), String> {
+        self.constraints.iter().try_for_each(|constraint| constraint.check(&self.parameter_to_id, &self.value_to_id))
+    }
+
+    /// Order the parameters using the given strategy, see [SUT::order_parameters].
+    ///
+    /// Should be called before [ConstrainedSUT::get_solver], as the solvers use the ids of the parameters.
+    pub fn order_parameters(&mut self, order: &ParameterOrder) -> Result<(), String> {
+        let permutation = order.permutation(&self.sub_sut, &self.constraint_densities())?;
+        self.sub_sut.permute(&permutation);
+        self.parameter_to_id = get_parameter_to_id(&self.sub_sut.parameter_names);
+        self.value_to_id = get_value_to_id(&self.sub_sut.values);
+        Ok(())
+    }
+
+    /// Returns the number of constraints which use each parameter.
+    pub fn constraint_densities(&self) -> Vec<usize> {
+        let mut result = vec![0; self.sub_sut.parameters.len()];
+        let mut parameter_ids = Vec::new();
+        for constraint in self.constraints.iter() {
+            parameter_ids.clear();
+            constraint.parameter_ids(&self.parameter_to_id, &self.value_to_id, &mut parameter_ids);
+            parameter_ids.sort_unstable();
+            parameter_ids.dedup();
+            for &parameter_id in parameter_ids.iter() {
+                result[parameter_id] += 1;
+            }
+        }
+        result
+    }
+
     /// Returns true if the SUT has constraints, otherwise returns false.
     pub fn has_constraints(&self) -> bool {
         !self.constraints.is_empty()
@@ -315,18 +517,27 @@ impl<ValueId: Number, ParameterId: Number> ConstrainedSUT<ValueId, ParameterId>
 
     /// Returns the number of constraints listed in the SUT.
     pub fn count_constraints(&self) -> usize { self.constraints.len() }
+
+    /// Returns a hash of the parameters, their values and the constraints, see [SUT::fingerprint].
+    pub fn fingerprint(&self) -> u64 {
+        self.constraints.iter().fold(self.sub_sut.fingerprint(), |hash, constraint| fnv1a(hash, format!("{:?}", constraint).as_bytes()))
+    }
 }
 
 impl Debug for ConstrainedSUT<usize, usize> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        for (parameter_name, values) in self.sub_sut.parameter_names.iter().zip(self.sub_sut.values.iter()) {
-            f.write_str(parameter_name)?;
+        for (parameter_name, (values, labels)) in self.sub_sut.parameter_names.iter().zip(self.sub_sut.values.iter().zip(self.sub_sut.value_labels.iter())) {
+            f.write_str(&parser::escape(parameter_name))?;
             f.write_str(": ")?;
-            let mut values_iter = values.iter();
-            f.write_str(values_iter.next().unwrap())?;
-            for value_name in values_iter {
-                f.write_str(", ")?;
-                f.write_str(value_name)?;
+            for (index, (value_name, label)) in values.iter().zip(labels.iter()).enumerate() {
+                if index != 0 {
+                    f.write_str(", ")?;
+                }
+                f.write_str(&parser::escape(value_name))?;
+                if let Some(label) = label {
+                    f.write_str(" = ")?;
+                    f.write_str(&parser::escape(label))?;
+                }
             }
             f.write_str(";\n")?;
         }
@@ -360,50 +571,77 @@ fn get_value_to_id(values: &UVec<UVec<String>>) -> UVec<HashMap<String, usize>>
     result
 }
 
-fn open_file() -> Result<String, String> {
+fn open_path() -> Result<PathBuf, String> {
     let args: Vec<String> = args().collect();
     if args.len() != 2 {
         return Err("Not the correct amount of arguments provided!".into());
     }
 
-    let path = Path::new(args[1].as_str());
+    let path = PathBuf::from(args[1].as_str());
     if !path.is_file() {
         return Err("Provided file does not exist".into());
     }
 
-    read_to_string(path).map_err(|e| e.to_string())
+    Ok(path)
+}
+
+fn check_loaded(loader: parser::model::Loader) -> Result<parser::model::Loader, String> {
+    if loader.parameters.is_empty() {
+        Err("No parameters found".into())
+    } else {
+        Ok(loader)
+    }
 }
 
 /// Parse the given `str` and return the unconstrained [SUT].
+///
+/// Included files are resolved relative to the current directory.
 pub fn parse_unconstrained(text: &str) -> Result<SUT<usize, usize>, String> {
-    Ok(SUT::new(parser::parameters::parse(text)?.1))
+    Ok(SUT::new(check_loaded(Loader::new(false).load_text(text)?)?.parameters))
+}
+
+/// Parse the file at the given path and return the unconstrained [SUT].
+///
+/// Included files are resolved relative to the directory of the file.
+pub fn parse_unconstrained_path(path: &Path) -> Result<SUT<usize, usize>, String> {
+    Ok(SUT::new(check_loaded(Loader::new(false).load_path(path)?)?.parameters))
 }
 
 /// Parse a file and return the unconstrained [SUT].
 ///
 /// The path to the file is retrieved from the commandline arguments, so these cannot be used for anything else if you use this method.
 pub fn parse_unconstrained_file() -> Result<SUT<usize, usize>, String> {
-    parse_unconstrained(open_file()?.as_str())
+    parse_unconstrained_path(&open_path()?)
 }
 
 /// Parse the given `str` and return the constrained SUT.
+///
+/// Included files are resolved relative to the current directory.
 pub fn parse_constrained(text: &str) -> Result<ConstrainedSUT<usize, usize>, String> {
-    let (text, parameters) = parser::parameters::parse(text)?;
-    Ok(ConstrainedSUT::new(parameters, parser::constraints::parse(text)?))
+    let loader = check_loaded(Loader::new(true).load_text(text)?)?;
+    ConstrainedSUT::new(loader.parameters, loader.constraints)
+}
+
+/// Parse the file at the given path and return the [ConstrainedSUT].
+///
+/// Included files are resolved relative to the directory of the file.
+pub fn parse_constrained_path(path: &Path) -> Result<ConstrainedSUT<usize, usize>, String> {
+    let loader = check_loaded(Loader::new(true).load_path(path)?)?;
+    ConstrainedSUT::new(loader.parameters, loader.constraints)
 }
 
 /// Parse a file and return the [ConstrainedSUT].
 ///
 /// The path to the file is retrieved from the commandline arguments, so these cannot be used for anything else if you use this method.
 pub fn parse_constrained_file() -> Result<ConstrainedSUT<usize, usize>, String> {
-    parse_constrained(open_file()?.as_str())
+    parse_constrained_path(&open_path()?)
 }
 
 /// Parse a file and return the number of parameters found.
 ///
 /// The path to the file is retrieved from the commandline arguments, so these cannot be used for anything else if you use this method.
 pub fn get_parameter_count() -> Result<usize, String> {
-    Ok(parser::parameters::parse(open_file()?.as_str())?.1.len())
+    Ok(Loader::new(false).load_path(&open_path()?)?.parameters.len())
 }
 
 /// Parse a file and return the levels of the parameters in descending order.
@@ -412,7 +650,7 @@ pub fn get_parameter_count() -> Result<usize, String> {
 ///
 /// The path to the file is retrieved from the commandline arguments, so these cannot be used for anything else if you use this method.
 pub fn get_parameter_levels() -> Result<Vec<usize>, String> {
-    let mut levels: Vec<usize> = parser::parameters::parse(open_file()?.as_str())?.1.into_iter().map(|p| p.values.len()).collect();
+    let mut levels: Vec<usize> = Loader::new(false).load_path(&open_path()?)?.parameters.into_iter().map(|p| p.values.len()).collect();
     if cfg!(not(feature="no-sort")) {
         levels.sort_unstable_by_key(|v| !v);
     }
@@ -423,9 +661,7 @@ pub fn get_parameter_levels() -> Result<Vec<usize>, String> {
 ///
 /// The path to the file is retrieved from the commandline arguments, so these cannot be used for anything else if you use this method.
 pub fn get_constraint_count() -> Result<usize, String> {
-    Ok(parser::constraints::parse(
-        parser::parameters::parse(open_file()?.as_str())?.0
-    )?.len())
+    Ok(Loader::new(true).load_path(&open_path()?)?.constraints.len())
 }
 
 #[cfg(test)]
@@ -6,7 +6,7 @@
 
 use common::{u_vec, UVec};
 
-use crate::parse_constrained;
+use crate::{parse_constrained, ParameterOrder};
 
 #[test]
 fn test_empty() {
@@ -71,6 +71,7 @@ fn test_normal_multiple_sorted() {
         Ok(obj) => {
             assert_eq!(obj.sub_sut.parameters, u_vec![3, 2]);
             assert_eq!(obj.sub_sut.parameter_names, u_vec!["p2".to_string(), "p1".to_string()]);
+            assert_eq!(obj.sub_sut.declaration_order, u_vec![1, 0]);
             assert_eq!(obj.sub_sut.values, u_vec![u_vec![
                 "v2".to_string(), "4".to_string(), "true".to_string(),
             ], u_vec![
@@ -88,6 +89,7 @@ fn test_normal_multiple_unsorted() {
         Ok(obj) => {
             assert_eq!(obj.sub_sut.parameters, u_vec![2, 3]);
             assert_eq!(obj.sub_sut.parameter_names, u_vec!["p1".to_string(), "p2".to_string()]);
+            assert_eq!(obj.sub_sut.declaration_order, u_vec![0, 1]);
             assert_eq!(obj.sub_sut.values, u_vec![u_vec![
                 "v1".to_string(), "3".to_string(),
             ], u_vec![
@@ -97,3 +99,128 @@ fn test_normal_multiple_unsorted() {
         Err(e) => panic!("Result for a simple line should not fail: {:?}", e),
     }
 }
+
+#[cfg(not(feature = "no-sort"))]
+#[test]
+fn test_range_sorted() {
+    match parse_constrained("p1 : v1, 3;\n timeout: int[0..3600] partitions {0, 1..59, 3600};") {
+        Ok(obj) => {
+            assert_eq!(obj.sub_sut.parameters, u_vec![5, 2]);
+            assert_eq!(obj.sub_sut.parameter_names, u_vec!["timeout".to_string(), "p1".to_string()]);
+            assert_eq!(obj.sub_sut.values[0], u_vec![
+                "0".to_string(), "1".to_string(), "30".to_string(), "59".to_string(), "3600".to_string(),
+            ]);
+            assert_eq!(obj.sub_sut.value_classes, u_vec![Some(u_vec![
+                "0".to_string(), "1..59".to_string(), "1..59".to_string(), "1..59".to_string(), "3600".to_string(),
+            ]), None]);
+        }
+        Err(e) => panic!("Result for a range should not fail: {:?}", e),
+    }
+}
+
+#[test]
+fn test_quoted_names_and_labels() {
+    let text = "# The operating system.\n\"operating system\": win10 = \"Windows 10\", linux, \"mac \\\"os\\\"\";\n\
+                version: 1.2.3, 2.0; // Plain names.\n";
+    match parse_constrained(text) {
+        Ok(obj) => {
+            assert_eq!(obj.sub_sut.parameter_names, u_vec!["operating system".to_string(), "version".to_string()]);
+            assert_eq!(obj.sub_sut.values[0], u_vec!["win10".to_string(), "linux".to_string(), "mac \"os\"".to_string()]);
+            assert_eq!(obj.sub_sut.value_labels[0], u_vec![Some("Windows 10".to_string()), None, None]);
+            assert_eq!(obj.sub_sut.display_value(0, 0), Some("Windows 10"));
+            assert_eq!(obj.sub_sut.display_value(1, 0), Some("1.2.3"));
+            assert_eq!(obj.sub_sut.display_value(1, 2), None);
+        }
+        Err(e) => panic!("Quoted names should be accepted: {:?}", e),
+    }
+}
+
+#[test]
+fn test_duplicate_labels() {
+    assert!(parse_constrained("p: a = b, b;").is_err());
+    assert!(parse_constrained("p: a = x, b = x;").is_err());
+    assert!(parse_constrained("p: a, a;").is_err());
+}
+
+#[test]
+fn test_unusable_constraints() {
+    let error = parse_constrained("p1: a, b; $assert p2 = a;").err().unwrap();
+    assert!(error.starts_with("Unknown parameter p2"), "{}", error);
+    let error = parse_constrained("p1: a, b; $assert p1 = c;").err().unwrap();
+    assert!(error.starts_with("Unknown value c of parameter p1"), "{}", error);
+    let error = parse_constrained("p1: 1, 2; $assert p1 + p2 < 3;").err().unwrap();
+    assert!(error.starts_with("Unknown parameter p2"), "{}", error);
+    let error = parse_constrained("p1: 1, 2; p2: 1, two; $assert p1 <= p2;").err().unwrap();
+    assert!(error.ends_with("uses parameter p2, which has the value two that is not an integer"), "{}", error);
+
+    assert!(parse_constrained("p1: a, b; p2: a, c; $assert p1 = p2;").is_ok());
+    assert!(parse_constrained("p1: 1, 2; p2: -1, 3; $assert p1 <= p2 + 1;").is_ok());
+}
+
+#[test]
+fn test_debug_round_trip() {
+    let text = "\"operating system\": win10 = \"Windows 10\", \"a\\\\b\", \"say \\\"hi\\\"\";\n\
+                version: 1.2.3, \"2 0\" = Two;\n\
+                cores: 1, 2, 4;\n\
+                $assert !(\"operating system\"=\"a\\\\b\" && version=\"2 0\");\n\
+                $assert cores + 1 >= 2;\n";
+    let first = format!("{:?}", parse_constrained(text).unwrap());
+    let second = format!("{:?}", parse_constrained(&first).unwrap());
+    assert_eq!(first, second);
+    assert!(first.contains("\"operating system\": win10 = \"Windows 10\", \"a\\\\b\", \"say \\\"hi\\\"\";"), "{}", first);
+}
+
+#[test]
+fn test_fingerprint() {
+    let sut = parse_constrained("p1: v1, v2; p2: v1, v2; $assert p1 = v1 => p2 = v2;").unwrap();
+    assert_eq!(sut.fingerprint(), parse_constrained("p1: v1, v2;\np2: v1, v2;\n$assert p1 = v1 => p2 = v2;").unwrap().fingerprint());
+    assert_ne!(sut.fingerprint(), sut.sub_sut.fingerprint());
+    assert_ne!(sut.fingerprint(), parse_constrained("p1: v1, v2; p2: v1, v2; $assert p1 = v1 => p2 = v1;").unwrap().fingerprint());
+    assert_ne!(sut.sub_sut.fingerprint(), parse_constrained("p1: v1, v2; p2: v1, v3;").unwrap().sub_sut.fingerprint());
+    assert_ne!(parse_constrained("p1: v1v2;").unwrap().sub_sut.fingerprint(), parse_constrained("p1v1: v2;").unwrap().sub_sut.fingerprint());
+
+    let fingerprint = |model: &str| parse_constrained(model).unwrap().sub_sut.fingerprint();
+    assert_ne!(fingerprint("p1: v1, v2;"), fingerprint("p1: v1 = \"one\", v2;"));
+    assert_ne!(fingerprint("p1: v1 = \"one\", v2;"), fingerprint("p1: v1 = \"two\", v2;"));
+    assert_ne!(fingerprint("t: 0, 1, 2;"), fingerprint("t: int[0..2];"));
+}
+
+#[test]
+fn test_parameter_order() {
+    assert_eq!(ParameterOrder::parse("constraints"), Ok(ParameterOrder::ConstraintDensity));
+    assert_eq!(ParameterOrder::parse("random:42"), Ok(ParameterOrder::Random(42)));
+    assert_eq!(ParameterOrder::parse("list:c, a"), Ok(ParameterOrder::List(vec!["c".to_string(), "a".to_string()])));
+    assert!(ParameterOrder::parse("sorted").is_err());
+    assert!(ParameterOrder::parse("random:x").is_err());
+    assert_eq!(ParameterOrder::parse("list:\"Operating system\",\"a, b\""), Ok(ParameterOrder::List(vec!["Operating system".to_string(), "a, b".to_string()])));
+    assert!(ParameterOrder::parse("list:").is_err());
+    assert!(ParameterOrder::parse("list:a,,b").is_err());
+
+    let text = "a: 1, 2; b: 1, 2, 3; c: 1, 2, 3, 4; d: 1, 2, 3; $assert a = 1 => d = 2; $assert d + a < 3;  $assert b = c;";
+    let names = |order: ParameterOrder| {
+        let mut sut = parse_constrained(text).unwrap();
+        sut.order_parameters(&order)?;
+        let declared: Vec<&str> = sut.sub_sut.declaration_order.iter().map(|&id| sut.sub_sut.parameter_names[id].as_str()).collect();
+        assert_eq!(declared, vec!["a", "b", "c", "d"]);
+        assert!(sut.sub_sut.parameter_names.iter().enumerate().all(|(id, name)| sut.parameter_to_id[name] == id));
+        Ok::<String, String>(sut.sub_sut.parameter_names.iter().map(|name| name.as_str()).collect())
+    };
+    assert_eq!(names(ParameterOrder::Descending), Ok("cbda".to_string()));
+    assert_eq!(names(ParameterOrder::Ascending), Ok("abdc".to_string()));
+    assert_eq!(names(ParameterOrder::Declaration), Ok("abcd".to_string()));
+    assert_eq!(names(ParameterOrder::ConstraintDensity), Ok("dacb".to_string()));
+    assert_eq!(names(ParameterOrder::List(vec!["d".to_string(), "a".to_string()])), Ok("dacb".to_string()));
+    assert!(names(ParameterOrder::List(vec!["e".to_string()])).is_err());
+    assert!(names(ParameterOrder::List(vec!["a".to_string(), "a".to_string()])).is_err());
+
+    let mut sut = parse_constrained("\"p, 1\": v1, v2; p2: v1, v2, v3;").unwrap();
+    sut.order_parameters(&ParameterOrder::parse("list:\"p, 1\"").unwrap()).unwrap();
+    assert_eq!(sut.sub_sut.parameter_names.iter().map(|name| name.as_str()).collect::<Vec<_>>(), vec!["p, 1", "p2"]);
+    assert!(sut.order_parameters(&ParameterOrder::parse("list:\"p\", 1").unwrap()).is_err());
+
+    let random = names(ParameterOrder::Random(7)).unwrap();
+    assert_eq!(names(ParameterOrder::Random(7)), Ok(random.clone()));
+    let mut sorted: Vec<char> = random.chars().collect();
+    sorted.sort_unstable();
+    assert_eq!(sorted, vec!['a', 'b', 'c', 'd']);
+}
@@ -0,0 +1,98 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module contains the strategies to order the parameters of a [SUT] before the generation.
+
+use std::cmp::Reverse;
+
+use common::{Number, split_mix};
+
+use crate::{parser, SUT};
+
+/// The strategy used to order the parameters of a [SUT], see [SUT::order_parameters].
+///
+/// Parameters which are equal according to the strategy keep the order in which they were declared.
+/// The order of the columns in the output does not depend on the strategy, see [SUT::declaration_order].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParameterOrder {
+    /// Descending level, on which IPOG usually generates the smallest MCA.
+    Descending,
+
+    /// Ascending level.
+    Ascending,
+
+    /// The order in which the parameters were declared in the model.
+    Declaration,
+
+    /// The parameters used in the most constraints first, followed by descending level.
+    ConstraintDensity,
+
+    /// The listed parameters first, followed by the others in descending level.
+    List(Vec<String>),
+
+    /// A random order determined by the seed.
+    Random(u64),
+}
+
+impl ParameterOrder {
+    /// Parse the strategy as written on the commandline.
+    ///
+    /// This is one of `descending`, `ascending`, `declaration`, `constraints`, `random:<seed>` or `list:<name>,<name>,...`.
+    /// The names in the list are written as in a model, so a name with a comma or space should be quoted.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text.split_once(':') {
+            None => match text {
+                "descending" => Ok(Self::Descending),
+                "ascending" => Ok(Self::Ascending),
+                "declaration" => Ok(Self::Declaration),
+                "constraints" => Ok(Self::ConstraintDensity),
+                _ => Err(format!("Unknown parameter order {}, expected descending, ascending, declaration, constraints, random:<seed> or list:<names>.", text)),
+            },
+            Some(("random", seed)) => seed.trim().parse().map(Self::Random)
+                .map_err(|_| format!("The seed of the random parameter order should be a number, not {}.", seed)),
+            Some(("list", names)) => parser::parse_names(names).map(Self::List)
+                .map_err(|e| format!("Invalid parameter order list: {}", e)),
+            Some(_) => Err(format!("Unknown parameter order {}, expected random:<seed> or list:<names>.", text)),
+        }
+    }
+
+    /// Returns the parameter ids of the [SUT] in the requested order.
+    ///
+    /// The `densities` contain the number of constraints which use each parameter.
+    pub(crate) fn permutation<ValueId: Number, ParameterId: Number>(
+        &self, sut: &SUT<ValueId, ParameterId>, densities: &[usize],
+    ) -> Result<Vec<usize>, String> {
+        let mut result: Vec<usize> = sut.declaration_order.iter().copied().collect();
+        match self {
+            Self::Descending => result.sort_by_key(|&id| Reverse(sut.parameters[id].as_usize())),
+            Self::Ascending => result.sort_by_key(|&id| sut.parameters[id].as_usize()),
+            Self::Declaration => {}
+            Self::ConstraintDensity => result.sort_by_key(|&id| (Reverse(densities[id]), Reverse(sut.parameters[id].as_usize()))),
+            Self::List(names) => {
+                let mut listed = Vec::with_capacity(names.len());
+                for name in names.iter() {
+                    let id = sut.parameter_names.iter().position(|parameter| parameter == name)
+                        .ok_or_else(|| format!("Unknown parameter {} in the parameter order.", name))?;
+                    if listed.contains(&id) {
+                        return Err(format!("The parameter {} is listed twice in the parameter order.", name));
+                    }
+                    listed.push(id);
+                }
+                result.retain(|id| !listed.contains(id));
+                result.sort_by_key(|&id| Reverse(sut.parameters[id].as_usize()));
+                listed.append(&mut result);
+                result = listed;
+            }
+            Self::Random(seed) => {
+                let mut state = *seed;
+                for index in (1..result.len()).rev() {
+                    result.swap(index, (split_mix(&mut state) % (index as u64 + 1)) as usize);
+                }
+            }
+        }
+        Ok(result)
+    }
+}
@@ -4,19 +4,17 @@
 // MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
 // modified, or distributed except according to those terms.
 
-use nom::{delimited, exact, IResult, named, tag};
+use nom::{delimited, IResult, named, tag};
 use nom::branch::alt;
-use nom::bytes::complete::{is_a, tag};
-use nom::combinator::opt;
+use nom::bytes::complete::tag;
+use nom::character::complete::multispace0;
+use nom::combinator::{not as not_followed_by, opt};
 use nom::error::{ErrorKind, Error};
-use nom::multi::many0;
-use nom::sequence::preceded;
+use nom::sequence::{preceded, terminated};
 
 use crate::expr;
 
-use super::{e2s, read_value};
-
-named!(eof<&str, Option<&str>>, exact!(opt(is_a(" \t\r\n"))));
+use super::read_value;
 
 fn not(text: &str) -> IResult<&str, Box<dyn expr::Expr>> {
     let (text, sub) = preceded(tag("!"), parse_expr)(text)?;
@@ -30,9 +28,89 @@ fn eq(text: &str) -> IResult<&str, Box<dyn expr::Expr>> {
     Ok((text, Box::new(expr::Eq { parameter: parameter.to_string(), value: value.to_string() })))
 }
 
+/// Returns a failure at the text if an integer in a relation overflows.
+fn too_large<T>(text: &str) -> IResult<&str, T> {
+    Err(nom::Err::Failure(Error::new(text, ErrorKind::TooLarge)))
+}
+
+/// Parses an integer, a parameter, or an integer times a parameter.
+fn atom(input: &str) -> IResult<&str, expr::Linear> {
+    let (text, first) = read_value(input)?;
+    let (text, second) = opt(preceded(tag("*"), read_value))(text)?;
+    let mut result = expr::Linear::default();
+    match (first.parse::<i64>(), second) {
+        (Ok(coefficient), Some(parameter)) => match parameter.parse::<i64>() {
+            Ok(constant) => match coefficient.checked_mul(constant) {
+                Some(product) => result.constant = product,
+                None => return too_large(input),
+            },
+            Err(_) => result.terms.push((coefficient, parameter.to_string())),
+        },
+        (Ok(constant), None) => result.constant = constant,
+        (Err(_), None) => result.terms.push((1, first.to_string())),
+        (Err(_), Some(_)) => return Err(nom::Err::Error(Error::new(text, ErrorKind::Digit))),
+    }
+    Ok((text, result))
+}
+
+/// Parses a sum of [atom]s.
+fn linear(text: &str) -> IResult<&str, expr::Linear> {
+    let (mut text, mut result) = atom(text)?;
+    loop {
+        let (rest, sign) = match alt((bin_op_sub("+", 1), bin_op_sub("-", -1)))(text.trim_start()) {
+            Ok(result) => result,
+            Err(_) => return Ok((text, result)),
+        };
+        let (rest, other) = atom(rest)?;
+        for (coefficient, parameter) in other.terms.into_iter() {
+            match coefficient.checked_mul(sign) {
+                Some(coefficient) => result.terms.push((coefficient, parameter)),
+                None => return too_large(text),
+            }
+        }
+        match other.constant.checked_mul(sign).and_then(|constant| result.constant.checked_add(constant)) {
+            Some(constant) => result.constant = constant,
+            None => return too_large(text),
+        }
+        text = rest;
+    }
+}
+
+fn relation_op(text: &str) -> IResult<&str, expr::ROp> {
+    alt((
+        bin_op_sub("<=", expr::ROp::Le),
+        bin_op_sub(">=", expr::ROp::Ge),
+        bin_op_sub("!=", expr::ROp::Ne),
+        bin_op_sub("<", expr::ROp::Lt),
+        bin_op_sub(">", expr::ROp::Gt),
+        terminated(bin_op_sub("=", expr::ROp::Eq), not_followed_by(tag(">"))),
+    ))(text)
+}
+
+/// Parses a comparison between sums of parameters and integers.
+///
+/// A comparison of a parameter with a single parameter or value using `=` results in an [expr::Eq],
+/// as it is not known yet whether the right-hand side is a value or a parameter.
+fn relation(text: &str) -> IResult<&str, Box<dyn expr::Expr>> {
+    let (text, left) = linear(text)?;
+    let (text, op) = relation_op(text)?;
+    let (text, _) = multispace0(text)?;
+    let (text, right_text) = nom::combinator::recognize(linear)(text)?;
+    let (_, right) = linear(right_text)?;
+
+    if op == expr::ROp::Eq && left.constant == 0 && left.terms.len() == 1 && left.terms[0].0 == 1 {
+        if let Ok((rest, value)) = read_value(right_text) {
+            if rest.is_empty() {
+                return Ok((text, Box::new(expr::Eq { parameter: left.terms[0].1.clone(), value: value.to_string() })));
+            }
+        }
+    }
+    Ok((text, Box::new(expr::Relation { left, op, right })))
+}
+
 named!(par<&str, Box<dyn expr::Expr>>, delimited!(tag!("("), parse_expr, tag!(")")));
 
-fn bin_op_sub<'a>(op_tag: &'a str, op: expr::BOp) -> impl Fn(&'a str) -> IResult<&'a str, expr::BOp> {
+fn bin_op_sub<'a, Op: Copy>(op_tag: &'a str, op: Op) -> impl Fn(&'a str) -> IResult<&'a str, Op> {
     move |text: &'a str| {
         let (rest, _) = tag(op_tag)(text)?;
         Ok((rest, op))
@@ -44,6 +122,7 @@ fn parse_expr(text: &str) -> IResult<&str, Box<dyn expr::Expr>> {
     let (text, left) = alt((
         not,
         par,
+        relation,
         eq,
     ))(text)?;
     let text = text.trim_start();
@@ -66,15 +145,57 @@ fn parse_expr(text: &str) -> IResult<&str, Box<dyn expr::Expr>> {
     }
 }
 
-fn parse_constraint(text: &str) -> IResult<&str, Box<dyn expr::Expr>> {
+pub(crate) fn parse_constraint(text: &str) -> IResult<&str, Box<dyn expr::Expr>> {
     let (text, _) = tag("$assert ")(text.trim_start())?;
     let (text, result) = parse_expr(text)?;
     let (text, _) = tag(";")(text)?;
     Ok((text, result))
 }
 
-pub(crate) fn parse(text: &str) -> Result<Vec<Box<dyn expr::Expr>>, String> {
-    let (text, constraints) = many0(parse_constraint)(text).map_err(e2s)?;
-    eof(text).map_err(e2s)?;
-    Ok(constraints)
+#[cfg(test)]
+mod constraint_tests {
+    use super::*;
+
+    fn check(text: &str, expected: &str) {
+        let (rest, result) = parse_expr(text).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(format!("{:?}", result), expected);
+    }
+
+    #[test]
+    fn test_parse_eq() {
+        check("p1=v1", "p1=v1");
+        check(" p1 = 3 ", "p1=3");
+        check("p1 = p2", "p1=p2");
+        check("(p1 = v1) => (p2 = -v2)", "(p1=v1 => p2=-v2)");
+        check("p1 = v1 => p2 = v2", "(p1=v1 => p2=v2)");
+        check("\"operating system\" = \"Windows 10\"", "\"operating system\"=\"Windows 10\"");
+        check("version=1.2.3 || version = \"2 0\"", "(version=1.2.3 || version=\"2 0\")");
+    }
+
+    #[test]
+    fn test_parse_relation() {
+        check("cores <= threads", "cores <= threads");
+        check("mem >= 2 * vms", "mem >= 2 * vms");
+        check("mem>=2*vms+1", "mem >= 2 * vms + 1");
+        check("a + b - 3 * c != 4", "a + b - 3 * c != 4");
+        check("p1 = p2 + 1", "p1 = p2 + 1");
+        check("2 * p1 = p2", "2 * p1 = p2");
+        check("a < b && b > 3", "(a < b && b > 3)");
+        check("!(a < b)", "!(a < b)");
+        assert!(parse_constraint("$assert a <= ;").is_err());
+        assert!(parse_constraint("$assert a * 2 <= b;").is_err());
+
+        check("a <= -9223372036854775808", "a <= -9223372036854775808");
+        check("a <= 3037000499 * 3037000499", "a <= 9223372030926249001");
+        check("a - 9223372036854775807 - 1 <= -9223372036854775808 * b", "a - 9223372036854775808 <= -9223372036854775808 * b");
+        for text in [
+            "$assert a <= 3037000500 * 3037000500;",
+            "$assert a - -9223372036854775808 * b <= 0;",
+            "$assert a <= 9223372036854775807 + 1;",
+            "$assert a <= 0 - -9223372036854775808;",
+        ] {
+            assert!(matches!(parse_constraint(text), Err(nom::Err::Failure(Error { code: ErrorKind::TooLarge, .. }))), "{}", text);
+        }
+    }
 }
@@ -4,45 +4,194 @@
 // MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
 // modified, or distributed except according to those terms.
 
-use nom::bytes::complete::{is_a, take_while1};
-use nom::combinator::opt;
+use nom::branch::alt;
+use nom::bytes::complete::{is_a, tag, take_till, take_while1};
+use nom::character::complete::char;
+use nom::combinator::{all_consuming, map, opt, recognize};
+use nom::error::{Error, ErrorKind};
+use nom::multi::separated_list1;
+use nom::sequence::pair;
 use nom::IResult;
+use std::borrow::Cow;
 use std::fmt::Debug;
 
 pub(crate) mod parameters;
 pub(crate) mod constraints;
+pub(crate) mod model;
 
 fn e2s<T: Debug>(e: T) -> String {
     format!("{:?}", e)
 }
 
 fn is_value_char(input: char) -> bool {
-    match input {
-        'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => true,
-        _ => false,
+    input.is_alphanumeric() || matches!(input, '-' | '_' | '.')
+}
+
+/// Returns the name as it should be written in a model, quoted and escaped if it cannot be read as a plain value.
+pub(crate) fn escape(name: &str) -> Cow<'_, str> {
+    if !name.is_empty() && name.chars().all(is_value_char) {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\"")))
+    }
+}
+
+/// Replace the `#` and `//` comments with spaces, the line numbers and positions of the remaining text are unchanged.
+///
+/// Comment markers inside quoted names are not considered.
+pub(crate) fn strip_comments(text: &str) -> Cow<'_, str> {
+    if !text.contains('#') && !text.contains("//") {
+        return Cow::Borrowed(text);
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        if quoted {
+            result.push(c);
+            match c {
+                '"' | '\n' => quoted = false,
+                '\\' => if let Some(&next) = chars.peek() {
+                    if next != '\n' {
+                        result.push(next);
+                        chars.next();
+                    }
+                },
+                _ => {}
+            }
+        } else if c == '#' || (c == '/' && chars.peek() == Some(&'/')) {
+            result.push(' ');
+            while let Some(&next) = chars.peek() {
+                if next == '\n' {
+                    break;
+                }
+                result.push(if next.is_whitespace() { next } else { ' ' });
+                chars.next();
+            }
+        } else {
+            quoted = c == '"';
+            result.push(c);
+        }
+    }
+    Cow::Owned(result)
+}
+
+/// Read a quoted name, in which `\"` and `\\` represent a quote and a backslash.
+fn read_quoted(input: &str) -> IResult<&str, Cow<'_, str>> {
+    let (input, _) = char('"')(input)?;
+    let mut result = String::new();
+    let mut chars = input.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Ok((&input[index + 1..], Cow::Owned(result))),
+            '\\' => match chars.next() {
+                Some((_, c @ ('"' | '\\'))) => result.push(c),
+                _ => return Err(nom::Err::Failure(Error::new(&input[index..], ErrorKind::Escaped))),
+            },
+            '\n' => break,
+            c => result.push(c),
+        }
+    }
+    Err(nom::Err::Failure(Error::new(input, ErrorKind::Char)))
+}
+
+/// Skip a statement up to and including the `;` which ends it, the quoted names in it may contain a `;`.
+///
+/// The remainder of the text is skipped if the statement does not end.
+fn skip_statement(mut input: &str) -> IResult<&str, ()> {
+    loop {
+        let (rest, _) = take_till(|c| c == '"' || c == ';')(input)?;
+        match rest.strip_prefix(';') {
+            Some(rest) => return Ok((rest, ())),
+            None if rest.is_empty() => return Ok((rest, ())),
+            None => input = read_quoted(rest)?.0,
+        }
     }
 }
 
-fn read_value(input: &str) -> IResult<&str, &str> {
+/// Parse a list of plain or quoted names separated by commas, as in a model.
+pub(crate) fn parse_names(text: &str) -> Result<Vec<String>, String> {
+    all_consuming(separated_list1(char(','), read_value))(text)
+        .map(|(_, names)| names.into_iter().map(Cow::into_owned).collect())
+        .map_err(|_| format!("Expected a list of names separated by commas, not {}.", text))
+}
+
+/// Read a reference to a value set, including the leading `$`.
+fn read_reference(input: &str) -> IResult<&str, &str> {
     let (input, _) = opt(is_a(" \t\r\n"))(input)?;
-    let (input, result) = take_while1(is_value_char)(input)?;
+    let (input, result) = recognize(pair(tag("$"), take_while1(is_value_char)))(input)?;
+    let (input, _) = opt(is_a(" \t\r\n"))(input)?;
+    Ok((input, result))
+}
+
+/// Read a plain or a quoted name of a parameter or value.
+fn read_value(input: &str) -> IResult<&str, Cow<'_, str>> {
+    let (input, _) = opt(is_a(" \t\r\n"))(input)?;
+    let (input, result) = alt((read_quoted, map(take_while1(is_value_char), Cow::Borrowed)))(input)?;
     let (input, _) = opt(is_a(" \t\r\n"))(input)?;
     Ok((input, result))
 }
 
 #[cfg(test)]
 mod parser_tests {
-    use super::read_value;
+    use super::{escape, parse_names, read_value, skip_statement, strip_comments};
 
     #[test]
     fn test_value_parse() {
-        assert_eq!(read_value("a"), Ok(("", "a")));
-        assert_eq!(read_value("-a"), Ok(("", "-a")));
-        assert_eq!(read_value("test_this"), Ok(("", "test_this")));
-        assert_eq!(read_value(" a b "), Ok(("b ", "a")));
+        assert_eq!(read_value("a"), Ok(("", "a".into())));
+        assert_eq!(read_value("-a"), Ok(("", "-a".into())));
+        assert_eq!(read_value("test_this"), Ok(("", "test_this".into())));
+        assert_eq!(read_value(" a b "), Ok(("b ", "a".into())));
+        assert_eq!(read_value("1.2.3,"), Ok((",", "1.2.3".into())));
+        assert_eq!(read_value("Zürich;"), Ok((";", "Zürich".into())));
         assert!(read_value(" ").is_err());
         assert!(read_value("").is_err());
     }
+
+    #[test]
+    fn test_quoted_value_parse() {
+        assert_eq!(read_value(" \"Windows 10\" ,"), Ok((",", "Windows 10".into())));
+        assert_eq!(read_value("\"a/b, c\";"), Ok((";", "a/b, c".into())));
+        assert_eq!(read_value("\"say \\\"hi\\\" \\\\\""), Ok(("", "say \"hi\" \\".into())));
+        assert_eq!(read_value("\"\""), Ok(("", "".into())));
+        assert!(read_value("\"open").is_err());
+        assert!(read_value("\"a\nb\"").is_err());
+        assert!(read_value("\"\\n\"").is_err());
+    }
+
+    #[test]
+    fn test_escape() {
+        for name in ["a", "1.2.3", "Windows 10", "a/b", "say \"hi\" \\", "", "$OS", "x,y", "é"] {
+            let escaped = escape(name);
+            assert_eq!(read_value(&escaped), Ok(("", name.into())), "{}", escaped);
+        }
+        assert_eq!(escape("a_b"), "a_b");
+        assert_eq!(escape("a b"), "\"a b\"");
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        assert_eq!(strip_comments("a: b; # c\nd: e; // f\n"), "a: b;    \nd: e;     \n");
+        assert_eq!(strip_comments("a: \"#1\", \"//\\\"#\"; #x"), "a: \"#1\", \"//\\\"#\";   ");
+        assert_eq!(strip_comments("a: b;"), "a: b;");
+    }
+
+    #[test]
+    fn test_parse_names() {
+        assert_eq!(parse_names(" c, a "), Ok(vec!["c".to_string(), "a".to_string()]));
+        assert_eq!(parse_names("\"a, b\",\"c:d\""), Ok(vec!["a, b".to_string(), "c:d".to_string()]));
+        for text in ["", "a,", ",a", "a,,b", "a b", "\"a"] {
+            assert!(parse_names(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_skip_statement() {
+        assert_eq!(skip_statement("$assert a = b; c: d;"), Ok((" c: d;", ())));
+        assert_eq!(skip_statement("$assert \"a;\" = \"\\\";\"; c: d;"), Ok((" c: d;", ())));
+        assert_eq!(skip_statement("$assert a = b"), Ok(("", ())));
+        assert!(skip_statement("$assert \"a; b: c;").is_err());
+    }
 }
 
 #[cfg(test)]
@@ -56,9 +205,8 @@ mod bench {
     fn bench_benchmarks(b: &mut Bencher) {
         for contents in Walker::new("./".into()) {
             b.iter(|| {
-                let (rest, parameters) = super::parameters::parse(&contents).unwrap();
-                let _constraints = super::constraints::parse(rest).unwrap();
-                assert_ne!(parameters.len(), 0);
+                let loader = super::model::Loader::new(true).load_text(&contents).unwrap();
+                assert_ne!(loader.parameters.len(), 0);
             })
         }
     }
@@ -0,0 +1,262 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use nom::bytes::complete::{is_not, tag};
+use nom::character::complete::{char, multispace0};
+use nom::IResult;
+use nom::sequence::delimited;
+
+use crate::{expr, TemporaryParameter};
+
+use super::{constraints, e2s, parameters, read_value, skip_statement, strip_comments};
+use super::parameters::{Definition, Entry};
+
+const INCLUDE_TAG: &str = "$include";
+const SET_TAG: &str = "$set";
+const ASSERT_TAG: &str = "$assert";
+const TEXT_NAME: &str = "<input>";
+
+fn parse_include(text: &str) -> IResult<&str, &str> {
+    let (text, _) = tag(INCLUDE_TAG)(text)?;
+    let (text, path) = delimited(multispace0, delimited(char('"'), is_not("\""), char('"')), multispace0)(text)?;
+    let (text, _) = tag(";")(text)?;
+    Ok((text, path))
+}
+
+fn parse_set(text: &str) -> IResult<&str, (String, Vec<Entry>)> {
+    let (text, _) = tag(SET_TAG)(text)?;
+    let (text, name) = read_value(text)?;
+    let (text, _) = tag("=")(text)?;
+    let (text, values) = parameters::parse_values(text)?;
+    let (text, _) = tag(";")(text)?;
+    Ok((text, (name.into_owned(), values)))
+}
+
+/// Loads a model consisting of the parameters and constraints in a text and all the files it includes.
+///
+/// Supports the following directives next to the parameters and constraints:
+///   * `$include "path/to/file.cocoa";` Load the parameters, constraints and value sets of another file.
+///     The path is relative to the directory of the file containing the directive.
+///     Each file is loaded at most once, including a file which is still being loaded is an error.
+///   * `$set NAME = v1, v2, v3;` Define a named set of values, which can be used in a list of values as `$NAME`.
+///
+/// The `#` and `//` comments are removed before parsing.
+pub(crate) struct Loader {
+    with_constraints: bool,
+    sets: HashMap<String, Vec<(String, Option<String>)>>,
+    stack: Vec<PathBuf>,
+    loaded: HashSet<PathBuf>,
+    pub(crate) parameters: Vec<TemporaryParameter>,
+    pub(crate) constraints: Vec<Box<dyn expr::Expr>>,
+}
+
+impl Loader {
+    /// Create a new [Loader]. The `$assert` statements are skipped without parsing them if `with_constraints` is false.
+    pub(crate) fn new(with_constraints: bool) -> Self {
+        Self {
+            with_constraints,
+            sets: HashMap::new(),
+            stack: vec![],
+            loaded: HashSet::new(),
+            parameters: vec![],
+            constraints: vec![],
+        }
+    }
+
+    /// Load the given text, included files are resolved relative to the current directory.
+    pub(crate) fn load_text(mut self, text: &str) -> Result<Self, String> {
+        self.load(text, TEXT_NAME, Path::new(""))?;
+        Ok(self)
+    }
+
+    /// Load the given file, included files are resolved relative to the directory of the file.
+    pub(crate) fn load_path(mut self, path: &Path) -> Result<Self, String> {
+        self.load_file(path)?;
+        Ok(self)
+    }
+
+    fn load_file(&mut self, path: &Path) -> Result<(), String> {
+        let canonical = path.canonicalize().map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+        if let Some(start) = self.stack.iter().position(|p| p == &canonical) {
+            let cycle: Vec<String> = self.stack[start..].iter().chain(Some(&canonical)).map(|p| p.display().to_string()).collect();
+            return Err(format!("Include cycle detected: {}", cycle.join(" -> ")));
+        }
+        if !self.loaded.insert(canonical.clone()) {
+            return Ok(());
+        }
+
+        let text = read_to_string(&canonical).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        self.stack.push(canonical.clone());
+        let result = self.load(&text, &path.display().to_string(), canonical.parent().unwrap_or(Path::new("")));
+        self.stack.pop();
+        result
+    }
+
+    fn load(&mut self, text: &str, name: &str, directory: &Path) -> Result<(), String> {
+        let text = strip_comments(text);
+        let text = text.as_ref();
+        let mut rest = text.trim_start();
+        while !rest.is_empty() {
+            let location = |rest: &str| format!("{}:{}", name, text[..text.len() - rest.len()].matches('\n').count() + 1);
+            rest = self.load_statement(rest, directory).map_err(|e| format!("{}: {}", location(rest), e))?.trim_start();
+        }
+        Ok(())
+    }
+
+    /// Load a single statement and return the remaining text.
+    fn load_statement<'a>(&mut self, text: &'a str, directory: &Path) -> Result<&'a str, String> {
+        if text.starts_with(INCLUDE_TAG) {
+            let (rest, path) = parse_include(text).map_err(e2s)?;
+            self.load_file(&directory.join(path))?;
+            Ok(rest)
+        } else if text.starts_with(SET_TAG) {
+            let (rest, (name, values)) = parse_set(text).map_err(e2s)?;
+            let values = self.expand(values)?;
+            if self.sets.insert(name.clone(), values).is_some() {
+                return Err(format!("The value set ${} is defined twice", name));
+            }
+            Ok(rest)
+        } else if text.starts_with(ASSERT_TAG) {
+            if self.with_constraints {
+                let (rest, constraint) = constraints::parse_constraint(text).map_err(e2s)?;
+                self.constraints.push(constraint);
+                Ok(rest)
+            } else {
+                Ok(skip_statement(text).map_err(e2s)?.0)
+            }
+        } else {
+            let (rest, definition) = parameters::parse_parameter(text).map_err(e2s)?;
+            let parameter = match definition {
+                Definition::Range(parameter) => parameter?,
+                Definition::List(name, entries) => {
+                    let (values, labels): (Vec<_>, Vec<_>) = self.expand(entries)?.into_iter().unzip();
+                    TemporaryParameter { name, values: values.into(), labels: labels.into(), classes: None }
+                }
+            };
+            check_unique(&parameter)?;
+            self.parameters.push(parameter);
+            Ok(rest)
+        }
+    }
+
+    /// Replace the references to value sets with the values and labels in these sets.
+    fn expand(&self, entries: Vec<Entry>) -> Result<Vec<(String, Option<String>)>, String> {
+        let mut result = Vec::with_capacity(entries.len());
+        for entry in entries.into_iter() {
+            match entry {
+                Entry::Value(value, label) => result.push((value, label)),
+                Entry::Reference(name) => {
+                    let set = self.sets.get(&name).ok_or_else(|| format!("Unknown value set ${}", name))?;
+                    result.extend(set.iter().cloned());
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Check that the values of the parameter can be told apart by both their names and the names used in the output.
+fn check_unique(parameter: &TemporaryParameter) -> Result<(), String> {
+    let mut names = HashSet::with_capacity(parameter.values.len());
+    let mut display_names = HashSet::with_capacity(parameter.values.len());
+    for (value, label) in parameter.values.iter().zip(parameter.labels.iter()) {
+        if !names.insert(value) {
+            return Err(format!("The value {} of parameter {} is defined twice", value, parameter.name));
+        }
+        let display_name = label.as_ref().unwrap_or(value);
+        if !display_names.insert(display_name) {
+            return Err(format!("The label {} of parameter {} is used for multiple values", display_name, parameter.name));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod model_tests {
+    use std::fs::{create_dir_all, write};
+
+    use super::*;
+
+    /// Write the files to a fresh directory in the temporary directory and return the path of the directory.
+    fn write_files(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("libreca-model-{}-{}", test, std::process::id()));
+        for (name, contents) in files {
+            let path = directory.join(name);
+            create_dir_all(path.parent().unwrap()).unwrap();
+            write(path, contents).unwrap();
+        }
+        directory
+    }
+
+    fn values(loader: &Loader) -> Vec<Vec<String>> {
+        loader.parameters.iter().map(|p| p.values.iter().cloned().collect()).collect()
+    }
+
+    #[test]
+    fn test_set() {
+        let loader = Loader::new(false).load_text("$set OS = linux, windows;\np1: $OS, mac;\np2: a, b;").unwrap();
+        assert_eq!(values(&loader), vec![vec!["linux", "windows", "mac"], vec!["a", "b"]]);
+    }
+
+    #[test]
+    fn test_unknown_set() {
+        let error = Loader::new(false).load_text("p1: a, b;\np2: $OS;").err().unwrap();
+        assert_eq!(error, "<input>:2: Unknown value set $OS");
+    }
+
+    #[test]
+    fn test_duplicate_set() {
+        assert!(Loader::new(false).load_text("$set OS = a;\n$set OS = b;").is_err());
+    }
+
+    #[test]
+    fn test_skip_constraints() {
+        let text = "p1: a, b;\n$assert p1=a || p1=b;";
+        assert_eq!(Loader::new(false).load_text(text).unwrap().constraints.len(), 0);
+        #[cfg(any(feature = "constraints-minisat", feature = "constraints-z3"))]
+        assert_eq!(Loader::new(true).load_text(text).unwrap().constraints.len(), 1);
+
+        let loader = Loader::new(false).load_text("p1: a, b;\n$assert \"p;2\" = \"c;\" => p1 = a;\n\"p;2\": \"c;\", d;").unwrap();
+        assert_eq!(values(&loader), vec![vec!["a", "b"], vec!["c;", "d"]]);
+    }
+
+    #[test]
+    fn test_include() {
+        let directory = write_files("include", &[
+            ("model.cocoa", "$include \"common/os.cocoa\";\n$include \"common/os.cocoa\";\nbrowser: $BROWSER;"),
+            ("common/os.cocoa", "$include \"browser.cocoa\";\nos: linux, windows;"),
+            ("common/browser.cocoa", "$set BROWSER = firefox, chrome;"),
+        ]);
+        let loader = Loader::new(false).load_path(&directory.join("model.cocoa")).unwrap();
+        assert_eq!(values(&loader), vec![vec!["linux", "windows"], vec!["firefox", "chrome"]]);
+    }
+
+    #[test]
+    fn test_include_cycle() {
+        let directory = write_files("cycle", &[
+            ("a.cocoa", "p1: a, b;\n$include \"b.cocoa\";"),
+            ("b.cocoa", "$include \"a.cocoa\";"),
+        ]);
+        let error = Loader::new(false).load_path(&directory.join("a.cocoa")).err().unwrap();
+        assert!(error.contains("Include cycle detected"), "{}", error);
+        assert!(error.contains("a.cocoa:2: "), "{}", error);
+        assert!(error.contains("b.cocoa:1: "), "{}", error);
+    }
+
+    #[test]
+    fn test_error_location() {
+        let directory = write_files("location", &[
+            ("model.cocoa", "p1: a, b;\n$include \"other.cocoa\";"),
+            ("other.cocoa", "p2: a, b;\n\np3 a, b;"),
+        ]);
+        let error = Loader::new(false).load_path(&directory.join("model.cocoa")).err().unwrap();
+        assert!(error.contains("other.cocoa:3: "), "{}", error);
+    }
+}
@@ -5,50 +5,193 @@
 // modified, or distributed except according to those terms.
 
 use nom::{named, separated_list1};
+use nom::branch::alt;
 use nom::bytes::complete::tag;
+use nom::character::complete::{char, digit1, multispace0};
+use nom::combinator::{map, opt, recognize};
+use nom::error::{Error, ErrorKind};
 use nom::IResult;
-use nom::multi::many1;
+use nom::sequence::{delimited, pair, preceded};
+
+use common::UVec;
 
 use crate::TemporaryParameter;
 
-use super::{e2s, read_value};
+use super::{read_reference, read_value};
+
+/// A single entry in a list of values.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Entry {
+    /// A value with an optional display label.
+    Value(String, Option<String>),
+
+    /// A reference to a value set, without the leading `$`.
+    Reference(String),
+}
+
+/// The definition of a parameter, the references in a list of values are resolved by the [super::model::Loader].
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub(crate) enum Definition {
+    /// A parameter declared as a numeric range, which is expanded immediately.
+    Range(Result<TemporaryParameter, String>),
+
+    /// A parameter declared with a name and a list of values.
+    List(String, Vec<Entry>),
+}
+
+/// Parses a value with an optional display label, written as `value = "Label"`.
+fn parse_entry(text: &str) -> IResult<&str, Entry> {
+    let (text, value) = read_value(text)?;
+    let (text, label) = opt(preceded(tag("="), read_value))(text)?;
+    Ok((text, Entry::Value(value.into_owned(), label.map(|l| l.into_owned()))))
+}
+
+fn parse_entry_or_reference(text: &str) -> IResult<&str, Entry> {
+    alt((map(read_reference, |r: &str| Entry::Reference(r[1..].to_string())), parse_entry))(text)
+}
+
+named!(pub(crate) parse_values<&str, Vec<Entry>>, separated_list1!(tag(","), parse_entry_or_reference));
+
+/// An inclusive interval of integers, a single integer is represented as an interval of length one.
+type Interval = (i64, i64);
+
+fn parse_integer(text: &str) -> IResult<&str, i64> {
+    let (text, number) = delimited(multispace0, recognize(pair(opt(char('-')), digit1)), multispace0)(text)?;
+    match number.parse() {
+        Ok(number) => Ok((text, number)),
+        Err(_) => Err(nom::Err::Failure(Error::new(number, ErrorKind::Digit))),
+    }
+}
+
+fn parse_interval(text: &str) -> IResult<&str, Interval> {
+    let (text, start) = parse_integer(text)?;
+    let (text, end) = opt(preceded(tag(".."), parse_integer))(text)?;
+    Ok((text, (start, end.unwrap_or(start))))
+}
+
+named!(parse_intervals<&str, Vec<Interval>>, separated_list1!(tag(","), parse_interval));
+
+fn parse_partitions(text: &str) -> IResult<&str, Vec<Interval>> {
+    let (text, _) = delimited(multispace0, tag("partitions"), multispace0)(text)?;
+    delimited(tag("{"), parse_intervals, tag("}"))(text)
+}
+
+fn parse_range(text: &str) -> IResult<&str, (Interval, Option<Vec<Interval>>)> {
+    let (text, _) = delimited(multispace0, tag("int"), multispace0)(text)?;
+    let (text, range) = delimited(tag("["), parse_interval, tag("]"))(text)?;
+    let (text, partitions) = opt(parse_partitions)(text)?;
+    Ok((text, (range, partitions)))
+}
+
+fn interval_to_string(interval: &Interval) -> String {
+    if interval.0 == interval.1 {
+        interval.0.to_string()
+    } else {
+        format!("{}..{}", interval.0, interval.1)
+    }
+}
+
+/// Expand the range into the boundary values and a representative value of each partition.
+///
+/// Every value remembers the partition it was taken from as its class.
+/// If no partitions are provided, the entire range is used as the only partition.
+fn expand_range(name: &str, range: Interval, partitions: Option<Vec<Interval>>) -> Result<TemporaryParameter, String> {
+    if range.0 > range.1 {
+        return Err(format!("The range of parameter {} is empty: {}", name, interval_to_string(&range)));
+    }
+    let partitions = partitions.unwrap_or_else(|| vec![range]);
 
-named!(parse_values<&str, Vec<&str>>, separated_list1!(tag(","), read_value));
+    let mut sorted = partitions.clone();
+    sorted.sort_unstable();
+    for partition in sorted.iter() {
+        if partition.0 > partition.1 || partition.0 < range.0 || range.1 < partition.1 {
+            return Err(format!(
+                "Partition {} of parameter {} is not a part of its range {}",
+                interval_to_string(partition), name, interval_to_string(&range),
+            ));
+        }
+    }
+    for pair in sorted.windows(2) {
+        if pair[1].0 <= pair[0].1 {
+            return Err(format!(
+                "Partitions {} and {} of parameter {} overlap",
+                interval_to_string(&pair[0]), interval_to_string(&pair[1]), name,
+            ));
+        }
+    }
+
+    let mut values = UVec::with_capacity(partitions.len() * 3);
+    let mut classes = UVec::with_capacity(partitions.len() * 3);
+    for (start, end) in partitions.into_iter() {
+        let class = interval_to_string(&(start, end));
+        // The difference does not fit in an i64 for the widest ranges, while half of it does
+        let middle = start + ((end as i128 - start as i128) / 2) as i64;
+        for value in [start, middle, end] {
+            if values.unwrap_ref().last() != Some(&value.to_string()) || classes.unwrap_ref().last() != Some(&class) {
+                values.push(value.to_string());
+                classes.push(class.clone());
+            }
+        }
+    }
 
-fn parse_parameter(text: &str) -> IResult<&str, TemporaryParameter> {
+    let labels = values.iter().map(|_| None).collect();
+    Ok(TemporaryParameter { name: name.to_string(), values, labels, classes: Some(classes) })
+}
+
+pub(crate) fn parse_parameter(text: &str) -> IResult<&str, Definition> {
     let (text, parameter) = read_value(text)?;
     let (text, _) = tag(":")(text)?;
+    if let Ok((text, (range, partitions))) = parse_range(text) {
+        let (text, _) = tag(";")(text)?;
+        return Ok((text, Definition::Range(expand_range(&parameter, range, partitions))));
+    }
     let (text, values) = parse_values(text)?;
     let (text, _) = tag(";")(text)?;
-    Ok((text, TemporaryParameter { name: parameter.to_string(), values: values.into_iter().map(|s| s.into()).collect() }))
-}
-
-pub(crate) fn parse(text: &str) -> Result<(&str, Vec<TemporaryParameter>), String> {
-    many1(parse_parameter)(text).map_err(e2s)
+    Ok((text, Definition::List(parameter.into_owned(), values)))
 }
 
 #[cfg(test)]
 mod parameter_tests {
     use super::*;
-    use common::{UVec, u_vec};
+    use common::u_vec;
+
+    fn values(values: &[&str]) -> Vec<Entry> {
+        values.iter().map(|v| Entry::Value(v.to_string(), None)).collect()
+    }
 
     #[test]
     fn test_parse_values() {
-        assert_eq!(parse_values("  a , b,c ,d, e"), Ok(("", vec!("a", "b", "c", "d", "e"))));
-        assert_eq!(parse_values("  a , b,c d, e"), Ok(("d, e", vec!("a", "b", "c"))));
-        assert_eq!(parse_values("  a : b,c ,d, e"), Ok((": b,c ,d, e", vec!("a"))));
-        assert_eq!(parse_values("  a ; b,c ,d, e"), Ok(("; b,c ,d, e", vec!("a"))));
-        assert_eq!(parse_values("  a ,; b,c ,d, e"), Ok((",; b,c ,d, e", vec!("a"))));
-        assert_eq!(parse_values("  a ,: b,c ,d, e"), Ok((",: b,c ,d, e", vec!("a"))));
-        assert_eq!(parse_values("a"), Ok(("", vec!("a"))));
+        assert_eq!(parse_values("  a , b,c ,d, e"), Ok(("", values(&["a", "b", "c", "d", "e"]))));
+        assert_eq!(parse_values("  a , b,c d, e"), Ok(("d, e", values(&["a", "b", "c"]))));
+        assert_eq!(parse_values("  a : b,c ,d, e"), Ok((": b,c ,d, e", values(&["a"]))));
+        assert_eq!(parse_values("  a ; b,c ,d, e"), Ok(("; b,c ,d, e", values(&["a"]))));
+        assert_eq!(parse_values("  a ,; b,c ,d, e"), Ok((",; b,c ,d, e", values(&["a"]))));
+        assert_eq!(parse_values("  a ,: b,c ,d, e"), Ok((",: b,c ,d, e", values(&["a"]))));
+        assert_eq!(parse_values("a"), Ok(("", values(&["a"]))));
+        assert_eq!(parse_values(" $OS , b"), Ok(("", vec![Entry::Reference("OS".into()), Entry::Value("b".into(), None)])));
+        assert_eq!(parse_values(" \"$OS\""), Ok(("", values(&["$OS"]))));
         assert!(parse_values("   ,: b, d, e").is_err());
         assert!(parse_values("").is_err());
     }
 
+    #[test]
+    fn test_parse_labels() {
+        assert_eq!(
+            parse_values("win10 = \"Windows 10\", linux, \"mac os\" = Mac"),
+            Ok(("", vec![
+                Entry::Value("win10".into(), Some("Windows 10".into())),
+                Entry::Value("linux".into(), None),
+                Entry::Value("mac os".into(), Some("Mac".into())),
+            ])),
+        );
+        assert!(parse_parameter("p: a = , b;").is_err());
+    }
+
     #[test]
     fn test_parse_parameter_line() {
-        assert_eq!(parse_parameter("0:  a , b,c ,d, e;"), Ok(("", TemporaryParameter { name: "0".to_string(), values: u_vec!["a".into(), "b".into(), "c".into(), "d".into(), "e".into()] })));
-        assert_eq!(parse_parameter("0:a;"), Ok(("", TemporaryParameter { name: "0".into(), values: u_vec!["a".into()] })));
+        assert_eq!(parse_parameter("0:  a , b,c ,d, e;"), Ok(("", Definition::List("0".into(), values(&["a", "b", "c", "d", "e"])))));
+        assert_eq!(parse_parameter("0:a;"), Ok(("", Definition::List("0".into(), values(&["a"])))));
+        assert_eq!(parse_parameter("\"Operating system\": \"Windows 10\";"), Ok(("", Definition::List("Operating system".into(), values(&["Windows 10"])))));
         assert!(parse_parameter(" 0:  a : b,c ,d, e;").is_err());
         assert!(parse_parameter(" 0:  a : b,c ,d, e").is_err());
         assert!(parse_parameter(" 0 :  a  b,c ,d, e;").is_err());
@@ -62,4 +205,55 @@ mod parameter_tests {
         assert!(parse_parameter("").is_err());
         assert!(parse_parameter("a").is_err());
     }
+
+    fn strings(values: &[&str]) -> UVec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_interval(" 1..59 "), Ok(("", (1, 59))));
+        assert_eq!(parse_interval("-5 "), Ok(("", (-5, -5))));
+        assert_eq!(parse_range(" int[0..3600];"), Ok((";", ((0, 3600), None))));
+        assert_eq!(
+            parse_range(" int[0..3600] partitions {0, 1..59, 60..3599, 3600};"),
+            Ok((";", ((0, 3600), Some(vec![(0, 0), (1, 59), (60, 3599), (3600, 3600)])))),
+        );
+        assert!(parse_range(" int[0..] partitions {0};").is_err());
+        assert!(parse_range(" a, b;").is_err());
+    }
+
+    #[test]
+    fn test_parse_range_parameter() {
+        assert_eq!(
+            parse_parameter("timeout: int[0..3600] partitions {0, 1..59, 60..3599, 3600};"),
+            Ok(("", Definition::Range(Ok(TemporaryParameter {
+                name: "timeout".into(),
+                values: strings(&["0", "1", "30", "59", "60", "1829", "3599", "3600"]),
+                labels: u_vec![None; 8],
+                classes: Some(strings(&["0", "1..59", "1..59", "1..59", "60..3599", "60..3599", "60..3599", "3600"])),
+            })))),
+        );
+        assert_eq!(
+            parse_parameter("small: int[-1..0];"),
+            Ok(("", Definition::Range(Ok(TemporaryParameter {
+                name: "small".into(),
+                values: strings(&["-1", "0"]),
+                labels: u_vec![None; 2],
+                classes: Some(strings(&["-1..0", "-1..0"])),
+            })))),
+        );
+        assert_eq!(
+            parse_parameter("wide: int[-9223372036854775808..9223372036854775807];"),
+            Ok(("", Definition::Range(Ok(TemporaryParameter {
+                name: "wide".into(),
+                values: strings(&["-9223372036854775808", "-1", "9223372036854775807"]),
+                labels: u_vec![None; 3],
+                classes: Some(strings(&["-9223372036854775808..9223372036854775807"; 3])),
+            })))),
+        );
+        for text in ["t: int[5..0];", "t: int[0..10] partitions {0..5, 5..10};", "t: int[0..10] partitions {0..11};"] {
+            assert!(matches!(parse_parameter(text), Ok((_, Definition::Range(Err(_))))), "{}", text);
+        }
+    }
 }
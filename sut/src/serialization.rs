@@ -0,0 +1,127 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::collections::HashSet;
+use std::marker::PhantomData;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use common::{Number, UVec};
+
+use crate::expr::expr_serde::SerdeExpr;
+use crate::{ConstrainedSUT, SUT};
+
+#[derive(Deserialize)]
+#[serde(rename = "SUT")]
+struct DeserializeSUT<ValueId: Number> {
+    parameters: UVec<ValueId>,
+    parameter_names: UVec<String>,
+    values: UVec<UVec<String>>,
+    value_classes: UVec<Option<UVec<String>>>,
+    value_labels: UVec<UVec<Option<String>>>,
+    declaration_order: UVec<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "ConstrainedSUT", bound = "SUT<ValueId, ParameterId>: Serialize")]
+struct SerializeConstrainedSUT<'s, ValueId: Number, ParameterId: Number> {
+    sub_sut: &'s SUT<ValueId, ParameterId>,
+    constraints: Vec<SerdeExpr>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "ConstrainedSUT", bound = "SUT<ValueId, ParameterId>: Deserialize<'de>")]
+struct DeserializeConstrainedSUT<ValueId: Number, ParameterId: Number> {
+    sub_sut: SUT<ValueId, ParameterId>,
+    constraints: Vec<SerdeExpr>,
+}
+
+/// Checks that the fields of the [SUT] describe the same parameters and values.
+fn check_sut<ValueId: Number, ParameterId: Number>(sut: &SUT<ValueId, ParameterId>) -> Result<(), String> {
+    let parameter_count = sut.parameters.len();
+    if parameter_count > ParameterId::dont_care().as_usize() {
+        return Err(format!("The SUT has {} parameters, which do not fit the parameter ids", parameter_count));
+    }
+    for (field, len) in [
+        ("parameter_names", sut.parameter_names.len()),
+        ("values", sut.values.len()),
+        ("value_classes", sut.value_classes.len()),
+        ("value_labels", sut.value_labels.len()),
+        ("declaration_order", sut.declaration_order.len()),
+    ] {
+        if len != parameter_count {
+            return Err(format!("The SUT has {} parameters, but {} has length {}", parameter_count, field, len));
+        }
+    }
+
+    if sut.parameter_names.iter().collect::<HashSet<_>>().len() != parameter_count {
+        return Err("The names of the parameters are not unique".into());
+    }
+
+    for (parameter_id, &level) in sut.parameters.iter().enumerate() {
+        let name = &sut.parameter_names[parameter_id];
+        if level == ValueId::default() || level >= ValueId::dont_care() {
+            return Err(format!("The level {} of parameter {} is not supported", level.as_usize(), name));
+        }
+        let level = level.as_usize();
+        let classes = sut.value_classes[parameter_id].as_ref().map_or(level, |classes| classes.len());
+        for (field, len) in [("values", sut.values[parameter_id].len()), ("value_classes", classes), ("value_labels", sut.value_labels[parameter_id].len())] {
+            if len != level {
+                return Err(format!("Parameter {} has level {}, but {} has length {}", name, level, field, len));
+            }
+        }
+        if sut.values[parameter_id].iter().collect::<HashSet<_>>().len() != level {
+            return Err(format!("The values of parameter {} are not unique", name));
+        }
+    }
+
+    let mut declared = vec![false; parameter_count];
+    for &parameter_id in sut.declaration_order.iter() {
+        if parameter_id >= parameter_count || std::mem::replace(&mut declared[parameter_id], true) {
+            return Err(format!("The declaration order {:?} is not a permutation of the parameters", sut.declaration_order));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the fields agree with each other, so the [SUT] can be used as if it was parsed.
+impl<'de, ValueId: Number + Deserialize<'de>, ParameterId: Number> Deserialize<'de> for SUT<ValueId, ParameterId> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = DeserializeSUT::deserialize(deserializer)?;
+        let result = SUT {
+            parameters: data.parameters,
+            parameter_names: data.parameter_names,
+            values: data.values,
+            value_classes: data.value_classes,
+            value_labels: data.value_labels,
+            declaration_order: data.declaration_order,
+            parameter_id: PhantomData,
+        };
+        check_sut(&result).map_err(D::Error::custom)?;
+        Ok(result)
+    }
+}
+
+/// Serializes the [SUT] and the constraints, the lookup tables are rebuilt during deserialization.
+impl<ValueId: Number, ParameterId: Number> Serialize for ConstrainedSUT<ValueId, ParameterId> where SUT<ValueId, ParameterId>: Serialize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializeConstrainedSUT {
+            sub_sut: &self.sub_sut,
+            constraints: self.constraints.iter().map(|constraint| constraint.to_serde()).collect(),
+        }.serialize(serializer)
+    }
+}
+
+impl<'de, ValueId: Number, ParameterId: Number> Deserialize<'de> for ConstrainedSUT<ValueId, ParameterId> where SUT<ValueId, ParameterId>: Deserialize<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = DeserializeConstrainedSUT::deserialize(deserializer)?;
+        let mut result = Self::wrap_sut(data.sub_sut);
+        result.constraints = data.constraints.into_iter().map(Box::from).collect();
+        result.check_constraints().map_err(D::Error::custom)?;
+        Ok(result)
+    }
+}
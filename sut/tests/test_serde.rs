@@ -0,0 +1,110 @@
+// Copyright 2021 A Veenstra.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms.
+
+use test_utils::Walker;
+
+use sut::{ConstrainedSUT, parse_constrained, SUT};
+
+#[test]
+fn test_round_trip_benchmarks() {
+    let benchmarks = std::env::current_dir().unwrap().join("tests").join("benchmarks");
+    let mut count = 0;
+
+    for contents in Walker::new(benchmarks) {
+        let sut = parse_constrained(&contents).unwrap_or_else(|e| panic!("{}", e));
+        check_round_trip(&sut);
+        check_round_trip_p(sut.mutate::<u8, u8>());
+        count += 1;
+    }
+
+    assert!(count > 20, "Did not find enough benchmarks? Successfully parsed {} benchmarks.", count);
+}
+
+fn check_round_trip(sut: &ConstrainedSUT<usize, usize>) {
+    let json = serde_json::to_string(sut).unwrap();
+    let result: ConstrainedSUT<usize, usize> = serde_json::from_str(&json).unwrap();
+
+    check_sub_sut(&sut.sub_sut, &result.sub_sut);
+    assert_eq!(result.count_constraints(), sut.count_constraints());
+    assert_eq!(result.fingerprint(), sut.fingerprint());
+    assert_eq!(result.parameter_to_id, sut.parameter_to_id);
+    assert_eq!(result.value_to_id, sut.value_to_id);
+    assert_eq!(serde_json::to_string(&result).unwrap(), json);
+}
+
+fn check_round_trip_p(sut: ConstrainedSUT<u8, u8>) {
+    let json = serde_json::to_string(&sut.sub_sut).unwrap();
+    let result: SUT<u8, u8> = serde_json::from_str(&json).unwrap();
+    check_sub_sut(&sut.sub_sut, &result);
+}
+
+fn check_sub_sut<ValueId: common::Number, ParameterId: common::Number>(expected: &SUT<ValueId, ParameterId>, result: &SUT<ValueId, ParameterId>) {
+    assert_eq!(result.parameters, expected.parameters);
+    assert_eq!(result.parameter_names, expected.parameter_names);
+    assert_eq!(result.values, expected.values);
+    assert_eq!(result.value_classes, expected.value_classes);
+    assert_eq!(result.value_labels, expected.value_labels);
+    assert_eq!(result.declaration_order, expected.declaration_order);
+}
+
+#[test]
+fn test_structured_constraints() {
+    let sut = parse_constrained("p1: v1, v2; p2: 1, 2; $assert p1 = v1 => !(p2 >= 2);").unwrap();
+    let json = serde_json::to_value(&sut).unwrap();
+    assert_eq!(json["constraints"], serde_json::json!([{
+        "bin_op": {
+            "left": { "eq": { "parameter": "p1", "value": "v1" } },
+            "op": "implies",
+            "right": { "not": { "sub": { "relation": {
+                "left": { "terms": [[1, "p2"]], "constant": 0 },
+                "op": "ge",
+                "right": { "terms": [], "constant": 2 },
+            } } } },
+        }
+    }]));
+}
+
+#[test]
+fn test_reject_inconsistent_sut() {
+    let sut = parse_constrained("p1: a, b, c; p2: x, y; p3: 1, 2;").unwrap();
+    let json = serde_json::to_value(&sut.sub_sut).unwrap();
+    assert!(serde_json::from_value::<SUT<usize, usize>>(json.clone()).is_ok());
+
+    let reject = |pointer: &str, value: serde_json::Value, expected: &str| {
+        let mut json = json.clone();
+        *json.pointer_mut(pointer).unwrap() = value;
+        let error = serde_json::from_value::<SUT<usize, usize>>(json).err().unwrap_or_else(|| panic!("Accepted the SUT with {} changed", pointer));
+        assert!(error.to_string().contains(expected), "Unexpected error: {}", error);
+    };
+
+    reject("/parameter_names", serde_json::json!(["p1", "p2"]), "parameter_names has length 2");
+    reject("/value_classes", serde_json::json!([null, null, null, null]), "value_classes has length 4");
+    reject("/parameters/0", serde_json::json!(4), "values has length 3");
+    reject("/value_labels/1", serde_json::json!([null]), "value_labels has length 1");
+    reject("/value_classes/2", serde_json::json!(["c"]), "value_classes has length 1");
+    reject("/values/1/1", serde_json::json!("x"), "not unique");
+    reject("/parameter_names/1", serde_json::json!("p1"), "not unique");
+    reject("/declaration_order", serde_json::json!([0, 0, 1]), "not a permutation");
+    reject("/declaration_order", serde_json::json!([0, 1, 3]), "not a permutation");
+}
+
+#[test]
+fn test_reject_unknown_names() {
+    let sut = parse_constrained("p1: a, b; p2: 1, 2; $assert p1 = a => p2 >= 2;").unwrap();
+    let json = serde_json::to_value(&sut).unwrap();
+    assert!(serde_json::from_value::<ConstrainedSUT<usize, usize>>(json.clone()).is_ok());
+
+    let reject = |pointer: &str, value: &str, expected: &str| {
+        let mut json = json.clone();
+        *json.pointer_mut(pointer).unwrap() = serde_json::json!(value);
+        let error = serde_json::from_value::<ConstrainedSUT<usize, usize>>(json).err().unwrap_or_else(|| panic!("Accepted the SUT with {} changed", pointer));
+        assert!(error.to_string().contains(expected), "Unexpected error: {}", error);
+    };
+
+    reject("/constraints/0/bin_op/left/eq/parameter", "p3", "Unknown parameter p3");
+    reject("/constraints/0/bin_op/left/eq/value", "c", "Unknown value c of parameter p1");
+    reject("/constraints/0/bin_op/right/relation/left/terms/0/1", "p4", "Unknown parameter p4");
+}
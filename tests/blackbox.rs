@@ -9,7 +9,7 @@ use std::process::Command;
 
 use itertools::Itertools;
 
-use common::{u_vec, UVec};
+use common::{CancellationToken, RunOptions, u_vec, UVec};
 use sut::Solver;
 
 const CARGO: &str = "cargo";
@@ -21,26 +21,27 @@ const TCAS_Z3: &str = "(declare-datatypes ((Ex3 0)) (((Ex3_v0) (Ex3_v1) (Ex3_v2)
 const LIBRE_CA_SUT: &str = "\
 ipog-type: libreca-s, libreca-m;
 feature-constraints: constraints-minisat, constraints-z3, constraints-glucose, off;
-feature-score: off, score-double, score-single;
-feature-filter-map: off, filter-map;
+feature-score: off, score-avx2;
 feature-sub-time: off, sub-time;
 feature-no-sort: off, no-sort;
-feature-cycle: off, no-cycle-split;
 flag-constraints: --constraints, --no-constraints;
+flag-scoring: off, --scoring=double, --scoring=single;
+flag-filter-map: off, --filter-map;
+flag-cycle: off, --no-cycle-split;
 output-type: ocfs, ocf-, oc-s, oc--, o-fs, o-f-, o--s, o---;
 
-$assert (ipog-type = libreca-s) => (feature-cycle = off);
-$assert (ipog-type = libreca-m) => (feature-filter-map = off);
+$assert (ipog-type = libreca-s) => (flag-cycle = off);
+$assert (ipog-type = libreca-m) => (flag-filter-map = off);
 $assert (flag-constraints = --constraints) => (! feature-constraints = off);
 
-$assert (output-type = ocfs) => ((  flag-constraints = --constraints) && (! feature-filter-map = off) && (! feature-no-sort = no-sort));
-$assert (output-type = ocf-) => ((  flag-constraints = --constraints) && (! feature-filter-map = off) && (  feature-no-sort = no-sort));
-$assert (output-type = oc-s) => ((  flag-constraints = --constraints) && (  feature-filter-map = off) && (! feature-no-sort = no-sort));
-$assert (output-type = oc--) => ((  flag-constraints = --constraints) && (  feature-filter-map = off) && (  feature-no-sort = no-sort));
-$assert (output-type = o-fs) => ((! flag-constraints = --constraints) && (! feature-filter-map = off) && (! feature-no-sort = no-sort));
-$assert (output-type = o-f-) => ((! flag-constraints = --constraints) && (! feature-filter-map = off) && (  feature-no-sort = no-sort));
-$assert (output-type = o--s) => ((! flag-constraints = --constraints) && (  feature-filter-map = off) && (! feature-no-sort = no-sort));
-$assert (output-type = o---) => ((! flag-constraints = --constraints) && (  feature-filter-map = off) && (  feature-no-sort = no-sort));
+$assert (output-type = ocfs) => ((  flag-constraints = --constraints) && (! flag-filter-map = off) && (! feature-no-sort = no-sort));
+$assert (output-type = ocf-) => ((  flag-constraints = --constraints) && (! flag-filter-map = off) && (  feature-no-sort = no-sort));
+$assert (output-type = oc-s) => ((  flag-constraints = --constraints) && (  flag-filter-map = off) && (! feature-no-sort = no-sort));
+$assert (output-type = oc--) => ((  flag-constraints = --constraints) && (  flag-filter-map = off) && (  feature-no-sort = no-sort));
+$assert (output-type = o-fs) => ((! flag-constraints = --constraints) && (! flag-filter-map = off) && (! feature-no-sort = no-sort));
+$assert (output-type = o-f-) => ((! flag-constraints = --constraints) && (! flag-filter-map = off) && (  feature-no-sort = no-sort));
+$assert (output-type = o--s) => ((! flag-constraints = --constraints) && (  flag-filter-map = off) && (! feature-no-sort = no-sort));
+$assert (output-type = o---) => ((! flag-constraints = --constraints) && (  flag-filter-map = off) && (  feature-no-sort = no-sort));
 ";
 
 mod sut_parsing {
@@ -141,7 +142,7 @@ fn test_features() {
         ipog_single::constrained::HorizontalExtension<usize, usize, u16, 2>,
         ipog_single::constrained::VerticalExtension<usize, usize, u16, 2>,
         2
-    >::run(&mut libre_ca_sut, &solver_init);
+    >::run(&mut libre_ca_sut, &solver_init, &mut (), &CancellationToken::new(), &mut (), &RunOptions::default());
 
     let mut uses = UVec::with_capacity(libre_ca_sut.sub_sut.parameters.len());
     for parameter in libre_ca_sut.sub_sut.parameters.iter() {
@@ -12,57 +12,137 @@ use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::PathBuf;
 
-use common::{DONT_CARE_TEXT, Number};
+use common::{DONT_CARE_TEXT, Number, Report};
 use sut::SUT;
-use mca::MCA;
+use mca::{MCA, Skipped};
+use prioritise::CoverageReport;
 
 const DONT_CARE_TEXT_BYTES: &[u8] = DONT_CARE_TEXT.as_bytes();
 
+/// The suffix of the column containing the equivalence class of the values of a numeric range parameter.
+pub const CLASS_SUFFIX: &str = ".class";
+
+/// Write a single field, which is quoted if it contains a separator, a quote or a line break or if it starts with a `#`.
+///
+/// Quotes in a quoted field are doubled, [split_line] reverses this.
+fn write_field(file: &mut BufWriter<File>, text: &str) -> std::io::Result<()> {
+    if text.starts_with('#') || text.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        file.write_all(b"\"")?;
+        file.write_all(text.replace('"', "\"\"").as_bytes())?;
+        file.write_all(b"\"")
+    } else {
+        file.write_all(text.as_bytes())
+    }
+}
+
+/// Split a line of the output into its fields, removing the quotes added when writing the fields.
+pub fn split_line(line: &str) -> Vec<String> {
+    let mut result = vec![String::new()];
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                result.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => result.push(String::new()),
+            c => result.last_mut().unwrap().push(c),
+        }
+    }
+    result
+}
+
 fn write_value<ValueId: Number, ParameterId: Number>(
     file: &mut BufWriter<File>,
     sut: &SUT<ValueId, ParameterId>,
     index: usize,
     value: ValueId,
 ) -> std::io::Result<()> {
-    match sut.values[index].get(value.as_usize()) {
-        Some(text) => file.write_all(text.as_ref()),
-        None => file.write_all(DONT_CARE_TEXT_BYTES),
+    match sut.display_value(index, value.as_usize()) {
+        Some(text) => write_field(file, text)?,
+        None => file.write_all(DONT_CARE_TEXT_BYTES)?,
+    }
+    if let Some(classes) = &sut.value_classes[index] {
+        file.write_all(b",")?;
+        match classes.get(value.as_usize()) {
+            Some(text) => write_field(file, text)?,
+            None => file.write_all(DONT_CARE_TEXT_BYTES)?,
+        }
+    }
+    Ok(())
+}
+
+/// Returns the parameter IDs in the order of the columns of the output.
+///
+/// This is the [SUT::declaration_order], unless the `internal_order` of the parameters is requested.
+fn column_order<ValueId: Number, ParameterId: Number>(sut: &SUT<ValueId, ParameterId>, internal_order: bool) -> Vec<usize> {
+    if internal_order {
+        (0..sut.parameters.len()).collect()
+    } else {
+        sut.declaration_order.iter().copied().collect()
     }
 }
 
 /// Write the given [MCA] to the given filename.
+///
+/// The columns are in the order in which the parameters were declared, unless the `internal_order` is requested.
+/// If the generation was cancelled, the [MCA::skipped] work is reported and noted in the header of the file.
 pub fn write_result<ValueId: Number, ParameterId: Number, LocationsType: Number>(
     sut: &SUT<ValueId, ParameterId>,
     mca: MCA<ValueId, LocationsType>,
     filename: PathBuf,
+    internal_order: bool,
 ) -> std::io::Result<()> {
-    write_result_iterable(
+    if let Some(skipped) = &mca.skipped {
+        tracing::warn!("The generation was {}", skipped);
+    }
+    write_result_skipped(
         sut,
         mca.array.len(),
         mca.array.into_iter().flatten(),
+        mca.skipped.as_ref(),
         filename,
+        internal_order,
     )
 }
 
+/// Write the name of the parameter, followed by the name of the column containing the class of the value if it has classes.
+fn write_header(file: &mut BufWriter<File>, parameter: &str, has_classes: bool) -> std::io::Result<()> {
+    write_field(file, parameter)?;
+    if has_classes {
+        file.write_all(b",")?;
+        write_field(file, &format!("{}{}", parameter, CLASS_SUFFIX))?;
+    }
+    Ok(())
+}
+
 fn write_headers<ValueId: Number, ParameterId: Number>(
     sut: &SUT<ValueId, ParameterId>,
+    columns: &[usize],
     mca_size: usize,
+    skipped: Option<&Skipped>,
     file: &mut BufWriter<File>,
 ) -> std::io::Result<()> {
     file.write_all(b"#  '*' represents don't care value\n")?;
     file.write_all(format!("# Number of parameters: {}\n", sut.parameters.len()).as_ref())?;
     file.write_all(format!("# Number of configurations: {}\n", mca_size).as_ref())?;
-    let mut parameters_iter = sut.parameter_names.iter();
-    file.write(parameters_iter.next().expect("No parameters?").as_bytes())?;
-    for parameter in parameters_iter {
+    if let Some(skipped) = skipped {
+        file.write_all(format!("# Generation {}\n", skipped).as_ref())?;
+    }
+    let (&first, rest) = columns.split_first().expect("No parameters?");
+    write_header(file, &sut.parameter_names[first], sut.value_classes[first].is_some())?;
+    for &parameter_id in rest {
         file.write_all(b",")?;
-        file.write_all(parameter.as_bytes())?;
+        write_header(file, &sut.parameter_names[parameter_id], sut.value_classes[parameter_id].is_some())?;
     }
     file.write_all(b"\n")
 }
 
 fn write_values<I, ValueId: Number, ParameterId: Number>(
     sut: &SUT<ValueId, ParameterId>,
+    columns: &[usize],
     mca_size: usize,
     mut mca: I,
     mut file: &mut BufWriter<File>,
@@ -70,31 +150,193 @@ fn write_values<I, ValueId: Number, ParameterId: Number>(
     where
         I: Iterator<Item=ValueId>,
 {
+    let mut row = Vec::with_capacity(sut.parameters.len());
     for _ in 0..mca_size {
-        write_value(&mut file, sut, 0, mca.next().unwrap())?;
+        row.clear();
+        row.extend(mca.by_ref().take(sut.parameters.len()));
 
-        for index in 1..sut.parameters.len() {
+        let (&first, rest) = columns.split_first().expect("No parameters?");
+        write_value(&mut file, sut, first, row[first])?;
+        for &index in rest {
             file.write_all(b",")?;
-            write_value(&mut file, sut, index, mca.next().unwrap())?;
+            write_value(&mut file, sut, index, row[index])?;
         }
         file.write_all(b"\n")?;
     }
     Ok(())
 }
 
-/// Write the provided [Iterator] to a file.
+/// Write the provided [Iterator] to a file, see [write_result] for the order of the columns.
 pub fn write_result_iterable<'a, I, ValueId: Number, ParameterId: Number>(
     sut: &SUT<ValueId, ParameterId>,
     mca_size: usize,
     mca: I,
     filename: PathBuf,
+    internal_order: bool,
+) -> std::io::Result<()>
+    where
+        I: Iterator<Item=ValueId>,
+{
+    write_result_skipped(sut, mca_size, mca, None, filename, internal_order)
+}
+
+fn write_result_skipped<I, ValueId: Number, ParameterId: Number>(
+    sut: &SUT<ValueId, ParameterId>,
+    mca_size: usize,
+    mca: I,
+    skipped: Option<&Skipped>,
+    filename: PathBuf,
+    internal_order: bool,
 ) -> std::io::Result<()>
     where
         I: Iterator<Item=ValueId>,
 {
-    println!("The resulting suite has {} tests", mca_size);
+    tracing::info!("The resulting suite has {} tests", mca_size);
+    let columns = column_order(sut, internal_order);
     let mut writer = BufWriter::new(File::create(filename)?);
-    write_headers(sut, mca_size, &mut writer)?;
-    write_values(sut, mca_size, mca, &mut writer)?;
+    write_headers(sut, &columns, mca_size, skipped, &mut writer)?;
+    write_values(sut, &columns, mca_size, mca, &mut writer)?;
     writer.flush()
 }
+
+/// Write the cumulative coverage of the rows in the provided [CoverageReport] to the given filename.
+pub fn write_coverage_report(report: &CoverageReport, filename: PathBuf) -> std::io::Result<()> {
+    let mut file = BufWriter::new(File::create(filename)?);
+    file.write_all(format!("# Strength: {}\n", report.strength).as_ref())?;
+    file.write_all(format!("# Number of interactions: {}\n", report.total).as_ref())?;
+    if let Some(lower_total) = report.lower_total {
+        file.write_all(format!("# Number of lower strength interactions: {}\n", lower_total).as_ref())?;
+        file.write_all(b"row,original_row,new,covered,coverage,lower_new,lower_covered,lower_coverage\n")?;
+    } else {
+        file.write_all(b"row,original_row,new,covered,coverage\n")?;
+    }
+
+    for (row, step) in report.steps.iter().enumerate() {
+        file.write_all(format!(
+            "{},{},{},{},{:.6}",
+            row, step.original_row, step.new, step.covered, step.covered as f64 / report.total as f64,
+        ).as_ref())?;
+        if let Some(lower_total) = report.lower_total {
+            file.write_all(format!(
+                ",{},{},{:.6}",
+                step.lower_new, step.lower_covered, step.lower_covered as f64 / lower_total as f64,
+            ).as_ref())?;
+        }
+        file.write_all(b"\n")?;
+    }
+    file.flush()
+}
+
+/// Quote the text as a JSON string.
+fn json_string(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 2);
+    result.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Format the [Report] as JSON, with all durations in seconds.
+fn format_report(report: &Report) -> String {
+    let phases: Vec<String> = report.phases.iter().map(|timing| format!(
+        "    {{\"phase\": {}, \"count\": {}, \"seconds\": {:.6}}}",
+        json_string(&timing.phase), timing.count, timing.duration.as_secs_f64(),
+    )).collect();
+    let parameters: Vec<String> = report.parameters.iter().map(|parameter| format!(
+        "    {{\"parameter\": {}, \"horizontal_seconds\": {:.6}, \"horizontal_rows\": {}, \"vertical_seconds\": {:.6}, \"vertical_rows\": {}}}",
+        parameter.parameter, parameter.horizontal.as_secs_f64(), parameter.horizontal_rows, parameter.vertical.as_secs_f64(), parameter.vertical_rows,
+    )).collect();
+    let solver_calls: Vec<String> = report.solver_calls.iter().map(|calls| format!(
+        "    {{\"method\": {}, \"calls\": {}, \"sat\": {}, \"unsat\": {}, \"sat_ratio\": {}}}",
+        json_string(calls.method), calls.calls(), calls.sat, calls.unsat,
+        calls.sat_ratio().map_or_else(|| "null".to_string(), |ratio| format!("{:.6}", ratio)),
+    )).collect();
+    let solver_cache = report.solver_cache.map_or_else(|| "null".to_string(), |cache| format!(
+        "{{\"queries\": {}, \"hits\": {}, \"misses\": {}, \"hit_ratio\": {}}}",
+        cache.queries(), cache.hits, cache.misses,
+        cache.hit_ratio().map_or_else(|| "null".to_string(), |ratio| format!("{:.6}", ratio)),
+    ));
+    let list = |items: Vec<String>| if items.is_empty() { "[]".to_string() } else { format!("[\n{}\n  ]", items.join(",\n")) };
+
+    format!(
+        "{{\n  \"phases\": {},\n  \"parameters\": {},\n  \"solver_calls\": {},\n  \"solver_cache\": {},\n  \"peak_coverage_map_bytes\": {},\n  \"rows\": {}\n}}\n",
+        list(phases), list(parameters), list(solver_calls), solver_cache, report.peak_coverage_map_bytes, report.rows,
+    )
+}
+
+/// Write the timings and statistics in the provided [Report] to the given filename as JSON.
+pub fn write_report(report: &Report, filename: PathBuf) -> std::io::Result<()> {
+    let mut file = BufWriter::new(File::create(filename)?);
+    file.write_all(format_report(report).as_bytes())?;
+    file.flush()
+}
+
+#[cfg(test)]
+mod writer_tests {
+    use std::time::Duration;
+
+    use common::{CacheHits, ParameterReport, PhaseTiming, Report, SolverCalls};
+
+    use super::{column_order, format_report, json_string, split_line};
+
+    #[test]
+    fn test_split_line() {
+        assert_eq!(split_line("a,b,*"), vec!["a", "b", "*"]);
+        assert_eq!(split_line("\"Windows 10, home\",\"say \"\"hi\"\"\",1.2.3"), vec!["Windows 10, home", "say \"hi\"", "1.2.3"]);
+        assert_eq!(split_line("\"#1\","), vec!["#1", ""]);
+    }
+
+    #[test]
+    fn test_column_order() {
+        let sut = sut::parse_unconstrained("p1: a, b; p2: x, y, z; p3: 1, 2, 3, 4;").unwrap();
+        let names = |columns: Vec<usize>| columns.into_iter().map(|parameter_id| sut.parameter_names[parameter_id].as_str()).collect::<Vec<_>>();
+        assert_eq!(names(column_order(&sut, false)), vec!["p1", "p2", "p3"]);
+        assert_eq!(names(column_order(&sut, true)), sut.parameter_names.iter().map(|name| name.as_str()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("Parsing"), "\"Parsing\"");
+        assert_eq!(json_string("a \"b\"\\\n"), "\"a \\\"b\\\"\\\\\\n\"");
+    }
+
+    #[test]
+    fn test_format_report() {
+        let report = Report {
+            phases: vec![PhaseTiming { phase: "Parsing".to_string(), count: 1, duration: Duration::from_millis(1500) }],
+            parameters: vec![ParameterReport { parameter: 2, horizontal: Duration::from_millis(2), horizontal_rows: 0, vertical: Duration::ZERO, vertical_rows: 3 }],
+            solver_calls: vec![SolverCalls { method: "check", sat: 1, unsat: 3 }, SolverCalls { method: "check_row", sat: 0, unsat: 0 }],
+            solver_cache: Some(CacheHits { hits: 3, misses: 1 }),
+            peak_coverage_map_bytes: 64,
+            rows: 9,
+        };
+        assert_eq!(format_report(&report), r#"{
+  "phases": [
+    {"phase": "Parsing", "count": 1, "seconds": 1.500000}
+  ],
+  "parameters": [
+    {"parameter": 2, "horizontal_seconds": 0.002000, "horizontal_rows": 0, "vertical_seconds": 0.000000, "vertical_rows": 3}
+  ],
+  "solver_calls": [
+    {"method": "check", "calls": 4, "sat": 1, "unsat": 3, "sat_ratio": 0.250000},
+    {"method": "check_row", "calls": 0, "sat": 0, "unsat": 0, "sat_ratio": null}
+  ],
+  "solver_cache": {"queries": 4, "hits": 3, "misses": 1, "hit_ratio": 0.750000},
+  "peak_coverage_map_bytes": 64,
+  "rows": 9
+}
+"#);
+        assert!(format_report(&Report::default()).contains("\"phases\": [],"));
+        assert!(format_report(&Report::default()).contains("\"solver_cache\": null,"));
+    }
+}